@@ -0,0 +1,300 @@
+use crate::backtest::result::BacktestResult;
+use crate::scorecard::Direction;
+use std::collections::BTreeMap;
+
+/// 默认的退化阈值: 变差超过10%(原值为0时为绝对变化超过0.1)时标记
+pub const DEFAULT_REGRESSION_THRESHOLD: f32 = 0.1;
+
+/// 单个指标的前后对比
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetricDelta {
+    /// 指标名，与BacktestResult的字段名一致
+    pub name: &'static str,
+    pub old: f32,
+    pub new: f32,
+    pub delta: f32,
+    /// 相对变化(0.1表示10%)，原值为0或非有限值时为None
+    pub pct_change: Option<f32>,
+    /// 优化方向，None表示无好坏之分(如交易次数)
+    pub direction: Option<Direction>,
+}
+
+impl MetricDelta {
+    fn new(name: &'static str, old: f32, new: f32, direction: Option<Direction>) -> Self {
+        // 两边同为无穷大(如没有亏损时的盈亏比)视为没有变化
+        let delta = if old == new { 0.0 } else { new - old };
+        let pct_change = if old != 0.0 && old.is_finite() && delta.is_finite() {
+            Some(delta / old.abs())
+        } else {
+            None
+        };
+        
+        Self { name, old, new, delta, pct_change, direction }
+    }
+    
+    /// 是否按优化方向变差且幅度超过threshold
+    pub fn is_regression(&self, threshold: f32) -> bool {
+        let worse = match self.direction {
+            Some(Direction::Maximize) => self.delta < 0.0,
+            Some(Direction::Minimize) => self.delta > 0.0,
+            None => false,
+        };
+        if !worse {
+            return false;
+        }
+        
+        match self.pct_change {
+            Some(pct) => pct.abs() > threshold,
+            None => !self.delta.is_finite() || self.delta.abs() > threshold,
+        }
+    }
+}
+
+/// 单只股票的交易次数变化
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolDelta {
+    pub symbol: String,
+    pub old_trades: usize,
+    pub new_trades: usize,
+}
+
+/// 两个回测结果的对比
+#[derive(Debug, Clone)]
+pub struct ResultComparison {
+    /// 各标量指标，顺序与BacktestResult字段一致
+    pub metrics: Vec<MetricDelta>,
+    /// 交易次数有变化的股票，任一结果没有交易详情时为None
+    pub symbols: Option<Vec<SymbolDelta>>,
+    regression_threshold: f32,
+}
+
+impl ResultComparison {
+    /// 设置退化阈值
+    pub fn set_regression_threshold(&mut self, threshold: f32) {
+        self.regression_threshold = threshold;
+    }
+    
+    /// 超过退化阈值的指标
+    pub fn regressions(&self) -> Vec<&MetricDelta> {
+        self.metrics.iter()
+            .filter(|metric| metric.is_regression(self.regression_threshold))
+            .collect()
+    }
+    
+    /// 按名称查找指标
+    pub fn metric(&self, name: &str) -> Option<&MetricDelta> {
+        self.metrics.iter().find(|metric| metric.name == name)
+    }
+    
+    /// 格式化为对齐的文本表格，变差超过阈值的指标以"!!"标记
+    pub fn format_comparison(&self) -> String {
+        let mut report = format!("{:<28} {:>12} {:>12} {:>12} {:>9}\n", "metric", "old", "new", "delta", "change");
+        
+        for metric in &self.metrics {
+            let change = match metric.pct_change {
+                Some(pct) => format!("{:.2}%", pct * 100.0),
+                None => "-".to_string(),
+            };
+            let marker = if metric.is_regression(self.regression_threshold) { " !!" } else { "" };
+            report.push_str(&format!("{:<28} {:>12.4} {:>12.4} {:>12.4} {:>9}{}\n",
+                metric.name, metric.old, metric.new, metric.delta, change, marker));
+        }
+        
+        let regressions = self.regressions().len();
+        if regressions > 0 {
+            report.push_str(&format!("\n{}项指标变差超过{:.0}%\n", regressions, self.regression_threshold * 100.0));
+        }
+        
+        if let Some(symbols) = &self.symbols {
+            report.push_str(&format!("\n交易次数变化的股票: {}\n", symbols.len()));
+            for symbol in symbols {
+                let diff = symbol.new_trades as i64 - symbol.old_trades as i64;
+                report.push_str(&format!("{:<10} {:>6} -> {:<6} ({:+})\n",
+                    symbol.symbol, symbol.old_trades, symbol.new_trades, diff));
+            }
+        }
+        
+        report
+    }
+}
+
+impl BacktestResult {
+    /// 与另一个结果对比，self为旧结果，other为新结果
+    pub fn compare(&self, other: &BacktestResult) -> ResultComparison {
+        use Direction::{Maximize, Minimize};
+        
+        let metrics = vec![
+            MetricDelta::new("total_trades", self.total_trades as f32, other.total_trades as f32, None),
+            MetricDelta::new("winning_trades", self.winning_trades as f32, other.winning_trades as f32, None),
+            MetricDelta::new("losing_trades", self.losing_trades as f32, other.losing_trades as f32, None),
+            MetricDelta::new("stop_loss_trades", self.stop_loss_trades as f32, other.stop_loss_trades as f32, None),
+            MetricDelta::new("stop_loss_fail_trades", self.stop_loss_fail_trades as f32, other.stop_loss_fail_trades as f32, None),
+            MetricDelta::new("win_rate", self.win_rate, other.win_rate, Some(Maximize)),
+            MetricDelta::new("stop_loss_rate", self.stop_loss_rate, other.stop_loss_rate, Some(Minimize)),
+            MetricDelta::new("stop_loss_fail_rate", self.stop_loss_fail_rate, other.stop_loss_fail_rate, Some(Minimize)),
+            MetricDelta::new("avg_return", self.avg_return, other.avg_return, Some(Maximize)),
+            MetricDelta::new("max_return", self.max_return, other.max_return, Some(Maximize)),
+            // 最大亏损为负数，越接近0越好
+            MetricDelta::new("max_loss", self.max_loss, other.max_loss, Some(Maximize)),
+            MetricDelta::new("avg_hold_days", self.avg_hold_days, other.avg_hold_days, None),
+            MetricDelta::new("sharpe_ratio", self.sharpe_ratio, other.sharpe_ratio, Some(Maximize)),
+            MetricDelta::new("max_drawdown", self.max_drawdown, other.max_drawdown, Some(Minimize)),
+            MetricDelta::new("profit_factor", self.profit_factor, other.profit_factor, Some(Maximize)),
+            MetricDelta::new("sortino_ratio", self.sortino_ratio, other.sortino_ratio, Some(Maximize)),
+            MetricDelta::new("calmar_ratio", self.calmar_ratio, other.calmar_ratio, Some(Maximize)),
+            MetricDelta::new("expectancy", self.expectancy, other.expectancy, Some(Maximize)),
+            MetricDelta::new("max_drawdown_duration_days", self.max_drawdown_duration_days as f32, other.max_drawdown_duration_days as f32, Some(Minimize)),
+            MetricDelta::new("time_underwater_ratio", self.time_underwater_ratio, other.time_underwater_ratio, Some(Minimize)),
+            MetricDelta::new("longest_recovery_days", self.longest_recovery_days as f32, other.longest_recovery_days as f32, Some(Minimize)),
+            MetricDelta::new("annualized_return", self.annualized_return, other.annualized_return, Some(Maximize)),
+            MetricDelta::new("annualized_volatility", self.annualized_volatility, other.annualized_volatility, Some(Minimize)),
+            MetricDelta::new("trades_per_month", self.trades_per_month, other.trades_per_month, None),
+        ];
+        
+        ResultComparison {
+            metrics,
+            symbols: self.symbol_deltas(other),
+            regression_threshold: DEFAULT_REGRESSION_THRESHOLD,
+        }
+    }
+    
+    /// 按股票对比交易次数，只保留有变化的股票(按代码排序)
+    fn symbol_deltas(&self, other: &BacktestResult) -> Option<Vec<SymbolDelta>> {
+        let (old, new) = (self.trade_details.as_ref()?, other.trade_details.as_ref()?);
+        
+        let mut counts: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+        for detail in old {
+            counts.entry(&detail.symbol).or_default().0 += 1;
+        }
+        for detail in new {
+            counts.entry(&detail.symbol).or_default().1 += 1;
+        }
+        
+        Some(counts.into_iter()
+            .filter(|(_, (old_trades, new_trades))| old_trades != new_trades)
+            .map(|(symbol, (old_trades, new_trades))| SymbolDelta {
+                symbol: symbol.to_string(),
+                old_trades,
+                new_trades,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::result::{ExitReason, TradeDetail};
+
+    fn trade(symbol: &str) -> TradeDetail {
+        TradeDetail {
+            symbol: symbol.to_string(),
+            direction: crate::targets::Direction::Long,
+            entry_date: "20240102".to_string(),
+            entry_price: 10.0,
+            exit_date: "20240103".to_string(),
+            exit_price: 10.2,
+            filled_entry_price: 10.0,
+            filled_exit_price: 10.2,
+            peak_price: 10.2,
+            return_pct: 0.02,
+            hold_days: 1,
+            exit_reason: ExitReason::TargetReached,
+            win: true,
+            slippage: 0.0,
+            cost: 0.0,
+            benchmark_return_pct: None,
+            legs: None,
+        }
+    }
+
+    fn before() -> BacktestResult {
+        BacktestResult {
+            total_trades: 10,
+            winning_trades: 6,
+            losing_trades: 4,
+            win_rate: 0.6,
+            avg_return: 0.02,
+            max_drawdown: 0.1,
+            profit_factor: f32::INFINITY,
+            trade_details: Some(vec![trade("000001.SZ"), trade("000001.SZ"), trade("600000.SH")]),
+            ..BacktestResult::default()
+        }
+    }
+
+    fn after() -> BacktestResult {
+        BacktestResult {
+            total_trades: 12,
+            winning_trades: 6,
+            losing_trades: 6,
+            win_rate: 0.5,
+            avg_return: 0.021,
+            max_drawdown: 0.08,
+            profit_factor: f32::INFINITY,
+            trade_details: Some(vec![trade("000001.SZ"), trade("600000.SH"), trade("830799.BJ")]),
+            ..BacktestResult::default()
+        }
+    }
+
+    #[test]
+    fn comparison_flags_regressions() {
+        let comparison = before().compare(&after());
+        
+        let win_rate = comparison.metric("win_rate").unwrap();
+        assert!((win_rate.delta + 0.1).abs() < 1e-6);
+        assert!((win_rate.pct_change.unwrap() + 1.0 / 6.0).abs() < 1e-5);
+        // 盈亏比两边都是无穷大，不算变化
+        assert_eq!(comparison.metric("profit_factor").unwrap().delta, 0.0);
+        
+        let flagged: Vec<&str> = comparison.regressions().iter().map(|metric| metric.name).collect();
+        assert_eq!(flagged, vec!["win_rate"]);
+        assert_eq!(comparison.symbols, Some(vec![
+            SymbolDelta { symbol: "000001.SZ".to_string(), old_trades: 2, new_trades: 1 },
+            SymbolDelta { symbol: "830799.BJ".to_string(), old_trades: 0, new_trades: 1 },
+        ]));
+        
+        let mut relaxed = comparison.clone();
+        relaxed.set_regression_threshold(0.2);
+        assert!(relaxed.regressions().is_empty());
+    }
+
+    #[test]
+    fn comparison_table_snapshot() {
+        let table = before().compare(&after()).format_comparison();
+        
+        let expected = concat!(
+            "metric                                old          new        delta    change\n",
+            "total_trades                      10.0000      12.0000       2.0000    20.00%\n",
+            "winning_trades                     6.0000       6.0000       0.0000     0.00%\n",
+            "losing_trades                      4.0000       6.0000       2.0000    50.00%\n",
+            "stop_loss_trades                   0.0000       0.0000       0.0000         -\n",
+            "stop_loss_fail_trades              0.0000       0.0000       0.0000         -\n",
+            "win_rate                           0.6000       0.5000      -0.1000   -16.67% !!\n",
+            "stop_loss_rate                     0.0000       0.0000       0.0000         -\n",
+            "stop_loss_fail_rate                0.0000       0.0000       0.0000         -\n",
+            "avg_return                         0.0200       0.0210       0.0010     5.00%\n",
+            "max_return                         0.0000       0.0000       0.0000         -\n",
+            "max_loss                           0.0000       0.0000       0.0000         -\n",
+            "avg_hold_days                      0.0000       0.0000       0.0000         -\n",
+            "sharpe_ratio                       0.0000       0.0000       0.0000         -\n",
+            "max_drawdown                       0.1000       0.0800      -0.0200   -20.00%\n",
+            "profit_factor                         inf          inf       0.0000         -\n",
+            "sortino_ratio                      0.0000       0.0000       0.0000         -\n",
+            "calmar_ratio                       0.0000       0.0000       0.0000         -\n",
+            "expectancy                         0.0000       0.0000       0.0000         -\n",
+            "max_drawdown_duration_days         0.0000       0.0000       0.0000         -\n",
+            "time_underwater_ratio              0.0000       0.0000       0.0000         -\n",
+            "longest_recovery_days              0.0000       0.0000       0.0000         -\n",
+            "annualized_return                  0.0000       0.0000       0.0000         -\n",
+            "annualized_volatility              0.0000       0.0000       0.0000         -\n",
+            "trades_per_month                   0.0000       0.0000       0.0000         -\n",
+            "\n",
+            "1项指标变差超过10%\n",
+            "\n",
+            "交易次数变化的股票: 2\n",
+            "000001.SZ       2 -> 1      (-1)\n",
+            "830799.BJ       0 -> 1      (+1)\n",
+        );
+        assert_eq!(table, expected);
+    }
+}
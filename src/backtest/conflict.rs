@@ -0,0 +1,77 @@
+use crate::backtest::result::ExitReason;
+use crate::targets::TradeOutcome;
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use serde::{Deserialize, Serialize};
+
+/// 模糊交易在胜率中按半笔盈利计算
+const AMBIGUOUS_WIN_WEIGHT: f32 = 0.5;
+
+/// 同一根K线既触及止损价又达到目标价时的处理规则
+///
+/// 日线无法区分两者的先后，规则只作用于卖出当天的K线
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// 按达到目标处理，收益率为目标收益率
+    TargetFirst,
+    /// 按止损处理，收益率为止损比例，用于查看最坏情况
+    StopFirst,
+    /// 收益率取两者的平均，胜率中按半笔盈利计算，退出原因记为Ambiguous
+    Proportional,
+}
+
+impl ConflictPolicy {
+    /// 按名称查找: target_first、stop_first或proportional
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "target_first" => Some(ConflictPolicy::TargetFirst),
+            "stop_first" => Some(ConflictPolicy::StopFirst),
+            "proportional" => Some(ConflictPolicy::Proportional),
+            _ => None,
+        }
+    }
+    
+    /// 卖出当天的K线同时触及止损价和目标价时按规则改写交易结果
+    ///
+    /// 没有止盈的目标(target_return不大于0)和非止盈止损的退出不受影响
+    pub fn resolve(&self, outcome: &mut TradeOutcome, bar: &DailyBar, buy_price: f32, target_return: f32, stop_loss: f32) {
+        if target_return <= 0.0 || buy_price <= 0.0 {
+            return;
+        }
+        if !matches!(outcome.exit_reason, ExitReason::TargetReached | ExitReason::StopLoss | ExitReason::StopLossFailed) {
+            return;
+        }
+        if bar.low > buy_price * (1.0 - stop_loss) || bar.high < buy_price * (1.0 + target_return) {
+            return;
+        }
+        
+        match self {
+            // 目标自身已按同一规则判定的交易保持原有收益率
+            ConflictPolicy::TargetFirst if outcome.is_win => {}
+            ConflictPolicy::StopFirst if !outcome.is_win => {}
+            ConflictPolicy::TargetFirst => {
+                outcome.return_pct = target_return;
+                outcome.is_win = true;
+                outcome.is_stop_loss = false;
+                outcome.exit_reason = ExitReason::TargetReached;
+            }
+            ConflictPolicy::StopFirst => {
+                outcome.return_pct = -stop_loss;
+                outcome.is_win = false;
+                outcome.is_stop_loss = true;
+                outcome.exit_reason = ExitReason::StopLoss;
+            }
+            ConflictPolicy::Proportional => {
+                outcome.return_pct = (target_return - stop_loss) / 2.0;
+                outcome.is_win = false;
+                outcome.is_stop_loss = false;
+                outcome.exit_reason = ExitReason::Ambiguous;
+            }
+        }
+    }
+}
+
+/// 计入模糊交易后的盈利笔数，用于计算胜率
+pub fn weighted_wins(winning_trades: usize, ambiguous_trades: usize) -> f32 {
+    winning_trades as f32 + AMBIGUOUS_WIN_WEIGHT * ambiguous_trades as f32
+}
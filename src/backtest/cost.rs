@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+/// 交易成本模型，费率均为成交金额的比例
+///
+/// 佣金和过户费买卖双向收取，印花税只在卖出时收取
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostModel {
+    /// 佣金费率
+    pub commission_rate: f32,
+    /// 单笔最低佣金(元)，只在trade_value大于0时生效
+    pub min_commission: f32,
+    /// 卖出印花税率
+    pub stamp_duty_rate: f32,
+    /// 过户费率
+    pub transfer_fee_rate: f32,
+    /// 每笔交易的买入金额(元)，用于计算最低佣金
+    #[serde(default)]
+    pub trade_value: f32,
+}
+
+impl CostModel {
+    /// A股常用费率: 佣金万2.5(最低5元)、卖出印花税0.05%、过户费0.001%，按每笔10万元计算最低佣金
+    pub fn a_share() -> Self {
+        Self {
+            commission_rate: 0.00025,
+            min_commission: 5.0,
+            stamp_duty_rate: 0.0005,
+            transfer_fee_rate: 0.00001,
+            trade_value: 100_000.0,
+        }
+    }
+    
+    /// 是否不产生任何成本
+    pub fn is_zero(&self) -> bool {
+        self.commission_rate == 0.0
+            && self.stamp_duty_rate == 0.0
+            && self.transfer_fee_rate == 0.0
+            && (self.min_commission == 0.0 || self.trade_value <= 0.0)
+    }
+    
+    /// 扣除买卖成本后的收益率
+    ///
+    /// 买入金额加买入成本为投入，卖出金额减卖出成本为回收；零成本时原样返回
+    pub fn net_return(&self, gross_return: f32) -> f32 {
+        if self.is_zero() {
+            return gross_return;
+        }
+        
+        let buy_value = if self.trade_value > 0.0 { self.trade_value as f64 } else { 1.0 };
+        let sell_value = buy_value * (1.0 + gross_return as f64);
+        
+        let buy_cost = self.commission(buy_value) + buy_value * self.transfer_fee_rate as f64;
+        let sell_cost = self.commission(sell_value)
+            + sell_value * (self.stamp_duty_rate as f64 + self.transfer_fee_rate as f64);
+        
+        ((sell_value - sell_cost) / (buy_value + buy_cost) - 1.0) as f32
+    }
+    
+    /// 成本对收益率的拖累，即毛收益率与净收益率之差
+    pub fn cost(&self, gross_return: f32) -> f32 {
+        gross_return - self.net_return(gross_return)
+    }
+    
+    /// 单边佣金
+    fn commission(&self, value: f64) -> f64 {
+        let commission = value * self.commission_rate as f64;
+        if self.trade_value > 0.0 {
+            commission.max(self.min_commission as f64)
+        } else {
+            commission
+        }
+    }
+}
@@ -1,32 +1,187 @@
-use crate::stock::data_provider::StockDataProvider;
+use crate::stock::adjust::AdjustMode;
+use crate::stock::calendar::TradingCalendar;
+use crate::stock::limits::{self, LimitBand};
+use crate::stock::coverage::{DataQualityReport, SymbolCoverage};
+use crate::stock::retry::{FetchError, LoadReport, RetryPolicy};
+use crate::stock::series::{self, BarOrder, BarSlice};
+use crate::stock::snapshot::{SurvivorshipReport, UniverseSnapshot};
+use crate::stock::data_provider::{
+    log_load_report, PriceRejection, SharedBars, RefreshReport, StockDataProvider, StockFilterConfig, StockMeta,
+};
+use crate::stock::source::{DataSource, MockDataSource};
+use crate::stock::validate::{self, ValidationPolicy, ValidationReport};
 use crate::strategies::StockSelector;
 use crate::signals::BuySignalGenerator;
-use crate::targets::Target;
-use crate::backtest::result::BacktestResult;
+use crate::targets::{self, short_target, summarize_outcomes, Direction, Target, TradeOutcome};
+use crate::backtest::conflict::{self, ConflictPolicy};
+use crate::backtest::cost::CostModel;
+use crate::backtest::execution::ExitExecution;
+use crate::backtest::portfolio::{PortfolioBacktest, PortfolioResult, PortfolioTrade};
+use crate::backtest::progress::{ProgressCallback, ProgressEvent};
+use crate::backtest::slippage::SlippageModel;
+use crate::backtest::walk_forward::{WalkForwardConfig, WalkForwardFold, WalkForwardResult};
+use crate::backtest::result::{self, BacktestResult, DailyScore, ExitReason, ExitReasonBreakdown, PnlAttribution, TradeDetail, TradeLeg, TradeTiming};
 use egostrategy_datahub::models::stock::DailyData as DailyBar;
 use std::sync::Arc;
 use rayon::prelude::*;
-use log::{info, debug};
-use std::collections::HashMap;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use log::{info, debug, warn};
+use crate::utils::csv;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// 买入信号: (代码, K线, 买入价)
+type Signals = Vec<(String, BarSlice, f32)>;
+
+/// 选股结果: (代码, K线)
+type Candidates = Vec<(String, BarSlice)>;
+
+/// 一个回测日
+#[derive(Debug, Clone, Copy)]
+struct TestDay {
+    /// 买入日在传入的各股票K线中的位置
+    forecast_idx: usize,
+    /// 按交易日历距最新交易日的天数，决定随机滑点
+    day_idx: usize,
+    /// 买入日期，交易日历为空时为None
+    entry_date: Option<i32>,
+}
+
+/// 默认允许最新K线落后全局最新交易日的交易日数
+pub const DEFAULT_MAX_STALENESS_DAYS: usize = 5;
+
+/// 导出股票池时计算平均成交额的天数
+const UNIVERSE_AMOUNT_DAYS: usize = 20;
+
+/// 并行度配置
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParallelismConfig {
+    /// 组合级并行的线程数，None表示使用rayon全局线程池
+    pub combination_threads: Option<usize>,
+    /// 引擎内部(数据加载、选股)并行的线程数，None表示使用rayon全局线程池
+    pub inner_threads: Option<usize>,
+}
+
+impl ParallelismConfig {
+    /// 按线程数创建独立线程池，未指定时返回None
+    pub fn build_pool(threads: Option<usize>) -> anyhow::Result<Option<ThreadPool>> {
+        match threads {
+            Some(n) => Ok(Some(ThreadPoolBuilder::new().num_threads(n).build()?)),
+            None => Ok(None),
+        }
+    }
+}
 
 /// 统一的回测引擎
 pub struct BacktestEngine {
-    data_provider: Arc<StockDataProvider>,
-    stock_data: HashMap<String, Vec<DailyBar>>,
+    data_provider: Arc<dyn DataSource>,
+    stock_data: Vec<(String, Arc<Vec<DailyBar>>)>,
     cache_enabled: bool,
     collect_trade_details: bool,
+    inner_pool: Option<ThreadPool>,
+    filter_config: StockFilterConfig,
+    min_history: usize,
+    history_bars: Option<usize>,
+    benchmark: Option<Arc<Vec<DailyBar>>>,
+    adjust_mode: AdjustMode,
+    validation_policy: ValidationPolicy,
+    validation_reports: Vec<ValidationReport>,
+    survivorship: Option<SurvivorshipReport>,
+    retry_policy: RetryPolicy,
+    /// 重试后仍失败的股票占比超过该百分比时中止加载
+    max_failure_pct: Option<f32>,
+    load_report: Option<LoadReport>,
+    calendar: TradingCalendar,
+    /// 详细回测中逐笔收益计入每日收益曲线的方式
+    pnl_attribution: PnlAttribution,
+    /// 详细回测中从逐笔收益扣除的交易成本
+    cost_model: CostModel,
+    /// 详细回测中买卖成交的滑点
+    slippage: SlippageModel,
+    /// 回测是否考虑涨跌停无法成交
+    price_limit_rules: bool,
+    /// 同一天触及止损价和目标价时的处理规则，None时沿用目标自身的判定
+    conflict_policy: Option<ConflictPolicy>,
+    /// 固定止损的成交方式
+    exit_execution: ExitExecution,
+    /// 最新K线落后全局最新交易日超过该交易日数的股票不参与回测，None时不检查
+    max_staleness_days: Option<usize>,
+    data_quality: Option<DataQualityReport>,
+    /// 回测进度回调
+    progress: Option<ProgressCallback>,
 }
 
 impl BacktestEngine {
-    /// 创建新的回测引擎
-    pub fn new(cache_enabled: bool) -> anyhow::Result<Self> {
-        let data_provider = Arc::new(StockDataProvider::new()?);
-        Ok(Self {
+    /// 创建新的回测引擎，max_staleness_days见with_source
+    pub fn new(cache_enabled: bool, max_staleness_days: Option<usize>) -> anyhow::Result<Self> {
+        Ok(Self::with_source(Arc::new(StockDataProvider::new()?), cache_enabled, max_staleness_days))
+    }
+    
+    /// 使用指定数据源创建回测引擎
+    ///
+    /// 加载数据后，最新K线落后全局最新交易日超过max_staleness_days个交易日的股票(停止更新、退市)不参与回测
+    pub fn with_source(data_provider: Arc<dyn DataSource>, cache_enabled: bool, max_staleness_days: Option<usize>) -> Self {
+        Self {
             data_provider,
-            stock_data: HashMap::new(),
+            stock_data: Vec::new(),
             cache_enabled,
             collect_trade_details: false,
-        })
+            inner_pool: None,
+            filter_config: StockFilterConfig::default(),
+            min_history: 120,
+            history_bars: None,
+            benchmark: None,
+            adjust_mode: AdjustMode::None,
+            validation_policy: ValidationPolicy::default(),
+            validation_reports: Vec::new(),
+            survivorship: None,
+            retry_policy: RetryPolicy::default(),
+            max_failure_pct: None,
+            load_report: None,
+            calendar: TradingCalendar::default(),
+            pnl_attribution: PnlAttribution::default(),
+            cost_model: CostModel::default(),
+            slippage: SlippageModel::default(),
+            price_limit_rules: false,
+            conflict_policy: None,
+            exit_execution: ExitExecution::default(),
+            max_staleness_days,
+            data_quality: None,
+            progress: None,
+        }
+    }
+    
+    /// 使用外部数据创建回测引擎，例如从CSV加载的K线，数据无需再调用load_data
+    pub fn with_data(stock_data: Vec<(String, Vec<DailyBar>)>, max_staleness_days: Option<usize>) -> anyhow::Result<Self> {
+        let source = MockDataSource::new(stock_data.clone());
+        let mut engine = Self::with_source(Arc::new(source), true, max_staleness_days);
+        
+        engine.stock_data = stock_data.into_iter()
+            .map(|(symbol, bars)| {
+                series::assert_order(&bars, BarOrder::NewestFirst);
+                (symbol, Arc::new(bars))
+            })
+            .collect();
+        // 按代码排序，回测结果不依赖传入顺序
+        engine.stock_data.sort_by(|(a, _), (b, _)| a.cmp(b));
+        engine.drop_stale_symbols();
+        
+        Ok(engine)
+    }
+    
+    /// 设置引擎内部并行使用的线程数，None表示使用rayon全局线程池
+    pub fn set_inner_threads(&mut self, threads: Option<usize>) -> anyhow::Result<()> {
+        self.inner_pool = ParallelismConfig::build_pool(threads)?;
+        Ok(())
+    }
+    
+    /// 在引擎内部线程池中执行
+    fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.inner_pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
     }
     
     /// 设置是否收集交易详情
@@ -34,47 +189,612 @@ impl BacktestEngine {
         self.collect_trade_details = collect;
     }
     
+    /// 设置股票池过滤规则，需在load_data之前调用
+    pub fn set_filter_config(&mut self, config: StockFilterConfig) {
+        self.filter_config = config;
+    }
+    
+    /// 设置复权方式，需在load_data之前调用
+    pub fn set_adjust_mode(&mut self, mode: AdjustMode) {
+        self.adjust_mode = mode;
+    }
+    
+    /// 加载复权因子文件，需在load_data之前调用
+    pub fn load_adjust_factors(&self, path: &Path) -> anyhow::Result<usize> {
+        self.data_provider.load_adjust_factors(path)
+    }
+    
+    /// 设置数据质量问题的处理策略，需在load_data之前调用
+    pub fn set_validation_policy(&mut self, policy: ValidationPolicy) {
+        self.validation_policy = policy;
+    }
+    
+    /// 最近一次加载中存在数据质量问题的股票报告
+    pub fn validation_reports(&self) -> &[ValidationReport] {
+        &self.validation_reports
+    }
+    
+    /// 设置获取K线时暂时性错误的重试策略
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = policy;
+    }
+    
+    /// 设置允许的获取失败比例(百分比)，超过时load_data返回错误；None表示不限制
+    pub fn set_max_failure_pct(&mut self, pct: Option<f32>) {
+        self.max_failure_pct = pct;
+    }
+    
+    /// 设置详细回测中逐笔收益计入每日收益曲线的方式，默认计入卖出当天
+    pub fn set_pnl_attribution(&mut self, attribution: PnlAttribution) {
+        self.pnl_attribution = attribution;
+    }
+    
+    /// 设置交易成本模型，详细回测的收益率按扣除成本后计算；默认零成本
+    pub fn set_cost_model(&mut self, model: CostModel) {
+        self.cost_model = model;
+    }
+    
+    /// 当前交易成本模型
+    pub fn cost_model(&self) -> &CostModel {
+        &self.cost_model
+    }
+    
+    /// 设置成交滑点，详细回测的买入价上浮、卖出价下浮；默认不计滑点
+    pub fn set_slippage(&mut self, slippage: SlippageModel) {
+        self.slippage = slippage;
+    }
+    
+    /// 当前滑点模型
+    pub fn slippage(&self) -> &SlippageModel {
+        &self.slippage
+    }
+    
+    /// 设置回测是否考虑涨跌停: 涨停价买入视为无法成交，止损当天封死跌停则顺延到下一交易日开盘卖出
+    pub fn set_price_limit_rules(&mut self, enabled: bool) {
+        self.price_limit_rules = enabled;
+    }
+    
+    /// 设置同一天触及止损价和目标价时的处理规则，评分卡和详细回测都按规则改写交易结果
+    pub fn set_conflict_policy(&mut self, policy: ConflictPolicy) {
+        self.conflict_policy = Some(policy);
+    }
+    
+    /// 设置回测进度回调，run_backtest和run_detailed_backtest每完成一个回测日调用一次
+    pub fn set_progress_callback(&mut self, callback: ProgressCallback) {
+        self.progress = Some(callback);
+    }
+    
+    /// 发出进度事件，未设置回调时忽略
+    pub(crate) fn report_progress(&self, event: ProgressEvent) {
+        if let Some(callback) = &self.progress {
+            callback(event);
+        }
+    }
+    
+    /// 当前的止盈止损冲突规则
+    pub fn conflict_policy(&self) -> Option<ConflictPolicy> {
+        self.conflict_policy
+    }
+    
+    /// 设置固定止损的成交方式
+    pub fn set_exit_execution(&mut self, execution: ExitExecution) {
+        self.exit_execution = execution;
+    }
+    
+    /// 当前固定止损的成交方式
+    pub fn exit_execution(&self) -> ExitExecution {
+        self.exit_execution
+    }
+    
+    /// 是否需要由逐笔结果重新汇总，而不是直接使用目标的评估结果
+    fn adjusts_outcomes(&self) -> bool {
+        self.price_limit_rules || self.conflict_policy.is_some() || self.exit_execution != ExitExecution::IntradayTouch
+    }
+    
+    /// 逐笔评估信号，并按冲突规则、止损成交方式和涨跌停规则调整
+    fn trade_outcomes(&self, signals: &Signals, target: &dyn Target, forecast_idx: usize) -> Vec<TradeOutcome> {
+        let direction = target.direction();
+        let mut outcomes = target.evaluate_trades(signals, forecast_idx);
+        if let Some(policy) = self.conflict_policy {
+            for outcome in outcomes.iter_mut() {
+                let (_, data, buy_price) = &signals[outcome.signal_idx];
+                // 做空时按镜像K线判断，与目标评估时一致
+                let bar = match direction {
+                    Direction::Long => data[outcome.exit_idx].clone(),
+                    Direction::Short => short_target::mirror_bar(&data[outcome.exit_idx], *buy_price),
+                };
+                policy.resolve(outcome, &bar, *buy_price, target.target_return(), target.stop_loss());
+            }
+        }
+        
+        // 持有期最后一天的下标，收盘后下单时卖出日可能在其之后
+        let window_end = forecast_idx - target.in_days().min(forecast_idx);
+        for outcome in outcomes.iter_mut() {
+            let (_, data, buy_price) = &signals[outcome.signal_idx];
+            self.exit_execution.resolve(outcome, data, *buy_price, target, window_end);
+        }
+        
+        self.defer_locked_exits(signals, outcomes, direction)
+    }
+    
+    /// 股票的涨跌停幅度，没有元数据时按非ST处理
+    fn price_band(&self, symbol: &str) -> LimitBand {
+        match self.limit_band(symbol) {
+            Some(pct) => LimitBand { pct },
+            None => LimitBand::for_symbol(symbol, false),
+        }
+    }
+    
+    /// 去掉无法开仓的信号: 做多时买入价达到涨停价，做空时开仓价达到跌停价，返回保留的信号和放弃的数量
+    fn reject_limit_entries(&self, signals: Signals, forecast_idx: usize, direction: Direction) -> (Signals, usize) {
+        if !self.price_limit_rules {
+            return (signals, 0);
+        }
+        
+        let total = signals.len();
+        let signals: Signals = signals.into_iter()
+            // 买入在forecast_idx - 1成交，前收盘价为信号日forecast_idx的收盘价
+            .filter(|(symbol, data, buy_price)| match data.get(forecast_idx) {
+                Some(prev) => match direction {
+                    Direction::Long => !limits::at_limit_up(*buy_price, prev.close, self.price_band(symbol)),
+                    Direction::Short => !limits::at_limit_down(*buy_price, prev.close, self.price_band(symbol)),
+                },
+                None => true,
+            })
+            .collect();
+        let rejected = total - signals.len();
+        
+        (signals, rejected)
+    }
+    
+    /// 止损当天无法平仓(做多封死跌停、做空封死涨停)时，改为下一交易日开盘价平仓
+    fn defer_locked_exits(&self, signals: &Signals, mut outcomes: Vec<TradeOutcome>, direction: Direction) -> Vec<TradeOutcome> {
+        if !self.price_limit_rules {
+            return outcomes;
+        }
+        
+        for outcome in outcomes.iter_mut() {
+            if !matches!(outcome.exit_reason, ExitReason::StopLoss | ExitReason::StopLossFailed) || outcome.exit_idx == 0 {
+                continue;
+            }
+            let (symbol, data, buy_price) = &signals[outcome.signal_idx];
+            let (Some(exit_bar), Some(prev)) = (data.get(outcome.exit_idx), data.get(outcome.exit_idx + 1)) else {
+                continue;
+            };
+            let locked = match direction {
+                Direction::Long => limits::is_locked_limit_down(exit_bar, prev.close, self.price_band(symbol)),
+                Direction::Short => limits::is_locked_limit_up(exit_bar, prev.close, self.price_band(symbol)),
+            };
+            if !locked {
+                continue;
+            }
+            
+            let next = &data[outcome.exit_idx - 1];
+            outcome.exit_idx -= 1;
+            outcome.hold_days += 1;
+            outcome.return_pct = match direction {
+                Direction::Long => (next.open - buy_price) / buy_price,
+                Direction::Short => (buy_price - next.open) / buy_price,
+            };
+            outcome.exit_reason = ExitReason::LimitLocked;
+        }
+        
+        outcomes
+    }
+    
+    /// 逐笔毛收益率扣除滑点和交易成本后的净收益率，trade_idx为该笔在本回测日的序号
+    fn net_return(&self, gross_return: f32, day_idx: usize, trade_idx: usize) -> f32 {
+        self.cost_model.net_return(self.slippage.filled_return(gross_return, day_idx, trade_idx))
+    }
+    
+    /// 最近一次加载的获取结果
+    pub fn load_report(&self) -> Option<&LoadReport> {
+        self.load_report.as_ref()
+    }
+    
+    /// 按历史快照加载时的幸存者偏差统计
+    pub fn survivorship(&self) -> Option<&SurvivorshipReport> {
+        self.survivorship.as_ref()
+    }
+    
+    /// 设置加载数据所需的最少K线数量，需在load_data之前调用
+    pub fn set_min_history(&mut self, min_history: usize) {
+        self.min_history = min_history;
+    }
+    
+    /// 设置每只股票保留的最近K线数量，None表示保留全部，需在load_data之前调用
+    pub fn set_history_bars(&mut self, history_bars: Option<usize>) {
+        self.history_bars = history_bars;
+    }
+    
+    /// 加载时每只股票需要保留的K线数量，至少为min_history
+    fn history_depth(&self) -> Option<usize> {
+        self.history_bars.map(|bars| bars.max(self.min_history))
+    }
+    
+    /// 按配置的深度获取日线数据，暂时性错误按重试策略重试
+    fn fetch_bars(&self, symbol: &str) -> Result<Arc<Vec<DailyBar>>, FetchError> {
+        let bars = self.retry_policy.run(symbol, || {
+            self.data_provider.try_daily_bars(symbol, self.adjust_mode)
+        })?;
+        
+        match self.history_depth() {
+            Some(depth) if bars.len() > depth => Ok(Arc::new(bars[..depth].to_vec())),
+            _ => Ok(bars),
+        }
+    }
+    
     /// 加载股票数据
-    pub fn load_data(&mut self) -> anyhow::Result<()> {
-        let symbols = self.data_provider.get_all_stocks();
-        let filtered_symbols = self.data_provider.filter_stocks(symbols);
+    pub fn load_data(&mut self) -> anyhow::Result<LoadReport> {
+        let symbols = self.data_provider.all_symbols();
+        let filtered_symbols = self.data_provider.filter_symbols(symbols, &self.filter_config);
+        
+        self.load_symbols(filtered_symbols)
+    }
+    
+    /// 按历史股票池快照加载数据，只回测快照日存在的股票
+    ///
+    /// 快照中存在但数据源中缺失的股票记入幸存者偏差统计
+    pub fn load_data_from_snapshot(&mut self, path: &Path) -> anyhow::Result<LoadReport> {
+        let snapshot = UniverseSnapshot::load(path)?;
+        let symbols = self.data_provider.filter_symbols(snapshot.symbols(), &self.filter_config);
+        let available: HashSet<String> = self.data_provider.all_symbols().into_iter().collect();
+        
+        let (present, report) = SurvivorshipReport::partition(snapshot.date, symbols, &available);
+        if report.missing_from_provider.is_empty() {
+            info!("股票池快照 {}: {} 只股票均可用", snapshot.date, report.snapshot_symbols);
+        } else {
+            warn!("股票池快照 {}: {} 只中 {} 只在数据源中缺失，幸存者偏差 {:.2}%",
+                snapshot.date, report.snapshot_symbols, report.missing_from_provider.len(), report.bias() * 100.0);
+        }
+        
+        self.survivorship = Some(report);
+        self.load_symbols(present)
+    }
+    
+    /// 从股票列表文件加载自定义股票池，不再应用代码前缀规则
+    ///
+    /// 未知代码默认只给出警告，strict为true时返回错误
+    pub fn load_symbols_from_file(&mut self, path: &Path, strict: bool) -> anyhow::Result<LoadReport> {
+        let universe = self.data_provider.load_universe_file(path)?;
+        
+        if !universe.unknown.is_empty() {
+            if strict {
+                anyhow::bail!("股票列表 {} 中存在未知代码: {}",
+                    path.display(), universe.unknown.join(", "));
+            }
+            warn!("股票列表 {} 中存在未知代码，已忽略: {}",
+                path.display(), universe.unknown.join(", "));
+        }
+        
+        self.load_symbols(universe.symbols)
+    }
+    
+    /// 获取单只股票的K线并做数据质量检查
+    fn load_symbol(&self, symbol: &str) -> Result<(Option<SharedBars>, ValidationReport), FetchError> {
+        let bars = self.fetch_bars(symbol)?;
+        let report = validate::validate_bars(symbol, &bars);
+        
+        // 没有需要处理的问题时直接共享数据源的K线
+        let bars = if report.has_bar_errors() {
+            validate::apply_policy(Arc::unwrap_or_clone(bars), &report, self.validation_policy).map(Arc::new)
+        } else {
+            Some(bars)
+        };
         
+        Ok((bars, report))
+    }
+    
+    /// 加载指定股票的K线，应用数据质量、历史长度和价格规则
+    fn load_symbols(&mut self, filtered_symbols: Vec<String>) -> anyhow::Result<LoadReport> {
         info!("Loading data for {} stocks", filtered_symbols.len());
         
         // 使用并行处理加速数据加载
-        if self.cache_enabled {
-            let stock_data: HashMap<String, Vec<DailyBar>> = filtered_symbols.par_iter()
-                .filter_map(|symbol| {
-                    self.data_provider.get_daily_bars(symbol)
-                        .filter(|bars| bars.len() >= 120)
-                        .map(|bars| (symbol.clone(), bars))
-                })
-                .collect();
-                
-            self.stock_data = stock_data;
+        let fetched: Vec<_> = if self.cache_enabled {
+            self.install(|| {
+                filtered_symbols.par_iter()
+                    .map(|symbol| (symbol.clone(), self.load_symbol(symbol)))
+                    .collect()
+            })
         } else {
-            for symbol in filtered_symbols {
-                if let Some(daily_bars) = self.data_provider.get_daily_bars(&symbol) {
-                    if daily_bars.len() >= 120 {  // 确保有足够的历史数据
-                        self.stock_data.insert(symbol.clone(), daily_bars.clone());
-                    }
-                }
+            filtered_symbols.iter()
+                .map(|symbol| (symbol.clone(), self.load_symbol(symbol)))
+                .collect()
+        };
+        
+        let mut load_report = LoadReport::default();
+        let mut loaded = Vec::new();
+        for (symbol, result) in fetched {
+            load_report.record(&symbol, &result);
+            if let Ok((bars, report)) = result {
+                loaded.push((symbol, bars, report));
+            }
+        }
+        log_load_report(&load_report);
+        if let Some(max_pct) = self.max_failure_pct {
+            if load_report.failure_pct() > max_pct {
+                anyhow::bail!("{} 只股票重试后仍获取失败({:.1}%)，超过允许的 {:.1}%",
+                    load_report.failed_after_retry.len(), load_report.failure_pct(), max_pct);
+            }
+        }
+        
+        let mut bar_counts: Vec<usize> = loaded.iter()
+            .filter_map(|(_, bars, _)| bars.as_ref().map(|bars| bars.len()))
+            .collect();
+        bar_counts.sort_unstable();
+        info!("数据覆盖: K线数量中位数 {}, 不足 {} 根的股票 {} 只",
+            bar_counts.get(bar_counts.len() / 2).copied().unwrap_or(0),
+            self.min_history,
+            bar_counts.iter().filter(|&&count| count < self.min_history).count());
+        
+        let mut reports = Vec::new();
+        let mut stock_data = Vec::new();
+        let (mut short_history, mut below_min, mut above_max) = (0, 0, 0);
+        for (symbol, bars, report) in loaded {
+            if !report.is_clean() {
+                reports.push(report);
             }
+            let Some(bars) = bars else {
+                continue;
+            };
+            series::assert_order(&bars, BarOrder::NewestFirst);
+            
+            // 确保有足够的历史数据，且最新价格在允许区间内
+            if bars.len() < self.min_history {
+                short_history += 1;
+                continue;
+            }
+            match self.filter_config.bars_rejection(&bars) {
+                Some(PriceRejection::BelowMin) => below_min += 1,
+                Some(PriceRejection::AboveMax) => above_max += 1,
+                None => stock_data.push((symbol, bars)),
+            }
+        }
+        
+        info!("加载过滤: 历史不足 {} 只, 低于最低价 {} 只, 高于最高价 {} 只",
+            short_history, below_min, above_max);
+        
+        for (rule, count) in validate::summarize(&reports) {
+            warn!("数据质量: {} {} 处", rule.name(), count);
         }
+        if !reports.is_empty() {
+            warn!("数据质量: {} 只股票存在问题，处理策略: {:?}", reports.len(), self.validation_policy);
+        }
+        
+        // 按代码排序，回测结果不依赖数据源返回的顺序
+        stock_data.sort_by(|(a, _), (b, _)| a.cmp(b));
+        self.stock_data = stock_data;
+        self.validation_reports = reports;
+        self.load_report = Some(load_report.clone());
+        self.drop_stale_symbols();
         
         info!("Loaded data for {} stocks", self.stock_data.len());
+        Ok(load_report)
+    }
+    
+    /// 增量刷新已加载股票的最新交易日数据，并用刷新后的缓存重建K线
+    pub fn refresh_data(&mut self) -> anyhow::Result<RefreshReport> {
+        let symbols: Vec<String> = self.stock_data.iter()
+            .map(|(symbol, _)| symbol.clone())
+            .collect();
+        
+        let report = self.data_provider.refresh_latest(&symbols)?;
+        self.load_symbols(symbols)?;
+        
+        Ok(report)
+    }
+    
+    /// 股票池(按过滤规则筛选后)中每只股票的数据覆盖情况
+    pub fn coverage_report(&self) -> Vec<SymbolCoverage> {
+        let symbols = self.data_provider.filter_symbols(self.data_provider.all_symbols(), &self.filter_config);
+        self.data_provider.coverage_report(&symbols)
+    }
+    
+    /// 检查已加载股票与全局最新交易日的对齐情况，去掉落后过多的股票并重建交易日历
+    fn drop_stale_symbols(&mut self) {
+        self.rebuild_calendar();
+        let report = DataQualityReport::new(
+            self.stock_data.iter().map(|(symbol, bars)| (symbol.as_str(), bars.as_slice())),
+            &self.calendar,
+            self.max_staleness_days,
+        );
+        
+        let stale: HashSet<String> = report.stale_symbols().iter().map(|alignment| alignment.symbol.clone()).collect();
+        if !stale.is_empty() {
+            warn!("数据对齐: {} 只股票最新K线落后 {} 超过 {} 个交易日，不参与回测",
+                stale.len(), report.latest_date.unwrap_or(0), self.max_staleness_days.unwrap_or(0));
+            self.stock_data.retain(|(symbol, _)| !stale.contains(symbol));
+            self.rebuild_calendar();
+        }
+        
+        self.data_quality = Some(report);
+    }
+    
+    /// 最近一次加载的数据对齐检查结果: 每只股票的最新K线日期、落后天数和缺失交易日数
+    pub fn data_quality_report(&self) -> Option<&DataQualityReport> {
+        self.data_quality.as_ref()
+    }
+    
+    /// 由已加载股票的日期并集重建交易日历
+    fn rebuild_calendar(&mut self) {
+        self.calendar = TradingCalendar::from_series(
+            self.stock_data.iter().map(|(_, bars)| bars.as_slice()));
+    }
+    
+    /// 由已加载数据得到的交易日历
+    pub fn calendar(&self) -> &TradingCalendar {
+        &self.calendar
+    }
+    
+    /// 回测范围内每个forecast_idx对应的交易日，按forecast_idx从小到大
+    pub fn backtest_dates(&self, target: &dyn Target, back_days: usize) -> Vec<Option<i32>> {
+        let Some(latest) = self.calendar.latest() else {
+            return vec![None; back_days];
+        };
+        
+        Self::backtest_range(target, back_days)
+            .map(|forecast_idx| self.calendar.offset(latest, -(forecast_idx as i64)))
+            .collect()
+    }
+    
+    /// 交易日历上最近back_days个可回测的交易日，按日期倒序，交易日历不足时少于back_days个
+    pub fn trading_dates(&self, target: &dyn Target, back_days: usize) -> Vec<i32> {
+        self.backtest_dates(target, back_days).into_iter().flatten().collect()
+    }
+    
+    /// 各股票截取K线使trade_date位于forecast_idx处
+    ///
+    /// 按每只股票自身的日期定位，当天没有K线(未上市、停牌缺行)或之后的K线不足forecast_idx根的股票不参与
+    fn stock_data_on_date(&self, trade_date: i32, forecast_idx: usize) -> Candidates {
+        self.stock_data
+            .iter()
+            .filter_map(|(symbol, bars)| {
+                // K线按日期倒序
+                let pos = bars.binary_search_by(|bar| trade_date.cmp(&bar.date)).ok()?;
+                (pos >= forecast_idx).then(|| (symbol.clone(), BarSlice::new(bars.clone()).skip(pos - forecast_idx)))
+            })
+            .collect()
+    }
+    
+    /// 按forecast_idx偏移的回测日
+    fn offset_day(&self, forecast_idx: usize) -> TestDay {
+        TestDay { forecast_idx, day_idx: forecast_idx, entry_date: self.entry_date(forecast_idx) }
+    }
+    
+    /// 按日期的回测日，K线已由stock_data_on_date截取
+    fn date_day(&self, trade_date: i32, forecast_idx: usize) -> TestDay {
+        let day_idx = self.calendar.latest().map_or(0, |latest| self.calendar.between(trade_date, latest));
+        TestDay { forecast_idx, day_idx, entry_date: Some(trade_date) }
+    }
+    
+    /// 注入基准指数K线(按日期倒序)
+    pub fn set_benchmark_bars(&mut self, bars: Vec<DailyBar>) {
+        self.benchmark = Some(Arc::new(bars));
+    }
+    
+    /// 按代码从数据提供者加载基准指数，每笔交易与同一买卖日期的基准收益比较
+    ///
+    /// 数据源没有该指数的K线时返回错误
+    pub fn set_benchmark(&mut self, symbol: &str) -> anyhow::Result<()> {
+        let bars = self.data_provider.index_bars(symbol)
+            .filter(|bars| !bars.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("基准指数 {} 没有K线数据", symbol))?;
+        
+        info!("基准指数 {}: {} 根K线", symbol, bars.len());
+        self.set_benchmark_bars(bars);
         Ok(())
     }
     
-    /// 获取股票数据
+    /// 从数据提供者加载基准指数，返回是否加载成功
+    pub fn load_benchmark(&mut self) -> bool {
+        match self.data_provider.benchmark() {
+            Some(bars) => {
+                self.set_benchmark_bars(bars);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// 基准指数从买入日收盘到卖出日收盘的收益率
+    ///
+    /// 某日没有指数K线时使用之前最近的一根，买入日早于全部指数数据时为None
+    fn benchmark_return(&self, entry_date: i32, exit_date: i32) -> Option<f32> {
+        let bars = self.benchmark.as_deref()?;
+        // 指数按日期倒序，取日期不晚于date的第一根
+        let close = |date: i32| bars.get(bars.partition_point(|bar| bar.date > date)).map(|bar| bar.close);
+        let entry = close(entry_date).filter(|&close| close > 0.0)?;
+        Some(close(exit_date)? / entry - 1.0)
+    }
+    
+    /// 单笔交易同期的基准收益率
+    fn trade_benchmark_return(&self, signals: &[(String, BarSlice, f32)], outcome: &TradeOutcome, forecast_idx: usize) -> Option<f32> {
+        let (_, data, _) = &signals[outcome.signal_idx];
+        self.benchmark_return(data[forecast_idx].date, data[outcome.exit_idx].date)
+    }
+    
+    /// 获取基准指数K线
+    pub fn benchmark(&self) -> Option<&[DailyBar]> {
+        self.benchmark.as_deref().map(|bars| bars.as_slice())
+    }
+    
+    /// 是否为ST股票
+    pub fn is_st(&self, symbol: &str) -> Option<bool> {
+        self.data_provider.is_st(symbol)
+    }
+    
+    /// 股票的涨跌停幅度
+    pub fn limit_band(&self, symbol: &str) -> Option<f32> {
+        self.data_provider.limit_band(symbol)
+    }
+    
+    /// 获取股票元数据
+    pub fn get_meta(&self, symbol: &str) -> Option<StockMeta> {
+        self.data_provider.meta(symbol)
+    }
+    
+    /// 获取股票名称
+    pub fn get_stock_name(&self, symbol: &str) -> Option<String> {
+        self.data_provider.meta(symbol).map(|meta| meta.name)
+    }
+    
+    /// 导出当前回测股票池: 每只已加载股票一行，包含名称、最新价、K线数和近期平均成交额
+    pub fn export_universe_csv(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        
+        let mut lines = vec![csv::join_row(&[
+            "symbol", "name", "last_date", "last_close", "bar_count", "avg_amount_20d",
+        ])];
+        
+        for (symbol, bars) in &self.stock_data {
+            // K线按日期倒序，前UNIVERSE_AMOUNT_DAYS根为最近的交易日
+            let recent = &bars[..bars.len().min(UNIVERSE_AMOUNT_DAYS)];
+            let avg_amount = if recent.is_empty() {
+                0.0
+            } else {
+                recent.iter().map(|bar| bar.amount as f64).sum::<f64>() / recent.len() as f64
+            };
+            
+            lines.push(csv::join_row(&[
+                symbol.clone(),
+                self.get_stock_name(symbol).unwrap_or_default(),
+                bars.first().map(|bar| bar.date.to_string()).unwrap_or_default(),
+                bars.first().map(|bar| bar.close.to_string()).unwrap_or_default(),
+                bars.len().to_string(),
+                format!("{:.0}", avg_amount),
+            ]));
+        }
+        
+        fs::write(path, lines.join("\n") + "\n")?;
+        
+        info!("回测股票池 {} 只已导出到 {}", self.stock_data.len(), path.display());
+        Ok(())
+    }
+    
+    /// 获取股票数据的共享视图，不复制K线
+    pub fn stock_data(&self) -> &[(String, Arc<Vec<DailyBar>>)] {
+        &self.stock_data
+    }
+    
+    /// 获取股票数据的K线视图，可直接传给选股策略，不复制K线
+    pub fn bar_slices(&self) -> Vec<(String, BarSlice)> {
+        self.stock_data
+            .iter()
+            .map(|(symbol, data)| (symbol.clone(), BarSlice::new(data.clone())))
+            .collect()
+    }
+    
+    /// 获取股票数据(复制一份完整数据)
     pub fn get_stock_data(&self) -> Vec<(String, Vec<DailyBar>)> {
         self.stock_data
             .iter()
-            .map(|(symbol, data)| (symbol.clone(), data.clone()))
+            .map(|(symbol, data)| (symbol.clone(), data.as_ref().clone()))
             .collect()
     }
     
-    /// 运行单次回测
+    /// 运行单次回测，forecast_idx为距最新一根K线的偏移，假定各股票K线日期对齐
     pub fn run_single_test(
         &self,
         selector: &dyn StockSelector,
@@ -82,30 +802,65 @@ impl BacktestEngine {
         target: &dyn Target,
         forecast_idx: usize,
     ) -> f32 {
-        let stock_data: Vec<(String, Vec<DailyBar>)> = self.stock_data
-            .iter()
-            .map(|(symbol, data)| (symbol.clone(), data.clone()))
-            .collect();
-            
+        self.single_test(&self.bar_slices(), selector, signal_generator, target, forecast_idx).0
+    }
+    
+    /// 在交易日trade_date(YYYYMMDD)运行单次回测，见stock_data_on_date
+    pub fn run_single_test_on_date(
+        &self,
+        selector: &dyn StockSelector,
+        signal_generator: &dyn BuySignalGenerator,
+        target: &dyn Target,
+        trade_date: i32,
+    ) -> f32 {
+        let forecast_idx = Self::backtest_range(target, 0).start;
+        let stock_data = self.stock_data_on_date(trade_date, forecast_idx);
+        self.single_test(&stock_data, selector, signal_generator, target, forecast_idx).0
+    }
+    
+    /// 在给定的K线上运行单次回测，返回成功率、交易数和盈利交易数
+    fn single_test(
+        &self,
+        stock_data: &[(String, BarSlice)],
+        selector: &dyn StockSelector,
+        signal_generator: &dyn BuySignalGenerator,
+        target: &dyn Target,
+        forecast_idx: usize,
+    ) -> (f32, usize, usize) {
         debug!("运行单次回测: 策略={}, 信号={}, 目标={}, 预测天数={}",
             selector.name(), signal_generator.name(), target.name(), forecast_idx);
             
         // 1. 选股
-        let candidates = selector.run(&stock_data, forecast_idx);
+        let candidates = self.install(|| selector.run(stock_data, forecast_idx));
         debug!("选股结果: 选出 {} 只股票", candidates.len());
         
         // 2. 生成买入信号
         let signals = signal_generator.generate_signals(candidates, forecast_idx);
         debug!("信号生成: 生成 {} 个买入信号", signals.len());
         
-        // 3. 评估目标
-        let success_rate = target.run(signals, forecast_idx);
-        debug!("目标评估: 成功率 = {:.2}%", success_rate * 100.0);
+        // 3. 评估目标，开启冲突或涨跌停规则时由调整后的逐笔结果计算成功率
+        let (success_rate, total_trades, winning_trades) = if self.adjusts_outcomes() {
+            let (signals, _) = self.reject_limit_entries(signals, forecast_idx, target.direction());
+            let outcomes = self.trade_outcomes(&signals, target, forecast_idx);
+            let winning_trades = outcomes.iter().filter(|outcome| outcome.is_win).count();
+            let ambiguous_trades = outcomes.iter().filter(|outcome| outcome.exit_reason == ExitReason::Ambiguous).count();
+            if outcomes.is_empty() {
+                (0.0, 0, 0)
+            } else {
+                (conflict::weighted_wins(winning_trades, ambiguous_trades) / outcomes.len() as f32, outcomes.len(), winning_trades)
+            }
+        } else {
+            // 与详细回测一致取自evaluate_signals，成功率为盈利交易数 / 交易数
+            let (total_trades, winning_trades, ..) = target.evaluate_signals(signals, forecast_idx);
+            let success_rate = if total_trades > 0 { winning_trades as f32 / total_trades as f32 } else { 0.0 };
+            (success_rate, total_trades, winning_trades)
+        };
+        debug!("目标评估: 成功率 = {:.2}%, 交易数 = {}", success_rate * 100.0, total_trades);
         
-        success_rate
+        (success_rate, total_trades, winning_trades)
     }
     
-    /// 运行回测
+    /// 在最近back_days个交易日上运行回测，得分为逐日成功率之和除以实际回测的天数
     pub fn run_backtest(
         &self,
         selector: &dyn StockSelector,
@@ -113,49 +868,331 @@ impl BacktestEngine {
         target: &dyn Target,
         back_days: usize,
     ) -> f32 {
-        // 修改范围，从target.in_days()+1开始，确保有足够的未来数据进行评估
-        // +1是因为T+1交易制度，需要额外一天用于买入
-        let range: Vec<usize> = (target.in_days()+1..target.in_days()+1+back_days).collect();
-        
-        let total_score: f32 = range.iter()
-            .map(|&idx| {
-                let forecast_idx = idx;
-                self.run_single_test(selector, signal_generator, target, forecast_idx)
-            })
-            .sum();
+        let daily = self.run_backtest_daily(selector, signal_generator, target, back_days);
+        let total_score: f32 = daily.iter().map(|day| day.score).sum();
             
-        total_score / back_days as f32
+        total_score / daily.len().max(1) as f32
     }
     
-    /// 运行单次回测并返回详细结果
-    pub fn run_detailed_test(
+    /// 在最近back_days个交易日上运行回测，返回每个回测日的成功率、交易数和盈利交易数，按日期倒序
+    ///
+    /// 没有任何股票可在当天回测(缺行或之后的K线不足)的交易日不计入结果
+    pub fn run_backtest_daily(
         &self,
         selector: &dyn StockSelector,
         signal_generator: &dyn BuySignalGenerator,
         target: &dyn Target,
-        forecast_idx: usize,
-    ) -> BacktestResult {
-        let stock_data: Vec<(String, Vec<DailyBar>)> = self.stock_data
-            .iter()
-            .map(|(symbol, data)| (symbol.clone(), data.clone()))
-            .collect();
-            
+        back_days: usize,
+    ) -> Vec<DailyScore> {
+        let forecast_idx = Self::backtest_range(target, 0).start;
+        let dates = self.trading_dates(target, back_days);
+        dates.iter()
+            .enumerate()
+            .filter_map(|(i, &trade_date)| {
+                let stock_data = self.stock_data_on_date(trade_date, forecast_idx);
+                let day = if stock_data.is_empty() {
+                    debug!("回测日 {} 没有可用的K线，跳过", trade_date);
+                    None
+                } else {
+                    let (score, total_trades, winning_trades) = self.single_test(&stock_data, selector, signal_generator, target, forecast_idx);
+                    Some(DailyScore { trade_date, score, total_trades, winning_trades })
+                };
+                self.report_progress(ProgressEvent::DayCompleted { idx: i + 1, total: dates.len() });
+                day
+            })
+            .collect()
+    }
+    
+    /// 在最近back_days个交易日上只运行选股，返回每个回测日的(买入日期, 入选股票)，按日期倒序
+    pub fn run_selection(
+        &self,
+        selector: &dyn StockSelector,
+        target: &dyn Target,
+        back_days: usize,
+    ) -> Vec<(i32, Candidates)> {
+        let forecast_idx = Self::backtest_range(target, 0).start;
+        
+        self.trading_dates(target, back_days)
+            .into_iter()
+            .map(|trade_date| {
+                let stock_data = self.stock_data_on_date(trade_date, forecast_idx);
+                (trade_date, self.install(|| selector.run(&stock_data, forecast_idx)))
+            })
+            .collect()
+    }
+    
+    /// 运行回测并合并每个回测日的详细结果
+    pub fn run_detailed_backtest(
+        &self,
+        selector: &dyn StockSelector,
+        signal_generator: &dyn BuySignalGenerator,
+        target: &dyn Target,
+        back_days: usize,
+    ) -> BacktestResult {
+        let results: Vec<BacktestResult> = self.run_backtest_detailed(selector, signal_generator, target, back_days)
+            .into_iter()
+            .map(|(_, result)| result)
+            .collect();
+            
+        BacktestResult::merge(results)
+    }
+    
+    /// 在最近back_days个交易日上运行详细回测，返回每个回测日的(买入日期, 详细结果)，按日期倒序
+    ///
+    /// 用于查看合并前各回测日的表现，例如得分集中在少数几天的组合
+    pub fn run_backtest_detailed(
+        &self,
+        selector: &dyn StockSelector,
+        signal_generator: &dyn BuySignalGenerator,
+        target: &dyn Target,
+        back_days: usize,
+    ) -> Vec<(i32, BacktestResult)> {
+        let dates = self.trading_dates(target, back_days);
+        dates.iter()
+            .enumerate()
+            .map(|(i, &trade_date)| {
+                let result = self.run_detailed_test_on_date(selector, signal_generator, target, trade_date);
+                self.report_progress(ProgressEvent::DayCompleted { idx: i + 1, total: dates.len() });
+                (trade_date, result)
+            })
+            .collect()
+    }
+    
+    /// 在有限资金下按时间顺序回测，持仓已满或资金不足时放弃新信号
+    ///
+    /// 需要目标提供逐笔结果(evaluate_trades)；收益率同样扣除滑点和交易成本，
+    /// 未开启collect_trade_details时结果不保留逐笔明细
+    pub fn run_portfolio_backtest(
+        &self,
+        selector: &dyn StockSelector,
+        signal_generator: &dyn BuySignalGenerator,
+        target: &dyn Target,
+        back_days: usize,
+        portfolio: &PortfolioBacktest,
+    ) -> PortfolioResult {
+        let forecast_idx = Self::backtest_range(target, 0).start;
+        
+        // 从最早的回测日开始
+        let trades: Vec<PortfolioTrade> = self.trading_dates(target, back_days)
+            .into_iter()
+            .rev()
+            .flat_map(|trade_date| {
+                let stock_data = self.stock_data_on_date(trade_date, forecast_idx);
+                let day = self.date_day(trade_date, forecast_idx);
+                let candidates = self.install(|| selector.run(&stock_data, forecast_idx));
+                let signals = signal_generator.generate_signals(candidates, forecast_idx);
+                let (signals, _) = self.reject_limit_entries(signals, forecast_idx, target.direction());
+                
+                self.trade_outcomes(&signals, target, forecast_idx)
+                    .iter()
+                    .enumerate()
+                    .map(|(trade_idx, outcome)| {
+                        let (_, data, buy_price) = &signals[outcome.signal_idx];
+                        PortfolioTrade {
+                            outcome: *outcome,
+                            detail: self.trade_detail(&signals, outcome, day, trade_idx, target),
+                            entry_date: data[forecast_idx].date,
+                            exit_date: data[outcome.exit_idx].date,
+                            // 做空的持仓价值随价格下跌而增加，按以开仓价镜像的收盘价估值
+                            closes: data[outcome.exit_idx..=forecast_idx].iter()
+                                .rev()
+                                .map(|bar| match target.direction() {
+                                    Direction::Long => (bar.date, bar.close),
+                                    Direction::Short => (bar.date, 2.0 * buy_price - bar.close),
+                                })
+                                .collect(),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        
+        let mut result = portfolio.simulate(trades, &self.calendar);
+        if !self.collect_trade_details {
+            result.result.trade_details = None;
+        }
+        
+        result
+    }
+    
+    /// 逐笔交易明细，收益率扣除滑点和交易成本
+    fn trade_detail(
+        &self,
+        signals: &[(String, BarSlice, f32)],
+        outcome: &TradeOutcome,
+        day: TestDay,
+        trade_idx: usize,
+        target: &dyn Target,
+    ) -> TradeDetail {
+        let TestDay { forecast_idx, day_idx, .. } = day;
+        let direction = target.direction();
+        let (symbol, data, buy_price) = &signals[outcome.signal_idx];
+        let (buy_slippage, sell_slippage) = self.slippage.rates(day_idx, trade_idx);
+        let filled_return = self.slippage.filled_return(outcome.return_pct, day_idx, trade_idx);
+        let held = &data[outcome.exit_idx..forecast_idx];
+        // 做空时开仓是卖出、平仓是买入，滑点方向随之相反
+        let (exit_price, filled_entry_price, filled_exit_price, peak_price) = match direction {
+            Direction::Long => {
+                let exit_price = buy_price * (1.0 + outcome.return_pct);
+                (exit_price, buy_price * (1.0 + buy_slippage), exit_price * (1.0 - sell_slippage),
+                    held.iter().map(|bar| bar.high).fold(*buy_price, f32::max))
+            }
+            Direction::Short => {
+                let exit_price = buy_price * (1.0 - outcome.return_pct);
+                (exit_price, buy_price * (1.0 - sell_slippage), exit_price * (1.0 + buy_slippage),
+                    held.iter().map(|bar| bar.low).fold(*buy_price, f32::min))
+            }
+        };
+        
+        TradeDetail {
+            symbol: symbol.clone(),
+            direction,
+            entry_date: data[forecast_idx].date.to_string(),
+            entry_price: *buy_price,
+            exit_date: data[outcome.exit_idx].date.to_string(),
+            exit_price,
+            filled_entry_price,
+            filled_exit_price,
+            peak_price,
+            return_pct: self.cost_model.net_return(filled_return),
+            hold_days: outcome.hold_days,
+            exit_reason: outcome.exit_reason,
+            win: outcome.is_win,
+            slippage: outcome.return_pct - filled_return,
+            cost: self.cost_model.cost(filled_return),
+            benchmark_return_pct: self.trade_benchmark_return(signals, outcome, forecast_idx),
+            legs: target.exit_legs(data, *buy_price, forecast_idx).map(|legs| {
+                legs.iter()
+                    .map(|leg| TradeLeg {
+                        exit_date: data[leg.exit_idx].date.to_string(),
+                        exit_price: leg.exit_price,
+                        fraction: leg.fraction,
+                        exit_reason: leg.exit_reason,
+                    })
+                    .collect()
+            }),
+        }
+    }
+    
+    /// 回测使用的forecast_idx范围
+    fn backtest_range(target: &dyn Target, back_days: usize) -> std::ops::Range<usize> {
+        // 从target.in_days()+1开始，确保有足够的未来数据进行评估
+        // +1是因为T+1交易制度，需要额外一天用于买入
+        target.in_days() + 1..target.in_days() + 1 + back_days
+    }
+    
+    /// 运行单次回测并返回详细结果，forecast_idx为距最新一根K线的偏移，假定各股票K线日期对齐
+    pub fn run_detailed_test(
+        &self,
+        selector: &dyn StockSelector,
+        signal_generator: &dyn BuySignalGenerator,
+        target: &dyn Target,
+        forecast_idx: usize,
+    ) -> BacktestResult {
+        let day = self.offset_day(forecast_idx);
+        self.detailed_test(&self.bar_slices(), selector, signal_generator, target, day).0
+    }
+    
+    /// 在交易日trade_date(YYYYMMDD)运行单次回测并返回详细结果，见stock_data_on_date
+    pub fn run_detailed_test_on_date(
+        &self,
+        selector: &dyn StockSelector,
+        signal_generator: &dyn BuySignalGenerator,
+        target: &dyn Target,
+        trade_date: i32,
+    ) -> BacktestResult {
+        self.detailed_test_on_date(selector, signal_generator, target, trade_date).0
+    }
+    
+    /// 按日期运行单次详细回测，同时返回选股数量
+    fn detailed_test_on_date(
+        &self,
+        selector: &dyn StockSelector,
+        signal_generator: &dyn BuySignalGenerator,
+        target: &dyn Target,
+        trade_date: i32,
+    ) -> (BacktestResult, usize) {
+        let forecast_idx = Self::backtest_range(target, 0).start;
+        let stock_data = self.stock_data_on_date(trade_date, forecast_idx);
+        let day = self.date_day(trade_date, forecast_idx);
+        self.detailed_test(&stock_data, selector, signal_generator, target, day)
+    }
+    
+    /// 在给定的K线上运行单次详细回测，同时返回选股数量
+    fn detailed_test(
+        &self,
+        stock_data: &[(String, BarSlice)],
+        selector: &dyn StockSelector,
+        signal_generator: &dyn BuySignalGenerator,
+        target: &dyn Target,
+        day: TestDay,
+    ) -> (BacktestResult, usize) {
+        let TestDay { forecast_idx, day_idx, entry_date } = day;
+            
         debug!("运行详细回测: 策略={}, 信号={}, 目标={}, 预测天数={}",
             selector.name(), signal_generator.name(), target.name(), forecast_idx);
             
         // 1. 选股
-        let candidates = selector.run(&stock_data, forecast_idx);
+        let candidates = self.install(|| selector.run(stock_data, forecast_idx));
+        let candidate_count = candidates.len();
         
-        // 2. 生成买入信号
+        // 2. 生成买入信号，开启涨跌停规则时去掉涨跌停价上无法开仓的信号
         let signals = signal_generator.generate_signals(candidates, forecast_idx);
+        let (signals, skipped_trades) = self.reject_limit_entries(signals, forecast_idx, target.direction());
+        let suspended_entries = signals.iter()
+            .filter(|(_, data, _)| forecast_idx.checked_sub(1).and_then(|idx| data.get(idx)).is_some_and(targets::is_suspended))
+            .count();
+        
+        // 3. 逐笔评估信号，汇总、按退出原因统计和交易明细都取自同一份结果
+        let outcomes = self.trade_outcomes(&signals, target, forecast_idx);
+        let suspended_skips = suspended_entries + outcomes.iter().filter(|outcome| outcome.suspended_days > 0).count();
+        let mut exit_reasons = ExitReasonBreakdown::default();
+        for (trade_idx, outcome) in outcomes.iter().enumerate() {
+            exit_reasons.record(outcome.exit_reason, self.net_return(outcome.return_pct, day_idx, trade_idx));
+        }
+        
+        let trade_details = if self.collect_trade_details && !outcomes.is_empty() {
+            let mut details: Vec<TradeDetail> = outcomes.iter()
+                .enumerate()
+                .map(|(trade_idx, outcome)| self.trade_detail(&signals, outcome, day, trade_idx, target))
+                .collect();
+            result::sort_trade_details(&mut details);
+            Some(details)
+        } else {
+            None
+        };
+        let stop_loss_fail_trades = exit_reasons.get(ExitReason::StopLossFailed).count;
+        
+        // 基准数据覆盖买卖日期的交易与同期基准比较
+        let benchmark_pairs: Vec<(f32, f32)> = outcomes.iter()
+            .enumerate()
+            .filter_map(|(trade_idx, outcome)| {
+                let benchmark = self.trade_benchmark_return(&signals, outcome, forecast_idx)?;
+                Some((self.net_return(outcome.return_pct, day_idx, trade_idx), benchmark))
+            })
+            .collect();
         
-        // 3. 评估信号 - 使用target的evaluate_signals方法
-        let (total_trades, winning_trades, losing_trades, stop_loss_trades, returns, hold_days) = 
-            target.evaluate_signals(signals, forecast_idx);
+        // 由已计算的逐笔结果汇总，冲突规则和跌停顺延对逐笔收益的调整也一并计入
+        let (total_trades, winning_trades, losing_trades, stop_loss_trades, returns, hold_days) = summarize_outcomes(&outcomes);
+        
+        // 收益率扣除滑点和交易成本，盈亏判定仍以目标为准
+        let avg_slippage = if returns.is_empty() || self.slippage.is_none() {
+            0.0
+        } else {
+            returns.iter()
+                .enumerate()
+                .map(|(trade_idx, &return_pct)| return_pct - self.slippage.filled_return(return_pct, day_idx, trade_idx))
+                .sum::<f32>() / returns.len() as f32
+        };
+        let returns: Vec<f32> = returns.into_iter()
+            .enumerate()
+            .map(|(trade_idx, return_pct)| self.net_return(return_pct, day_idx, trade_idx))
+            .collect();
         
         // 4. 计算统计指标
+        // 止盈止损同日的模糊交易按半笔盈利计入胜率
         let win_rate = if total_trades > 0 {
-            winning_trades as f32 / total_trades as f32
+            conflict::weighted_wins(winning_trades, exit_reasons.get(ExitReason::Ambiguous).count) / total_trades as f32
         } else {
             0.0
         };
@@ -167,6 +1204,12 @@ impl BacktestEngine {
             0.0
         };
         
+        let stop_loss_fail_rate = if total_trades > 0 {
+            stop_loss_fail_trades as f32 / total_trades as f32
+        } else {
+            0.0
+        };
+        
         let avg_return = if returns.is_empty() {
             0.0
         } else {
@@ -187,23 +1230,918 @@ impl BacktestEngine {
             winning_trades,
             losing_trades,
             stop_loss_trades,
-            stop_loss_fail_trades: 0,
+            stop_loss_fail_trades,
+            skipped_trades,
+            suspended_skips,
             win_rate,
             stop_loss_rate,
-            stop_loss_fail_rate: 0.0,
+            stop_loss_fail_rate,
             avg_return,
             max_return,
             max_loss,
             avg_hold_days,
+            avg_slippage,
             sharpe_ratio: 0.0,
             max_drawdown: 0.0,
             profit_factor: 0.0,
-            trade_details: None,
+            sortino_ratio: 0.0,
+            calmar_ratio: 0.0,
+            expectancy: 0.0,
+            max_drawdown_duration_days: 0,
+            time_underwater_ratio: 0.0,
+            longest_recovery_days: 0,
+            annualized_return: 0.0,
+            annualized_volatility: 0.0,
+            trades_per_month: 0.0,
+            benchmark_return: 0.0,
+            avg_excess_return: 0.0,
+            information_ratio: 0.0,
+            trade_details,
+            trade_returns: Vec::new(),
+            benchmark_returns: Vec::new(),
+            excess_returns: Vec::new(),
+            daily_pnl: None,
+            cumulative_curve: None,
+            equity_curve: None,
+            exit_reasons,
         };
         
+        // 收集交易明细时按买入日期生成净值曲线，回撤以此计算
+        if result.trade_details.is_some() {
+            result.set_equity_curve_from_details();
+        }
+        
+        // 按交易日历生成每日收益曲线
+        if let Some(entry_date) = entry_date {
+            let timings: Vec<TradeTiming> = returns.iter()
+                .zip(hold_days.iter())
+                .map(|(&return_pct, &days)| TradeTiming {
+                    entry_date,
+                    return_pct,
+                    hold_days: days.round().max(0.0) as usize,
+                })
+                .collect();
+            result.set_trade_timings(&timings, &self.calendar, self.pnl_attribution);
+        }
+        
         // 计算高级指标
         result.calculate_advanced_metrics(&returns);
+        result.trade_returns = returns;
+        result.set_benchmark_returns(&benchmark_pairs);
         
-        result
+        (result, candidate_count)
+    }
+    
+    /// 滚动前推分析: 每折先在样本内窗口回测，再在其后(更近)的样本外窗口检验
+    ///
+    /// 最近的一折的样本外窗口从最新可回测的一天开始，各折依次向前平移test_days天，
+    /// 结果按时间从早到晚排列
+    pub fn run_walk_forward(
+        &self,
+        selector: &dyn StockSelector,
+        signal_generator: &dyn BuySignalGenerator,
+        target: &dyn Target,
+        config: &WalkForwardConfig,
+    ) -> WalkForwardResult {
+        let newest = Self::backtest_range(target, 0).start;
+        
+        let folds = (0..config.folds)
+            .rev()
+            .map(|fold| {
+                let test = newest + fold * config.test_days..newest + (fold + 1) * config.test_days;
+                let train = test.end..test.end + config.train_days;
+                
+                let (in_sample, _) = self.window_test(selector, signal_generator, target, train.clone());
+                let (out_of_sample, candidates) = self.window_test(selector, signal_generator, target, test.clone());
+                
+                WalkForwardFold {
+                    index: config.folds - 1 - fold,
+                    train_start: self.entry_date(train.end.saturating_sub(1)),
+                    test_start: self.entry_date(test.end.saturating_sub(1)),
+                    test_end: self.entry_date(test.start),
+                    in_sample,
+                    out_of_sample,
+                    candidates,
+                }
+            })
+            .collect();
+        
+        WalkForwardResult::new(*config, folds)
+    }
+    
+    /// 在一段forecast_idx对应的交易日上运行详细回测并合并，同时返回选股总数
+    fn window_test(
+        &self,
+        selector: &dyn StockSelector,
+        signal_generator: &dyn BuySignalGenerator,
+        target: &dyn Target,
+        range: std::ops::Range<usize>,
+    ) -> (BacktestResult, usize) {
+        let (results, candidates): (Vec<BacktestResult>, Vec<usize>) = range
+            .filter_map(|forecast_idx| self.entry_date(forecast_idx))
+            .map(|trade_date| self.detailed_test_on_date(selector, signal_generator, target, trade_date))
+            .unzip();
+        
+        (BacktestResult::merge(results), candidates.iter().sum())
+    }
+    
+    /// forecast_idx对应的买入日期
+    fn entry_date(&self, forecast_idx: usize) -> Option<i32> {
+        self.calendar.latest()
+            .and_then(|latest| self.calendar.offset(latest, -(forecast_idx as i64)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::signals::price::OpenPriceSignal;
+    use crate::stock::mock_data::create_mock_daily_bars;
+    use crate::targets::return_target::ReturnTarget;
+    use crate::targets::trailing_stop_target::TrailingStopTarget;
+
+    #[test]
+    fn build_pool_respects_configured_threads() {
+        let pool = ParallelismConfig::build_pool(Some(3)).unwrap().unwrap();
+        // 在探针任务中观察线程池实际大小
+        assert_eq!(pool.install(rayon::current_num_threads), 3);
+        
+        let pool = ParallelismConfig::build_pool(Some(1)).unwrap().unwrap();
+        assert_eq!(pool.install(rayon::current_num_threads), 1);
+    }
+
+    #[test]
+    fn build_pool_defaults_to_global_pool() {
+        assert!(ParallelismConfig::build_pool(None).unwrap().is_none());
+    }
+
+    fn provider(bars: i32) -> Arc<StockDataProvider> {
+        let daily = (0..bars)
+            .map(|i| DailyBar {
+                date: 20230630 - i,
+                open: 10.0,
+                high: 10.0,
+                low: 10.0,
+                close: 10.0,
+                volume: 10000,
+                amount: 100000,
+            })
+            .collect();
+        let stock = egostrategy_datahub::models::stock::StockData {
+            exchange: "SZ".to_string(),
+            symbol: "000001.SZ".to_string(),
+            name: "平安银行".to_string(),
+            daily,
+        };
+        
+        Arc::new(StockDataProvider::with_stocks(vec![stock]).unwrap())
+    }
+
+    #[test]
+    fn history_bars_trims_loaded_series() {
+        let mut engine = BacktestEngine::with_source(provider(300), true, None);
+        engine.set_history_bars(Some(150));
+        engine.load_data().unwrap();
+        
+        let bars = &engine.stock_data()[0].1;
+        assert_eq!(bars.len(), 150);
+        assert_eq!(bars[0].date, 20230630);
+    }
+
+    #[test]
+    fn history_depth_is_at_least_min_history() {
+        let mut engine = BacktestEngine::with_source(provider(300), false, None);
+        engine.set_min_history(120);
+        engine.set_history_bars(Some(50));
+        engine.load_data().unwrap();
+        
+        assert_eq!(engine.stock_data()[0].1.len(), 120);
+    }
+
+    #[test]
+    fn full_history_is_kept_by_default() {
+        let mut engine = BacktestEngine::with_source(provider(300), true, None);
+        engine.load_data().unwrap();
+        
+        assert_eq!(engine.stock_data()[0].1.len(), 300);
+    }
+
+    #[test]
+    fn universe_file_warns_on_unknown_symbols_unless_strict() {
+        let path = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/universe.txt"));
+        
+        let mut engine = BacktestEngine::with_source(provider(300), true, None);
+        engine.load_symbols_from_file(path, false).unwrap();
+        assert_eq!(engine.stock_data().len(), 1);
+        assert_eq!(engine.stock_data()[0].0, "000001.SZ");
+        
+        let mut strict = BacktestEngine::with_source(provider(300), true, None);
+        assert!(strict.load_symbols_from_file(path, true).is_err());
+        assert!(strict.stock_data().is_empty());
+    }
+
+    #[test]
+    fn with_data_uses_injected_bars_without_load() {
+        let bars: Vec<DailyBar> = (0..5)
+            .map(|i| DailyBar {
+                date: 20230630 - i,
+                open: 10.0,
+                high: 10.0,
+                low: 10.0,
+                close: 10.0,
+                volume: 10000,
+                amount: 100000,
+            })
+            .collect();
+        
+        let engine = BacktestEngine::with_data(vec![("000001.SZ".to_string(), bars)], None).unwrap();
+        
+        assert_eq!(engine.stock_data().len(), 1);
+        assert_eq!(engine.stock_data()[0].1.len(), 5);
+        assert_eq!(engine.get_stock_name("000001.SZ").as_deref(), Some("000001.SZ"));
+    }
+
+    #[test]
+    fn load_data_applies_validation_policy() {
+        let mut bars: Vec<DailyBar> = (0..130)
+            .map(|i| DailyBar {
+                date: 20230630 - i,
+                open: 10.0,
+                high: 10.0,
+                low: 10.0,
+                close: 10.0,
+                volume: 10000,
+                amount: 100000,
+            })
+            .collect();
+        bars[5].close = 0.0;
+        let stock = egostrategy_datahub::models::stock::StockData {
+            exchange: "SZ".to_string(),
+            symbol: "000001.SZ".to_string(),
+            name: "平安银行".to_string(),
+            daily: bars,
+        };
+        let provider = Arc::new(StockDataProvider::with_stocks(vec![stock]).unwrap());
+        
+        let mut engine = BacktestEngine::with_source(provider.clone(), true, None);
+        engine.load_data().unwrap();
+        assert_eq!(engine.stock_data()[0].1.len(), 129);
+        assert_eq!(engine.validation_reports().len(), 1);
+        
+        let mut engine = BacktestEngine::with_source(provider, true, None);
+        engine.set_validation_policy(ValidationPolicy::DropSymbol);
+        engine.load_data().unwrap();
+        assert!(engine.stock_data().is_empty());
+    }
+
+    /// 各股票最新价格不同的数据提供者
+    fn priced_provider(prices: &[(&str, f32)]) -> Arc<StockDataProvider> {
+        let stocks = prices.iter()
+            .map(|&(symbol, close)| egostrategy_datahub::models::stock::StockData {
+                exchange: symbol[7..].to_string(),
+                symbol: symbol.to_string(),
+                name: String::new(),
+                daily: (0..130)
+                    .map(|i| DailyBar {
+                        date: 20230630 - i,
+                        open: close,
+                        high: close,
+                        low: close,
+                        close,
+                        volume: 10000,
+                        amount: 100000,
+                    })
+                    .collect(),
+            })
+            .collect();
+        
+        Arc::new(StockDataProvider::with_stocks(stocks).unwrap())
+    }
+
+    fn loaded_symbols(engine: &BacktestEngine) -> Vec<&str> {
+        engine.stock_data().iter().map(|(symbol, _)| symbol.as_str()).collect()
+    }
+
+    #[test]
+    fn price_ceiling_defaults_to_100_and_can_be_lifted() {
+        let provider = priced_provider(&[("000001.SZ", 12.0), ("600519.SH", 150.0)]);
+        
+        let mut engine = BacktestEngine::with_source(provider.clone(), true, None);
+        engine.load_data().unwrap();
+        assert_eq!(loaded_symbols(&engine), vec!["000001.SZ"]);
+        
+        let mut engine = BacktestEngine::with_source(provider, true, None);
+        engine.set_filter_config(StockFilterConfig { max_price: None, ..StockFilterConfig::default() });
+        engine.load_data().unwrap();
+        let mut symbols = loaded_symbols(&engine);
+        symbols.sort();
+        assert_eq!(symbols, vec!["000001.SZ", "600519.SH"]);
+    }
+
+    #[test]
+    fn price_floor_drops_cheap_symbols() {
+        let provider = priced_provider(&[("000001.SZ", 12.0), ("600083.SH", 1.5)]);
+        
+        let mut engine = BacktestEngine::with_source(provider, false, None);
+        engine.set_filter_config(StockFilterConfig { min_price: Some(2.0), ..StockFilterConfig::default() });
+        engine.load_data().unwrap();
+        
+        assert_eq!(loaded_symbols(&engine), vec!["000001.SZ"]);
+    }
+
+    #[test]
+    fn calendar_is_union_of_loaded_dates() {
+        let bars = |dates: &[i32]| -> Vec<DailyBar> {
+            dates.iter()
+                .map(|&date| DailyBar {
+                    date,
+                    open: 10.0,
+                    high: 10.0,
+                    low: 10.0,
+                    close: 10.0,
+                    volume: 10000,
+                    amount: 100000,
+                })
+                .collect()
+        };
+        let engine = BacktestEngine::with_data(vec![
+            ("000001.SZ".to_string(), bars(&[20230703, 20230630])),
+            ("600000.SH".to_string(), bars(&[20230704, 20230629])),
+        ], None).unwrap();
+        
+        assert_eq!(engine.calendar().dates(), &[20230629, 20230630, 20230703, 20230704]);
+        assert_eq!(engine.calendar().offset(20230630, 1), Some(20230703));
+    }
+
+    #[test]
+    fn load_data_runs_against_mock_source() {
+        let bars = |n: i32| -> Vec<DailyBar> {
+            (0..n)
+                .map(|i| DailyBar {
+                    date: 20230630 - i,
+                    open: 10.0,
+                    high: 10.0,
+                    low: 10.0,
+                    close: 10.0,
+                    volume: 10000,
+                    amount: 100000,
+                })
+                .collect()
+        };
+        let mut source = MockDataSource::default();
+        source.insert("000001.SZ", "平安银行", bars(130));
+        source.insert("600000.SH", "浦发银行", bars(60));
+        source.insert("600083.SH", "*ST博信", bars(130));
+        source.set_benchmark(bars(130));
+        
+        let mut engine = BacktestEngine::with_source(Arc::new(source), true, None);
+        engine.set_filter_config(StockFilterConfig { exclude_st: true, ..StockFilterConfig::default() });
+        engine.load_data().unwrap();
+        
+        // 历史不足和ST股票被过滤
+        assert_eq!(loaded_symbols(&engine), vec!["000001.SZ"]);
+        assert!(engine.load_benchmark());
+        assert_eq!(engine.calendar().latest(), Some(20230630));
+    }
+
+    #[test]
+    fn snapshots_restrict_universe_and_report_missing_symbols() {
+        let bars: Vec<DailyBar> = (0..130)
+            .map(|i| DailyBar {
+                date: 20230630 - i,
+                open: 10.0,
+                high: 10.0,
+                low: 10.0,
+                close: 10.0,
+                volume: 10000,
+                amount: 100000,
+            })
+            .collect();
+        let mut source = MockDataSource::default();
+        for symbol in ["000001.SZ", "600000.SH", "601398.SH", "600036.SH"] {
+            source.insert(symbol, symbol, bars.clone());
+        }
+        let source = Arc::new(source);
+        let fixture = |name: &str| Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+        
+        let mut engine = BacktestEngine::with_source(source.clone(), true, None);
+        engine.load_data_from_snapshot(&fixture("snapshot_20220630.json")).unwrap();
+        assert_eq!(loaded_symbols(&engine), vec!["000001.SZ", "600000.SH"]);
+        let report = engine.survivorship().unwrap();
+        assert_eq!(report.missing_from_provider, vec!["000023.SZ", "600087.SH"]);
+        assert_eq!(report.bias(), 0.5);
+        
+        let mut engine = BacktestEngine::with_source(source, true, None);
+        engine.load_data_from_snapshot(&fixture("snapshot_20230630.json")).unwrap();
+        assert_eq!(loaded_symbols(&engine), vec!["000001.SZ", "600000.SH", "601398.SH"]);
+        assert_eq!(engine.survivorship().unwrap().bias(), 0.0);
+    }
+
+    /// 前几次获取返回暂时性错误的数据源，另外列出一只数据源中不存在的股票
+    struct FlakySource {
+        inner: MockDataSource,
+        remaining_failures: Mutex<HashMap<String, u32>>,
+        calls: AtomicUsize,
+    }
+
+    impl FlakySource {
+        fn new(failures: &[(&str, u32)]) -> Self {
+            let bars: Vec<DailyBar> = (0..130)
+                .map(|i| DailyBar {
+                    date: 20230630 - i,
+                    open: 10.0,
+                    high: 10.0,
+                    low: 10.0,
+                    close: 10.0,
+                    volume: 10000,
+                    amount: 100000,
+                })
+                .collect();
+            let mut inner = MockDataSource::default();
+            for &(symbol, _) in failures {
+                inner.insert(symbol, symbol, bars.clone());
+            }
+            
+            Self {
+                inner,
+                remaining_failures: Mutex::new(failures.iter()
+                    .map(|&(symbol, count)| (symbol.to_string(), count))
+                    .collect()),
+                calls: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    impl DataSource for FlakySource {
+        fn all_symbols(&self) -> Vec<String> {
+            let mut symbols = self.inner.all_symbols();
+            symbols.push("000404.SZ".to_string());
+            symbols
+        }
+        
+        fn daily_bars(&self, symbol: &str) -> Option<Arc<Vec<DailyBar>>> {
+            self.inner.daily_bars(symbol)
+        }
+        
+        fn meta(&self, symbol: &str) -> Option<StockMeta> {
+            self.inner.meta(symbol)
+        }
+        
+        fn try_daily_bars(&self, symbol: &str, mode: AdjustMode) -> Result<Arc<Vec<DailyBar>>, FetchError> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            let mut remaining = self.remaining_failures.lock().unwrap();
+            match remaining.get_mut(symbol) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    Err(FetchError::Transient("connection reset".to_string()))
+                }
+                _ => self.inner.daily_bars_adjusted(symbol, mode).ok_or(FetchError::NotFound),
+            }
+        }
+    }
+
+    #[test]
+    fn load_retries_transient_failures_and_reports_counts() {
+        let source = Arc::new(FlakySource::new(&[("000001.SZ", 0), ("600000.SH", 2), ("600036.SH", 5)]));
+        let mut engine = BacktestEngine::with_source(source.clone(), false, None);
+        engine.set_retry_policy(RetryPolicy { attempts: 3, backoff_ms: 0 });
+        
+        let report = engine.load_data().unwrap();
+        
+        assert_eq!(report.loaded, 2);
+        assert_eq!(report.not_found, vec!["000404.SZ"]);
+        assert_eq!(report.failed_after_retry, vec!["600036.SH"]);
+        assert_eq!(engine.load_report(), Some(&report));
+        assert_eq!(loaded_symbols(&engine), vec!["000001.SZ", "600000.SH"]);
+        // 000001一次，600000两次失败后成功，600036三次均失败，000404不重试
+        assert_eq!(source.calls.load(Ordering::Relaxed), 1 + 3 + 3 + 1);
+    }
+
+    #[test]
+    fn load_aborts_when_failures_exceed_threshold() {
+        let source = Arc::new(FlakySource::new(&[("000001.SZ", 0), ("600036.SH", 5)]));
+        let mut engine = BacktestEngine::with_source(source, false, None);
+        engine.set_retry_policy(RetryPolicy::none());
+        engine.set_max_failure_pct(Some(20.0));
+        
+        let err = engine.load_data().unwrap_err();
+        
+        // 3只中1只失败
+        assert!(err.to_string().contains("1 只股票重试后仍获取失败(33.3%)"), "{}", err);
+    }
+
+    /// 选出全部股票
+    struct AllSelector;
+
+    impl StockSelector for AllSelector {
+        fn name(&self) -> String {
+            "全部".to_string()
+        }
+        
+        fn run(&self, stock_data: &[(String, BarSlice)], _forecast_idx: usize) -> Vec<(String, BarSlice)> {
+            stock_data.to_vec()
+        }
+    }
+
+    /// 由按日期顺序的(开盘价, 收盘价)生成K线(倒序)，最高价和最低价取两者的较大和较小值
+    fn bars(prices: &[(f32, f32)]) -> Vec<DailyBar> {
+        let mut bars = create_mock_daily_bars(prices.len());
+        for (bar, &(open, close)) in bars.iter_mut().rev().zip(prices) {
+            bar.open = open;
+            bar.close = close;
+            bar.high = open.max(close);
+            bar.low = open.min(close);
+        }
+        bars
+    }
+
+    /// 6根K线: 第2天为买入日期，第3天开盘以10元买入，持有第3~5天，held为这三天的收盘价
+    fn trade(held: [f32; 3]) -> Vec<DailyBar> {
+        bars(&[(10.0, 10.0), (10.0, 10.0), (10.0, held[0]), (held[0], held[1]), (held[1], held[2]), (held[2], held[2])])
+    }
+
+    /// 收集交易明细的引擎
+    fn engine(stocks: Vec<(&str, Vec<DailyBar>)>) -> BacktestEngine {
+        let stocks = stocks.into_iter().map(|(symbol, bars)| (symbol.to_string(), bars)).collect();
+        let mut engine = BacktestEngine::with_data(stocks, None).unwrap();
+        engine.set_collect_trade_details(true);
+        engine
+    }
+
+    /// 目标5%、止损3%、持有3天，forecast_idx为4
+    fn target() -> ReturnTarget {
+        ReturnTarget::new(0.05, 0.03, 3)
+    }
+
+    fn details(result: &BacktestResult) -> &[TradeDetail] {
+        result.trade_details.as_deref().unwrap()
+    }
+
+    /// 单只股票按held持有的唯一一笔交易
+    fn single_trade(held: [f32; 3]) -> (BacktestResult, TradeDetail) {
+        let engine = engine(vec![("000001.SZ", trade(held))]);
+        let result = engine.run_detailed_test(&AllSelector, &OpenPriceSignal, &target(), 4);
+        let detail = details(&result)[0].clone();
+        (result, detail)
+    }
+
+    #[test]
+    fn profit_factor_is_gross_profit_over_gross_loss() {
+        let engine = engine(vec![
+            ("000001.SZ", trade([10.6, 10.6, 10.6])),
+            ("000002.SZ", trade([9.6, 9.6, 9.6])),
+        ]);
+        let result = engine.run_detailed_test(&AllSelector, &OpenPriceSignal, &target(), 4);
+        
+        // 0.06 / 0.04
+        assert_eq!(result.total_trades, 2);
+        assert!((result.profit_factor - 1.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn profit_factor_without_losses_is_infinite() {
+        let engine = engine(vec![
+            ("000001.SZ", trade([10.6, 10.6, 10.6])),
+            ("000002.SZ", trade([10.2, 10.3, 10.8])),
+        ]);
+        let result = engine.run_detailed_test(&AllSelector, &OpenPriceSignal, &target(), 4);
+        
+        assert_eq!(result.winning_trades, 2);
+        assert_eq!(result.profit_factor, f32::INFINITY);
+    }
+
+    #[test]
+    fn trade_win_follows_target_not_net_return() {
+        let mut engine = engine(vec![
+            ("000001.SZ", trade([10.6, 10.6, 10.6])),
+            ("000002.SZ", trade([9.6, 9.6, 9.6])),
+        ]);
+        // 单边4%的佣金使达到目标的交易净亏损
+        engine.set_cost_model(CostModel {
+            commission_rate: 0.04,
+            min_commission: 0.0,
+            stamp_duty_rate: 0.0,
+            transfer_fee_rate: 0.0,
+            trade_value: 0.0,
+        });
+        let result = engine.run_detailed_test(&AllSelector, &OpenPriceSignal, &target(), 4);
+        let details = details(&result);
+        let won = details.iter().find(|detail| detail.symbol == "000001.SZ").unwrap();
+        let lost = details.iter().find(|detail| detail.symbol == "000002.SZ").unwrap();
+        
+        assert_eq!(won.exit_reason, ExitReason::TargetReached);
+        assert!(won.win);
+        assert!(won.return_pct < 0.0);
+        assert_eq!(lost.exit_reason, ExitReason::StopLoss);
+        assert!(!lost.win);
+    }
+
+    #[test]
+    fn single_test_success_rate_matches_detailed_win_rate() {
+        let engine = engine(vec![
+            ("000001.SZ", trade([10.6, 10.6, 10.6])),
+            ("000002.SZ", trade([9.6, 9.6, 9.6])),
+            ("000003.SZ", trade([10.8, 10.8, 10.8])),
+        ]);
+        let success_rate = engine.run_single_test(&AllSelector, &OpenPriceSignal, &target(), 4);
+        let result = engine.run_detailed_test(&AllSelector, &OpenPriceSignal, &target(), 4);
+        
+        assert!((success_rate - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(success_rate, result.win_rate);
+    }
+
+    /// 统计逐笔评估和汇总评估次数的目标
+    struct CountingTarget {
+        inner: ReturnTarget,
+        evaluations: AtomicUsize,
+    }
+
+    impl Target for CountingTarget {
+        fn name(&self) -> String {
+            self.inner.name()
+        }
+        
+        fn target_return(&self) -> f32 {
+            self.inner.target_return()
+        }
+        
+        fn stop_loss(&self) -> f32 {
+            self.inner.stop_loss()
+        }
+        
+        fn in_days(&self) -> usize {
+            self.inner.in_days()
+        }
+        
+        fn run(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize) -> f32 {
+            self.inner.run(signals, forecast_idx)
+        }
+        
+        fn evaluate_signals(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize)
+            -> (usize, usize, usize, usize, Vec<f32>, Vec<f32>) {
+            self.evaluations.fetch_add(1, Ordering::Relaxed);
+            self.inner.evaluate_signals(signals, forecast_idx)
+        }
+        
+        fn evaluate_trades(&self, signals: &[(String, BarSlice, f32)], forecast_idx: usize) -> Vec<TradeOutcome> {
+            self.evaluations.fetch_add(1, Ordering::Relaxed);
+            self.inner.evaluate_trades(signals, forecast_idx)
+        }
+    }
+
+    #[test]
+    fn detailed_test_evaluates_target_once() {
+        let engine = engine(vec![
+            ("000001.SZ", trade([10.6, 10.6, 10.6])),
+            ("000002.SZ", trade([9.6, 9.6, 9.6])),
+        ]);
+        let target = CountingTarget { inner: target(), evaluations: AtomicUsize::new(0) };
+        let result = engine.run_detailed_test(&AllSelector, &OpenPriceSignal, &target, 4);
+        
+        assert_eq!(target.evaluations.load(Ordering::Relaxed), 1);
+        assert_eq!(result.total_trades, 2);
+        assert_eq!(result.winning_trades, 1);
+        assert_eq!(result.stop_loss_trades, 1);
+    }
+
+    #[test]
+    fn first_day_gap_below_stop_fails_stop_loss() {
+        let (result, detail) = single_trade([9.2, 9.3, 9.4]);
+        
+        assert_eq!(detail.exit_reason, ExitReason::StopLossFailed);
+        assert!((detail.return_pct + 0.08).abs() < 1e-5);
+        assert_eq!(detail.hold_days, 1);
+        assert_eq!(result.stop_loss_fail_trades, 1);
+    }
+    
+    #[test]
+    fn close_below_stop_is_normal_stop_loss() {
+        let (result, detail) = single_trade([10.1, 9.6, 9.4]);
+        
+        assert_eq!(detail.exit_reason, ExitReason::StopLoss);
+        assert!((detail.return_pct + 0.04).abs() < 1e-5);
+        assert_eq!(detail.hold_days, 2);
+        assert_eq!(result.stop_loss_trades, 1);
+    }
+    
+    #[test]
+    fn target_reached_on_second_day() {
+        let (result, detail) = single_trade([10.2, 10.6, 9.0]);
+        
+        assert_eq!(detail.exit_reason, ExitReason::TargetReached);
+        assert!((detail.return_pct - 0.06).abs() < 1e-5);
+        assert_eq!(detail.hold_days, 2);
+        assert_eq!(detail.exit_date, "20230104");
+        assert_eq!(result.winning_trades, 1);
+    }
+    
+    #[test]
+    fn holding_window_expires_at_last_close() {
+        let (result, detail) = single_trade([10.1, 10.2, 10.3]);
+        
+        assert_eq!(detail.exit_reason, ExitReason::TimeExpired);
+        assert!((detail.return_pct - 0.03).abs() < 1e-5);
+        assert_eq!(detail.hold_days, 3);
+        assert_eq!(detail.exit_date, "20230105");
+        assert_eq!(result.losing_trades, 1);
+    }
+
+    #[test]
+    fn limit_up_entry_is_rejected() {
+        // 前收盘价10元，开盘价11元为涨停价，无法买入
+        let limit_up = bars(&[(10.0, 10.0), (10.0, 10.0), (11.0, 11.5), (11.5, 11.5), (11.5, 11.5), (11.5, 11.5)]);
+        let mut engine = engine(vec![
+            ("000001.SZ", limit_up),
+            ("000002.SZ", trade([10.6, 10.6, 10.6])),
+        ]);
+        
+        let result = engine.run_detailed_test(&AllSelector, &OpenPriceSignal, &target(), 4);
+        assert_eq!(result.total_trades, 2);
+        assert_eq!(result.skipped_trades, 0);
+        
+        engine.set_price_limit_rules(true);
+        let result = engine.run_detailed_test(&AllSelector, &OpenPriceSignal, &target(), 4);
+        assert_eq!(result.total_trades, 1);
+        assert_eq!(result.skipped_trades, 1);
+        assert_eq!(details(&result)[0].symbol, "000002.SZ");
+    }
+    
+    #[test]
+    fn limit_up_uses_signal_day_close() {
+        // 信号日收盘10元，前一天收盘11元: 以11元开盘买入是涨停，无法成交
+        let limit_up = bars(&[(11.0, 11.0), (10.0, 10.0), (11.0, 11.5), (11.5, 11.5), (11.5, 11.5), (11.5, 11.5)]);
+        // 信号日收盘9.9元，前一天收盘9元: 以10元开盘买入低于涨停价10.89元，可以成交
+        let tradable = bars(&[(9.0, 9.0), (9.9, 9.9), (10.0, 10.6), (10.6, 10.6), (10.6, 10.6), (10.6, 10.6)]);
+        let mut engine = engine(vec![("000001.SZ", limit_up), ("000002.SZ", tradable)]);
+        engine.set_price_limit_rules(true);
+        
+        let result = engine.run_detailed_test(&AllSelector, &OpenPriceSignal, &target(), 4);
+        
+        assert_eq!(result.total_trades, 1);
+        assert_eq!(result.skipped_trades, 1);
+        assert_eq!(details(&result)[0].symbol, "000002.SZ");
+    }
+    
+    #[test]
+    fn suspended_fill_day_skips_entry() {
+        let trailing = TrailingStopTarget { stop_loss: 0.03, activation_return: 0.05, trail_pct: 0.02, in_days: 3 };
+        let targets: [&dyn Target; 2] = [&target(), &trailing];
+        for target in targets {
+            // 000001在买入成交日data[3]停牌；000002只在信号日data[4]停牌，次日可以买入
+            let mut fill_suspended = trade([10.6, 10.6, 10.6]);
+            fill_suspended[3].volume = 0;
+            let mut signal_suspended = trade([10.6, 10.6, 10.6]);
+            signal_suspended[4].volume = 0;
+            let engine = engine(vec![("000001.SZ", fill_suspended), ("000002.SZ", signal_suspended)]);
+            
+            let result = engine.run_detailed_test(&AllSelector, &OpenPriceSignal, target, 4);
+            
+            assert_eq!(result.total_trades, 1, "{}", target.name());
+            assert_eq!(result.suspended_skips, 1, "{}", target.name());
+            assert_eq!(details(&result)[0].symbol, "000002.SZ");
+        }
+    }
+    
+    #[test]
+    fn locked_limit_down_stop_slides_to_next_open() {
+        // 第二个持有日以跌停价9元开盘并收盘，最低价也是9元，次日开盘9.5元
+        let locked = bars(&[(10.0, 10.0), (10.0, 10.0), (10.0, 10.0), (9.0, 9.0), (9.5, 9.6), (9.6, 9.6)]);
+        let mut engine = engine(vec![("000001.SZ", locked)]);
+        
+        let result = engine.run_detailed_test(&AllSelector, &OpenPriceSignal, &target(), 4);
+        let detail = &details(&result)[0];
+        assert_eq!(detail.exit_reason, ExitReason::StopLossFailed);
+        assert!((detail.return_pct + 0.1).abs() < 1e-5);
+        
+        engine.set_price_limit_rules(true);
+        let result = engine.run_detailed_test(&AllSelector, &OpenPriceSignal, &target(), 4);
+        let detail = &details(&result)[0];
+        assert_eq!(detail.exit_reason, ExitReason::LimitLocked);
+        assert!((detail.return_pct + 0.05).abs() < 1e-5);
+        assert_eq!(detail.hold_days, 3);
+        assert_eq!(detail.exit_date, "20230105");
+    }
+    
+    #[test]
+    fn stop_above_limit_down_is_not_deferred() {
+        // 收盘价9.1元高于跌停价9元，当天可以卖出
+        let unlocked = bars(&[(10.0, 10.0), (10.0, 10.0), (10.0, 10.0), (9.5, 9.1), (9.5, 9.6), (9.6, 9.6)]);
+        let mut engine = engine(vec![("000001.SZ", unlocked)]);
+        engine.set_price_limit_rules(true);
+        
+        let result = engine.run_detailed_test(&AllSelector, &OpenPriceSignal, &target(), 4);
+        let detail = &details(&result)[0];
+        assert_eq!(detail.exit_reason, ExitReason::StopLossFailed);
+        assert!((detail.return_pct + 0.09).abs() < 1e-5);
+        assert_eq!(detail.exit_date, "20230104");
+    }
+
+    /// 8根模拟K线中去掉missing日期(按日期顺序的序号)后的K线，价格仍按原日期取值
+    fn bars_without(missing: &[usize]) -> Vec<DailyBar> {
+        let mut bars = create_mock_daily_bars(8);
+        bars.reverse();
+        let mut kept: Vec<DailyBar> = bars.into_iter()
+            .enumerate()
+            .filter(|(i, _)| !missing.contains(i))
+            .map(|(_, bar)| bar)
+            .collect();
+        kept.reverse();
+        kept
+    }
+    
+    #[test]
+    fn trade_date_is_located_per_symbol() {
+        let trade_date = 20230104;
+        let engine = engine(vec![
+            ("000001.SZ", bars_without(&[])),
+            // 回看窗口中缺一天，交易日期之后的K线完整
+            ("000002.SZ", bars_without(&[1])),
+            // 交易日期当天没有K线
+            ("000003.SZ", bars_without(&[3])),
+        ]);
+        
+        let stock_data = engine.stock_data_on_date(trade_date, 4);
+        let symbols: Vec<&str> = stock_data.iter().map(|(symbol, _)| symbol.as_str()).collect();
+        assert_eq!(symbols, ["000001.SZ", "000002.SZ"]);
+        assert!(stock_data.iter().all(|(_, data)| data[4].date == trade_date));
+        
+        let result = engine.run_detailed_test_on_date(&AllSelector, &OpenPriceSignal, &target(), trade_date);
+        let details = details(&result);
+        assert_eq!(result.total_trades, 2);
+        // 两只股票在同一天以同样的价格买入，并在同一天到期卖出
+        for detail in details {
+            assert_eq!(detail.entry_date, "20230104");
+            assert_eq!(detail.exit_date, "20230107");
+            assert_eq!(detail.entry_price, 10.4);
+            assert_eq!(detail.exit_reason, ExitReason::TimeExpired);
+            assert!((detail.return_pct - 0.4 / 10.4).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn days_without_usable_bars_do_not_dilute_score() {
+        // 000001只有20230101~20230120；000002只有20230121~20230130，每天上涨5%
+        let mut rising = bars(&(0..10).map(|i| {
+            let close = 10.0 * 1.05f32.powi(i);
+            (close, close)
+        }).collect::<Vec<_>>());
+        for bar in rising.iter_mut() {
+            bar.date += 20;
+        }
+        let engine = engine(vec![("000001.SZ", create_mock_daily_bars(20)), ("000002.SZ", rising)]);
+        let target = ReturnTarget::new(0.01, 0.03, 3);
+        
+        // 最近10个回测日为20230126~20230117，其中20230120及之前没有股票有足够的后续K线
+        let daily = engine.run_backtest_daily(&AllSelector, &OpenPriceSignal, &target, 10);
+        let dates: Vec<i32> = daily.iter().map(|day| day.trade_date).collect();
+        assert_eq!(dates, vec![20230126, 20230125, 20230124, 20230123, 20230122, 20230121]);
+        assert!(daily.iter().all(|day| day.score == 1.0));
+        assert_eq!(engine.run_backtest(&AllSelector, &OpenPriceSignal, &target, 10), 1.0);
+    }
+
+    /// 价格按scale缩放、每隔几天下跌的20根模拟K线，使各股票的退出日期和收益不同
+    fn scaled_bars(scale: f32) -> Vec<DailyBar> {
+        let mut bars = create_mock_daily_bars(20);
+        for (i, bar) in bars.iter_mut().rev().enumerate() {
+            let drop = if i % 4 == 3 { 0.5 } else { 0.0 };
+            bar.open *= scale;
+            bar.close = (bar.close - drop) * scale;
+            bar.high *= scale;
+            bar.low = (bar.low - drop) * scale;
+        }
+        bars
+    }
+    
+    #[test]
+    fn detailed_backtest_json_is_byte_identical_across_runs() {
+        let stocks = vec![
+            ("600000.SH", scaled_bars(1.3)),
+            ("000002.SZ", scaled_bars(0.8)),
+            ("000001.SZ", scaled_bars(1.0)),
+            ("300001.SZ", scaled_bars(2.1)),
+        ];
+        let mut reversed = stocks.clone();
+        reversed.reverse();
+        
+        let run = |stocks: Vec<(&str, Vec<DailyBar>)>| {
+            let result = engine(stocks).run_detailed_backtest(&AllSelector, &OpenPriceSignal, &target(), 8);
+            serde_json::to_string_pretty(&result).unwrap()
+        };
+        let first = run(stocks.clone());
+        
+        assert_eq!(first, run(stocks));
+        assert_eq!(first, run(reversed));
+        
+        let result: BacktestResult = serde_json::from_str(&first).unwrap();
+        let keys: Vec<(String, String)> = details(&result).iter()
+            .map(|detail| (detail.entry_date.clone(), detail.symbol.clone()))
+            .collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(result.total_trades, 32);
+        assert_eq!(keys, sorted);
     }
 }
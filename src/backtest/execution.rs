@@ -0,0 +1,84 @@
+use crate::backtest::result::ExitReason;
+use crate::targets::{is_suspended, next_tradable, Direction, Target, TradeOutcome};
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use serde::{Deserialize, Serialize};
+
+/// 固定止损的成交方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitExecution {
+    /// 沿用目标自身的判定，触及止损即按止损价或当天价格成交
+    #[default]
+    IntradayTouch,
+    /// 收盘价突破止损价后，下一交易日开盘价卖出，适合收盘后才下单的系统
+    NextOpenAfterCloseBreach,
+}
+
+impl ExitExecution {
+    /// 按名称查找: intraday_touch或next_open_after_close_breach
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "intraday_touch" => Some(ExitExecution::IntradayTouch),
+            "next_open_after_close_breach" => Some(ExitExecution::NextOpenAfterCloseBreach),
+            _ => None,
+        }
+    }
+    
+    /// 按成交方式改写止损交易的卖出日和收益率
+    ///
+    /// 从目标判定的卖出日起按日期顺序找第一个收盘价突破止损价(做多在买入价的1 - stop_loss之下，
+    /// 做空在1 + stop_loss之上)的交易日，在其后第一个可交易日开盘卖出，卖出日可以晚于持有期最后一天window_end；
+    /// 之后没有K线时按当天收盘价卖出。持有期内收盘价都未突破时按window_end的收盘价到期卖出，不再检查之后的止盈。
+    /// 只作用于固定止损(StopLoss、StopLossFailed)，移动止损和保本止损保持目标的成交价
+    pub fn resolve(&self, outcome: &mut TradeOutcome, data: &[DailyBar], buy_price: f32, target: &dyn Target, window_end: usize) {
+        let (target_return, stop_loss, direction) = (target.target_return(), target.stop_loss(), target.direction());
+        if *self == ExitExecution::IntradayTouch || buy_price <= 0.0 || stop_loss <= 0.0 {
+            return;
+        }
+        if !matches!(outcome.exit_reason, ExitReason::StopLoss | ExitReason::StopLossFailed) {
+            return;
+        }
+        
+        let breached = |bar: &DailyBar| match direction {
+            Direction::Long => bar.close <= buy_price * (1.0 - stop_loss),
+            Direction::Short => bar.close >= buy_price * (1.0 + stop_loss),
+        };
+        let return_at = |price: f32| match direction {
+            Direction::Long => (price - buy_price) / buy_price,
+            Direction::Short => (buy_price - price) / buy_price,
+        };
+        
+        // K线按日期倒序，下标从exit_idx递减到window_end为日期顺序
+        let window_end = window_end.min(outcome.exit_idx);
+        let breach = (window_end..=outcome.exit_idx)
+            .rev()
+            .find(|&idx| !is_suspended(&data[idx]) && breached(&data[idx]));
+        
+        let (exit_idx, return_pct, exit_reason) = match breach {
+            Some(idx) if idx > 0 => {
+                let fill_idx = next_tradable(data, idx - 1);
+                outcome.suspended_days += idx - 1 - fill_idx;
+                let return_pct = return_at(data[fill_idx].open);
+                let reason = if return_pct < -2.0 * stop_loss { ExitReason::StopLossFailed } else { ExitReason::StopLoss };
+                (fill_idx, return_pct, reason)
+            }
+            Some(idx) => {
+                let return_pct = return_at(data[idx].close);
+                let reason = if return_pct < -2.0 * stop_loss { ExitReason::StopLossFailed } else { ExitReason::StopLoss };
+                (idx, return_pct, reason)
+            }
+            None => {
+                let last_idx = next_tradable(data, window_end);
+                outcome.suspended_days += window_end - last_idx;
+                (last_idx, return_at(data[last_idx].close), ExitReason::TimeExpired)
+            }
+        };
+        
+        outcome.hold_days += outcome.exit_idx - exit_idx;
+        outcome.exit_idx = exit_idx;
+        outcome.return_pct = return_pct;
+        outcome.exit_reason = exit_reason;
+        outcome.is_win = exit_reason == ExitReason::TimeExpired && target_return > 0.0 && return_pct >= target_return;
+        outcome.is_stop_loss = exit_reason == ExitReason::StopLoss;
+    }
+}
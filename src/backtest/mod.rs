@@ -1,5 +1,26 @@
+pub mod compare;
+pub mod conflict;
+pub mod cost;
 pub mod engine;
+pub mod execution;
+pub mod portfolio;
+pub mod progress;
+pub mod report;
 pub mod result;
+pub mod slippage;
+pub mod walk_forward;
 
-pub use engine::BacktestEngine;
-pub use result::{BacktestResult, ExitReason};
+pub use compare::{MetricDelta, ResultComparison, SymbolDelta};
+pub use conflict::ConflictPolicy;
+pub use cost::CostModel;
+pub use engine::{BacktestEngine, ParallelismConfig, DEFAULT_MAX_STALENESS_DAYS};
+pub use execution::ExitExecution;
+pub use portfolio::{PortfolioBacktest, PortfolioResult, PositionSizing};
+pub use progress::{ProgressCallback, ProgressEvent};
+pub use report::RunMeta;
+pub use result::{
+    BacktestResult, BootstrapInterval, ConfidenceInterval, DailyScore, ExitReason, ExitReasonBreakdown, ExitReasonStat, MonteCarloSummary,
+    PnlAttribution, Period, TradeTiming,
+};
+pub use slippage::SlippageModel;
+pub use walk_forward::{WalkForwardConfig, WalkForwardFold, WalkForwardResult};
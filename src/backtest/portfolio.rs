@@ -0,0 +1,248 @@
+use crate::backtest::conflict;
+use crate::backtest::result::{BacktestResult, ExitReason, TradeDetail};
+use crate::scorecard::sizing::round_to_lot;
+use crate::stock::calendar::TradingCalendar;
+use crate::targets::TradeOutcome;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// 组合回测中每笔买入的金额规则
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PositionSizing {
+    /// 当前权益的固定比例，0.2表示每笔买入权益的20%
+    FixedFraction(f32),
+    /// 当前权益按max_positions平均分配
+    EqualWeight,
+    /// 每笔固定金额(元)
+    FixedCash(f32),
+}
+
+/// 组合回测配置: 有限资金、仓位规则和最大同时持仓数
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PortfolioBacktest {
+    /// 初始资金(元)
+    pub initial_capital: f32,
+    pub position_size: PositionSizing,
+    /// 最多同时持有的股票数
+    pub max_positions: usize,
+}
+
+/// 组合回测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioResult {
+    pub config: PortfolioBacktest,
+    /// 实际成交的交易统计，收益曲线和回撤按组合权益计算
+    pub result: BacktestResult,
+    /// 按日期正序的每日权益(日期, 权益)，持仓按当日收盘价估值
+    pub equity_curve: Vec<(i32, f32)>,
+}
+
+impl PortfolioResult {
+    /// 期末权益，没有交易日时为初始资金
+    pub fn final_equity(&self) -> f32 {
+        self.equity_curve.last().map_or(self.config.initial_capital, |&(_, equity)| equity)
+    }
+    
+    /// 组合总收益率
+    pub fn total_return(&self) -> f32 {
+        if self.config.initial_capital > 0.0 {
+            self.final_equity() / self.config.initial_capital - 1.0
+        } else {
+            0.0
+        }
+    }
+    
+    /// 格式化为人类可读的报告
+    pub fn format_report(&self) -> String {
+        let mut report = String::new();
+        
+        report.push_str(&format!("初始资金: {:.2}\n", self.config.initial_capital));
+        report.push_str(&format!("期末权益: {:.2}\n", self.final_equity()));
+        report.push_str(&format!("组合收益率: {:.2}%\n", self.total_return() * 100.0));
+        report.push_str(&format!("最大持仓数: {}\n", self.config.max_positions));
+        report.push_str(&self.result.format_report());
+        
+        report
+    }
+}
+
+/// 组合回测的候选交易
+pub(crate) struct PortfolioTrade {
+    pub outcome: TradeOutcome,
+    /// 逐笔明细，return_pct为扣除滑点和成本后的收益率
+    pub detail: TradeDetail,
+    pub entry_date: i32,
+    pub exit_date: i32,
+    /// 持有期间的(日期, 收盘价)，按日期正序
+    pub closes: Vec<(i32, f32)>,
+}
+
+/// 持仓
+struct Position {
+    shares: u32,
+    /// 买入金额
+    entry_value: f64,
+    exit_date: i32,
+    closes: Vec<(i32, f32)>,
+    /// 卖出后回收的资金
+    proceeds: f64,
+}
+
+impl Position {
+    /// 按当日或之前最近的收盘价估值，买入当天之前按买入金额
+    fn value(&self, date: i32) -> f64 {
+        match self.closes.iter().rev().find(|&&(close_date, _)| close_date <= date) {
+            Some(&(_, close)) => self.shares as f64 * close as f64,
+            None => self.entry_value,
+        }
+    }
+}
+
+impl PortfolioBacktest {
+    pub fn new(initial_capital: f32, position_size: PositionSizing, max_positions: usize) -> Self {
+        Self { initial_capital, position_size, max_positions }
+    }
+    
+    /// 当前权益下单笔买入的金额上限
+    fn budget(&self, equity: f64) -> f64 {
+        match self.position_size {
+            PositionSizing::FixedFraction(fraction) => equity * fraction as f64,
+            PositionSizing::EqualWeight => equity / self.max_positions.max(1) as f64,
+            PositionSizing::FixedCash(cash) => cash as f64,
+        }
+    }
+    
+    /// 按日期顺序模拟: 每天先卖出到期持仓，再按信号顺序买入，持仓已满或资金不足时放弃
+    pub(crate) fn simulate(&self, trades: Vec<PortfolioTrade>, calendar: &TradingCalendar) -> PortfolioResult {
+        let mut entries: BTreeMap<i32, Vec<PortfolioTrade>> = BTreeMap::new();
+        for trade in trades {
+            entries.entry(trade.entry_date).or_default().push(trade);
+        }
+        
+        // 权益曲线覆盖第一笔买入到最后一笔卖出之间的交易日
+        let mut dates: BTreeSet<i32> = BTreeSet::new();
+        for trade in entries.values().flatten() {
+            dates.insert(trade.entry_date);
+            dates.insert(trade.exit_date);
+        }
+        if let (Some(&first), Some(&last)) = (dates.first(), dates.last()) {
+            dates.extend(calendar.dates().iter().filter(|&&date| date >= first && date <= last));
+        }
+        
+        let mut cash = self.initial_capital as f64;
+        let mut positions: Vec<Position> = Vec::new();
+        let mut taken: Vec<PortfolioTrade> = Vec::new();
+        let mut skipped_trades = 0;
+        let mut equity_curve = Vec::with_capacity(dates.len());
+        
+        for date in dates {
+            // 卖出到期持仓
+            positions.retain(|position| {
+                if position.exit_date <= date {
+                    cash += position.proceeds;
+                    false
+                } else {
+                    true
+                }
+            });
+            
+            for trade in entries.remove(&date).unwrap_or_default() {
+                if positions.len() >= self.max_positions {
+                    skipped_trades += 1;
+                    continue;
+                }
+                
+                let equity = cash + positions.iter().map(|position| position.value(date)).sum::<f64>();
+                let budget = self.budget(equity).min(cash);
+                let price = trade.detail.entry_price as f64;
+                let shares = if price > 0.0 { round_to_lot(budget / price) } else { 0 };
+                if shares == 0 {
+                    skipped_trades += 1;
+                    continue;
+                }
+                
+                let entry_value = shares as f64 * price;
+                cash -= entry_value;
+                positions.push(Position {
+                    shares,
+                    entry_value,
+                    exit_date: trade.exit_date,
+                    closes: trade.closes.clone(),
+                    proceeds: entry_value * (1.0 + trade.detail.return_pct as f64),
+                });
+                taken.push(trade);
+            }
+            
+            let equity = cash + positions.iter().map(|position| position.value(date)).sum::<f64>();
+            equity_curve.push((date, equity as f32));
+        }
+        
+        let mut result = self.trade_result(&taken, &equity_curve);
+        result.skipped_trades = skipped_trades;
+        
+        PortfolioResult { config: *self, result, equity_curve }
+    }
+    
+    /// 由实际成交的交易和权益曲线计算回测结果
+    fn trade_result(&self, taken: &[PortfolioTrade], equity_curve: &[(i32, f32)]) -> BacktestResult {
+        let mut result = BacktestResult::new();
+        let returns: Vec<f32> = taken.iter().map(|trade| trade.detail.return_pct).collect();
+        let total_trades = taken.len();
+        
+        for trade in taken {
+            result.exit_reasons.record(trade.outcome.exit_reason, trade.detail.return_pct);
+        }
+        
+        result.total_trades = total_trades;
+        result.winning_trades = taken.iter().filter(|trade| trade.outcome.is_win).count();
+        result.losing_trades = total_trades - result.winning_trades;
+        result.stop_loss_trades = taken.iter()
+            .filter(|trade| !trade.outcome.is_win && trade.outcome.is_stop_loss)
+            .count();
+        result.stop_loss_fail_trades = result.exit_reasons.get(ExitReason::StopLossFailed).count;
+        
+        if total_trades > 0 {
+            let count = total_trades as f32;
+            result.win_rate = conflict::weighted_wins(result.winning_trades, result.exit_reasons.get(ExitReason::Ambiguous).count) / count;
+            result.stop_loss_rate = result.stop_loss_trades as f32 / count;
+            result.stop_loss_fail_rate = result.stop_loss_fail_trades as f32 / count;
+            result.avg_return = returns.iter().sum::<f32>() / count;
+            result.avg_hold_days = taken.iter().map(|trade| trade.outcome.hold_days as f32).sum::<f32>() / count;
+            result.avg_slippage = taken.iter().map(|trade| trade.detail.slippage).sum::<f32>() / count;
+        }
+        result.max_return = returns.iter().fold(0.0, |max, &r| r.max(max));
+        result.max_loss = returns.iter().fold(0.0, |min, &r| r.min(min));
+        
+        // 每日收益为权益变化占初始资金的比例，累计收益曲线即组合权益曲线
+        let initial = self.initial_capital;
+        if initial > 0.0 && !equity_curve.is_empty() {
+            let mut previous = initial;
+            let pnl = equity_curve.iter()
+                .map(|&(date, equity)| {
+                    let value = (equity - previous) / initial;
+                    previous = equity;
+                    (date, value)
+                })
+                .collect();
+            result.set_daily_pnl(pnl);
+        }
+        
+        result.trade_details = Some(taken.iter().map(|trade| trade.detail.clone()).collect());
+        result.calculate_advanced_metrics(&returns);
+        result.trade_returns = returns;
+        
+        let benchmark_pairs: Vec<(f32, f32)> = taken.iter()
+            .filter_map(|trade| Some((trade.detail.return_pct, trade.detail.benchmark_return_pct?)))
+            .collect();
+        result.set_benchmark_returns(&benchmark_pairs);
+        
+        result
+    }
+}
+
+impl Default for PortfolioBacktest {
+    fn default() -> Self {
+        Self::new(100_000.0, PositionSizing::EqualWeight, 5)
+    }
+}
+
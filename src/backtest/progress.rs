@@ -0,0 +1,29 @@
+/// 回测进度事件
+///
+/// 评分卡并行评估各组合，事件可能来自不同线程且交错到达
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+    /// 开始评估一个组合，total为本轮的组合数
+    CombinationStarted {
+        selector: String,
+        signal: String,
+        target: String,
+        total: usize,
+    },
+    /// 一个组合评估完成，completed为本轮已完成的组合数
+    ///
+    /// completed在组合完成时计数，多个线程的事件到达回调的顺序可能与计数顺序不同
+    CombinationFinished {
+        score: f32,
+        completed: usize,
+        total: usize,
+    },
+    /// 一个组合完成了第idx个回测日(从1开始)
+    DayCompleted {
+        idx: usize,
+        total: usize,
+    },
+}
+
+/// 进度回调，可能在rayon的多个工作线程上同时调用，需要自行保证输出不交错
+pub type ProgressCallback = Box<dyn Fn(ProgressEvent) + Send + Sync>;
@@ -0,0 +1,331 @@
+use crate::backtest::result::{BacktestResult, Period};
+use anyhow::Result;
+use chrono::Local;
+use std::fs;
+use std::path::Path;
+
+/// 收益曲线图的尺寸(像素)
+const CHART_WIDTH: f32 = 800.0;
+const CHART_HEIGHT: f32 = 240.0;
+const CHART_PADDING: f32 = 30.0;
+
+/// 报告中列出的最大交易笔数，超出部分只显示数量
+const MAX_TRADE_ROWS: usize = 500;
+
+pub(crate) const STYLE: &str = "body{font-family:sans-serif;margin:24px;color:#222}\
+h1{font-size:20px}h2{font-size:16px;margin-top:28px}\
+.cards{display:flex;flex-wrap:wrap;gap:12px}\
+.card{border:1px solid #ddd;border-radius:6px;padding:10px 14px;min-width:120px}\
+.card .label{font-size:12px;color:#666}.card .value{font-size:18px;font-weight:bold}\
+table{border-collapse:collapse;font-size:13px}th,td{border:1px solid #ddd;padding:4px 8px;text-align:right}\
+th:first-child,td:first-child{text-align:left}.pos{color:#c0392b}.neg{color:#27ae60}\
+.meta{color:#666;font-size:13px}";
+
+/// 报告标题中的运行信息
+#[derive(Debug, Clone)]
+pub struct RunMeta {
+    pub strategy: String,
+    pub signal: String,
+    pub target: String,
+    pub back_days: usize,
+    /// 生成时间，new()时取当前时间
+    pub generated_at: String,
+}
+
+impl RunMeta {
+    pub fn new(strategy: &str, signal: &str, target: &str, back_days: usize) -> Self {
+        Self {
+            strategy: strategy.to_string(),
+            signal: signal.to_string(),
+            target: target.to_string(),
+            back_days,
+            generated_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+}
+
+/// 生成单文件HTML回测报告: 指标卡片、退出原因、月度收益、收益曲线(内嵌SVG)和交易列表
+///
+/// 不依赖外部JS或CSS，没有交易时各部分显示为空
+pub fn write_html(result: &BacktestResult, meta: &RunMeta, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    
+    fs::write(path, render_html(result, meta))?;
+    Ok(())
+}
+
+/// 生成HTML报告内容
+pub fn render_html(result: &BacktestResult, meta: &RunMeta) -> String {
+    let title = format!("回测报告: {} / {} / {}", meta.strategy, meta.signal, meta.target);
+    
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape(&title)));
+    html.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", STYLE));
+    html.push_str(&format!("<h1>{}</h1>\n", escape(&title)));
+    html.push_str(&format!("<p class=\"meta\">回测天数: {} | 生成时间: {}</p>\n",
+        meta.back_days, escape(&meta.generated_at)));
+    
+    html.push_str(&summary_cards(result));
+    html.push_str(&equity_chart(result));
+    html.push_str(&exit_reason_table(result));
+    html.push_str(&monthly_table(result));
+    html.push_str(&trade_table(result));
+    
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn summary_cards(result: &BacktestResult) -> String {
+    let cards = [
+        ("总交易次数", result.total_trades.to_string()),
+        ("胜率", percent(result.win_rate)),
+        ("平均收益率", percent(result.avg_return)),
+        ("年化收益率", percent(result.annualized_return)),
+        ("最大回撤", percent(result.max_drawdown)),
+        ("最长回撤持续", format!("{}天", result.max_drawdown_duration_days)),
+        ("水下时间占比", percent(result.time_underwater_ratio)),
+        ("最长恢复时间", format!("{}天", result.longest_recovery_days)),
+        ("夏普比率", format!("{:.2}", result.sharpe_ratio)),
+        ("盈亏比", format!("{:.2}", result.profit_factor)),
+        ("止损率", percent(result.stop_loss_rate)),
+        ("平均持有天数", format!("{:.1}", result.avg_hold_days)),
+    ];
+    
+    let mut html = String::from("<h2>概览</h2>\n<div class=\"cards\">\n");
+    for (label, value) in cards {
+        html.push_str(&format!("<div class=\"card\"><div class=\"label\">{}</div><div class=\"value\">{}</div></div>\n",
+            label, escape(&value)));
+    }
+    html.push_str("</div>\n");
+    html
+}
+
+/// 由cumulative_curve生成收益曲线SVG，纵轴包含0线
+fn equity_chart(result: &BacktestResult) -> String {
+    let mut html = String::from("<h2>累计收益曲线</h2>\n");
+    html.push_str(&format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = CHART_WIDTH, h = CHART_HEIGHT));
+    
+    let curve = result.cumulative_curve.as_deref().unwrap_or(&[]);
+    if curve.is_empty() {
+        html.push_str(&format!("<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" fill=\"#999\">没有收益曲线数据</text>\n",
+            CHART_WIDTH / 2.0, CHART_HEIGHT / 2.0));
+        html.push_str("</svg>\n");
+        return html;
+    }
+    
+    let min = curve.iter().map(|&(_, value)| value).fold(0.0f32, f32::min);
+    let max = curve.iter().map(|&(_, value)| value).fold(0.0f32, f32::max);
+    let range = if max > min { max - min } else { 1.0 };
+    let plot_width = CHART_WIDTH - 2.0 * CHART_PADDING;
+    let plot_height = CHART_HEIGHT - 2.0 * CHART_PADDING;
+    
+    let x = |i: usize| {
+        if curve.len() > 1 {
+            CHART_PADDING + plot_width * i as f32 / (curve.len() - 1) as f32
+        } else {
+            CHART_PADDING + plot_width / 2.0
+        }
+    };
+    let y = |value: f32| CHART_PADDING + plot_height * (max - value) / range;
+    
+    let zero = y(0.0);
+    html.push_str(&format!("<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#ccc\" stroke-dasharray=\"4 3\"/>\n",
+        CHART_PADDING, zero, CHART_WIDTH - CHART_PADDING, zero));
+    
+    let mut path = String::new();
+    for (i, &(_, value)) in curve.iter().enumerate() {
+        let command = if i == 0 { 'M' } else { 'L' };
+        path.push_str(&format!("{}{:.1},{:.1} ", command, x(i), y(value)));
+    }
+    html.push_str(&format!("<path d=\"{}\" fill=\"none\" stroke=\"#2c7be5\" stroke-width=\"1.5\"/>\n", path.trim_end()));
+    
+    // 标注起止日期和最高最低值
+    let (first_date, last_date) = (curve[0].0, curve[curve.len() - 1].0);
+    html.push_str(&format!("<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"11\" fill=\"#666\">{}</text>\n",
+        CHART_PADDING, CHART_HEIGHT - 8.0, first_date));
+    html.push_str(&format!("<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"11\" fill=\"#666\" text-anchor=\"end\">{}</text>\n",
+        CHART_WIDTH - CHART_PADDING, CHART_HEIGHT - 8.0, last_date));
+    html.push_str(&format!("<text x=\"4\" y=\"{:.1}\" font-size=\"11\" fill=\"#666\">{}</text>\n",
+        CHART_PADDING - 8.0, percent(max)));
+    html.push_str(&format!("<text x=\"4\" y=\"{:.1}\" font-size=\"11\" fill=\"#666\">{}</text>\n",
+        CHART_HEIGHT - CHART_PADDING + 14.0, percent(min)));
+    
+    html.push_str("</svg>\n");
+    html
+}
+
+fn exit_reason_table(result: &BacktestResult) -> String {
+    let mut html = String::from("<h2>退出原因</h2>\n");
+    let classified = result.exit_reasons.total();
+    if classified == 0 {
+        html.push_str("<p class=\"meta\">没有退出原因统计</p>\n");
+        return html;
+    }
+    
+    html.push_str("<table>\n<tr><th>退出原因</th><th>交易数</th><th>占比</th><th>平均收益率</th></tr>\n");
+    for (reason, stat) in result.exit_reasons.iter() {
+        html.push_str(&format!("<tr><td>{}</td><td>{}</td><td>{}</td>{}</tr>\n",
+            reason.label(),
+            stat.count,
+            percent(stat.count as f32 / classified as f32),
+            return_cell(stat.avg_return())));
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn monthly_table(result: &BacktestResult) -> String {
+    let mut html = String::from("<h2>月度收益</h2>\n");
+    let rows = result.returns_by_period(Period::Month);
+    if rows.is_empty() {
+        html.push_str("<p class=\"meta\">无可按日期汇总的交易记录</p>\n");
+        return html;
+    }
+    
+    html.push_str("<table>\n<tr><th>月份</th><th>收益率</th><th>平均收益</th><th>交易数</th></tr>\n");
+    for (label, total, count) in rows {
+        html.push_str(&format!("<tr><td>{}</td>{}{}<td>{}</td></tr>\n",
+            escape(&label), return_cell(total), return_cell(total / count as f32), count));
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn trade_table(result: &BacktestResult) -> String {
+    let mut html = String::from("<h2>交易列表</h2>\n");
+    let details = result.trade_details.as_deref().unwrap_or(&[]);
+    if details.is_empty() {
+        html.push_str("<p class=\"meta\">没有逐笔交易明细</p>\n");
+        return html;
+    }
+    
+    html.push_str("<table>\n<tr><th>代码</th><th>买入日期</th><th>买入价</th><th>卖出日期</th><th>卖出价</th>\
+        <th>收益率</th><th>持有天数</th><th>退出原因</th></tr>\n");
+    for detail in details.iter().take(MAX_TRADE_ROWS) {
+        html.push_str(&format!("<tr><td>{}</td><td>{}</td><td>{:.2}</td><td>{}</td><td>{:.2}</td>{}<td>{}</td><td>{}</td></tr>\n",
+            escape(&detail.symbol),
+            escape(&detail.entry_date),
+            detail.entry_price,
+            escape(&detail.exit_date),
+            detail.exit_price,
+            return_cell(detail.return_pct),
+            detail.hold_days,
+            detail.exit_reason.label()));
+    }
+    html.push_str("</table>\n");
+    
+    if details.len() > MAX_TRADE_ROWS {
+        html.push_str(&format!("<p class=\"meta\">仅显示前{}笔，共{}笔</p>\n", MAX_TRADE_ROWS, details.len()));
+    }
+    html
+}
+
+/// 收益率单元格，A股习惯红涨绿跌
+fn return_cell(value: f32) -> String {
+    let class = if value > 0.0 { "pos" } else if value < 0.0 { "neg" } else { "" };
+    format!("<td class=\"{}\">{}</td>", class, percent(value))
+}
+
+fn percent(value: f32) -> String {
+    format!("{:.2}%", value * 100.0)
+}
+
+/// 转义HTML特殊字符
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::result::{ExitReason, TradeDetail};
+    use crate::targets::Direction;
+
+    fn meta() -> RunMeta {
+        RunMeta::new("atr", "breakout", "return<a&b>", 30)
+    }
+
+    fn sample() -> BacktestResult {
+        let mut result = BacktestResult {
+            total_trades: 2,
+            win_rate: 0.5,
+            avg_return: 0.0125,
+            max_drawdown: 0.08,
+            trade_details: Some(vec![TradeDetail {
+                symbol: "000001.SZ".to_string(),
+                direction: Direction::Long,
+                entry_date: "20240102".to_string(),
+                entry_price: 10.0,
+                exit_date: "20240105".to_string(),
+                exit_price: 12.5,
+                filled_entry_price: 10.0,
+                filled_exit_price: 12.5,
+                peak_price: 12.5,
+                return_pct: 0.25,
+                hold_days: 3,
+                exit_reason: ExitReason::TargetReached,
+                win: true,
+                slippage: 0.0,
+                cost: 0.0,
+                benchmark_return_pct: None,
+                legs: None,
+            }]),
+            ..BacktestResult::default()
+        };
+        result.exit_reasons.record(ExitReason::TargetReached, 0.25);
+        result.exit_reasons.record(ExitReason::StopLoss, -0.10);
+        result.set_daily_pnl(vec![(20240105, 0.25), (20240110, -0.10)]);
+        result
+    }
+
+    #[test]
+    fn report_contains_metrics_and_chart() {
+        let html = render_html(&sample(), &meta());
+        
+        assert!(html.contains("<div class=\"label\">胜率</div><div class=\"value\">50.00%</div>"));
+        assert!(html.contains("<div class=\"label\">最大回撤</div><div class=\"value\">8.00%</div>"));
+        assert!(html.contains("<tr><td>2024-01</td><td class=\"pos\">25.00%</td>"));
+        assert!(html.contains("<td>止损</td><td>1</td><td>50.00%</td><td class=\"neg\">-10.00%</td>"));
+        // 标题中的特殊字符被转义
+        assert!(html.contains("return&lt;a&amp;b&gt;"));
+        
+        let svg_start = html.find("<svg ").unwrap();
+        let svg_end = html.find("</svg>").unwrap();
+        assert_eq!(html.matches("<svg ").count(), 1);
+        assert!(svg_start < svg_end);
+        // 累计收益0.25 -> 0.15，纵轴范围为0..0.25
+        assert!(html[svg_start..svg_end].contains("<path d=\"M30.0,30.0 L770.0,102.0\""));
+        assert!(html.ends_with("</html>\n"));
+    }
+
+    #[test]
+    fn report_renders_without_trades() {
+        let dir = std::env::temp_dir().join(format!("strategy_lab_report_{}", std::process::id()));
+        let path = dir.join("empty.html");
+        
+        write_html(&BacktestResult::default(), &meta(), &path).unwrap();
+        
+        let html = fs::read_to_string(&path).unwrap();
+        assert!(html.contains("没有收益曲线数据"));
+        assert!(html.contains("没有逐笔交易明细"));
+        assert!(html.contains("</svg>"));
+        assert!(!html.contains("<path"));
+        
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,25 +1,247 @@
+use crate::backtest::conflict;
+use crate::stock::calendar::TradingCalendar;
+use crate::targets::Direction;
+use chrono::{Datelike, NaiveDate};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use crate::utils::csv;
+use crate::utils::metrics;
+use crate::utils::rng::SplitMix64;
 use serde::{Serialize, Deserialize};
 
+/// 年化使用的每年交易日数(A股约244天)
+pub const TRADING_DAYS_PER_YEAR: f32 = 244.0;
+
+/// 按交易日期跨度年化时每年的自然日数
+const CALENDAR_DAYS_PER_YEAR: f64 = 365.25;
+
 /// 交易详情
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeDetail {
     pub symbol: String,
+    /// 交易方向，做空时entry_price为卖出开仓价，exit_price为买入平仓价
+    #[serde(default)]
+    pub direction: Direction,
     pub entry_date: String,
+    /// 理论买入价
     pub entry_price: f32,
     pub exit_date: String,
+    /// 理论卖出价，即按目标计算的毛收益对应的价格
     pub exit_price: f32,
+    /// 计入滑点后的买入价
+    #[serde(default)]
+    pub filled_entry_price: f32,
+    /// 计入滑点后的卖出价
+    #[serde(default)]
+    pub filled_exit_price: f32,
+    /// 买入后到卖出当天的最高价，做空时为开仓后到平仓当天的最低价
+    #[serde(default)]
+    pub peak_price: f32,
+    /// 扣除滑点和交易成本后的收益率
     pub return_pct: f32,
     pub hold_days: usize,
     pub exit_reason: ExitReason,
+    /// 目标判定的盈亏，不能由return_pct的正负推断(如恰好达到目标后止损)
+    #[serde(default)]
+    pub win: bool,
+    /// 滑点对收益率的拖累，return_pct已扣除
+    #[serde(default)]
+    pub slippage: f32,
+    /// 交易成本对收益率的拖累，return_pct已扣除
+    #[serde(default)]
+    pub cost: f32,
+    /// 同一买卖日期基准指数的收益率，未设置基准或基准数据未覆盖买卖日期时为None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub benchmark_return_pct: Option<f32>,
+    /// 分批卖出时每一批的卖出明细，exit_date和exit_price为最后一批；一次全部卖出时为None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legs: Option<Vec<TradeLeg>>,
 }
 
-/// 退出原因
+/// 分批卖出中一批的明细
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeLeg {
+    pub exit_date: String,
+    /// 理论卖出价，未计滑点
+    pub exit_price: f32,
+    /// 卖出的仓位比例
+    pub fraction: f32,
+    pub exit_reason: ExitReason,
+}
+
+/// 单个回测日的成功率、交易数和盈利交易数
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DailyScore {
+    /// 买入日期(YYYYMMDD)
+    pub trade_date: i32,
+    /// 当天信号的成功率，没有交易时为0
+    pub score: f32,
+    pub total_trades: usize,
+    /// 盈利的交易数，开启冲突规则时无法判定的交易不计入
+    #[serde(default)]
+    pub winning_trades: usize,
+}
+
+/// 退出原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ExitReason {
     TargetReached,
     StopLoss,
     StopLossFailed,
     TimeExpired,
+    /// 止损当天封死跌停，顺延到下一交易日开盘卖出
+    LimitLocked,
+    /// 盈利后回撤触发移动止损
+    TrailingStop,
+    /// 盈利达到保本触发线后止损上移到买入价，回落到买入价时卖出
+    BreakEvenStop,
+    /// 同一天触及止损价和目标价，按ConflictPolicy::Proportional各算一半
+    Ambiguous,
+}
+
+impl ExitReason {
+    /// 导出时使用的固定名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExitReason::TargetReached => "target_reached",
+            ExitReason::StopLoss => "stop_loss",
+            ExitReason::StopLossFailed => "stop_loss_failed",
+            ExitReason::TimeExpired => "time_expired",
+            ExitReason::LimitLocked => "limit_locked",
+            ExitReason::TrailingStop => "trailing_stop",
+            ExitReason::BreakEvenStop => "break_even_stop",
+            ExitReason::Ambiguous => "ambiguous",
+        }
+    }
+    
+    /// 报告中使用的名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExitReason::TargetReached => "达到目标",
+            ExitReason::StopLoss => "止损",
+            ExitReason::StopLossFailed => "止损失败(跳空)",
+            ExitReason::TimeExpired => "到期平仓",
+            ExitReason::LimitLocked => "跌停顺延",
+            ExitReason::TrailingStop => "移动止损",
+            ExitReason::BreakEvenStop => "保本止损",
+            ExitReason::Ambiguous => "止盈止损同日",
+        }
+    }
+}
+
+/// 单个退出原因的统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExitReasonStat {
+    pub count: usize,
+    /// 收益率之和，用于合并后重新计算均值
+    pub total_return: f32,
+}
+
+impl ExitReasonStat {
+    /// 平均收益率，没有交易时为0
+    pub fn avg_return(&self) -> f32 {
+        if self.count > 0 {
+            self.total_return / self.count as f32
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 按退出原因汇总的交易统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExitReasonBreakdown {
+    pub target_reached: ExitReasonStat,
+    pub stop_loss: ExitReasonStat,
+    pub stop_loss_failed: ExitReasonStat,
+    pub time_expired: ExitReasonStat,
+    #[serde(default)]
+    pub limit_locked: ExitReasonStat,
+    #[serde(default)]
+    pub trailing_stop: ExitReasonStat,
+    #[serde(default)]
+    pub break_even_stop: ExitReasonStat,
+    #[serde(default)]
+    pub ambiguous: ExitReasonStat,
+}
+
+impl ExitReasonBreakdown {
+    /// 从交易详情统计
+    pub fn from_details(details: &[TradeDetail]) -> Self {
+        let mut breakdown = Self::default();
+        for detail in details {
+            breakdown.record(detail.exit_reason, detail.return_pct);
+        }
+        breakdown
+    }
+    
+    /// 记录一笔交易
+    pub fn record(&mut self, reason: ExitReason, return_pct: f32) {
+        let stat = self.get_mut(reason);
+        stat.count += 1;
+        stat.total_return += return_pct;
+    }
+    
+    pub fn get(&self, reason: ExitReason) -> &ExitReasonStat {
+        match reason {
+            ExitReason::TargetReached => &self.target_reached,
+            ExitReason::StopLoss => &self.stop_loss,
+            ExitReason::StopLossFailed => &self.stop_loss_failed,
+            ExitReason::TimeExpired => &self.time_expired,
+            ExitReason::LimitLocked => &self.limit_locked,
+            ExitReason::TrailingStop => &self.trailing_stop,
+            ExitReason::BreakEvenStop => &self.break_even_stop,
+            ExitReason::Ambiguous => &self.ambiguous,
+        }
+    }
+    
+    fn get_mut(&mut self, reason: ExitReason) -> &mut ExitReasonStat {
+        match reason {
+            ExitReason::TargetReached => &mut self.target_reached,
+            ExitReason::StopLoss => &mut self.stop_loss,
+            ExitReason::StopLossFailed => &mut self.stop_loss_failed,
+            ExitReason::TimeExpired => &mut self.time_expired,
+            ExitReason::LimitLocked => &mut self.limit_locked,
+            ExitReason::TrailingStop => &mut self.trailing_stop,
+            ExitReason::BreakEvenStop => &mut self.break_even_stop,
+            ExitReason::Ambiguous => &mut self.ambiguous,
+        }
+    }
+    
+    /// 合并另一份统计
+    pub fn combine(&mut self, other: &Self) {
+        for (reason, stat) in other.iter() {
+            let own = self.get_mut(reason);
+            own.count += stat.count;
+            own.total_return += stat.total_return;
+        }
+    }
+    
+    /// 按固定顺序遍历各退出原因
+    pub fn iter(&self) -> impl Iterator<Item = (ExitReason, &ExitReasonStat)> {
+        [
+            ExitReason::TargetReached,
+            ExitReason::StopLoss,
+            ExitReason::StopLossFailed,
+            ExitReason::TimeExpired,
+            ExitReason::LimitLocked,
+            ExitReason::TrailingStop,
+            ExitReason::BreakEvenStop,
+            ExitReason::Ambiguous,
+        ]
+        .into_iter()
+        .map(move |reason| (reason, self.get(reason)))
+    }
+    
+    /// 统计的交易总数
+    pub fn total(&self) -> usize {
+        self.iter().map(|(_, stat)| stat.count).sum()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
 }
 
 /// 增强的回测结果
@@ -31,6 +253,12 @@ pub struct BacktestResult {
     pub losing_trades: usize,
     pub stop_loss_trades: usize,
     pub stop_loss_fail_trades: usize,
+    /// 放弃的信号数: 组合回测中持仓已满或资金不足，或开启涨跌停规则时涨停价无法买入
+    #[serde(default)]
+    pub skipped_trades: usize,
+    /// 因停牌放弃买入或顺延卖出的次数
+    #[serde(default)]
+    pub suspended_skips: usize,
     
     // 比率
     pub win_rate: f32,
@@ -42,15 +270,202 @@ pub struct BacktestResult {
     pub max_return: f32,
     pub max_loss: f32,
     pub avg_hold_days: f32,
+    /// 滑点对每笔收益率的平均拖累
+    #[serde(default)]
+    pub avg_slippage: f32,
     
     // 高级指标
     pub sharpe_ratio: f32,
     pub max_drawdown: f32,
     pub profit_factor: f32,
+    /// 索提诺比率: 平均收益 / 下行标准差，没有亏损交易时为无穷大
+    #[serde(default)]
+    pub sortino_ratio: f32,
+    /// 卡尔马比率: 平均收益 / 最大回撤，没有回撤时为无穷大
+    #[serde(default)]
+    pub calmar_ratio: f32,
+    /// 单笔交易的期望收益
+    #[serde(default)]
+    pub expectancy: f32,
+    
+    /// 最长回撤持续时间(自然日): 从前高到重新创新高，未恢复时计到曲线最后一天
+    #[serde(default)]
+    pub max_drawdown_duration_days: usize,
+    /// 累计收益曲线上低于此前高点的日期占比
+    #[serde(default)]
+    pub time_underwater_ratio: f32,
+    /// 已恢复的回撤中，从谷底到重新创新高的最长自然日数
+    #[serde(default)]
+    pub longest_recovery_days: usize,
+    
+    /// 年化收益率，逐笔收益复利计算，见calculate_advanced_metrics
+    #[serde(default)]
+    pub annualized_return: f32,
+    /// 年化波动率: 逐笔收益率标准差 * sqrt(每年交易次数)
+    #[serde(default)]
+    pub annualized_volatility: f32,
+    /// 平均每月交易次数
+    #[serde(default)]
+    pub trades_per_month: f32,
+    
+    /// 逐笔交易同期基准指数收益率的平均值
+    #[serde(default)]
+    pub benchmark_return: f32,
+    /// 平均超额收益: 逐笔收益率减去同期基准收益率
+    #[serde(default)]
+    pub avg_excess_return: f32,
+    /// 信息比率: 平均超额收益 / 超额收益标准差
+    #[serde(default)]
+    pub information_ratio: f32,
     
     // 详细交易记录(可选)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trade_details: Option<Vec<TradeDetail>>,
+    
+    /// 逐笔交易收益率，用于自助法估计置信区间
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trade_returns: Vec<f32>,
+    
+    /// 逐笔交易同期基准收益率，只包含基准数据覆盖买卖日期的交易
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub benchmark_returns: Vec<f32>,
+    
+    /// 与benchmark_returns逐项对应的超额收益率
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excess_returns: Vec<f32>,
+    
+    /// 按日期正序的每日收益(日期, 当天计入的收益率之和)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_pnl: Option<Vec<(i32, f32)>>,
+    
+    /// 按日期正序的累计收益曲线(日期, 截至当天的累计收益率)，最后一点等于全部交易收益率之和
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cumulative_curve: Option<Vec<(i32, f32)>>,
+    
+    /// 逐笔交易按买入日期排序后依次复利的净值曲线(买入日期, 该笔卖出后的净值)，起点净值为1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equity_curve: Option<Vec<(String, f32)>>,
+    
+    /// 按退出原因的交易数和平均收益
+    #[serde(default, skip_serializing_if = "ExitReasonBreakdown::is_empty")]
+    pub exit_reasons: ExitReasonBreakdown,
+}
+
+/// 逐笔收益计入每日收益的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PnlAttribution {
+    /// 全部计入卖出当天
+    #[default]
+    ExitDate,
+    /// 平均分摊到买入后的每个持有交易日
+    SpreadOverHolding,
+}
+
+/// 收益汇总的时间周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// ISO周，标签如"2024-W05"
+    Week,
+    /// 自然月，标签如"2024-03"
+    Month,
+}
+
+impl Period {
+    fn label(self, date: NaiveDate) -> String {
+        match self {
+            Period::Week => {
+                let week = date.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Period::Month => format!("{}-{:02}", date.year(), date.month()),
+        }
+    }
+}
+
+/// 解析交易记录中的日期，支持"20240315"和"2024-03-15"两种写法
+fn parse_trade_date(date: &str) -> Option<NaiveDate> {
+    let date = date.trim();
+    NaiveDate::parse_from_str(date, "%Y%m%d")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+        .ok()
+}
+
+/// 交易明细按(买入日期, 代码)排序，使多次运行的输出一致
+pub(crate) fn sort_trade_details(details: &mut [TradeDetail]) {
+    details.sort_by(|a, b| {
+        (parse_trade_date(&a.entry_date), &a.symbol).cmp(&(parse_trade_date(&b.entry_date), &b.symbol))
+    });
+}
+
+/// 生成每日收益所需的单笔交易时间信息
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradeTiming {
+    /// 买入日(YYYYMMDD)
+    pub entry_date: i32,
+    pub return_pct: f32,
+    /// 持有交易日数，卖出日为买入日之后第hold_days个交易日
+    pub hold_days: usize,
+}
+
+impl TradeTiming {
+    /// 按交易日历把收益率计入日期，卖出日超出日历时记在日历最后一天
+    pub fn attribute(&self, calendar: &TradingCalendar, attribution: PnlAttribution) -> Vec<(i32, f32)> {
+        let day = |n: usize| calendar.offset(self.entry_date, n as i64)
+            .or(calendar.latest())
+            .unwrap_or(self.entry_date);
+        
+        match attribution {
+            PnlAttribution::ExitDate => vec![(day(self.hold_days), self.return_pct)],
+            PnlAttribution::SpreadOverHolding => {
+                let days = self.hold_days.max(1);
+                let share = self.return_pct / days as f32;
+                (1..=days).map(|n| (day(n), share)).collect()
+            }
+        }
+    }
+}
+
+/// 置信区间
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceInterval {
+    pub lower: f32,
+    pub upper: f32,
+}
+
+impl ConfidenceInterval {
+    /// 区间半宽
+    pub fn half_width(&self) -> f32 {
+        (self.upper - self.lower) / 2.0
+    }
+}
+
+/// 自助法估计的胜率和期望收益置信区间
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BootstrapInterval {
+    /// 置信水平，例如0.9
+    pub confidence: f32,
+    pub win_rate: ConfidenceInterval,
+    /// 每笔交易的平均收益率
+    pub expectancy: ConfidenceInterval,
+}
+
+/// 逐笔收益蒙特卡洛重抽样的分位数
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MonteCarloSummary {
+    pub iterations: usize,
+    pub win_rate_p5: f32,
+    pub win_rate_p95: f32,
+    /// 每笔交易平均收益率的分位数
+    pub avg_return_p5: f32,
+    pub avg_return_p95: f32,
+    /// 按重抽样顺序复利的净值曲线最大回撤的95%分位数
+    pub max_drawdown_p95: f32,
+}
+
+impl Default for BacktestResult {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BacktestResult {
@@ -62,6 +477,8 @@ impl BacktestResult {
             losing_trades: 0,
             stop_loss_trades: 0,
             stop_loss_fail_trades: 0,
+            skipped_trades: 0,
+            suspended_skips: 0,
             win_rate: 0.0,
             stop_loss_rate: 0.0,
             stop_loss_fail_rate: 0.0,
@@ -69,10 +486,30 @@ impl BacktestResult {
             max_return: 0.0,
             max_loss: 0.0,
             avg_hold_days: 0.0,
+            avg_slippage: 0.0,
             sharpe_ratio: 0.0,
             max_drawdown: 0.0,
             profit_factor: 0.0,
+            sortino_ratio: 0.0,
+            calmar_ratio: 0.0,
+            expectancy: 0.0,
+            max_drawdown_duration_days: 0,
+            time_underwater_ratio: 0.0,
+            longest_recovery_days: 0,
+            annualized_return: 0.0,
+            annualized_volatility: 0.0,
+            trades_per_month: 0.0,
+            benchmark_return: 0.0,
+            avg_excess_return: 0.0,
+            information_ratio: 0.0,
             trade_details: None,
+            trade_returns: Vec::new(),
+            benchmark_returns: Vec::new(),
+            excess_returns: Vec::new(),
+            daily_pnl: None,
+            cumulative_curve: None,
+            equity_curve: None,
+            exit_reasons: ExitReasonBreakdown::default(),
         }
     }
     
@@ -87,12 +524,20 @@ impl BacktestResult {
         let mut losing_trades = 0;
         let mut stop_loss_trades = 0;
         let mut stop_loss_fail_trades = 0;
+        let mut skipped_trades = 0;
+        let mut suspended_skips = 0;
         let mut total_return = 0.0;
         let mut max_return: f32 = -1.0;
         let mut max_loss: f32 = 0.0;
         let mut total_hold_days = 0.0;
+        let mut total_slippage = 0.0;
         let mut all_returns = Vec::new();
+        let mut benchmark_returns = Vec::new();
+        let mut excess_returns = Vec::new();
         let mut all_trade_details = Vec::new();
+        let mut all_daily_pnl: Option<Vec<(i32, f32)>> = None;
+        let mut exit_reasons = ExitReasonBreakdown::default();
+        let had_equity_curve = results.iter().any(|result| result.equity_curve.is_some());
         
         for result in results {
             total_trades += result.total_trades;
@@ -100,23 +545,42 @@ impl BacktestResult {
             losing_trades += result.losing_trades;
             stop_loss_trades += result.stop_loss_trades;
             stop_loss_fail_trades += result.stop_loss_fail_trades;
+            skipped_trades += result.skipped_trades;
+            suspended_skips += result.suspended_skips;
             
             total_return += result.avg_return * result.total_trades as f32;
             max_return = max_return.max(result.max_return);
             max_loss = max_loss.min(result.max_loss);
             total_hold_days += result.avg_hold_days * result.total_trades as f32;
+            total_slippage += result.avg_slippage * result.total_trades as f32;
+            // 只有交易详情的结果从详情统计退出原因
+            if result.exit_reasons.is_empty() {
+                if let Some(details) = &result.trade_details {
+                    exit_reasons.combine(&ExitReasonBreakdown::from_details(details));
+                }
+            } else {
+                exit_reasons.combine(&result.exit_reasons);
+            }
             
             // 收集所有交易的收益率用于计算高级指标
             if let Some(details) = result.trade_details {
-                for detail in &details {
-                    all_returns.push(detail.return_pct);
+                if result.trade_returns.is_empty() {
+                    all_returns.extend(details.iter().map(|detail| detail.return_pct));
                 }
                 all_trade_details.extend(details);
             }
+            all_returns.extend(result.trade_returns);
+            benchmark_returns.extend(result.benchmark_returns);
+            excess_returns.extend(result.excess_returns);
+            
+            if let Some(pnl) = result.daily_pnl {
+                all_daily_pnl.get_or_insert_with(Vec::new).extend(pnl);
+            }
         }
         
+        // 止盈止损同日的模糊交易按半笔盈利计入胜率
         let win_rate = if total_trades > 0 {
-            winning_trades as f32 / total_trades as f32
+            conflict::weighted_wins(winning_trades, exit_reasons.get(ExitReason::Ambiguous).count) / total_trades as f32
         } else {
             0.0
         };
@@ -145,12 +609,22 @@ impl BacktestResult {
             0.0
         };
         
+        let avg_slippage = if total_trades > 0 {
+            total_slippage / total_trades as f32
+        } else {
+            0.0
+        };
+        
+        sort_trade_details(&mut all_trade_details);
+        
         let mut result = Self {
             total_trades,
             winning_trades,
             losing_trades,
             stop_loss_trades,
             stop_loss_fail_trades,
+            skipped_trades,
+            suspended_skips,
             win_rate,
             stop_loss_rate,
             stop_loss_fail_rate,
@@ -158,89 +632,508 @@ impl BacktestResult {
             max_return,
             max_loss,
             avg_hold_days,
+            avg_slippage,
             sharpe_ratio: 0.0,
             max_drawdown: 0.0,
             profit_factor: 0.0,
+            sortino_ratio: 0.0,
+            calmar_ratio: 0.0,
+            expectancy: 0.0,
+            max_drawdown_duration_days: 0,
+            time_underwater_ratio: 0.0,
+            longest_recovery_days: 0,
+            annualized_return: 0.0,
+            annualized_volatility: 0.0,
+            trades_per_month: 0.0,
+            benchmark_return: 0.0,
+            avg_excess_return: 0.0,
+            information_ratio: 0.0,
             trade_details: if all_trade_details.is_empty() {
                 None
             } else {
                 Some(all_trade_details)
             },
+            trade_returns: Vec::new(),
+            benchmark_returns: Vec::new(),
+            excess_returns: Vec::new(),
+            daily_pnl: None,
+            cumulative_curve: None,
+            equity_curve: None,
+            exit_reasons,
         };
         
+        if let Some(pnl) = all_daily_pnl {
+            result.set_daily_pnl(pnl);
+        }
+        
+        // 有净值曲线的结果按合并后的全部交易重新生成
+        if had_equity_curve {
+            result.set_equity_curve_from_details();
+        }
+        
         // 计算高级指标
         result.calculate_advanced_metrics(&all_returns);
+        result.trade_returns = all_returns;
+        result.set_benchmark_comparison(benchmark_returns, excess_returns);
         
         result
     }
     
-    /// 计算高级指标
-    pub fn calculate_advanced_metrics(&mut self, returns: &[f32]) {
-        // 计算夏普比率
-        self.sharpe_ratio = Self::calculate_sharpe_ratio(returns);
+    /// 由逐笔(交易收益率, 同期基准收益率)计算基准比较指标
+    pub fn set_benchmark_returns(&mut self, pairs: &[(f32, f32)]) {
+        let benchmark_returns = pairs.iter().map(|&(_, benchmark)| benchmark).collect();
+        let excess_returns = pairs.iter().map(|&(return_pct, benchmark)| return_pct - benchmark).collect();
+        self.set_benchmark_comparison(benchmark_returns, excess_returns);
+    }
+    
+    fn set_benchmark_comparison(&mut self, benchmark_returns: Vec<f32>, excess_returns: Vec<f32>) {
+        let mean = |values: &[f32]| if values.is_empty() {
+            0.0
+        } else {
+            values.iter().sum::<f32>() / values.len() as f32
+        };
         
-        // 计算最大回撤
-        self.max_drawdown = Self::calculate_max_drawdown(returns);
+        self.benchmark_return = mean(&benchmark_returns);
+        self.avg_excess_return = mean(&excess_returns);
+        self.information_ratio = metrics::information_ratio(&excess_returns);
+        self.benchmark_returns = benchmark_returns;
+        self.excess_returns = excess_returns;
+    }
+    
+    /// 是否有与基准比较的交易
+    pub fn has_benchmark(&self) -> bool {
+        !self.benchmark_returns.is_empty()
+    }
+    
+    /// 设置每日收益: 同一天的收益合并，按日期正序排列，并生成累计收益曲线
+    pub fn set_daily_pnl(&mut self, mut pnl: Vec<(i32, f32)>) {
+        pnl.sort_by_key(|&(date, _)| date);
         
-        // 计算盈亏比
-        self.profit_factor = if self.losing_trades > 0 {
-            (self.winning_trades as f32 * self.avg_return.max(0.0)) / 
-            (self.losing_trades as f32 * self.max_loss.abs().max(0.001))
-        } else {
-            f32::INFINITY
+        let mut merged: Vec<(i32, f32)> = Vec::with_capacity(pnl.len());
+        for (date, value) in pnl {
+            match merged.last_mut() {
+                Some(last) if last.0 == date => last.1 += value,
+                _ => merged.push((date, value)),
+            }
+        }
+        
+        let mut cumulative = 0.0;
+        let curve = merged.iter()
+            .map(|&(date, value)| {
+                cumulative += value;
+                (date, cumulative)
+            })
+            .collect();
+        
+        self.daily_pnl = Some(merged);
+        self.cumulative_curve = Some(curve);
+        self.calculate_drawdown_durations();
+    }
+    
+    /// 由累计收益曲线计算回撤持续时间、水下时间占比和最长恢复时间
+    ///
+    /// 起点净值为1，天数按曲线日期之间的自然日计算；merge后由合并的曲线重新计算
+    fn calculate_drawdown_durations(&mut self) {
+        self.max_drawdown_duration_days = 0;
+        self.time_underwater_ratio = 0.0;
+        self.longest_recovery_days = 0;
+        
+        let curve: Vec<(NaiveDate, f32)> = self.cumulative_curve.iter()
+            .flatten()
+            .filter_map(|&(date, cumulative)| Some((parse_trade_date(&date.to_string())?, 1.0 + cumulative)))
+            .collect();
+        let Some(&(first_date, _)) = curve.first() else {
+            return;
         };
+        let days = |from: NaiveDate, to: NaiveDate| (to - from).num_days().max(0) as usize;
+        
+        let mut peak: f32 = 1.0;
+        let mut peak_date = first_date;
+        // 当前回撤的谷底(净值, 日期)，不在回撤中时为None
+        let mut trough: Option<(f32, NaiveDate)> = None;
+        let mut underwater = 0;
+        
+        for &(date, value) in &curve {
+            if value >= peak {
+                if let Some((_, trough_date)) = trough.take() {
+                    self.max_drawdown_duration_days = self.max_drawdown_duration_days.max(days(peak_date, date));
+                    self.longest_recovery_days = self.longest_recovery_days.max(days(trough_date, date));
+                }
+                peak = value;
+                peak_date = date;
+            } else {
+                underwater += 1;
+                match trough {
+                    Some((low, _)) if low <= value => {}
+                    _ => trough = Some((value, date)),
+                }
+            }
+        }
+        
+        // 未恢复的回撤计到最后一天
+        if trough.is_some() {
+            let last_date = curve[curve.len() - 1].0;
+            self.max_drawdown_duration_days = self.max_drawdown_duration_days.max(days(peak_date, last_date));
+        }
+        self.time_underwater_ratio = underwater as f32 / curve.len() as f32;
     }
     
-    // 辅助方法
-    fn calculate_sharpe_ratio(returns: &[f32]) -> f32 {
-        if returns.is_empty() {
-            return 0.0;
+    /// 由逐笔交易时间生成每日收益和累计收益曲线，并按曲线重算最大回撤
+    pub fn set_trade_timings(&mut self, trades: &[TradeTiming], calendar: &TradingCalendar, attribution: PnlAttribution) {
+        let pnl = trades.iter()
+            .flat_map(|trade| trade.attribute(calendar, attribution))
+            .collect();
+        self.set_daily_pnl(pnl);
+        
+        if self.cumulative_curve.is_some() {
+            self.max_drawdown = metrics::max_drawdown(&self.equity_values(&[]));
         }
+    }
+    
+    /// 按卖出日期所在的周或月汇总交易收益，返回(周期标签, 收益率之和, 交易数)，按时间正序
+    ///
+    /// 需要trade_details，卖出日期无法解析的交易被忽略
+    pub fn returns_by_period(&self, period: Period) -> Vec<(String, f32, usize)> {
+        let mut buckets: BTreeMap<String, (f32, usize)> = BTreeMap::new();
         
-        let mean: f32 = returns.iter().sum::<f32>() / returns.len() as f32;
+        for detail in self.trade_details.iter().flatten() {
+            if let Some(date) = parse_trade_date(&detail.exit_date) {
+                let bucket = buckets.entry(period.label(date)).or_insert((0.0, 0));
+                bucket.0 += detail.return_pct;
+                bucket.1 += 1;
+            }
+        }
         
-        let variance = returns.iter()
-            .map(|&r| (r - mean).powi(2))
-            .sum::<f32>() / returns.len() as f32;
-            
-        let std_dev = variance.sqrt();
+        buckets.into_iter()
+            .map(|(label, (total, count))| (label, total, count))
+            .collect()
+    }
+    
+    /// 格式化分周期收益汇总
+    pub fn format_returns_by_period(&self, period: Period) -> String {
+        let rows = self.returns_by_period(period);
+        if rows.is_empty() {
+            return "无可按日期汇总的交易记录\n".to_string();
+        }
         
-        if std_dev == 0.0 {
-            return 0.0;
+        let mut report = format!("{:<10} {:>10} {:>10} {:>6}\n", "周期", "收益率", "平均收益", "交易数");
+        for (label, total, count) in rows {
+            report.push_str(&format!("{:<10} {:>9.2}% {:>9.2}% {:>6}\n",
+                label, total * 100.0, total / count as f32 * 100.0, count));
         }
         
-        // 假设无风险利率为0
-        mean / std_dev
+        report
     }
     
-    fn calculate_max_drawdown(returns: &[f32]) -> f32 {
-        if returns.is_empty() {
-            return 0.0;
+    /// 将逐笔交易明细导出为CSV，每笔交易一行，没有明细时只写表头
+    pub fn export_trades_csv(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
         }
         
-        // 计算累积收益
-        let mut cumulative = Vec::with_capacity(returns.len());
-        let mut cum_return = 1.0;
+        let mut lines = vec![csv::join_row(&[
+            "symbol", "direction", "entry_date", "entry_price", "exit_date", "exit_price",
+            "filled_entry_price", "filled_exit_price", "peak_price",
+            "return_pct", "hold_days", "exit_reason", "win", "slippage", "cost",
+            "benchmark_return_pct",
+        ])];
         
-        for &ret in returns {
-            cum_return *= 1.0 + ret;
-            cumulative.push(cum_return);
+        for detail in self.trade_details.iter().flatten() {
+            lines.push(csv::join_row(&[
+                detail.symbol.clone(),
+                detail.direction.as_str().to_string(),
+                detail.entry_date.clone(),
+                detail.entry_price.to_string(),
+                detail.exit_date.clone(),
+                detail.exit_price.to_string(),
+                detail.filled_entry_price.to_string(),
+                detail.filled_exit_price.to_string(),
+                detail.peak_price.to_string(),
+                detail.return_pct.to_string(),
+                detail.hold_days.to_string(),
+                detail.exit_reason.as_str().to_string(),
+                detail.win.to_string(),
+                detail.slippage.to_string(),
+                detail.cost.to_string(),
+                detail.benchmark_return_pct.map_or(String::new(), |benchmark| benchmark.to_string()),
+            ]));
+        }
+        
+        fs::write(path, lines.join("\n") + "\n")?;
+        Ok(())
+    }
+    
+    /// 对逐笔收益率做有放回重抽样，估计胜率和期望收益的置信区间
+    ///
+    /// 收益率大于0的交易计为盈利。全胜或全负的小样本重抽样后区间退化为一点，
+    /// 因此胜率区间与Wilson区间取并集。没有交易记录时返回None
+    pub fn bootstrap(&self, resamples: usize, confidence: f32, seed: u64) -> Option<BootstrapInterval> {
+        let returns = &self.trade_returns;
+        if returns.is_empty() || resamples == 0 {
+            return None;
         }
         
-        // 计算最大回撤
-        let mut max_dd: f32 = 0.0;
-        let mut peak = cumulative[0];
+        let n = returns.len();
+        let mut rng = SplitMix64::new(seed);
+        let mut win_rates = Vec::with_capacity(resamples);
+        let mut expectancies = Vec::with_capacity(resamples);
         
-        for &value in &cumulative {
-            if value > peak {
-                peak = value;
+        for _ in 0..resamples {
+            let mut wins = 0;
+            let mut total = 0.0;
+            
+            for _ in 0..n {
+                let ret = returns[rng.next_index(n)];
+                if ret > 0.0 {
+                    wins += 1;
+                }
+                total += ret;
             }
             
-            let dd = (peak - value) / peak;
-            max_dd = max_dd.max(dd);
+            win_rates.push(wins as f32 / n as f32);
+            expectancies.push(total / n as f32);
+        }
+        
+        let alpha = (1.0 - confidence).clamp(0.0, 1.0) / 2.0;
+        let bootstrap_win_rate = Self::percentile_interval(&mut win_rates, alpha);
+        let wilson = Self::wilson_interval(
+            returns.iter().filter(|&&r| r > 0.0).count(),
+            n,
+            Self::normal_quantile(1.0 - alpha),
+        );
+        
+        Some(BootstrapInterval {
+            confidence,
+            win_rate: ConfidenceInterval {
+                lower: bootstrap_win_rate.lower.min(wilson.lower),
+                upper: bootstrap_win_rate.upper.max(wilson.upper),
+            },
+            expectancy: Self::percentile_interval(&mut expectancies, alpha),
+        })
+    }
+    
+    /// 对逐笔收益做有放回重抽样，统计胜率、平均收益率和最大回撤的分布
+    ///
+    /// 优先使用交易明细中的收益率，没有明细时使用trade_returns。收益率大于0的交易计为盈利，
+    /// 相同的种子得到相同的结果。没有交易记录时返回None
+    pub fn monte_carlo(&self, n_iterations: usize, seed: u64) -> Option<MonteCarloSummary> {
+        let returns: Vec<f32> = match &self.trade_details {
+            Some(details) if !details.is_empty() => details.iter().map(|detail| detail.return_pct).collect(),
+            _ => self.trade_returns.clone(),
+        };
+        if returns.is_empty() || n_iterations == 0 {
+            return None;
+        }
+        
+        let n = returns.len();
+        let mut rng = SplitMix64::new(seed);
+        let mut win_rates = Vec::with_capacity(n_iterations);
+        let mut avg_returns = Vec::with_capacity(n_iterations);
+        let mut drawdowns = Vec::with_capacity(n_iterations);
+        let mut equity = Vec::with_capacity(n + 1);
+        
+        for _ in 0..n_iterations {
+            let mut wins = 0;
+            let mut total = 0.0;
+            equity.clear();
+            equity.push(1.0);
+            
+            for _ in 0..n {
+                let ret = returns[rng.next_index(n)];
+                if ret > 0.0 {
+                    wins += 1;
+                }
+                total += ret;
+                let last = equity[equity.len() - 1];
+                equity.push(last * (1.0 + ret));
+            }
+            
+            win_rates.push(wins as f32 / n as f32);
+            avg_returns.push(total / n as f32);
+            drawdowns.push(metrics::max_drawdown(&equity));
+        }
+        
+        let win_rate = Self::percentile_interval(&mut win_rates, 0.05);
+        let avg_return = Self::percentile_interval(&mut avg_returns, 0.05);
+        let drawdown = Self::percentile_interval(&mut drawdowns, 0.05);
+        
+        Some(MonteCarloSummary {
+            iterations: n_iterations,
+            win_rate_p5: win_rate.lower,
+            win_rate_p95: win_rate.upper,
+            avg_return_p5: avg_return.lower,
+            avg_return_p95: avg_return.upper,
+            max_drawdown_p95: drawdown.upper,
+        })
+    }
+    
+    fn percentile_interval(samples: &mut [f32], alpha: f32) -> ConfidenceInterval {
+        samples.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        
+        let last = samples.len() - 1;
+        let lower_idx = ((last as f32) * alpha).round() as usize;
+        let upper_idx = ((last as f32) * (1.0 - alpha)).round() as usize;
+        
+        ConfidenceInterval {
+            lower: samples[lower_idx.min(last)],
+            upper: samples[upper_idx.min(last)],
+        }
+    }
+    
+    fn wilson_interval(wins: usize, n: usize, z: f32) -> ConfidenceInterval {
+        let n = n as f32;
+        let p = wins as f32 / n;
+        let z2 = z * z;
+        
+        let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let margin = z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+        
+        ConfidenceInterval {
+            lower: (center - margin).max(0.0),
+            upper: (center + margin).min(1.0),
+        }
+    }
+    
+    /// 标准正态分布分位数(Acklam有理逼近)
+    fn normal_quantile(p: f32) -> f32 {
+        let p = (p as f64).clamp(1e-9, 1.0 - 1e-9);
+        
+        const A: [f64; 6] = [-3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2,
+            1.38357751867269e2, -3.066479806614716e1, 2.506628277459239];
+        const B: [f64; 5] = [-5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2,
+            6.680131188771972e1, -1.328068155288572e1];
+        const C: [f64; 6] = [-7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838,
+            -2.549732539343734, 4.374664141464968, 2.938163982698783];
+        const D: [f64; 4] = [7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996,
+            3.754408661907416];
+        
+        let tail = |q: f64| {
+            (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+                / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+        };
+        
+        let x = if p < 0.02425 {
+            tail((-2.0 * p.ln()).sqrt())
+        } else if p > 1.0 - 0.02425 {
+            -tail((-2.0 * (1.0 - p).ln()).sqrt())
+        } else {
+            let q = p - 0.5;
+            let r = q * q;
+            (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+                / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+        };
+        
+        x as f32
+    }
+    
+    /// 计算高级指标
+    pub fn calculate_advanced_metrics(&mut self, returns: &[f32]) {
+        // 逐笔收益率的夏普和索提诺比率，假设无风险利率为0
+        self.sharpe_ratio = metrics::sharpe_ratio(returns, 0.0);
+        self.sortino_ratio = metrics::sortino_ratio(returns, 0.0);
+        
+        // 计算最大回撤: 有按日期排列的收益曲线时优先使用
+        let equity = self.equity_values(returns);
+        self.max_drawdown = metrics::max_drawdown(&equity);
+        self.calmar_ratio = metrics::calmar_ratio(returns, &equity, 0.0);
+        
+        // 期望收益: 胜率 * 平均盈利 + (1 - 胜率) * 平均亏损
+        self.expectancy = metrics::expected_return(returns);
+        
+        // 计算盈亏比: 总盈利 / 总亏损，没有亏损时为无穷大
+        self.profit_factor = metrics::profit_factor(returns);
+        
+        self.calculate_annualized_metrics(returns);
+    }
+    
+    /// 计算年化收益率、年化波动率和每月交易次数
+    ///
+    /// 假设所有交易在同一账户中依次复利，每年交易次数的估计方式:
+    /// - 有交易详情时，按最早买入日到最晚卖出日的自然日跨度(每年365.25天)折算
+    /// - 否则假设同一时间只持有一笔，每年交易次数 = TRADING_DAYS_PER_YEAR / 平均持有天数
+    ///
+    /// 年化收益率 = (1 + 几何平均单笔收益)^每年交易次数 - 1，例如每笔1天赚1%时为1.01^244 - 1；
+    /// 单笔亏损达到100%时为-100%
+    fn calculate_annualized_metrics(&mut self, returns: &[f32]) {
+        self.annualized_return = 0.0;
+        self.annualized_volatility = 0.0;
+        self.trades_per_month = 0.0;
+        
+        if returns.is_empty() {
+            return;
         }
         
-        max_dd
+        let trades_per_year = match self.trade_span_years() {
+            Some(years) => returns.len() as f64 / years,
+            None if self.avg_hold_days > 0.0 => (TRADING_DAYS_PER_YEAR / self.avg_hold_days) as f64,
+            None => return,
+        };
+        self.trades_per_month = (trades_per_year / 12.0) as f32;
+        
+        // 在f64中累加对数收益，避免连乘溢出
+        self.annualized_return = if returns.iter().any(|&r| r <= -1.0) {
+            -1.0
+        } else {
+            let mean_log = returns.iter().map(|&r| (1.0 + r as f64).ln()).sum::<f64>() / returns.len() as f64;
+            ((mean_log * trades_per_year).exp() - 1.0) as f32
+        };
+        
+        let n = returns.len() as f64;
+        let mean = returns.iter().map(|&r| r as f64).sum::<f64>() / n;
+        let variance = returns.iter().map(|&r| (r as f64 - mean).powi(2)).sum::<f64>() / n;
+        self.annualized_volatility = (variance.sqrt() * trades_per_year.sqrt()) as f32;
+    }
+    
+    /// 交易详情中最早买入日到最晚卖出日的年数，不足1天按1天计；没有可解析的日期时为None
+    fn trade_span_years(&self) -> Option<f64> {
+        let details = self.trade_details.as_ref()?;
+        let first = details.iter().filter_map(|detail| parse_trade_date(&detail.entry_date)).min()?;
+        let last = details.iter().filter_map(|detail| parse_trade_date(&detail.exit_date)).max()?;
+        let days = (last - first).num_days().max(1);
+        Some(days as f64 / CALENDAR_DAYS_PER_YEAR)
+    }
+    
+    /// 由交易明细生成净值曲线: 按买入日期(相同时按卖出日期)排序后依次复利
+    ///
+    /// 没有交易明细时清空曲线
+    pub fn set_equity_curve_from_details(&mut self) {
+        let Some(details) = self.trade_details.as_ref().filter(|details| !details.is_empty()) else {
+            self.equity_curve = None;
+            return;
+        };
+        
+        let mut ordered: Vec<&TradeDetail> = details.iter().collect();
+        ordered.sort_by_key(|detail| {
+            (parse_trade_date(&detail.entry_date), parse_trade_date(&detail.exit_date))
+        });
+        
+        let mut value = 1.0;
+        let curve = ordered.into_iter()
+            .map(|detail| {
+                value *= 1.0 + detail.return_pct;
+                (detail.entry_date.clone(), value)
+            })
+            .collect();
+        self.equity_curve = Some(curve);
+    }
+    
+    /// 按日期排列的净值序列，起点净值为1，用于计算回撤
+    ///
+    /// 依次使用: 按买入日期复利的净值曲线、1 + 累计收益曲线、逐笔收益率依次复利(此时顺序与时间无关)
+    fn equity_values(&self, returns: &[f32]) -> Vec<f32> {
+        let mut values = vec![1.0];
+        match (&self.equity_curve, &self.cumulative_curve) {
+            (Some(curve), _) => values.extend(curve.iter().map(|&(_, value)| value)),
+            (None, Some(curve)) => values.extend(curve.iter().map(|&(_, cumulative)| 1.0 + cumulative)),
+            (None, None) => {
+                let mut value = 1.0;
+                for &ret in returns {
+                    value *= 1.0 + ret;
+                    values.push(value);
+                }
+            }
+        }
+        values
     }
     
     /// 格式化为人类可读的报告
@@ -248,17 +1141,322 @@ impl BacktestResult {
         let mut report = String::new();
         
         report.push_str(&format!("总交易次数: {}\n", self.total_trades));
+        if self.skipped_trades > 0 {
+            report.push_str(&format!("放弃的信号: {}\n", self.skipped_trades));
+        }
+        if self.suspended_skips > 0 {
+            report.push_str(&format!("停牌跳过: {}\n", self.suspended_skips));
+        }
         report.push_str(&format!("胜率: {:.2}%\n", self.win_rate * 100.0));
         report.push_str(&format!("止损率: {:.2}%\n", self.stop_loss_rate * 100.0));
         report.push_str(&format!("止损失败率: {:.2}%\n", self.stop_loss_fail_rate * 100.0));
         report.push_str(&format!("平均收益率: {:.2}%\n", self.avg_return * 100.0));
+        if self.avg_slippage != 0.0 {
+            report.push_str(&format!("平均滑点拖累: {:.3}%\n", self.avg_slippage * 100.0));
+        }
         report.push_str(&format!("最大收益率: {:.2}%\n", self.max_return * 100.0));
         report.push_str(&format!("最大亏损率: {:.2}%\n", self.max_loss * 100.0));
         report.push_str(&format!("平均持有天数: {:.1}天\n", self.avg_hold_days));
         report.push_str(&format!("夏普比率: {:.2}\n", self.sharpe_ratio));
         report.push_str(&format!("最大回撤: {:.2}%\n", self.max_drawdown * 100.0));
+        report.push_str(&format!("最长回撤持续: {}天\n", self.max_drawdown_duration_days));
+        report.push_str(&format!("水下时间占比: {:.2}%\n", self.time_underwater_ratio * 100.0));
+        report.push_str(&format!("最长恢复时间: {}天\n", self.longest_recovery_days));
         report.push_str(&format!("盈亏比: {:.2}\n", self.profit_factor));
+        report.push_str(&format!("索提诺比率: {:.2}\n", self.sortino_ratio));
+        report.push_str(&format!("卡尔马比率: {:.2}\n", self.calmar_ratio));
+        report.push_str(&format!("期望收益: {:.2}%\n", self.expectancy * 100.0));
+        report.push_str(&format!("年化收益率: {:.2}%\n", self.annualized_return * 100.0));
+        report.push_str(&format!("年化波动率: {:.2}%\n", self.annualized_volatility * 100.0));
+        report.push_str(&format!("月均交易次数: {:.1}\n", self.trades_per_month));
+        if self.has_benchmark() {
+            report.push_str(&format!("同期基准收益率: {:.2}%\n", self.benchmark_return * 100.0));
+            report.push_str(&format!("平均超额收益: {:.2}%\n", self.avg_excess_return * 100.0));
+            report.push_str(&format!("信息比率: {:.2}\n", self.information_ratio));
+        }
+        
+        let classified = self.exit_reasons.total();
+        if classified > 0 {
+            report.push_str("\n退出原因:\n");
+            for (reason, stat) in self.exit_reasons.iter() {
+                report.push_str(&format!(
+                    "  {}: {}笔 ({:.2}%), 平均收益率 {:.2}%\n",
+                    reason.label(),
+                    stat.count,
+                    stat.count as f32 / classified as f32 * 100.0,
+                    stat.avg_return() * 100.0,
+                ));
+            }
+        }
         
         report
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 胜率70%的n笔交易收益率
+    fn result_with_trades(n: usize) -> BacktestResult {
+        BacktestResult {
+            trade_returns: (0..n)
+                .map(|i| if i % 10 < 7 { 0.03 } else { -0.02 })
+                .collect(),
+            ..BacktestResult::default()
+        }
+    }
+
+    #[test]
+    fn interval_narrows_with_more_trades() {
+        let small = result_with_trades(20).bootstrap(1000, 0.9, 7).unwrap();
+        let large = result_with_trades(500).bootstrap(1000, 0.9, 7).unwrap();
+        
+        assert!(large.win_rate.half_width() < small.win_rate.half_width());
+        assert!(large.expectancy.half_width() < small.expectancy.half_width());
+        assert!(large.win_rate.lower <= 0.7 && 0.7 <= large.win_rate.upper);
+    }
+
+    #[test]
+    fn bootstrap_is_reproducible_with_shared_seed() {
+        let result = result_with_trades(50);
+        
+        assert_eq!(result.bootstrap(500, 0.9, 42), result.bootstrap(500, 0.9, 42));
+    }
+
+    #[test]
+    fn all_winning_small_sample_keeps_wide_win_rate_interval() {
+        let result = BacktestResult {
+            trade_returns: vec![0.05, 0.04, 0.06],
+            ..BacktestResult::default()
+        };
+        
+        let interval = result.bootstrap(1000, 0.9, 7).unwrap();
+        
+        assert_eq!(interval.win_rate.upper, 1.0);
+        assert!(interval.win_rate.lower < 0.6);
+    }
+
+    #[test]
+    fn no_trades_has_no_interval() {
+        assert!(BacktestResult::default().bootstrap(1000, 0.9, 7).is_none());
+    }
+
+    fn day_result(date: i32, return_pct: f32) -> BacktestResult {
+        let mut result = BacktestResult {
+            total_trades: 1,
+            avg_return: return_pct,
+            trade_returns: vec![return_pct],
+            ..BacktestResult::default()
+        };
+        result.set_daily_pnl(vec![(date, return_pct)]);
+        result
+    }
+
+    #[test]
+    fn merged_curve_is_sorted_by_date() {
+        // 亏损日的结果先出现，合并后仍按日期排列
+        let merged = BacktestResult::merge(vec![
+            day_result(20230105, -0.10),
+            day_result(20230103, 0.25),
+        ]);
+        
+        assert_eq!(merged.daily_pnl, Some(vec![(20230103, 0.25), (20230105, -0.10)]));
+        let curve = merged.cumulative_curve.unwrap();
+        assert!((curve.last().unwrap().1 - 0.15).abs() < 1e-6);
+        // 回撤按净值1.25到1.15计算
+        assert!((merged.max_drawdown - 0.08).abs() < 1e-6);
+    }
+
+    #[test]
+    fn trade_timing_follows_calendar() {
+        let calendar = TradingCalendar::from_dates([20230103, 20230104, 20230105, 20230106]);
+        let trade = TradeTiming { entry_date: 20230103, return_pct: 0.06, hold_days: 2 };
+        
+        assert_eq!(trade.attribute(&calendar, PnlAttribution::ExitDate), vec![(20230105, 0.06)]);
+        assert_eq!(
+            trade.attribute(&calendar, PnlAttribution::SpreadOverHolding),
+            vec![(20230104, 0.03), (20230105, 0.03)],
+        );
+        // 卖出日超出日历时记在最后一天
+        let late = TradeTiming { hold_days: 10, ..trade };
+        assert_eq!(late.attribute(&calendar, PnlAttribution::ExitDate), vec![(20230106, 0.06)]);
+    }
+
+    fn exit_on(exit_date: &str, return_pct: f32) -> TradeDetail {
+        TradeDetail {
+            symbol: "000001.SZ".to_string(),
+            direction: Direction::Long,
+            entry_date: String::new(),
+            entry_price: 10.0,
+            exit_date: exit_date.to_string(),
+            exit_price: 10.0 * (1.0 + return_pct),
+            filled_entry_price: 10.0,
+            filled_exit_price: 10.0 * (1.0 + return_pct),
+            peak_price: 10.0 * (1.0 + return_pct),
+            return_pct,
+            hold_days: 1,
+            exit_reason: ExitReason::TimeExpired,
+            win: return_pct > 0.0,
+            slippage: 0.0,
+            cost: 0.0,
+            benchmark_return_pct: None,
+            legs: None,
+        }
+    }
+
+    #[test]
+    fn returns_are_grouped_by_exit_period() {
+        let result = BacktestResult {
+            trade_details: Some(vec![
+                exit_on("20240130", 0.03),
+                exit_on("2024-01-31", -0.02),
+                exit_on("20240201", 0.05),
+                exit_on("bad", 0.10),
+            ]),
+            ..BacktestResult::default()
+        };
+        
+        let monthly = result.returns_by_period(Period::Month);
+        assert_eq!(monthly.len(), 2);
+        assert_eq!((monthly[0].0.as_str(), monthly[0].2), ("2024-01", 2));
+        assert!((monthly[0].1 - 0.01).abs() < 1e-6);
+        assert_eq!((monthly[1].0.as_str(), monthly[1].1, monthly[1].2), ("2024-02", 0.05, 1));
+        
+        let weekly = result.returns_by_period(Period::Week);
+        assert_eq!(weekly.len(), 1);
+        assert_eq!((weekly[0].0.as_str(), weekly[0].2), ("2024-W05", 3));
+    }
+
+    #[test]
+    fn exported_trades_round_trip_through_csv() {
+        let mut quoted = exit_on("20240201", -0.05);
+        quoted.symbol = "\"特\",殊".to_string();
+        quoted.exit_reason = ExitReason::StopLoss;
+        let result = BacktestResult {
+            trade_details: Some(vec![exit_on("20240130", 0.03), quoted]),
+            ..BacktestResult::default()
+        };
+        
+        let path = std::env::temp_dir()
+            .join(format!("strategy_lab_trades_{}", std::process::id()))
+            .join("trades.csv");
+        result.export_trades_csv(&path).unwrap();
+        
+        let content = fs::read_to_string(&path).unwrap();
+        let rows: Vec<Vec<String>> = content.lines().map(csv::split_row).collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0][0], "symbol");
+        assert_eq!(rows[2][0], "\"特\",殊");
+        let reason = rows[0].iter().position(|column| column == "exit_reason").unwrap();
+        assert_eq!(rows[2][reason], "stop_loss");
+        
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn exit_reasons_merge_breakdowns_and_details() {
+        let mut gap = exit_on("20240201", -0.12);
+        gap.exit_reason = ExitReason::StopLossFailed;
+        // 只有交易详情的结果
+        let detailed = BacktestResult {
+            total_trades: 2,
+            trade_details: Some(vec![exit_on("20240130", 0.02), gap]),
+            ..BacktestResult::default()
+        };
+        let mut counted = BacktestResult { total_trades: 2, ..BacktestResult::default() };
+        counted.exit_reasons.record(ExitReason::StopLoss, -0.05);
+        counted.exit_reasons.record(ExitReason::StopLossFailed, -0.08);
+        
+        let merged = BacktestResult::merge(vec![detailed, counted]);
+        
+        let failed = merged.exit_reasons.get(ExitReason::StopLossFailed);
+        assert_eq!(failed.count, 2);
+        assert!((failed.avg_return() + 0.10).abs() < 1e-6);
+        assert_eq!(merged.exit_reasons.get(ExitReason::StopLoss).count, 1);
+        assert_eq!(merged.exit_reasons.get(ExitReason::TimeExpired).count, 1);
+        assert_eq!(merged.exit_reasons.total(), 4);
+        
+        let report = merged.format_report();
+        assert!(report.contains("止损失败(跳空): 2笔 (50.00%), 平均收益率 -10.00%"));
+        assert!(!BacktestResult::default().format_report().contains("退出原因"));
+    }
+
+    #[test]
+    fn one_percent_per_day_annualizes_over_244_days() {
+        let returns = vec![0.01; 50];
+        let mut result = BacktestResult { avg_hold_days: 1.0, ..BacktestResult::default() };
+        
+        result.calculate_advanced_metrics(&returns);
+        
+        let expected = 1.01f32.powf(TRADING_DAYS_PER_YEAR) - 1.0;
+        assert!((result.annualized_return - expected).abs() < 1e-3 * expected);
+        assert!((result.annualized_return - 10.335).abs() < 0.01);
+        assert!((result.trades_per_month - TRADING_DAYS_PER_YEAR / 12.0).abs() < 1e-3);
+        assert!(result.annualized_volatility.abs() < 1e-5);
+    }
+
+    #[test]
+    fn trade_dates_set_the_annualization_span() {
+        // 一年内两笔交易，各赚10%
+        let mut first = exit_on("20230301", 0.10);
+        first.entry_date = "20230101".to_string();
+        let mut second = exit_on("20240101", 0.10);
+        second.entry_date = "20231001".to_string();
+        let mut result = BacktestResult {
+            avg_hold_days: 1.0,
+            trade_details: Some(vec![first, second]),
+            ..BacktestResult::default()
+        };
+        
+        result.calculate_advanced_metrics(&[0.10, 0.10]);
+        
+        assert!((result.annualized_return - 0.21).abs() < 0.005);
+        assert!((result.trades_per_month - 2.0 / 12.0).abs() < 0.005);
+        
+        result.calculate_advanced_metrics(&[0.10, -1.0]);
+        assert_eq!(result.annualized_return, -1.0);
+    }
+
+    #[test]
+    fn ratios_match_hand_computed_values() {
+        let returns = [0.05, -0.02, 0.03, -0.04, 0.01];
+        let mut result = BacktestResult::default();
+        
+        result.calculate_advanced_metrics(&returns);
+        
+        // 下行偏差只统计亏损交易: sqrt((0.02² + 0.04²) / 2)，均值0.006
+        assert!((result.sortino_ratio - 0.1897).abs() < 1e-3);
+        // 净值1.05 -> 1.029 -> 1.05987 -> 1.01747 -> 1.02765，回撤从1.05987算起
+        assert!((result.max_drawdown - 0.04).abs() < 1e-4);
+        assert!((result.calmar_ratio - 0.15).abs() < 1e-3);
+        assert!((result.expectancy - 0.006).abs() < 1e-6);
+    }
+
+    #[test]
+    fn drawdown_durations_follow_curve_dates() {
+        // 1月5日创新高1.10，1月15日跌到谷底1.00，2月1日恢复到1.11
+        let pnl = vec![
+            (20240103, 0.05), (20240105, 0.05), (20240108, -0.03), (20240110, -0.02),
+            (20240115, -0.05), (20240120, 0.03), (20240125, 0.03), (20240201, 0.05),
+        ];
+        let mut result = BacktestResult::default();
+        result.set_daily_pnl(pnl.clone());
+        result.calculate_advanced_metrics(&[]);
+        
+        assert_eq!(result.max_drawdown_duration_days, 27);
+        assert_eq!(result.longest_recovery_days, 17);
+        assert!((result.time_underwater_ratio - 5.0 / 8.0).abs() < 1e-6);
+        
+        // merge由合并后的曲线重新计算
+        let (early, late) = pnl.split_at(4);
+        let part = |pnl: &[(i32, f32)]| {
+            let mut part = BacktestResult::default();
+            part.set_daily_pnl(pnl.to_vec());
+            part
+        };
+        let merged = BacktestResult::merge(vec![part(late), part(early)]);
+        assert_eq!(merged.max_drawdown_duration_days, 27);
+        assert_eq!(merged.longest_recovery_days, 17);
+    }
+}
@@ -0,0 +1,54 @@
+use crate::utils::rng::SplitMix64;
+use serde::{Deserialize, Serialize};
+
+/// 成交滑点模型，买入价上浮、卖出价下浮
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum SlippageModel {
+    /// 按理论价格成交
+    #[default]
+    None,
+    /// 固定比例滑点，0.002表示买卖各偏离0.2%
+    Fixed(f32),
+    /// 每笔买卖各在[0, max)内随机取滑点，同一种子的结果可复现
+    Random { max: f32, seed: u64 },
+}
+
+impl SlippageModel {
+    pub fn is_none(&self) -> bool {
+        match self {
+            SlippageModel::None => true,
+            SlippageModel::Fixed(rate) => *rate == 0.0,
+            SlippageModel::Random { max, .. } => *max == 0.0,
+        }
+    }
+    
+    /// 某笔交易的(买入滑点, 卖出滑点)
+    ///
+    /// 随机滑点由种子、回测日和该日的交易序号决定，与并行执行顺序无关
+    pub fn rates(&self, forecast_idx: usize, trade_idx: usize) -> (f32, f32) {
+        match *self {
+            SlippageModel::None => (0.0, 0.0),
+            SlippageModel::Fixed(rate) => (rate, rate),
+            SlippageModel::Random { max, seed } => {
+                let key = ((forecast_idx as u64) << 32) ^ trade_idx as u64;
+                let mut rng = SplitMix64::new(seed ^ SplitMix64::new(key).next_u64());
+                (unit(&mut rng) * max, unit(&mut rng) * max)
+            }
+        }
+    }
+    
+    /// 按滑点调整后的收益率，不计滑点时原样返回
+    pub fn filled_return(&self, gross_return: f32, forecast_idx: usize, trade_idx: usize) -> f32 {
+        if self.is_none() {
+            return gross_return;
+        }
+        
+        let (buy, sell) = self.rates(forecast_idx, trade_idx);
+        ((1.0 + gross_return as f64) * (1.0 - sell as f64) / (1.0 + buy as f64) - 1.0) as f32
+    }
+}
+
+/// [0, 1)内的均匀随机数
+fn unit(rng: &mut SplitMix64) -> f32 {
+    ((rng.next_u64() >> 40) as f32) / (1u64 << 24) as f32
+}
@@ -0,0 +1,116 @@
+use crate::backtest::result::BacktestResult;
+use serde::{Deserialize, Serialize};
+
+/// 滚动前推分析的窗口设置，单位为回测日
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalkForwardConfig {
+    /// 样本内窗口天数
+    pub train_days: usize,
+    /// 样本外窗口天数，也是相邻两折之间平移的天数
+    pub test_days: usize,
+    /// 折数
+    pub folds: usize,
+}
+
+impl WalkForwardConfig {
+    pub fn new(train_days: usize, test_days: usize, folds: usize) -> Self {
+        Self { train_days, test_days, folds }
+    }
+}
+
+/// 单折的回测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkForwardFold {
+    /// 折序号，0为最早的一折
+    pub index: usize,
+    /// 样本内窗口的第一个买入日
+    pub train_start: Option<i32>,
+    /// 样本外窗口的第一个和最后一个买入日
+    pub test_start: Option<i32>,
+    pub test_end: Option<i32>,
+    pub in_sample: BacktestResult,
+    pub out_of_sample: BacktestResult,
+    /// 样本外窗口中选股结果的总数
+    pub candidates: usize,
+}
+
+impl WalkForwardFold {
+    /// 样本外窗口中选股没有选出任何股票
+    pub fn is_empty(&self) -> bool {
+        self.candidates == 0
+    }
+}
+
+/// 滚动前推分析结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkForwardResult {
+    pub config: WalkForwardConfig,
+    /// 按时间从早到晚排列
+    pub folds: Vec<WalkForwardFold>,
+    /// 有选股结果的各折样本外结果合并
+    pub aggregate: BacktestResult,
+    /// 有选股结果的各折样本外胜率的标准差，越小越稳定
+    pub win_rate_std: f32,
+}
+
+impl WalkForwardResult {
+    /// 汇总各折结果，没有选股结果的折不参与合并和稳定性计算
+    pub fn new(config: WalkForwardConfig, folds: Vec<WalkForwardFold>) -> Self {
+        let valid: Vec<&WalkForwardFold> = folds.iter().filter(|fold| !fold.is_empty()).collect();
+        let aggregate = BacktestResult::merge(valid.iter().map(|fold| fold.out_of_sample.clone()).collect());
+
+        let win_rates: Vec<f32> = valid.iter().map(|fold| fold.out_of_sample.win_rate).collect();
+        let win_rate_std = if win_rates.is_empty() {
+            0.0
+        } else {
+            let mean = win_rates.iter().sum::<f32>() / win_rates.len() as f32;
+            let variance = win_rates.iter().map(|rate| (rate - mean).powi(2)).sum::<f32>() / win_rates.len() as f32;
+            variance.sqrt()
+        };
+
+        Self { config, folds, aggregate, win_rate_std }
+    }
+
+    /// 样本外没有选出股票的折
+    pub fn empty_folds(&self) -> Vec<&WalkForwardFold> {
+        self.folds.iter().filter(|fold| fold.is_empty()).collect()
+    }
+
+    /// 参与汇总的折数
+    pub fn valid_folds(&self) -> usize {
+        self.folds.len() - self.empty_folds().len()
+    }
+
+    /// 格式化为人类可读的报告
+    pub fn format_report(&self) -> String {
+        let mut report = String::new();
+        let date = |date: Option<i32>| date.map_or("-".to_string(), |date| date.to_string());
+
+        report.push_str(&format!("样本内{}天 / 样本外{}天, 共{}折\n",
+            self.config.train_days, self.config.test_days, self.config.folds));
+        for fold in &self.folds {
+            if fold.is_empty() {
+                report.push_str(&format!("  第{}折 样本外{}~{}: 未选出股票，不参与汇总\n",
+                    fold.index + 1, date(fold.test_start), date(fold.test_end)));
+                continue;
+            }
+            report.push_str(&format!(
+                "  第{}折 样本外{}~{}: 样本内胜率 {:.2}%, 样本外胜率 {:.2}%, 样本外交易 {}笔, 平均收益率 {:.2}%\n",
+                fold.index + 1,
+                date(fold.test_start),
+                date(fold.test_end),
+                fold.in_sample.win_rate * 100.0,
+                fold.out_of_sample.win_rate * 100.0,
+                fold.out_of_sample.total_trades,
+                fold.out_of_sample.avg_return * 100.0,
+            ));
+        }
+
+        report.push_str(&format!("有效折数: {}/{}\n", self.valid_folds(), self.folds.len()));
+        report.push_str(&format!("样本外胜率标准差: {:.2}%\n", self.win_rate_std * 100.0));
+        report.push_str("样本外汇总:\n");
+        report.push_str(&self.aggregate.format_report());
+
+        report
+    }
+}
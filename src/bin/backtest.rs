@@ -1,28 +1,28 @@
-use strategy_lab::backtest::BacktestEngine;
-use strategy_lab::strategies::{
-    trend::atr::AtrSelector,
-    volume::volume_decline::VolumeDecliningSelector,
-    reversal::breakthrough_pullback::BreakthroughPullbackSelector,
+use strategy_lab::backtest::{
+    report, BacktestEngine, BacktestResult, ConflictPolicy, ExitExecution, ParallelismConfig, ProgressCallback, ProgressEvent, RunMeta,
+    DEFAULT_MAX_STALENESS_DAYS,
 };
-use strategy_lab::signals::{
-    price::close::ClosePriceSignal,
-    price::open::OpenPriceSignal,
-    pattern::bottom_reverse::BottomReverseSignal,
-    volume::surge::VolumeSurgeSignal,
-};
-use strategy_lab::targets::{
-    return_target::ReturnTarget,
-    guard_target::GuardTarget,
-};
-use strategy_lab::scorecard::Scorecard;
-use std::fs::{self, File};
-use std::io::Write;
+use strategy_lab::stock::coverage::{self, SymbolCoverage};
+use strategy_lab::stock::csv_loader::{self, CsvFormat};
+use strategy_lab::stock::data_provider::{limit_band, StockDataProvider};
+use strategy_lab::stock::snapshot::UniverseSnapshot;
+use strategy_lab::stock::source::{CompositeDataSource, DataSource as _, MockDataSource};
+use strategy_lab::strategies::StockSelector;
+use strategy_lab::signals::BuySignalGenerator;
+use strategy_lab::targets::Target;
+use strategy_lab::scorecard::optimize::{GridSearch, DEFAULT_MAX_COMBINATIONS};
+use strategy_lab::scorecard::{scorer, PositionSizing, RankingMetric, ScoreEntry, Scorecard, DEFAULT_MIN_TRADES};
+use strategy_lab::export::{self, ExportData, RecommendationExport, BEST_COMBINATIONS};
+use strategy_lab::registry::{self, ComponentKind, ComponentsConfig};
+use std::io::{IsTerminal, Write};
 use std::path::Path;
-use serde::{Serialize, Deserialize};
-use chrono::Local;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use env_logger;
+
+/// 导出JSON和报告中每个最佳组合的推荐股票数
+const EXPORT_RECOMMENDATIONS: usize = 5;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -31,14 +31,110 @@ struct Cli {
     #[arg(short, long, value_name = "FILE")]
     config: Option<String>,
     
-    /// 回测天数
-    #[arg(short, long, default_value_t = 12)]
-    days: usize,
+    /// 回测天数，默认使用配置中的back_days，配置也未指定时为12
+    #[arg(short, long)]
+    days: Option<usize>,
     
     /// 输出文件路径
     #[arg(short, long, value_name = "FILE")]
     output: Option<String>,
     
+    /// 导出JSON的best_combinations中按得分排列的组合数
+    #[arg(long, value_name = "K", default_value_t = BEST_COMBINATIONS)]
+    top_k: usize,
+    
+    /// 评分卡CSV输出路径
+    #[arg(long, value_name = "FILE")]
+    csv: Option<String>,
+    
+    /// 评分卡报告输出路径，扩展名为.html或.htm时生成内嵌CSS的HTML，否则生成Markdown
+    #[arg(long, value_name = "PATH")]
+    report: Option<String>,
+    
+    /// 详细模式下的组合评分函数: win_rate、expectancy、kelly、sharpe或weighted[:胜率权重,收益率权重,回撤惩罚]
+    #[arg(long)]
+    scorer: Option<String>,
+    
+    /// 将过滤后的回测股票池导出为CSV
+    #[arg(long, value_name = "PATH")]
+    dump_universe: Option<String>,
+    
+    /// 按代码或名称查找单只股票并打印其数据概况
+    #[arg(long, value_name = "QUERY")]
+    symbol: Option<String>,
+    
+    /// 打印数据覆盖最差的股票
+    #[arg(long)]
+    coverage: bool,
+    
+    /// 自定义股票池文件，每行一个股票代码
+    #[arg(long, value_name = "FILE")]
+    universe: Option<String>,
+    
+    /// 从目录加载每只股票一个的CSV日线文件，替代datahub数据源
+    #[arg(long, value_name = "DIR", conflicts_with = "universe")]
+    data_dir: Option<String>,
+    
+    /// datahub缺少的股票或最新K线从该目录的CSV文件补充
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["universe", "data_dir", "snapshot"])]
+    fallback_dir: Option<String>,
+    
+    /// 按历史股票池快照回测，并统计幸存者偏差
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["universe", "data_dir"])]
+    snapshot: Option<String>,
+    
+    /// 保存当前股票池快照后退出
+    #[arg(long, value_name = "FILE")]
+    save_snapshot: Option<String>,
+    
+    /// 重试后仍获取失败的股票占比超过该百分比时中止回测
+    #[arg(long, value_name = "PCT")]
+    max_failure_pct: Option<f32>,
+    
+    /// 最新K线落后全局最新交易日超过该交易日数的股票不参与回测
+    #[arg(long, value_name = "DAYS", default_value_t = DEFAULT_MAX_STALENESS_DAYS)]
+    max_staleness_days: usize,
+    
+    /// 股票池文件中存在未知代码时报错而不是忽略
+    #[arg(long)]
+    strict: bool,
+    
+    /// 组合级并行线程数，默认使用全部CPU
+    #[arg(long)]
+    threads: Option<usize>,
+    
+    /// 引擎内部并行线程数，默认使用全部CPU
+    #[arg(long)]
+    inner_threads: Option<usize>,
+    
+    /// 同一天触及止损价和目标价时的处理规则: target_first、stop_first或proportional
+    #[arg(long, value_name = "POLICY")]
+    conflict_policy: Option<String>,
+    
+    /// 固定止损的成交方式: intraday_touch(默认)或next_open_after_close_breach
+    #[arg(long, value_name = "MODE")]
+    exit_execution: Option<String>,
+    
+    /// 基准指数代码，每笔交易与同一买卖日期的指数收益比较
+    #[arg(long, value_name = "SYMBOL")]
+    benchmark: Option<String>,
+    
+    /// 对逐笔收益做蒙特卡洛重抽样，打印胜率5%分位数并按其选出最佳组合
+    #[arg(long)]
+    monte_carlo: bool,
+    
+    /// 保留每个组合的逐日得分，打印逐日得分的最低、最高和标准差
+    #[arg(long)]
+    daily_scores: bool,
+    
+    /// 回测期内累计交易数低于该值的组合标记为样本不足，不参与最佳组合的选择
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MIN_TRADES)]
+    min_trades: usize,
+    
+    /// 不在stderr上显示评分卡进度条，stderr不是终端时始终不显示
+    #[arg(long)]
+    no_progress: bool,
+    
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -58,20 +154,88 @@ enum Commands {
         /// 目标名称
         #[arg(long)]
         target: String,
+        
+        /// 将逐笔交易明细导出到CSV文件
+        #[arg(long, value_name = "PATH")]
+        trades: Option<String>,
+        
+        /// 生成单文件HTML回测报告
+        #[arg(long, value_name = "PATH")]
+        html: Option<String>,
+    },
+    
+    /// 用最新数据为单一组合生成次日推荐，不运行回测
+    Recommend {
+        /// 策略名称
+        #[arg(long, required_unless_present = "use_best_from")]
+        strategy: Option<String>,
+        
+        /// 信号名称
+        #[arg(long, required_unless_present = "use_best_from")]
+        signal: Option<String>,
+        
+        /// 目标名称
+        #[arg(long, required_unless_present = "use_best_from")]
+        target: Option<String>,
+        
+        /// 使用导出结果JSON中的最佳组合，组件按名称在组件配置中查找
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["strategy", "signal", "target"])]
+        use_best_from: Option<String>,
+        
+        /// 推荐股票数量
+        #[arg(long, default_value_t = 5)]
+        count: usize,
+        
+        /// 可用资金，用于计算建议股数
+        #[arg(long, requires = "risk_per_trade")]
+        capital: Option<f64>,
+        
+        /// 单笔交易止损时最多亏损的资金比例，0.01表示1%
+        #[arg(long, requires = "capital")]
+        risk_per_trade: Option<f32>,
+        
+        /// 将推荐结果导出为JSON
+        #[arg(long, value_name = "PATH")]
+        json: Option<String>,
+        
+        /// 将推荐结果导出为CSV
+        #[arg(long, value_name = "PATH")]
+        csv: Option<String>,
+    },
+    
+    /// 对选股策略参数做网格搜索，信号和目标固定
+    Optimize {
+        /// 策略名称，为组件配置中的id、组件类型或简写，其参数作为网格的基础
+        #[arg(long)]
+        strategy: String,
+        
+        /// 信号名称
+        #[arg(long)]
+        signal: String,
+        
+        /// 目标名称
+        #[arg(long)]
+        target: String,
+        
+        /// 参数的候选取值，格式为 名称=值1,值2,...，可多次指定，取各参数的笛卡尔积
+        #[arg(long = "param", value_name = "NAME=VALUES", required = true)]
+        params: Vec<String>,
+        
+        /// 最近N个回测日作为留出期，不参与排序，用于标记疑似过拟合的参数
+        #[arg(long, value_name = "DAYS", default_value_t = 0)]
+        holdout_days: usize,
+        
+        /// 参数组合数上限，超过时不运行
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_COMBINATIONS)]
+        max_combinations: usize,
+    },
+    
+    /// 列出可用的组件及其参数
+    List {
+        /// 以JSON格式输出
+        #[arg(long)]
+        json: bool,
     },
-}
-
-#[derive(Serialize, Deserialize)]
-struct BacktestResult {
-    strategy: String,
-    signal: String,
-    target: String,
-    win_rate: f32,
-    avg_return: f32,
-    max_return: f32,
-    max_loss: f32,
-    sharpe_ratio: f32,
-    max_drawdown: f32,
 }
 
 fn main() -> Result<()> {
@@ -81,16 +245,117 @@ fn main() -> Result<()> {
     // 初始化日志
     env_logger::init();
     
+    if let Some(Commands::List { json }) = &cli.command {
+        return list_components(*json);
+    }
+    
     log::info!("开始运行回测...");
+    let parallelism = ParallelismConfig {
+        combination_threads: cli.threads,
+        inner_threads: cli.inner_threads,
+    };
+    let source = match (&cli.data_dir, &cli.universe, &cli.snapshot) {
+        (Some(dir), _, _) => DataSource::CsvDir(Path::new(dir)),
+        (None, Some(path), _) => DataSource::Universe(Path::new(path), cli.strict),
+        (None, None, Some(path)) => DataSource::Snapshot(Path::new(path)),
+        (None, None, None) => match &cli.fallback_dir {
+            Some(dir) => DataSource::Fallback(Path::new(dir)),
+            None => DataSource::Datahub,
+        },
+    };
+    
+    let conflict_policy = match &cli.conflict_policy {
+        Some(name) => Some(ConflictPolicy::from_name(name)
+            .ok_or_else(|| anyhow::anyhow!("未知的冲突规则: {}，可选: target_first、stop_first、proportional", name))?),
+        None => None,
+    };
+    let exit_execution = match &cli.exit_execution {
+        Some(name) => ExitExecution::from_name(name)
+            .ok_or_else(|| anyhow::anyhow!("未知的止损成交方式: {}，可选: intraday_touch、next_open_after_close_breach", name))?,
+        None => ExitExecution::default(),
+    };
+    
+    let load = LoadOptions {
+        source,
+        dump_universe: cli.dump_universe.as_deref().map(Path::new),
+        max_failure_pct: cli.max_failure_pct,
+        max_staleness_days: cli.max_staleness_days,
+        conflict_policy,
+        exit_execution,
+        benchmark: cli.benchmark.as_deref(),
+        progress: !cli.no_progress && std::io::stderr().is_terminal(),
+        min_trades: cli.min_trades,
+    };
+    
+    if let Some(path) = &cli.save_snapshot {
+        StockDataProvider::new()?.snapshot_universe(Path::new(path))?;
+        return Ok(());
+    }
+    
+    if let Some(query) = &cli.symbol {
+        return inspect_symbol(query);
+    }
+    
+    if cli.coverage {
+        return print_coverage(source);
+    }
+    
+    // 组件配置，未指定时使用内置配置
+    let components = match &cli.config {
+        Some(path) => ComponentsConfig::load(Path::new(path))?,
+        None => ComponentsConfig::builtin(),
+    };
+    let back_days = components.back_days(cli.days);
+    
     // 根据命令执行不同的回测
     match &cli.command {
-        Some(Commands::Single { strategy, signal, target }) => {
+        Some(Commands::Single { strategy, signal, target, trades, html }) => {
             // 运行单一策略回测
-            run_single_backtest(strategy, signal, target, cli.days)?;
+            let combination = SingleCombination { strategy, signal, target };
+            let outputs = SingleOutputs { trades: trades.as_deref(), html: html.as_deref() };
+            run_single_backtest(&components, combination, outputs, back_days, parallelism, load)?;
+        }
+        Some(Commands::Optimize { strategy, signal, target, params, holdout_days, max_combinations }) => {
+            let options = OptimizeOptions {
+                params,
+                holdout_days: *holdout_days,
+                max_combinations: *max_combinations,
+                scorer: cli.scorer.as_deref(),
+            };
+            let combination = SingleCombination { strategy, signal, target };
+            run_optimize(&components, combination, options, back_days, parallelism, load)?;
+        }
+        Some(Commands::Recommend {
+            strategy, signal, target, use_best_from, count, capital, risk_per_trade, json, csv,
+        }) => {
+            let spec = match (use_best_from, strategy, signal, target) {
+                (Some(path), _, _, _) => CombinationSpec::BestFrom(Path::new(path)),
+                (None, Some(strategy), Some(signal), Some(target)) => {
+                    CombinationSpec::Named(SingleCombination { strategy, signal, target })
+                }
+                _ => anyhow::bail!("需要指定 --strategy、--signal 和 --target，或使用 --use-best-from"),
+            };
+            let options = RecommendOptions {
+                count: *count,
+                sizing: capital.zip(*risk_per_trade).map(|(capital, risk)| PositionSizing::new(capital, risk)),
+                json: json.as_deref(),
+                csv: csv.as_deref(),
+            };
+            run_recommend(&components, spec, options, parallelism, load)?;
         }
+        Some(Commands::List { .. }) => unreachable!(),
         None => {
             // 运行完整评分卡
-            run_full_scorecard(cli.days, cli.output)?;
+            let options = ScorecardOptions {
+                output: cli.output.as_deref(),
+                csv: cli.csv.as_deref(),
+                report: cli.report.as_deref(),
+                scorer: cli.scorer.as_deref(),
+                monte_carlo: cli.monte_carlo,
+                daily_scores: cli.daily_scores,
+                top_k: cli.top_k,
+            };
+            run_full_scorecard(&components, back_days, options, parallelism, load)?;
         }
     }
     
@@ -99,310 +364,643 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// 打印所有已注册组件的参数和示例配置
+fn list_components(json: bool) -> Result<()> {
+    let schemas = registry::schemas();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&schemas)?);
+        return Ok(());
+    }
+    
+    for kind in [ComponentKind::Selector, ComponentKind::Signal, ComponentKind::Target] {
+        println!("== {} ({}) ==", kind.label(), kind.section());
+        for schema in schemas.iter().filter(|schema| schema.kind == kind) {
+            println!();
+            println!("{} - {}", schema.name, schema.display_name);
+            if schema.params.is_empty() {
+                println!("  (无参数)");
+            }
+            for param in &schema.params {
+                let default = match &param.default {
+                    Some(value) => format!("= {}", value),
+                    None if param.required => "(必填)".to_string(),
+                    None => "(可选)".to_string(),
+                };
+                println!("  {}: {} {}  {}", param.name, param.kind, default, param.description);
+            }
+            if let Some(shorthand) = schema.example_shorthand() {
+                println!("  简写: {}", shorthand);
+            }
+            println!("  示例:");
+            for line in schema.example_config().lines() {
+                println!("    {}", line);
+            }
+        }
+        println!();
+    }
+    
+    Ok(())
+}
+
+/// --coverage 打印的股票数量
+const COVERAGE_WORST: usize = 20;
+
+/// 回测数据来源
+#[derive(Clone, Copy)]
+enum DataSource<'a> {
+    /// datahub全市场数据
+    Datahub,
+    /// datahub中股票池文件列出的股票，bool表示是否严格校验
+    Universe(&'a Path, bool),
+    /// 目录下每只股票一个的CSV文件
+    CsvDir(&'a Path),
+    /// datahub中历史股票池快照列出的股票
+    Snapshot(&'a Path),
+    /// datahub全市场数据，缺失的股票和K线由CSV目录补充
+    Fallback(&'a Path),
+}
+
+/// 打印数据覆盖最差的股票
+fn print_coverage(source: DataSource) -> Result<()> {
+    let report = match source {
+        DataSource::Datahub => BacktestEngine::new(true, None)?.coverage_report(),
+        DataSource::Universe(path, _) => {
+            let provider = StockDataProvider::new()?;
+            let universe = provider.load_universe_file(path)?;
+            provider.coverage_report(&universe.symbols)
+        }
+        DataSource::Snapshot(path) => {
+            let snapshot = UniverseSnapshot::load(path)?;
+            StockDataProvider::new()?.coverage_report(&snapshot.symbols())
+        }
+        DataSource::Fallback(dir) => {
+            let composite = fallback_source(dir)?;
+            composite.coverage_report(&composite.all_symbols())
+        }
+        DataSource::CsvDir(dir) => {
+            csv_loader::load_daily_bars_dir(dir, &CsvFormat::per_file())?
+                .iter()
+                .map(|(symbol, bars)| SymbolCoverage::from_bars(symbol, bars))
+                .collect()
+        }
+    };
+    
+    print!("{}", coverage::format_coverage(&report, COVERAGE_WORST));
+    
+    Ok(())
+}
+
+/// 打印单只股票的元数据和数据概况
+fn inspect_symbol(query: &str) -> Result<()> {
+    let provider = StockDataProvider::new()?;
+    let symbol = provider.resolve(query)?;
+    let meta = provider.get_meta(&symbol)
+        .ok_or_else(|| anyhow::anyhow!("未找到股票: {}", symbol))?;
+    
+    println!("代码: {}", meta.symbol);
+    println!("名称: {}", meta.name);
+    println!("交易所: {}", meta.exchange);
+    println!("上市日期: {}", meta.list_date.map(|d| d.to_string()).unwrap_or_else(|| "-".to_string()));
+    println!("ST: {}", if meta.is_st { "是" } else { "否" });
+    println!("涨跌停幅度: {:.0}%", limit_band(&meta.symbol, meta.is_st) * 100.0);
+    
+    if let Some(coverage) = provider.coverage_report(std::slice::from_ref(&symbol)).first() {
+        print!("{}", coverage::format_coverage(std::slice::from_ref(coverage), 1));
+    }
+    
+    Ok(())
+}
+
+/// datahub为主、CSV目录为后备的组合数据源
+fn fallback_source(dir: &Path) -> Result<CompositeDataSource> {
+    let stock_data = csv_loader::load_daily_bars_dir(dir, &CsvFormat::per_file())?;
+    log::info!("后备数据源 {} 包含 {} 只股票", dir.display(), stock_data.len());
+    
+    Ok(CompositeDataSource::new(vec![
+        Arc::new(StockDataProvider::new()?),
+        Arc::new(MockDataSource::new(stock_data)),
+    ]))
+}
+
+/// 评分卡的数据加载选项
+#[derive(Clone, Copy)]
+struct LoadOptions<'a> {
+    source: DataSource<'a>,
+    /// 加载后将股票池导出到该CSV
+    dump_universe: Option<&'a Path>,
+    /// 重试后仍失败的股票占比超过该百分比时中止
+    max_failure_pct: Option<f32>,
+    /// 最新K线落后全局最新交易日超过该交易日数的股票不参与回测
+    max_staleness_days: usize,
+    /// 创建引擎后设置的止盈止损冲突规则
+    conflict_policy: Option<ConflictPolicy>,
+    /// 创建引擎后设置的固定止损成交方式
+    exit_execution: ExitExecution,
+    /// 创建引擎后加载的基准指数代码
+    benchmark: Option<&'a str>,
+    /// 在stderr上显示评分卡进度条
+    progress: bool,
+    /// 创建评分卡后设置的最少交易数
+    min_trades: usize,
+}
+
+/// 按数据来源创建评分卡
+fn build_scorecard(
+    back_days: usize,
+    selectors: Vec<Box<dyn StockSelector>>,
+    signals: Vec<Box<dyn BuySignalGenerator>>,
+    targets: Vec<Box<dyn Target>>,
+    parallelism: ParallelismConfig,
+    load: LoadOptions,
+) -> Result<Scorecard> {
+    let mut engine = match load.source {
+        DataSource::CsvDir(dir) => {
+            let stock_data = csv_loader::load_daily_bars_dir(dir, &CsvFormat::per_file())?;
+            log::info!("从 {} 加载了 {} 只股票", dir.display(), stock_data.len());
+            let mut engine = BacktestEngine::with_data(stock_data, Some(load.max_staleness_days))?;
+            engine.set_inner_threads(parallelism.inner_threads)?;
+            engine
+        }
+        source => {
+            let mut engine = match source {
+                DataSource::Fallback(dir) => {
+                    BacktestEngine::with_source(Arc::new(fallback_source(dir)?), true, Some(load.max_staleness_days))
+                }
+                _ => BacktestEngine::new(true, Some(load.max_staleness_days))?,
+            };
+            engine.set_inner_threads(parallelism.inner_threads)?;
+            engine.set_max_failure_pct(load.max_failure_pct);
+            match source {
+                DataSource::Universe(path, strict) => engine.load_symbols_from_file(path, strict)?,
+                DataSource::Snapshot(path) => engine.load_data_from_snapshot(path)?,
+                _ => engine.load_data()?,
+            };
+            engine
+        }
+    };
+    
+    if let Some(policy) = load.conflict_policy {
+        engine.set_conflict_policy(policy);
+    }
+    engine.set_exit_execution(load.exit_execution);
+    if let Some(symbol) = load.benchmark {
+        engine.set_benchmark(symbol)?;
+    }
+    
+    let mut scorecard = Scorecard::from_engine(back_days, engine, selectors, signals, targets, parallelism)?;
+    scorecard.set_min_trades(load.min_trades);
+    if load.progress {
+        scorecard.set_progress_callback(ProgressBar::callback());
+    }
+    
+    if let Some(path) = load.dump_universe {
+        scorecard.engine.export_universe_csv(path)?;
+    }
+    
+    Ok(scorecard)
+}
+
+/// 进度条宽度(字符数)
+const PROGRESS_WIDTH: usize = 30;
+
+/// stderr上的评分卡进度条
+///
+/// 回调在多个rayon线程上同时调用，更新状态和整行重绘都在同一把锁内完成，输出不会交错
+struct ProgressBar {
+    started: Instant,
+    completed: usize,
+    total: usize,
+    /// 本轮已完成的回测日，累计所有组合
+    days: usize,
+    /// 最近开始评估的组合
+    current: String,
+}
+
+impl ProgressBar {
+    fn callback() -> ProgressCallback {
+        let bar = Mutex::new(ProgressBar {
+            started: Instant::now(),
+            completed: 0,
+            total: 0,
+            days: 0,
+            current: String::new(),
+        });
+        Box::new(move |event| bar.lock().unwrap().update(event))
+    }
+    
+    fn update(&mut self, event: ProgressEvent) {
+        match event {
+            ProgressEvent::CombinationStarted { selector, signal, target, total } => {
+                self.current = format!("{} + {} + {}", selector, signal, target);
+                self.total = total;
+            }
+            // 事件到达顺序可能与完成计数不同，在锁内自行计数
+            ProgressEvent::CombinationFinished { total, .. } => {
+                self.completed += 1;
+                self.total = total;
+            }
+            ProgressEvent::DayCompleted { .. } => self.days += 1,
+        }
+        self.draw();
+        
+        // 一轮结束后换行，下一轮(详细回测)从新的一行重新计数
+        if self.total > 0 && self.completed == self.total {
+            eprintln!();
+            self.completed = 0;
+            self.total = 0;
+            self.days = 0;
+            self.current.clear();
+        }
+    }
+    
+    fn draw(&self) {
+        let filled = (self.completed * PROGRESS_WIDTH).checked_div(self.total).unwrap_or(0).min(PROGRESS_WIDTH);
+        let elapsed = self.started.elapsed().as_secs();
+        let total = if self.total == 0 { "?".to_string() } else { self.total.to_string() };
+        
+        let mut line = format!("\r[{:02}:{:02}] [", elapsed / 60, elapsed % 60);
+        line.push_str(&"#".repeat(filled));
+        line.push_str(&"-".repeat(PROGRESS_WIDTH - filled));
+        line.push_str(&format!("] {}/{} 组合, {} 个回测日 {}\x1b[K", self.completed, total, self.days, self.current));
+        
+        let mut stderr = std::io::stderr().lock();
+        let _ = stderr.write_all(line.as_bytes());
+        let _ = stderr.flush();
+    }
+}
+
+/// 单一策略回测的组件，按配置中的id或组件类型名查找
+struct SingleCombination<'a> {
+    strategy: &'a str,
+    signal: &'a str,
+    target: &'a str,
+}
+
+/// 单一策略回测的输出文件
+struct SingleOutputs<'a> {
+    /// 逐笔交易明细CSV
+    trades: Option<&'a str>,
+    /// HTML报告
+    html: Option<&'a str>,
+}
+
+impl SingleOutputs<'_> {
+    /// 是否需要详细回测结果
+    fn needs_details(&self) -> bool {
+        self.trades.is_some() || self.html.is_some()
+    }
+}
+
+/// 完整评分卡的输出和评分选项
+struct ScorecardOptions<'a> {
+    /// 导出JSON
+    output: Option<&'a str>,
+    /// 评分卡CSV
+    csv: Option<&'a str>,
+    /// Markdown或HTML报告
+    report: Option<&'a str>,
+    /// 组合评分函数名称
+    scorer: Option<&'a str>,
+    /// 蒙特卡洛重抽样并按胜率5%分位数选出最佳组合
+    monte_carlo: bool,
+    /// 打印逐日得分的分布
+    daily_scores: bool,
+    /// 导出JSON中best_combinations的组合数
+    top_k: usize,
+}
+
+impl ScorecardOptions<'_> {
+    /// 导出、报告、自定义评分函数和蒙特卡洛都需要每个组合的详细回测结果
+    fn needs_details(&self) -> bool {
+        self.output.is_some() || self.csv.is_some() || self.report.is_some() || self.scorer.is_some() || self.monte_carlo
+    }
+}
+
 /// 运行单一策略回测
 fn run_single_backtest(
-    strategy_name: &str,
-    signal_name: &str,
-    target_name: &str,
+    components: &ComponentsConfig,
+    combination: SingleCombination,
+    outputs: SingleOutputs,
     back_days: usize,
+    parallelism: ParallelismConfig,
+    load: LoadOptions,
 ) -> Result<()> {
+    let SingleCombination { strategy: strategy_name, signal: signal_name, target: target_name } = combination;
     log::info!("运行单一策略回测: 策略={}, 信号={}, 目标={}", strategy_name, signal_name, target_name);
     
-    // 创建策略
-    let selector = match strategy_name {
-        "atr" => Box::new(AtrSelector {
-            top_n: 10,
-            lookback_days: 100,
-            score_weights: Default::default(),
-        }) as Box<dyn strategy_lab::strategies::StockSelector>,
-        "volume_decline" => Box::new(VolumeDecliningSelector {
-            top_n: 10,
-            lookback_days: 30,
-            min_consecutive_decline_days: 3,
-            min_volume_decline_ratio: 0.1,
-            price_period: 20,
-            check_support_level: false,
-            max_support_ratio: 0.06
-        }) as Box<dyn strategy_lab::strategies::StockSelector>,
-        "breakthrough" => Box::new(BreakthroughPullbackSelector {
-            top_n: 10,
-            lookback_days: 10,
-            min_breakthrough_percent: 5.0,
-            max_pullback_percent: 5.0,
-            volume_decline_ratio: 0.7,
-        }) as Box<dyn strategy_lab::strategies::StockSelector>,
-        _ => return Err(anyhow::anyhow!("未知的策略: {}", strategy_name)),
-    };
-    
-    // 创建信号
-    let signal = match signal_name {
-        "close" => Box::new(ClosePriceSignal) as Box<dyn strategy_lab::signals::BuySignalGenerator>,
-        "open" => Box::new(OpenPriceSignal) as Box<dyn strategy_lab::signals::BuySignalGenerator>,
-        "bottom_reverse" => Box::new(BottomReverseSignal::default()) as Box<dyn strategy_lab::signals::BuySignalGenerator>,
-        "volume_surge" => Box::new(VolumeSurgeSignal::default()) as Box<dyn strategy_lab::signals::BuySignalGenerator>,
-        _ => return Err(anyhow::anyhow!("未知的信号: {}", signal_name)),
-    };
-    
-    // 创建目标
-    let target = match target_name {
-        "return_1d" => Box::new(ReturnTarget { target_return: 0.02, stop_loss: 0.01, in_days: 1 }) as Box<dyn strategy_lab::targets::Target>,
-        "return_3d" => Box::new(ReturnTarget { target_return: 0.06, stop_loss: 0.01, in_days: 3 }) as Box<dyn strategy_lab::targets::Target>,
-        "return_5d" => Box::new(ReturnTarget { target_return: 0.01, stop_loss: 0.01, in_days: 5 }) as Box<dyn strategy_lab::targets::Target>,
-        "guard_3d" => Box::new(GuardTarget { stop_loss: 0.01, in_days: 3 }) as Box<dyn strategy_lab::targets::Target>,
-        _ => return Err(anyhow::anyhow!("未知的目标: {}", target_name)),
-    };
+    // 按配置中的id或组件类型名创建组件
+    let selector = components.selector(strategy_name)?;
+    let signal = components.signal(signal_name)?;
+    let target = components.target(target_name)?;
     
     // 创建评分卡
-    let scorecard = Scorecard::new(
+    let mut scorecard = build_scorecard(
         back_days,
         vec![selector],
         vec![signal],
         vec![target],
+        parallelism,
+        load,
     )?;
+    scorecard.engine.set_collect_trade_details(outputs.needs_details());
     
-    // 运行评分卡
-    let results = scorecard.run();
+    // 运行评分卡，导出交易明细、报告或与基准比较时需要详细结果
+    let results = if outputs.needs_details() || scorecard.engine.benchmark().is_some() {
+        scorecard.run_detailed()
+    } else {
+        scorecard.run_results()
+    };
     
     // 打印结果
     scorecard.print_results(&results);
+    scorecard.print_timing(&results);
+    
+    if let Some(path) = outputs.trades {
+        export_trades(&results.entries, Path::new(path))?;
+    }
+    
+    if let Some(path) = outputs.html {
+        let result = BacktestResult::merge(results.entries.iter().filter_map(|entry| entry.result.clone()).collect());
+        let meta = RunMeta::new(strategy_name, signal_name, target_name, back_days);
+        report::write_html(&result, &meta, Path::new(path))?;
+        println!("HTML报告已生成: {}", path);
+    }
     
     Ok(())
 }
 
-/// 运行完整评分卡
-fn run_full_scorecard(
+/// optimize子命令的网格和评分选项
+struct OptimizeOptions<'a> {
+    /// 名称=值1,值2,... 形式的参数取值
+    params: &'a [String],
+    holdout_days: usize,
+    max_combinations: usize,
+    /// 组合评分函数名称
+    scorer: Option<&'a str>,
+}
+
+/// 对选股策略参数做网格搜索
+fn run_optimize(
+    components: &ComponentsConfig,
+    combination: SingleCombination,
+    options: OptimizeOptions,
     back_days: usize,
-    output_path: Option<String>,
+    parallelism: ParallelismConfig,
+    load: LoadOptions,
 ) -> Result<()> {
-    log::info!("运行完整评分卡...");
-    
-    // 创建选股策略
-    let selectors: Vec<Box<dyn strategy_lab::strategies::StockSelector>> = vec![
-        Box::new(AtrSelector {
-            top_n: 10,
-            lookback_days: 100,
-            score_weights: Default::default(),
-        }),
-        Box::new(VolumeDecliningSelector {
-            top_n: 10,
-            lookback_days: 30,
-            min_consecutive_decline_days: 3,
-            min_volume_decline_ratio: 0.1,
-            price_period: 20,
-            check_support_level: false,
-            max_support_ratio: 0.06
-        }),
-        Box::new(BreakthroughPullbackSelector {
-            top_n: 10,
-            lookback_days: 10,
-            min_breakthrough_percent: 5.0,
-            max_pullback_percent: 5.0,
-            volume_decline_ratio: 0.7,
-        }),
-    ];
-    
-    // 创建买入信号生成器
-    let signals: Vec<Box<dyn strategy_lab::signals::BuySignalGenerator>> = vec![
-        Box::new(ClosePriceSignal),
-        Box::new(OpenPriceSignal),
-        Box::new(BottomReverseSignal::default()),
-        Box::new(VolumeSurgeSignal::default()),
-    ];
-    
-    // 创建目标
-    let targets: Vec<Box<dyn strategy_lab::targets::Target>> = vec![
-        Box::new(ReturnTarget { target_return: 0.02, stop_loss: 0.01, in_days: 1 }),
-        Box::new(ReturnTarget { target_return: 0.06, stop_loss: 0.01, in_days: 3 }),
-        Box::new(ReturnTarget { target_return: 0.01, stop_loss: 0.01, in_days: 5 }),
-        Box::new(GuardTarget { stop_loss: 0.01, in_days: 3 }),
-    ];
-    
-    // 创建评分卡
-    let scorecard = Scorecard::new(
+    let SingleCombination { strategy, signal, target } = combination;
+    let (name, base) = components.selector_params(strategy)?;
+    
+    // 解析各参数的候选取值
+    let axes = options.params.iter()
+        .map(|spec| {
+            let (param, values) = spec.split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("参数取值应为 名称=值1,值2,...: {}", spec))?;
+            let values = values.split(',')
+                .map(|value| registry::parse_param_value(ComponentKind::Selector, &name, param, value.trim()))
+                .collect::<Result<Vec<_>>>()?;
+            Ok((param.to_string(), values))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    
+    let grid = GridSearch::cartesian(&base, &axes);
+    let factory_name = name.clone();
+    let search = GridSearch::new(Box::new(move |params| registry::build_selector(&factory_name, params)), grid)
+        .with_holdout_days(options.holdout_days)
+        .with_max_combinations(options.max_combinations);
+    
+    let mut scorecard = build_scorecard(
         back_days,
-        selectors,
-        signals,
-        targets,
+        vec![components.selector(strategy)?],
+        vec![components.signal(signal)?],
+        vec![components.target(target)?],
+        parallelism,
+        load,
     )?;
+    if let Some(scorer_name) = options.scorer {
+        let scorer = scorer::builtin(scorer_name)
+            .ok_or_else(|| anyhow::anyhow!("未知的评分函数: {}", scorer_name))?;
+        scorecard.set_scorer(scorer);
+    }
     
-    // 运行评分卡
-    let results = scorecard.run();
-    
-    // 打印结果
-    scorecard.print_results(&results);
-    
-    // 打印最佳组合
-    let best_combination = scorecard.find_best_combination(&results);
-    scorecard.print_best_combination(&results);
+    let report = scorecard.run_grid_search(&search, 0, 0)?;
+    println!("选股策略 {} 参数网格搜索:", name);
+    println!("===========================================================");
+    print!("{}", report.format_report());
+    println!("===========================================================");
     
-    // 导出结果
-    if let Some(path) = output_path {
-        export_results_to_json(&scorecard, &results, best_combination, &path)?;
+    let suspects = report.overfit_suspects().len();
+    if suspects > 0 {
+        println!("{}组参数样本内排名靠前但留出期明显变差，疑似过拟合", suspects);
     }
     
     Ok(())
 }
 
-/// 导出结果到JSON
-fn export_results_to_json(
-    scorecard: &Scorecard,
-    results: &[Vec<Vec<f32>>],
-    best_combination: (usize, usize, usize, f32),
-    output_path: &str,
+/// recommend子命令的组合来源
+enum CombinationSpec<'a> {
+    /// 按配置中的id或组件类型名指定
+    Named(SingleCombination<'a>),
+    /// 导出结果JSON中得分最高的组合
+    BestFrom(&'a Path),
+}
+
+/// recommend子命令的数量、仓位和输出选项
+struct RecommendOptions<'a> {
+    count: usize,
+    /// 未指定资金和风险比例时不计算建议股数
+    sizing: Option<PositionSizing>,
+    json: Option<&'a str>,
+    csv: Option<&'a str>,
+}
+
+/// 单一组合的选股策略、信号和目标
+type Components = (Box<dyn StockSelector>, Box<dyn BuySignalGenerator>, Box<dyn Target>);
+
+/// 按组合来源创建组件
+fn resolve_combination(
+    components: &ComponentsConfig,
+    spec: CombinationSpec,
+) -> Result<Components> {
+    match spec {
+        CombinationSpec::Named(combination) => Ok((
+            components.selector(combination.strategy)?,
+            components.signal(combination.signal)?,
+            components.target(combination.target)?,
+        )),
+        CombinationSpec::BestFrom(path) => {
+            let data = export::read_export(path)?;
+            let best = data.best_combinations.first()
+                .and_then(|&idx| data.strategies.get(idx))
+                .ok_or_else(|| anyhow::anyhow!("{} 中没有最佳组合", path.display()))?;
+            log::info!("使用 {} 中的最佳组合: 策略={}, 信号={}, 目标={}",
+                path.display(), best.strategy_name, best.signal_name, best.target_name);
+            
+            Ok((
+                find_by_name(components.build_selectors()?, &best.strategy_name, "选股策略", |c| c.name())?,
+                find_by_name(components.build_signals()?, &best.signal_name, "信号", |c| c.name())?,
+                find_by_name(components.build_targets()?, &best.target_name, "目标", |c| c.name())?,
+            ))
+        }
+    }
+}
+
+/// 在组件列表中按name()查找
+fn find_by_name<T: ?Sized>(
+    components: Vec<Box<T>>,
+    name: &str,
+    kind: &str,
+    name_of: impl Fn(&T) -> String,
+) -> Result<Box<T>> {
+    components.into_iter()
+        .find(|component| name_of(component.as_ref()) == name)
+        .ok_or_else(|| anyhow::anyhow!("组件配置中没有名为 {} 的{}", name, kind))
+}
+
+/// 用最新数据为单一组合生成推荐并计算建议仓位
+fn run_recommend(
+    components: &ComponentsConfig,
+    spec: CombinationSpec,
+    options: RecommendOptions,
+    parallelism: ParallelismConfig,
+    load: LoadOptions,
 ) -> Result<()> {
-    log::info!("导出结果到JSON: {}", output_path);
-    
-    // 创建输出目录
-    if let Some(parent) = Path::new(output_path).parent() {
-        fs::create_dir_all(parent)?;
-    }
-    
-    // 准备导出数据
-    let mut export_data = serde_json::Map::new();
-    export_data.insert("update_date".to_string(), serde_json::Value::String(Local::now().format("%Y-%m-%d").to_string()));
-    
-    let mut strategies = Vec::new();
-    
-    // 获取所有策略组合的结果
-    for (t_idx, target_results) in results.iter().enumerate() {
-        for (s_idx, selector_results) in target_results.iter().enumerate() {
-            for (sig_idx, &score) in selector_results.iter().enumerate() {
-                // 只处理成功率大于0的策略
-                if score > 0.0 {
-                    // 获取策略、信号和目标
-                    let selector = &scorecard.selectors[s_idx];
-                    let signal = &scorecard.signals[sig_idx];
-                    let target = &scorecard.targets[t_idx];
-                    
-                    // 运行详细回测以获取性能指标
-                    let backtest_result = run_detailed_backtest_for_export(
-                        &scorecard.engine,
-                        selector.as_ref(),
-                        signal.as_ref(),
-                        target.as_ref(),
-                        scorecard.back_days
-                    );
-                    
-                    // 创建策略结果
-                    let mut strategy_data = serde_json::Map::new();
-                    strategy_data.insert("strategy".to_string(), serde_json::Value::String(selector.name()));
-                    strategy_data.insert("signal".to_string(), serde_json::Value::String(signal.name()));
-                    strategy_data.insert("target".to_string(), serde_json::Value::String(target.name()));
-                    strategy_data.insert("win_rate".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(backtest_result.win_rate as f64).unwrap()));
-                    strategy_data.insert("avg_return".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(backtest_result.avg_return as f64).unwrap()));
-                    strategy_data.insert("max_return".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(backtest_result.max_return as f64).unwrap()));
-                    strategy_data.insert("max_loss".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(backtest_result.max_loss as f64).unwrap()));
-                    strategy_data.insert("sharpe_ratio".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(backtest_result.sharpe_ratio as f64).unwrap()));
-                    strategy_data.insert("max_drawdown".to_string(), serde_json::Value::Number(serde_json::Number::from_f64(backtest_result.max_drawdown as f64).unwrap()));
-                    
-                    strategies.push(serde_json::Value::Object(strategy_data));
-                }
-            }
+    let (selector, signal, target) = resolve_combination(components, spec)?;
+    let scorecard = build_scorecard(0, vec![selector], vec![signal], vec![target], parallelism, load)?;
+    
+    let mut recommendations = scorecard.recommend(&ScoreEntry::from((0, 0, 0, 0.0)), options.count);
+    if let Some(sizing) = &options.sizing {
+        for recommendation in &mut recommendations {
+            recommendation.apply_sizing(sizing);
         }
     }
     
-    export_data.insert("strategies".to_string(), serde_json::Value::Array(strategies));
+    let data = RecommendationExport {
+        update_date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+        strategy_name: scorecard.selectors[0].name(),
+        signal_name: scorecard.signals[0].name(),
+        target_name: scorecard.targets[0].name(),
+        sizing: options.sizing,
+        recommendations,
+    };
+    print_recommendations(&data);
     
-    // 设置最佳组合
-    let (best_t, best_s, best_sig, _) = best_combination;
-    let best_strategy = format!("{}-{}-{}", 
-        scorecard.selectors[best_s].name(),
-        scorecard.signals[best_sig].name(),
-        scorecard.targets[best_t].name()
-    );
-    export_data.insert("best_strategy".to_string(), serde_json::Value::String(best_strategy));
+    if let Some(path) = options.json {
+        export::write_recommendations_json(Path::new(path), &data)?;
+    }
     
-    // 序列化为JSON
-    let json = serde_json::to_string_pretty(&export_data)?;
+    if let Some(path) = options.csv {
+        export::write_recommendations_csv(Path::new(path), &data.recommendations)?;
+    }
     
-    // 写入文件
-    let mut file = File::create(output_path)?;
-    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// 打印推荐表格
+fn print_recommendations(data: &RecommendationExport) {
+    println!("推荐股票 ({}):", data.update_date);
+    println!("策略: {}, 信号: {}, 目标: {}", data.strategy_name, data.signal_name, data.target_name);
+    if let Some(sizing) = &data.sizing {
+        println!("资金: {:.2}, 单笔风险: {:.2}%", sizing.capital, sizing.risk_per_trade * 100.0);
+    }
+    println!("===========================================================");
     
-    log::info!("结果已导出到 {}", output_path);
+    if data.recommendations.is_empty() {
+        println!("没有符合条件的股票");
+    } else {
+        println!("{:<10} {:<10} {:>8} {:>8} {:>8} {:>8} {:>12}",
+            "代码", "名称", "买入价", "目标价", "止损价", "股数", "金额");
+    }
+    
+    for rec in &data.recommendations {
+        println!("{:<10} {:<10} {:>8.2} {:>8.2} {:>8.2} {:>8} {:>12}",
+            rec.symbol,
+            rec.name,
+            rec.buy_price,
+            rec.target_price,
+            rec.stop_loss_price,
+            rec.shares.map(|shares| shares.to_string()).unwrap_or_else(|| "-".to_string()),
+            rec.position_value().map(|value| format!("{:.2}", value)).unwrap_or_else(|| "-".to_string()));
+    }
+    
+    println!("===========================================================");
+}
+
+/// 导出单一组合的逐笔交易明细
+fn export_trades(entries: &[ScoreEntry], path: &Path) -> Result<()> {
+    let result = BacktestResult::merge(entries.iter().filter_map(|entry| entry.result.clone()).collect());
+    let count = result.trade_details.as_ref().map_or(0, |details| details.len());
+    if count == 0 {
+        log::warn!("回测结果中没有逐笔交易明细，{} 只包含表头", path.display());
+    }
     
+    result.export_trades_csv(path)?;
+    println!("{} 笔交易已导出到 {}", count, path.display());
     Ok(())
 }
 
-/// 运行详细回测以获取性能指标（用于导出）
-fn run_detailed_backtest_for_export(
-    engine: &BacktestEngine,
-    selector: &dyn strategy_lab::strategies::StockSelector,
-    signal: &dyn strategy_lab::signals::BuySignalGenerator,
-    target: &dyn strategy_lab::targets::Target,
-    back_days: usize
-) -> strategy_lab::backtest::BacktestResult {
-    log::info!("运行详细回测以获取性能指标...");
-    
-    let mut total_trades = 0;
-    let mut winning_trades = 0;
-    let mut losing_trades = 0;
-    let mut stop_loss_trades = 0;
-    let mut total_return = 0.0;
-    let mut max_return: f32 = -1.0;
-    let mut max_loss: f32 = 0.0;
-    let mut total_hold_days = 0.0;
-    
-    // 对每个回测日期运行回测
-    for forecast_idx in 1..=back_days {
-        let result = engine.run_detailed_test(selector, signal, target, forecast_idx);
-        
-        // 累加结果
-        total_trades += result.total_trades;
-        winning_trades += result.winning_trades;
-        losing_trades += result.losing_trades;
-        stop_loss_trades += result.stop_loss_trades;
-        total_return += result.avg_return * result.total_trades as f32;
-        max_return = max_return.max(result.max_return);
-        max_loss = max_loss.min(result.max_loss);
-        total_hold_days += result.avg_hold_days * result.total_trades as f32;
-    }
-    
-    // 计算平均值
-    let avg_return = if total_trades > 0 {
-        total_return / total_trades as f32
-    } else {
-        0.0
-    };
+/// 运行完整评分卡
+fn run_full_scorecard(
+    components: &ComponentsConfig,
+    back_days: usize,
+    options: ScorecardOptions,
+    parallelism: ParallelismConfig,
+    load: LoadOptions,
+) -> Result<()> {
+    log::info!("运行完整评分卡...");
     
-    let avg_hold_days = if total_trades > 0 {
-        total_hold_days / total_trades as f32
-    } else {
-        target.in_days() as f32 / 2.0 // 默认值
-    };
+    // 按配置创建全部组件
+    let selectors = components.build_selectors()?;
+    let signals = components.build_signals()?;
+    let targets = components.build_targets()?;
     
-    let win_rate = if total_trades > 0 {
-        winning_trades as f32 / total_trades as f32
-    } else {
-        0.0
-    };
+    // 创建评分卡
+    let mut scorecard = build_scorecard(
+        back_days,
+        selectors,
+        signals,
+        targets,
+        parallelism,
+        load,
+    )?;
+    
+    // 导出JSON中的净值曲线需要逐笔交易明细
+    scorecard.engine.set_collect_trade_details(options.output.is_some());
+    scorecard.set_print_monte_carlo(options.monte_carlo);
+    scorecard.set_keep_daily_scores(options.daily_scores);
     
-    // 计算止损率和止损失败率
-    let stop_loss_rate = if total_trades > 0 {
-        stop_loss_trades as f32 / total_trades as f32
+    if let Some(name) = options.scorer {
+        let scorer = scorer::builtin(name)
+            .ok_or_else(|| anyhow::anyhow!("未知的评分函数: {}", name))?;
+        scorecard.set_scorer(scorer);
+    }
+    
+    // 与基准比较同样需要详细结果
+    let results = if options.needs_details() || scorecard.engine.benchmark().is_some() {
+        scorecard.run_detailed()
     } else {
-        0.0
+        scorecard.run_results()
     };
     
-    // 创建结果对象
-    let result = strategy_lab::backtest::BacktestResult {
-        total_trades,
-        winning_trades,
-        losing_trades,
-        stop_loss_trades,
-        stop_loss_fail_trades: 0,
-        win_rate,
-        stop_loss_rate,
-        stop_loss_fail_rate: 0.0,
-        avg_return,
-        max_return,
-        max_loss,
-        avg_hold_days,
-        sharpe_ratio: 0.0,
-        max_drawdown: 0.0,
-        profit_factor: 0.0,
-        trade_details: None,
-    };
+    let ranking = if options.monte_carlo { RankingMetric::MonteCarloP5 } else { RankingMetric::PointEstimate };
+    scorecard.print_results(&results);
+    scorecard.print_best_combination(&results, ranking);
+    scorecard.print_timing(&results);
     
-    result
+    if let Some(path) = options.output {
+        log::info!("导出结果到JSON: {}", path);
+        let export_data = ExportData::from_results(&scorecard, &results, EXPORT_RECOMMENDATIONS, options.top_k);
+        export::write_export(Path::new(path), &export_data)?;
+    }
+    
+    if let Some(path) = options.csv {
+        export::write_csv(Path::new(path), &scorecard, &results)?;
+    }
+    
+    if let Some(path) = options.report {
+        strategy_lab::scorecard::report::write_report(
+            Path::new(path), &scorecard, &results, ranking, options.top_k, EXPORT_RECOMMENDATIONS)?;
+    }
+    
+    Ok(())
 }
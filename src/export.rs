@@ -0,0 +1,480 @@
+use crate::backtest::{BacktestResult, DailyScore};
+use crate::scorecard::{PositionSizing, Recommendation, Scorecard, ScorecardResults, DEFAULT_SCORER_NAME};
+use crate::stock::retry::LoadReport;
+use crate::stock::snapshot::SurvivorshipReport;
+use crate::utils::csv;
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use log::info;
+use serde::{Serialize, Deserialize};
+use std::fs;
+use std::path::Path;
+
+/// 当前导出格式版本
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// 默认导出的最佳组合数量
+pub const BEST_COMBINATIONS: usize = 2;
+
+/// 策略性能指标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyPerformance {
+    pub success_rate: f32,
+    #[serde(default)]
+    pub total_trades: usize,
+    #[serde(default)]
+    pub win_rate: f32,
+    pub stop_loss_rate: f32,
+    /// 跳空越过止损价、未能按止损价成交的交易数
+    #[serde(default)]
+    pub stop_loss_fail_trades: usize,
+    pub stop_loss_fail_rate: f32,
+    pub avg_return: f32,
+    pub max_return: f32,
+    pub max_loss: f32,
+    pub avg_hold_days: f32,
+    pub sharpe_ratio: f32,
+    pub max_drawdown: f32,
+    #[serde(default, deserialize_with = "ratio_or_infinity")]
+    pub profit_factor: f32,
+    #[serde(default, deserialize_with = "ratio_or_infinity")]
+    pub sortino_ratio: f32,
+    #[serde(default, deserialize_with = "ratio_or_infinity")]
+    pub calmar_ratio: f32,
+    #[serde(default)]
+    pub expectancy: f32,
+    #[serde(default)]
+    pub annualized_return: f32,
+    #[serde(default)]
+    pub annualized_volatility: f32,
+    #[serde(default)]
+    pub trades_per_month: f32,
+}
+
+impl StrategyPerformance {
+    /// 由评分卡得分和详细回测结果构建
+    pub fn new(success_rate: f32, result: &BacktestResult) -> Self {
+        Self {
+            success_rate,
+            total_trades: result.total_trades,
+            win_rate: result.win_rate,
+            stop_loss_rate: result.stop_loss_rate,
+            stop_loss_fail_trades: result.stop_loss_fail_trades,
+            stop_loss_fail_rate: result.stop_loss_fail_rate,
+            avg_return: result.avg_return,
+            max_return: result.max_return,
+            max_loss: result.max_loss,
+            avg_hold_days: result.avg_hold_days,
+            sharpe_ratio: result.sharpe_ratio,
+            max_drawdown: result.max_drawdown,
+            profit_factor: result.profit_factor,
+            sortino_ratio: result.sortino_ratio,
+            calmar_ratio: result.calmar_ratio,
+            expectancy: result.expectancy,
+            annualized_return: result.annualized_return,
+            annualized_volatility: result.annualized_volatility,
+            trades_per_month: result.trades_per_month,
+        }
+    }
+}
+
+/// 没有亏损或回撤时比率为无穷大，JSON中写为null，读回时还原为无穷大
+fn ratio_or_infinity<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    Ok(Option::<f32>::deserialize(deserializer)?.unwrap_or(f32::INFINITY))
+}
+
+/// 单个策略组合的导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyResult {
+    pub strategy_name: String,
+    pub signal_name: String,
+    pub target_name: String,
+    pub performance: StrategyPerformance,
+    /// 按买入日期复利的净值曲线(日期, 净值)，需要回测时收集交易明细
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equity_curve: Option<Vec<(String, f32)>>,
+    /// 评分卡保留逐日得分时每个回测日的成功率和交易数，按日期倒序
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_scores: Option<Vec<DailyScore>>,
+    #[serde(default)]
+    pub recommendations: Vec<Recommendation>,
+}
+
+/// 导出数据(docs/data/stocks.json)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportData {
+    /// 格式版本，旧版无版本号的文件读取时视为0
+    #[serde(default)]
+    pub schema_version: u32,
+    pub update_date: String,
+    /// 得分所用评分函数的名称，旧版文件为逐日成功率
+    #[serde(default = "default_scorer")]
+    pub scorer: String,
+    /// 按历史股票池快照回测时的幸存者偏差统计
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub survivorship: Option<SurvivorshipReport>,
+    /// 数据获取结果: 成功、不存在和重试后仍失败的股票
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load_report: Option<LoadReport>,
+    /// 最佳组合在strategies中的索引，按得分从高到低
+    pub best_combinations: Vec<usize>,
+    pub strategies: Vec<StrategyResult>,
+}
+
+fn default_scorer() -> String {
+    DEFAULT_SCORER_NAME.to_string()
+}
+
+impl ExportData {
+    /// 由评分卡详细结果构建导出数据，只导出得分大于0且交易数足够的组合
+    ///
+    /// best_combinations按Scorecard::find_top_combinations取得分最高的top_k个组合
+    pub fn from_results(scorecard: &Scorecard, results: &ScorecardResults, max_recommendations: usize, top_k: usize) -> Self {
+        let mut strategies = Vec::new();
+        let mut exported = Vec::new();
+        
+        for entry in &results.entries {
+            if entry.insufficient_samples || entry.score <= 0.0 {
+                continue;
+            }
+            exported.push((entry.target_idx, entry.selector_idx, entry.signal_idx));
+            
+            let detail = entry.result.clone().unwrap_or_default();
+            
+            strategies.push(StrategyResult {
+                strategy_name: scorecard.selectors[entry.selector_idx].name(),
+                signal_name: scorecard.signals[entry.signal_idx].name(),
+                target_name: scorecard.targets[entry.target_idx].name(),
+                performance: StrategyPerformance::new(entry.score, &detail),
+                equity_curve: detail.equity_curve.clone(),
+                daily_scores: entry.daily_scores.clone(),
+                recommendations: if max_recommendations > 0 {
+                    scorecard.recommend(entry, max_recommendations)
+                } else {
+                    Vec::new()
+                },
+            });
+        }
+        
+        let best_combinations = scorecard.find_top_combinations(&results.scores, top_k)
+            .into_iter()
+            .filter_map(|(t, s, sig, _)| exported.iter().position(|&combination| combination == (t, s, sig)))
+            .collect();
+        
+        Self {
+            schema_version: SCHEMA_VERSION,
+            update_date: Local::now().format("%Y-%m-%d").to_string(),
+            scorer: results.scorer.clone(),
+            survivorship: scorecard.engine.survivorship().cloned(),
+            load_report: scorecard.engine.load_report().cloned(),
+            best_combinations,
+            strategies,
+        }
+    }
+    
+    /// 得分最高的n个组合在strategies中的索引，按得分从高到低，得分相同时索引较小的在前
+    pub fn top_combinations(&self, n: usize) -> Vec<usize> {
+        let mut ranked: Vec<usize> = (0..self.strategies.len()).collect();
+        ranked.sort_by(|&a, &b| {
+            self.strategies[b].performance.success_rate
+                .partial_cmp(&self.strategies[a].performance.success_rate)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        ranked.truncate(n);
+        ranked
+    }
+    
+    /// 将旧版本的数据升级到当前版本
+    fn upgrade(&mut self) {
+        if self.schema_version == 0 {
+            // 版本0: 缺少的字段已由serde默认值补齐；最佳组合可能是预设的索引，按得分重新计算
+            self.best_combinations = self.top_combinations(BEST_COMBINATIONS);
+        }
+        
+        self.schema_version = SCHEMA_VERSION;
+    }
+}
+
+/// 写入JSON文件，自动创建上级目录
+pub fn write_export(path: &Path, data: &ExportData) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    
+    let json = serde_json::to_string_pretty(data)?;
+    fs::write(path, json)?;
+    
+    info!("结果已导出到 {}", path.display());
+    Ok(())
+}
+
+/// 读取JSON文件，旧版无版本号的格式会被升级到当前版本
+pub fn read_export(path: &Path) -> Result<ExportData> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("读取导出文件失败: {}", path.display()))?;
+    let mut data: ExportData = serde_json::from_str(&content)
+        .with_context(|| format!("解析导出文件失败: {}", path.display()))?;
+    
+    if data.schema_version > SCHEMA_VERSION {
+        bail!("导出文件 {} 的格式版本为 {}，当前程序只支持到版本 {}",
+            path.display(), data.schema_version, SCHEMA_VERSION);
+    }
+    
+    data.upgrade();
+    
+    Ok(data)
+}
+
+/// 将评分卡结果写入CSV，每个组合一行；详细指标仅在详细模式下填写
+pub fn write_csv(path: &Path, scorecard: &Scorecard, results: &ScorecardResults) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    
+    let mut lines = vec![csv::join_row(&[
+        "strategy", "signal", "target", "scorer", "score", "elapsed_ms",
+        "total_trades", "win_rate", "stop_loss_rate", "avg_return", "max_drawdown", "sharpe_ratio",
+        "sortino_ratio", "calmar_ratio", "expectancy",
+        "annualized_return", "annualized_volatility", "trades_per_month", "avg_slippage",
+    ])];
+    
+    for entry in &results.entries {
+        let mut row = vec![
+            scorecard.selectors[entry.selector_idx].name(),
+            scorecard.signals[entry.signal_idx].name(),
+            scorecard.targets[entry.target_idx].name(),
+            results.scorer.clone(),
+            entry.score.to_string(),
+            entry.elapsed_ms.to_string(),
+        ];
+        
+        match &entry.result {
+            Some(result) => row.extend([
+                result.total_trades.to_string(),
+                result.win_rate.to_string(),
+                result.stop_loss_rate.to_string(),
+                result.avg_return.to_string(),
+                result.max_drawdown.to_string(),
+                result.sharpe_ratio.to_string(),
+                result.sortino_ratio.to_string(),
+                result.calmar_ratio.to_string(),
+                result.expectancy.to_string(),
+                result.annualized_return.to_string(),
+                result.annualized_volatility.to_string(),
+                result.trades_per_month.to_string(),
+                result.avg_slippage.to_string(),
+            ]),
+            None => {
+                row.push(entry.total_trades.to_string());
+                row.extend(std::iter::repeat_n(String::new(), 12));
+            }
+        }
+        
+        lines.push(csv::join_row(&row));
+    }
+    
+    fs::write(path, lines.join("\n") + "\n")?;
+    
+    info!("评分卡结果已导出到 {}", path.display());
+    Ok(())
+}
+
+/// 单个组合的推荐导出(recommend子命令)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendationExport {
+    pub update_date: String,
+    pub strategy_name: String,
+    pub signal_name: String,
+    pub target_name: String,
+    /// 计算建议股数使用的仓位规则
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sizing: Option<PositionSizing>,
+    pub recommendations: Vec<Recommendation>,
+}
+
+/// 写入推荐JSON文件，自动创建上级目录
+pub fn write_recommendations_json(path: &Path, data: &RecommendationExport) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    
+    fs::write(path, serde_json::to_string_pretty(data)?)?;
+    
+    info!("推荐结果已导出到 {}", path.display());
+    Ok(())
+}
+
+/// 将推荐股票写入CSV，每只股票一行；未计算仓位时股数和金额为空
+pub fn write_recommendations_csv(path: &Path, recommendations: &[Recommendation]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    
+    let mut lines = vec![csv::join_row(&[
+        "symbol", "name", "buy_price", "target_price", "stop_loss_price", "prev_close", "score",
+        "shares", "position_value",
+    ])];
+    
+    for rec in recommendations {
+        lines.push(csv::join_row(&[
+            rec.symbol.clone(),
+            rec.name.clone(),
+            rec.buy_price.to_string(),
+            rec.target_price.to_string(),
+            rec.stop_loss_price.to_string(),
+            rec.prev_close.map(|close| close.to_string()).unwrap_or_default(),
+            rec.score.to_string(),
+            rec.shares.map(|shares| shares.to_string()).unwrap_or_default(),
+            rec.position_value().map(|value| format!("{:.2}", value)).unwrap_or_default(),
+        ]));
+    }
+    
+    fs::write(path, lines.join("\n") + "\n")?;
+    
+    info!("推荐结果已导出到 {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// 旧版main.rs写出的无版本号格式，取自docs/data/stocks.json，best_combinations为旧版预设的[0, 1]
+    const V0_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/stocks_v0.json");
+    
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("strategy_lab_{}_{}", std::process::id(), name))
+    }
+    
+    fn strategy(name: &str, success_rate: f32, result: &BacktestResult) -> StrategyResult {
+        StrategyResult {
+            strategy_name: name.to_string(),
+            signal_name: "开盘价信号".to_string(),
+            target_name: "收益率目标 2% / 1天".to_string(),
+            performance: StrategyPerformance::new(success_rate, result),
+            equity_curve: None,
+            daily_scores: None,
+            recommendations: Vec::new(),
+        }
+    }
+    
+    #[test]
+    fn reads_unversioned_fixture() {
+        let data = read_export(Path::new(V0_FIXTURE)).unwrap();
+        
+        assert_eq!(data.schema_version, SCHEMA_VERSION);
+        assert_eq!(data.update_date, "2026-05-02");
+        assert_eq!(data.scorer, DEFAULT_SCORER_NAME);
+        assert_eq!(data.strategies.len(), 4);
+        
+        // 预设的[0, 1]按成功率重新计算为最高的两个组合(0.583、0.525)
+        assert_eq!(data.best_combinations, vec![3, 2]);
+        
+        let first = &data.strategies[0];
+        assert_eq!(first.strategy_name, "ATR选股策略");
+        assert_eq!(first.performance.success_rate, 0.35);
+        assert_eq!(first.performance.stop_loss_rate, 0.09166667);
+        assert_eq!(first.recommendations.len(), 2);
+        assert_eq!(first.recommendations[0].symbol, "200553");
+        assert_eq!(first.recommendations[0].prev_close, Some(2.36));
+        
+        // 旧版没有的字段取默认值
+        assert_eq!(first.performance.total_trades, 0);
+        assert_eq!(first.performance.profit_factor, 0.0);
+        assert!(first.equity_curve.is_none());
+        assert!(data.load_report.is_none());
+    }
+    
+    #[test]
+    fn rejects_newer_schema_version() {
+        let path = temp_path("newer.json");
+        let content = fs::read_to_string(V0_FIXTURE).unwrap()
+            .replacen('{', &format!("{{\n  \"schema_version\": {},", SCHEMA_VERSION + 1), 1);
+        fs::write(&path, content).unwrap();
+        
+        let err = read_export(&path).unwrap_err().to_string();
+        fs::remove_file(&path).unwrap();
+        assert!(err.contains("格式版本"), "{}", err);
+    }
+    
+    #[test]
+    fn infinite_ratios_round_trip_as_null() {
+        let result = BacktestResult {
+            total_trades: 3,
+            winning_trades: 3,
+            profit_factor: f32::INFINITY,
+            sortino_ratio: f32::INFINITY,
+            calmar_ratio: f32::INFINITY,
+            ..Default::default()
+        };
+        let data = ExportData {
+            schema_version: SCHEMA_VERSION,
+            update_date: "2026-05-02".to_string(),
+            scorer: DEFAULT_SCORER_NAME.to_string(),
+            survivorship: None,
+            load_report: None,
+            best_combinations: vec![0],
+            strategies: vec![strategy("ATR选股策略", 1.0, &result)],
+        };
+        
+        let path = temp_path("infinity.json");
+        write_export(&path, &data).unwrap();
+        let json = fs::read_to_string(&path).unwrap();
+        let read = read_export(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        
+        assert!(json.contains("\"profit_factor\": null"));
+        assert!(json.contains("\"sortino_ratio\": null"));
+        let performance = &read.strategies[0].performance;
+        assert_eq!(performance.profit_factor, f32::INFINITY);
+        assert_eq!(performance.sortino_ratio, f32::INFINITY);
+        assert_eq!(performance.calmar_ratio, f32::INFINITY);
+    }
+    
+    #[test]
+    fn current_format_round_trips() {
+        let winner = BacktestResult {
+            total_trades: 10,
+            winning_trades: 6,
+            losing_trades: 4,
+            stop_loss_trades: 3,
+            stop_loss_fail_trades: 1,
+            win_rate: 0.6,
+            stop_loss_rate: 0.3,
+            stop_loss_fail_rate: 0.1,
+            avg_return: 0.012,
+            profit_factor: 1.8,
+            ..Default::default()
+        };
+        let loser = BacktestResult {
+            total_trades: 8,
+            winning_trades: 2,
+            losing_trades: 6,
+            win_rate: 0.25,
+            avg_return: -0.01,
+            profit_factor: 0.4,
+            ..Default::default()
+        };
+        // 当前版本的best_combinations原样保留，不按成功率重新计算
+        let data = ExportData {
+            schema_version: SCHEMA_VERSION,
+            update_date: "2026-10-15".to_string(),
+            scorer: DEFAULT_SCORER_NAME.to_string(),
+            survivorship: None,
+            load_report: None,
+            best_combinations: vec![1, 0],
+            strategies: vec![strategy("ATR选股策略", 0.6, &winner), strategy("突破回调策略", 0.25, &loser)],
+        };
+        
+        let path = temp_path("round_trip.json");
+        write_export(&path, &data).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        let read = read_export(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        
+        assert_eq!(read.schema_version, SCHEMA_VERSION);
+        assert_eq!(read.best_combinations, vec![1, 0]);
+        assert_eq!(read.strategies[0].performance.stop_loss_fail_rate, 0.1);
+        assert_eq!(serde_json::to_string_pretty(&read).unwrap(), written);
+    }
+}
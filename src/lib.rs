@@ -5,10 +5,12 @@ pub mod strategies;
 pub mod targets;
 pub mod scorecard;
 pub mod utils;
+pub mod export;
+pub mod registry;
 
 // Re-export commonly used types
 pub use backtest::{BacktestEngine, BacktestResult};
 pub use signals::BuySignalGenerator;
 pub use strategies::StockSelector;
 pub use targets::Target;
-pub use scorecard::Scorecard;
+pub use scorecard::{Scorecard, ScoreEntry, ScorecardResults, Recommendation, RankingMetric};
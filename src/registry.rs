@@ -0,0 +1,757 @@
+use crate::signals::BuySignalGenerator;
+use crate::signals::{
+    pattern::bottom_reverse::BottomReverseSignal,
+    price::close::ClosePriceSignal,
+    price::open::OpenPriceSignal,
+    volume::decline::VolumeDeclineSignal,
+    volume::surge::VolumeSurgeSignal,
+};
+use crate::strategies::StockSelector;
+use crate::strategies::{
+    reversal::breakthrough_pullback::BreakthroughPullbackSelector,
+    trend::atr::AtrSelector,
+    volume::volume_decline::VolumeDecliningSelector,
+};
+use crate::targets::Target;
+use crate::targets::{
+    combined_target::CombinedTarget, guard_target::GuardTarget, return_target::ReturnTarget,
+    scaled_exit_target::ScaledExitTarget, short_target::ShortTarget, trailing_stop_target::TrailingStopTarget,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// 组件参数: 参数名 -> 值
+pub type Params = toml::Table;
+
+/// 可用的选股策略
+pub const SELECTORS: &[&str] = &["atr", "volume_decline", "breakthrough"];
+/// 可用的买入信号
+pub const SIGNALS: &[&str] = &["close", "open", "bottom_reverse", "volume_surge", "volume_decline"];
+/// 可用的目标
+pub const TARGETS: &[&str] = &["return", "guard", "trailing_stop", "scaled_exit", "combined", "short"];
+
+/// 内置的默认组件配置，与config/backtest.toml相同
+pub const DEFAULT_CONFIG: &str = include_str!("../config/backtest.toml");
+
+/// 主程序内置的组件配置，与config/main.toml相同
+pub const MAIN_CONFIG: &str = include_str!("../config/main.toml");
+
+/// 配置和命令行都未指定时的回测天数
+pub const DEFAULT_BACK_DAYS: usize = 12;
+
+/// 按名称创建选股策略，未填写的参数使用默认值
+pub fn build_selector(name: &str, params: &Params) -> Result<Box<dyn StockSelector>> {
+    Ok(match name {
+        "atr" => Box::new(parse_params::<AtrSelector>("选股策略", name, params)?),
+        "volume_decline" => Box::new(parse_params::<VolumeDecliningSelector>("选股策略", name, params)?),
+        "breakthrough" => Box::new(parse_params::<BreakthroughPullbackSelector>("选股策略", name, params)?),
+        _ => bail!("未知的选股策略: {}，可选: {}", name, SELECTORS.join(", ")),
+    })
+}
+
+/// 按名称创建买入信号，未填写的参数使用默认值
+pub fn build_signal(name: &str, params: &Params) -> Result<Box<dyn BuySignalGenerator>> {
+    Ok(match name {
+        "close" => {
+            expect_no_params("信号", name, params)?;
+            Box::new(ClosePriceSignal)
+        }
+        "open" => {
+            expect_no_params("信号", name, params)?;
+            Box::new(OpenPriceSignal)
+        }
+        "bottom_reverse" => Box::new(parse_params::<BottomReverseSignal>("信号", name, params)?),
+        "volume_surge" => Box::new(parse_params::<VolumeSurgeSignal>("信号", name, params)?),
+        "volume_decline" => Box::new(parse_params::<VolumeDeclineSignal>("信号", name, params)?),
+        _ => bail!("未知的信号: {}，可选: {}", name, SIGNALS.join(", ")),
+    })
+}
+
+/// 按名称创建目标，return、guard、trailing_stop和scaled_exit的参数都必须填写
+///
+/// combined的参数为targets(子目标列表，每项含name和参数)和可选的weights，
+/// short的参数为target(做空评估的目标，含name和参数)
+pub fn build_target(name: &str, params: &Params) -> Result<Box<dyn Target>> {
+    Ok(match name {
+        "return" => Box::new(parse_params::<ReturnTarget>("目标", name, params)?),
+        "guard" => Box::new(parse_params::<GuardTarget>("目标", name, params)?),
+        "trailing_stop" => Box::new(parse_params::<TrailingStopTarget>("目标", name, params)?),
+        "scaled_exit" => Box::new(parse_params::<ScaledExitTarget>("目标", name, params)?),
+        "combined" => Box::new(build_combined_target(params)?),
+        "short" => Box::new(build_short_target(params)?),
+        _ => bail!("未知的目标: {}，可选: {}", name, TARGETS.join(", ")),
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CombinedParams {
+    targets: Vec<ComponentConfig>,
+    weights: Option<Vec<f32>>,
+}
+
+fn build_combined_target(params: &Params) -> Result<CombinedTarget> {
+    let combined = parse_params::<CombinedParams>("目标", "combined", params)?;
+    if combined.targets.is_empty() {
+        bail!("目标combined的参数targets不能为空");
+    }
+    
+    let targets = combined.targets.iter()
+        .map(|target| build_target(&target.name, &target.params))
+        .collect::<Result<Vec<_>>>()?;
+    
+    match combined.weights {
+        Some(weights) if weights.len() != targets.len() => {
+            bail!("目标combined的weights数量({})与targets数量({})不同", weights.len(), targets.len())
+        }
+        Some(weights) => Ok(CombinedTarget::with_weights(targets, weights)),
+        None => Ok(CombinedTarget::new(targets)),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ShortParams {
+    target: ComponentConfig,
+}
+
+fn build_short_target(params: &Params) -> Result<ShortTarget> {
+    let short = parse_params::<ShortParams>("目标", "short", params)?;
+    Ok(ShortTarget::new(build_target(&short.target.name, &short.target.params)?))
+}
+
+/// 将参数表解析为组件，错误信息包含组件名和出错的参数名
+fn parse_params<T: DeserializeOwned>(kind: &str, name: &str, params: &Params) -> Result<T> {
+    toml::Value::Table(params.clone())
+        .try_into()
+        .map_err(|err| anyhow!("{}{}的参数无效: {}", kind, name, err))
+}
+
+fn expect_no_params(kind: &str, name: &str, params: &Params) -> Result<()> {
+    match params.keys().next() {
+        Some(key) => bail!("{}{}没有参数，不支持: {}", kind, name, key),
+        None => Ok(()),
+    }
+}
+
+/// 配置文件中的一个组件
+#[derive(Debug, Clone, Deserialize)]
+pub struct ComponentConfig {
+    /// 引用名称，未填写时与name相同
+    #[serde(default)]
+    pub id: Option<String>,
+    /// 组件类型
+    pub name: String,
+    /// 其余键均为组件参数
+    #[serde(flatten)]
+    pub params: Params,
+}
+
+impl ComponentConfig {
+    pub fn id(&self) -> &str {
+        self.id.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// 回测组件配置
+///
+/// ```toml
+/// back_days = 20
+///
+/// [[selectors]]
+/// name = "atr"
+/// lookback_days = 60
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ComponentsConfig {
+    /// 回测天数，命令行指定时以命令行为准
+    pub back_days: Option<usize>,
+    pub selectors: Vec<ComponentConfig>,
+    pub signals: Vec<ComponentConfig>,
+    pub targets: Vec<ComponentConfig>,
+}
+
+impl ComponentsConfig {
+    /// 内置的默认配置
+    pub fn builtin() -> Self {
+        Self::parse(DEFAULT_CONFIG).expect("内置组件配置无效")
+    }
+    
+    /// 主程序内置的配置，发布的评分卡使用这组参数
+    pub fn builtin_main() -> Self {
+        Self::parse(MAIN_CONFIG).expect("主程序内置组件配置无效")
+    }
+    
+    /// 从TOML文件加载
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("无法读取配置文件: {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("配置文件无效: {}", path.display()))
+    }
+    
+    /// 解析TOML文本
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+    
+    /// 回测天数: 优先使用override_days，其次是配置中的back_days，都没有时为DEFAULT_BACK_DAYS
+    pub fn back_days(&self, override_days: Option<usize>) -> usize {
+        override_days.or(self.back_days).unwrap_or(DEFAULT_BACK_DAYS)
+    }
+    
+    /// 创建全部选股策略
+    pub fn build_selectors(&self) -> Result<Vec<Box<dyn StockSelector>>> {
+        self.selectors.iter()
+            .map(|component| build_selector(&component.name, &component.params))
+            .collect()
+    }
+    
+    /// 创建全部买入信号
+    pub fn build_signals(&self) -> Result<Vec<Box<dyn BuySignalGenerator>>> {
+        self.signals.iter()
+            .map(|component| build_signal(&component.name, &component.params))
+            .collect()
+    }
+    
+    /// 创建全部目标
+    pub fn build_targets(&self) -> Result<Vec<Box<dyn Target>>> {
+        self.targets.iter()
+            .map(|component| build_target(&component.name, &component.params))
+            .collect()
+    }
+    
+    /// 按id创建选股策略，配置中没有时按类型名或简写创建，见parse_shorthand
+    pub fn selector(&self, id: &str) -> Result<Box<dyn StockSelector>> {
+        let (name, params) = self.selector_params(id)?;
+        build_selector(&name, &params)
+    }
+    
+    /// 按id查找选股策略的类型名和参数，配置中没有时按类型名或简写解析
+    pub fn selector_params(&self, id: &str) -> Result<(String, Params)> {
+        match find(&self.selectors, id) {
+            Some(component) => Ok((component.name.clone(), component.params.clone())),
+            None => parse_shorthand(ComponentKind::Selector, id),
+        }
+    }
+    
+    /// 按id创建买入信号，配置中没有时按类型名或简写创建，见parse_shorthand
+    pub fn signal(&self, id: &str) -> Result<Box<dyn BuySignalGenerator>> {
+        let (name, params) = match find(&self.signals, id) {
+            Some(component) => (component.name.clone(), component.params.clone()),
+            None => parse_shorthand(ComponentKind::Signal, id)?,
+        };
+        build_signal(&name, &params)
+    }
+    
+    /// 按id创建目标，配置中没有时按类型名或简写创建(需要参数的目标只写类型名会报错)，见parse_shorthand
+    pub fn target(&self, id: &str) -> Result<Box<dyn Target>> {
+        let (name, params) = match find(&self.targets, id) {
+            Some(component) => (component.name.clone(), component.params.clone()),
+            None => parse_shorthand(ComponentKind::Target, id)?,
+        };
+        build_target(&name, &params)
+    }
+}
+
+fn find<'a>(components: &'a [ComponentConfig], id: &str) -> Option<&'a ComponentConfig> {
+    components.iter().find(|component| component.id() == id)
+}
+
+/// 解析简写"类型名:参数1:参数2..."，例如"return:0.06:0.01:3"
+///
+/// 参数按组件说明中的顺序依次填写，未写出的参数使用默认值；表和数组类型的参数不能简写。
+/// 不含冒号时只有类型名，参数表为空
+pub fn parse_shorthand(kind: ComponentKind, id: &str) -> Result<(String, Params)> {
+    let Some((name, values)) = id.split_once(':') else {
+        return Ok((id.to_string(), Params::new()));
+    };
+    let schema = schema(kind, name)
+        .ok_or_else(|| anyhow!("未知的{}: {}，可选: {}", kind.label(), name, kind.names().join(", ")))?;
+    
+    let values: Vec<&str> = values.split(':').collect();
+    if values.len() > schema.params.len() {
+        bail!("{}{}最多有{}个参数，简写{}中有{}个", kind.label(), name, schema.params.len(), id, values.len());
+    }
+    
+    let params = schema.params.iter()
+        .zip(values)
+        .map(|(param, value)| Ok((param.name.to_string(), param.parse_value(value)?)))
+        .collect::<Result<Params>>()
+        .with_context(|| format!("{}简写无效: {}", kind.label(), id))?;
+    Ok((name.to_string(), params))
+}
+
+/// 按组件说明中的参数类型解析一个参数值，表和数组类型不支持
+pub fn parse_param_value(kind: ComponentKind, name: &str, param: &str, text: &str) -> Result<toml::Value> {
+    let schema = schema(kind, name)
+        .ok_or_else(|| anyhow!("未知的{}: {}，可选: {}", kind.label(), name, kind.names().join(", ")))?;
+    let param = schema.params.iter()
+        .find(|candidate| candidate.name == param)
+        .ok_or_else(|| anyhow!("{}{}没有参数{}", kind.label(), name, param))?;
+    param.parse_value(text)
+}
+
+/// 组件类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentKind {
+    Selector,
+    Signal,
+    Target,
+}
+
+impl ComponentKind {
+    /// 配置文件中的表名
+    pub fn section(&self) -> &'static str {
+        match self {
+            ComponentKind::Selector => "selectors",
+            ComponentKind::Signal => "signals",
+            ComponentKind::Target => "targets",
+        }
+    }
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            ComponentKind::Selector => "选股策略",
+            ComponentKind::Signal => "信号",
+            ComponentKind::Target => "目标",
+        }
+    }
+    
+    /// 该类别可用的类型名
+    pub fn names(&self) -> &'static [&'static str] {
+        match self {
+            ComponentKind::Selector => SELECTORS,
+            ComponentKind::Signal => SIGNALS,
+            ComponentKind::Target => TARGETS,
+        }
+    }
+}
+
+/// 组件参数说明
+#[derive(Debug, Clone, Serialize)]
+pub struct ParamSchema {
+    pub name: &'static str,
+    /// 参数类型，如usize、f32、bool、table、array
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// 默认值，必填参数和无默认值的可选参数为None
+    pub default: Option<toml::Value>,
+    pub required: bool,
+    /// 必填参数在示例配置中使用的值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<toml::Value>,
+    pub description: &'static str,
+}
+
+impl ParamSchema {
+    fn new(name: &'static str, kind: &'static str, default: toml::Value, description: &'static str) -> Self {
+        Self { name, kind, default: Some(default), required: false, example: None, description }
+    }
+    
+    fn required(name: &'static str, kind: &'static str, example: toml::Value, description: &'static str) -> Self {
+        Self { name, kind, default: None, required: true, example: Some(example), description }
+    }
+    
+    fn int(name: &'static str, default: usize, description: &'static str) -> Self {
+        Self::new(name, "usize", toml::Value::Integer(default as i64), description)
+    }
+    
+    fn float(name: &'static str, default: f32, description: &'static str) -> Self {
+        Self::new(name, "f32", float_value(default), description)
+    }
+    
+    fn bool(name: &'static str, default: bool, description: &'static str) -> Self {
+        Self::new(name, "bool", toml::Value::Boolean(default), description)
+    }
+    
+    /// 示例配置中使用的值
+    fn sample(&self) -> Option<&toml::Value> {
+        self.default.as_ref().or(self.example.as_ref())
+    }
+    
+    /// 按参数类型解析简写中的一个值
+    fn parse_value(&self, text: &str) -> Result<toml::Value> {
+        let invalid = || anyhow!("参数{}应为{}，实际为: {}", self.name, self.kind, text);
+        Ok(match self.kind {
+            "usize" => toml::Value::Integer(text.parse::<usize>().map_err(|_| invalid())? as i64),
+            "f32" => toml::Value::Float(text.parse().map_err(|_| invalid())?),
+            "bool" => toml::Value::Boolean(text.parse().map_err(|_| invalid())?),
+            "string" => toml::Value::String(text.to_string()),
+            _ => bail!("参数{}的类型为{}，不能简写", self.name, self.kind),
+        })
+    }
+}
+
+/// f32按十进制显示值转换，避免0.1变成0.10000000149011612
+fn float_value(value: f32) -> toml::Value {
+    toml::Value::Float(value.to_string().parse().unwrap_or(value as f64))
+}
+
+/// 组件说明: 类型名、显示名称和参数
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentSchema {
+    pub kind: ComponentKind,
+    /// 配置和命令行中使用的类型名
+    pub name: &'static str,
+    /// 按默认(或示例)参数创建的组件name()
+    pub display_name: String,
+    pub params: Vec<ParamSchema>,
+}
+
+impl ComponentSchema {
+    /// 默认参数和必填参数的示例值组成的参数表
+    pub fn sample_params(&self) -> Params {
+        self.params.iter()
+            .filter_map(|param| Some((param.name.to_string(), param.sample()?.clone())))
+            .collect()
+    }
+    
+    /// 简写示例: 类型名加上到最后一个必填参数为止的参数，见parse_shorthand；必填参数不能简写时为None
+    pub fn example_shorthand(&self) -> Option<String> {
+        let count = self.params.iter().rposition(|param| param.required).map_or(0, |idx| idx + 1);
+        let mut shorthand = self.name.to_string();
+        for param in &self.params[..count] {
+            match param.sample()? {
+                toml::Value::String(value) => shorthand.push_str(&format!(":{}", value)),
+                toml::Value::Table(_) | toml::Value::Array(_) => return None,
+                value => shorthand.push_str(&format!(":{}", value)),
+            }
+        }
+        Some(shorthand)
+    }
+    
+    /// 示例配置片段
+    pub fn example_config(&self) -> String {
+        let mut snippet = format!("[[{}]]\nname = \"{}\"\n", self.kind.section(), self.name);
+        for param in &self.params {
+            if let Some(value) = param.sample() {
+                snippet.push_str(&format!("{} = {}\n", param.name, value));
+            }
+        }
+        snippet
+    }
+}
+
+/// 所有已注册组件的说明，顺序与SELECTORS、SIGNALS、TARGETS一致
+pub fn schemas() -> Vec<ComponentSchema> {
+    let mut schemas: Vec<ComponentSchema> = Vec::new();
+    for kind in [ComponentKind::Selector, ComponentKind::Signal, ComponentKind::Target] {
+        schemas.extend(kind.names().iter().filter_map(|name| schema(kind, name)));
+    }
+    schemas
+}
+
+/// 单个组件的说明，未注册时为None
+pub fn schema(kind: ComponentKind, name: &str) -> Option<ComponentSchema> {
+    let (name, params) = match (kind, name) {
+        (ComponentKind::Selector, "atr") => ("atr", atr_params()),
+        (ComponentKind::Selector, "volume_decline") => ("volume_decline", volume_decline_selector_params()),
+        (ComponentKind::Selector, "breakthrough") => ("breakthrough", breakthrough_params()),
+        (ComponentKind::Signal, "close") => ("close", Vec::new()),
+        (ComponentKind::Signal, "open") => ("open", Vec::new()),
+        (ComponentKind::Signal, "bottom_reverse") => ("bottom_reverse", bottom_reverse_params()),
+        (ComponentKind::Signal, "volume_surge") => ("volume_surge", volume_surge_params()),
+        (ComponentKind::Signal, "volume_decline") => ("volume_decline", volume_decline_signal_params()),
+        (ComponentKind::Target, "return") => ("return", return_target_params()),
+        (ComponentKind::Target, "guard") => ("guard", guard_target_params()),
+        (ComponentKind::Target, "trailing_stop") => ("trailing_stop", trailing_stop_target_params()),
+        (ComponentKind::Target, "scaled_exit") => ("scaled_exit", scaled_exit_target_params()),
+        (ComponentKind::Target, "combined") => ("combined", combined_target_params()),
+        (ComponentKind::Target, "short") => ("short", short_target_params()),
+        _ => return None,
+    };
+    
+    let mut schema = ComponentSchema { kind, name, display_name: String::new(), params };
+    let params = schema.sample_params();
+    schema.display_name = match kind {
+        ComponentKind::Selector => build_selector(name, &params).map(|c| c.name()),
+        ComponentKind::Signal => build_signal(name, &params).map(|c| c.name()),
+        ComponentKind::Target => build_target(name, &params).map(|c| c.name()),
+    }
+    .unwrap_or_default();
+    Some(schema)
+}
+
+fn atr_params() -> Vec<ParamSchema> {
+    let d = AtrSelector::default();
+    let weights: Params = [
+        ("atr_weight", d.score_weights.atr_weight),
+        ("volume_weight", d.score_weights.volume_weight),
+        ("trend_weight", d.score_weights.trend_weight),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), float_value(value)))
+    .collect();
+    
+    vec![
+        ParamSchema::int("top_n", d.top_n, "最多选出的股票数"),
+        ParamSchema::int("lookback_days", d.lookback_days, "计算ATR和趋势的回看天数"),
+        ParamSchema::new("score_weights", "table", toml::Value::Table(weights), "ATR、成交量和趋势得分的权重(atr_weight、volume_weight、trend_weight)"),
+    ]
+}
+
+fn volume_decline_selector_params() -> Vec<ParamSchema> {
+    let d = VolumeDecliningSelector::default();
+    vec![
+        ParamSchema::int("top_n", d.top_n, "最多选出的股票数"),
+        ParamSchema::int("lookback_days", d.lookback_days, "回看天数"),
+        ParamSchema::int("min_consecutive_decline_days", d.min_consecutive_decline_days, "成交量连续萎缩的最少天数"),
+        ParamSchema::float("min_volume_decline_ratio", d.min_volume_decline_ratio, "每天成交量的最小萎缩比例"),
+        ParamSchema::int("price_period", d.price_period, "计算支撑位和压力位的天数"),
+        ParamSchema::bool("check_support_level", d.check_support_level, "是否要求价格接近支撑位"),
+        ParamSchema::float("max_support_ratio", d.max_support_ratio, "价格高于支撑位的最大比例"),
+    ]
+}
+
+fn breakthrough_params() -> Vec<ParamSchema> {
+    let d = BreakthroughPullbackSelector::default();
+    vec![
+        ParamSchema::int("top_n", d.top_n, "最多选出的股票数"),
+        ParamSchema::int("lookback_days", d.lookback_days, "寻找突破的回看天数"),
+        ParamSchema::float("min_breakthrough_percent", d.min_breakthrough_percent, "突破日的最小涨幅(百分数)"),
+        ParamSchema::float("max_pullback_percent", d.max_pullback_percent, "突破后回踩的最大跌幅(百分数)"),
+        ParamSchema::float("volume_decline_ratio", d.volume_decline_ratio, "回踩期间成交量相对突破日的最大比例"),
+    ]
+}
+
+fn bottom_reverse_params() -> Vec<ParamSchema> {
+    let d = BottomReverseSignal::default();
+    vec![
+        ParamSchema::float("min_body_ratio", d.min_body_ratio, "当日实体相对前一日实体的最小比例"),
+    ]
+}
+
+fn volume_surge_params() -> Vec<ParamSchema> {
+    let d = VolumeSurgeSignal::default();
+    vec![
+        ParamSchema::float("volume_ratio", d.volume_ratio, "当日成交量相对前5日均量的最小倍数"),
+        ParamSchema::bool("price_filter", d.price_filter, "是否过滤价格下跌的情况"),
+    ]
+}
+
+fn volume_decline_signal_params() -> Vec<ParamSchema> {
+    let d = VolumeDeclineSignal::default();
+    vec![
+        ParamSchema::int("min_consecutive_days", d.min_consecutive_days, "成交量连续萎缩的最少天数"),
+        ParamSchema::float("decline_ratio", d.decline_ratio, "每天成交量相对前一天的最大比例"),
+        ParamSchema::bool("price_filter", d.price_filter, "是否过滤价格下跌的情况"),
+    ]
+}
+
+fn return_target_params() -> Vec<ParamSchema> {
+    vec![
+        ParamSchema::required("target_return", "f32", float_value(0.02), "目标收益率(0.02表示2%)"),
+        ParamSchema::required("stop_loss", "f32", float_value(0.01), "止损比例"),
+        ParamSchema::required("in_days", "usize", toml::Value::Integer(1), "持有天数"),
+        ParamSchema::new("fill_mode", "string", toml::Value::String("close_only".to_string()), "判断达到目标的价格: close_only或intraday_high(盘中最高价达到即按目标价卖出)"),
+        ParamSchema {
+            name: "breakeven_trigger",
+            kind: "f32",
+            default: None,
+            required: false,
+            example: None,
+            description: "最高价涨幅达到该比例后，之后的交易日止损价移到买入价，未填写时不启用",
+        },
+    ]
+}
+
+fn guard_target_params() -> Vec<ParamSchema> {
+    vec![
+        ParamSchema::required("stop_loss", "f32", float_value(0.01), "止损比例"),
+        ParamSchema::required("in_days", "usize", toml::Value::Integer(3), "持有天数"),
+    ]
+}
+
+fn trailing_stop_target_params() -> Vec<ParamSchema> {
+    vec![
+        ParamSchema::required("stop_loss", "f32", float_value(0.02), "启动移动止损前的固定止损比例"),
+        ParamSchema::required("activation_return", "f32", float_value(0.03), "最高价涨幅达到该比例后启动移动止损"),
+        ParamSchema::required("trail_pct", "f32", float_value(0.02), "移动止损价相对最高价的回撤比例"),
+        ParamSchema::required("in_days", "usize", toml::Value::Integer(5), "最长持有天数"),
+    ]
+}
+
+fn scaled_exit_target_params() -> Vec<ParamSchema> {
+    let legs = [(0.03, 0.5), (0.06, 0.5)]
+        .into_iter()
+        .map(|(target_return, fraction)| toml::Value::Array(vec![float_value(target_return), float_value(fraction)]))
+        .collect();
+    vec![
+        ParamSchema::required("legs", "array", toml::Value::Array(legs), "各档[收益率, 卖出的仓位比例]，最高价达到该档目标价时卖出对应比例"),
+        ParamSchema::required("stop_loss", "f32", float_value(0.02), "剩余仓位的止损比例"),
+        ParamSchema::required("in_days", "usize", toml::Value::Integer(5), "最长持有天数，到期卖出剩余仓位"),
+    ]
+}
+
+/// 嵌套目标的示例: name加上该目标的必填参数
+fn sub_target(name: &str, params: Vec<ParamSchema>) -> toml::Value {
+    let mut table = Params::new();
+    table.insert("name".to_string(), toml::Value::String(name.to_string()));
+    table.extend(params.into_iter()
+        .filter(|param| param.required)
+        .filter_map(|param| Some((param.name.to_string(), param.sample()?.clone()))));
+    toml::Value::Table(table)
+}
+
+fn combined_target_params() -> Vec<ParamSchema> {
+    let targets = vec![sub_target("return", return_target_params()), sub_target("guard", guard_target_params())];
+    
+    vec![
+        ParamSchema::required("targets", "array", toml::Value::Array(targets), "子目标列表，每项含name和该目标的参数"),
+        ParamSchema {
+            name: "weights",
+            kind: "array",
+            default: None,
+            required: false,
+            example: None,
+            description: "各子目标的权重，未填写时平均分配",
+        },
+    ]
+}
+
+fn short_target_params() -> Vec<ParamSchema> {
+    vec![
+        ParamSchema::required("target", "table", sub_target("return", return_target_params()), "做空评估的目标，含name和该目标的参数；止损价在开仓价之上，目标价在开仓价之下"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Debug;
+
+    #[test]
+    fn json_lists_every_component_once() {
+        let json = serde_json::to_string_pretty(&schemas()).unwrap();
+        let listed: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        
+        for (kind, names) in [("selector", SELECTORS), ("signal", SIGNALS), ("target", TARGETS)] {
+            for name in names {
+                let count = listed.iter()
+                    .filter(|schema| schema["kind"] == kind && schema["name"] == *name)
+                    .count();
+                assert_eq!(count, 1, "{} {}", kind, name);
+            }
+        }
+        assert_eq!(listed.len(), SELECTORS.len() + SIGNALS.len() + TARGETS.len());
+        assert!(listed.iter().all(|schema| !schema["display_name"].as_str().unwrap().is_empty()));
+    }
+
+    #[test]
+    fn example_snippets_build() {
+        for schema in schemas() {
+            let config = ComponentsConfig::parse(&schema.example_config()).unwrap();
+            match schema.kind {
+                ComponentKind::Selector => assert_eq!(config.build_selectors().unwrap().len(), 1),
+                ComponentKind::Signal => assert_eq!(config.build_signals().unwrap().len(), 1),
+                ComponentKind::Target => assert_eq!(config.build_targets().unwrap().len(), 1),
+            }
+        }
+    }
+
+    /// 按component的类型解析参数并格式化，用于与手写的组件逐字段比较
+    fn parsed<T: DeserializeOwned + Debug>(component: &ComponentConfig) -> String {
+        format!("{:?}", parse_params::<T>("组件", &component.name, &component.params).unwrap())
+    }
+
+    fn names(components: &[ComponentConfig]) -> Vec<&str> {
+        components.iter().map(ComponentConfig::id).collect()
+    }
+
+    /// 内置配置与原先写在bin/backtest.rs中的组件一致，另加入与收益率目标对比的移动止损目标
+    #[test]
+    fn builtin_config_reproduces_hard_coded_components() {
+        let config = ComponentsConfig::builtin();
+        
+        assert_eq!(config.back_days(None), 12);
+        assert_eq!(names(&config.selectors), ["atr", "volume_decline", "breakthrough"]);
+        assert_eq!(parsed::<AtrSelector>(&config.selectors[0]), format!("{:?}", AtrSelector {
+            top_n: 10,
+            lookback_days: 100,
+            score_weights: Default::default(),
+        }));
+        assert_eq!(parsed::<VolumeDecliningSelector>(&config.selectors[1]), format!("{:?}", VolumeDecliningSelector {
+            top_n: 10,
+            lookback_days: 30,
+            min_consecutive_decline_days: 3,
+            min_volume_decline_ratio: 0.1,
+            price_period: 20,
+            check_support_level: false,
+            max_support_ratio: 0.06,
+        }));
+        assert_eq!(parsed::<BreakthroughPullbackSelector>(&config.selectors[2]), format!("{:?}", BreakthroughPullbackSelector {
+            top_n: 10,
+            lookback_days: 10,
+            min_breakthrough_percent: 5.0,
+            max_pullback_percent: 5.0,
+            volume_decline_ratio: 0.7,
+        }));
+        
+        assert_eq!(names(&config.signals), ["close", "open", "bottom_reverse", "volume_surge"]);
+        assert_eq!(parsed::<BottomReverseSignal>(&config.signals[2]), format!("{:?}", BottomReverseSignal::default()));
+        assert_eq!(parsed::<VolumeSurgeSignal>(&config.signals[3]), format!("{:?}", VolumeSurgeSignal::default()));
+        
+        assert_eq!(names(&config.targets), ["return_1d", "return_3d", "return_5d", "guard_3d", "trailing_5d"]);
+        assert_eq!(parsed::<ReturnTarget>(&config.targets[0]), format!("{:?}", ReturnTarget::new(0.02, 0.01, 1)));
+        assert_eq!(parsed::<ReturnTarget>(&config.targets[1]), format!("{:?}", ReturnTarget::new(0.06, 0.01, 3)));
+        assert_eq!(parsed::<ReturnTarget>(&config.targets[2]), format!("{:?}", ReturnTarget::new(0.01, 0.01, 5)));
+        assert_eq!(parsed::<GuardTarget>(&config.targets[3]), format!("{:?}", GuardTarget { stop_loss: 0.01, in_days: 3 }));
+        assert_eq!(parsed::<TrailingStopTarget>(&config.targets[4]), format!("{:?}", TrailingStopTarget {
+            stop_loss: 0.02,
+            activation_return: 0.03,
+            trail_pct: 0.02,
+            in_days: 5,
+        }));
+        
+        assert_eq!(config.build_selectors().unwrap().len(), 3);
+        assert_eq!(config.build_signals().unwrap().len(), 4);
+        assert_eq!(config.build_targets().unwrap().len(), 5);
+    }
+
+    /// 主程序配置与原先写在main.rs中的组件一致
+    #[test]
+    fn main_config_reproduces_hard_coded_components() {
+        let config = ComponentsConfig::builtin_main();
+        
+        assert_eq!(config.back_days(None), 12);
+        assert_eq!(names(&config.selectors), ["atr", "volume_decline", "breakthrough"]);
+        assert_eq!(parsed::<AtrSelector>(&config.selectors[0]), format!("{:?}", AtrSelector {
+            top_n: 10,
+            lookback_days: 100,
+            score_weights: Default::default(),
+        }));
+        assert_eq!(parsed::<VolumeDecliningSelector>(&config.selectors[1]), format!("{:?}", VolumeDecliningSelector {
+            top_n: 10,
+            lookback_days: 30,
+            min_consecutive_decline_days: 3,
+            min_volume_decline_ratio: 0.05,
+            price_period: 20,
+            check_support_level: true,
+            max_support_ratio: 0.18,
+        }));
+        assert_eq!(parsed::<BreakthroughPullbackSelector>(&config.selectors[2]), format!("{:?}", BreakthroughPullbackSelector {
+            top_n: 10,
+            lookback_days: 10,
+            min_breakthrough_percent: 5.0,
+            max_pullback_percent: 5.0,
+            volume_decline_ratio: 0.7,
+        }));
+        
+        assert_eq!(names(&config.signals), ["close", "open", "bottom_reverse"]);
+        assert_eq!(parsed::<BottomReverseSignal>(&config.signals[2]), format!("{:?}", BottomReverseSignal::default()));
+        
+        assert_eq!(names(&config.targets), ["return_1d", "return_3d", "return_5d"]);
+        assert_eq!(parsed::<ReturnTarget>(&config.targets[0]), format!("{:?}", ReturnTarget::new(0.02, 0.01, 1)));
+        assert_eq!(parsed::<ReturnTarget>(&config.targets[1]), format!("{:?}", ReturnTarget::new(0.06, 0.01, 3)));
+        assert_eq!(parsed::<ReturnTarget>(&config.targets[2]), format!("{:?}", ReturnTarget::new(0.01, 0.01, 5)));
+        
+        assert_eq!(config.build_selectors().unwrap().len(), 3);
+        assert_eq!(config.build_signals().unwrap().len(), 3);
+        assert_eq!(config.build_targets().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::strategies::StockSelector;
+use crate::stock::series::BarSlice;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// 一次选股的入选股票
+type Candidates = Vec<(String, BarSlice)>;
+
+/// 缓存键: (选股策略下标, 买入日期, forecast_idx)
+type CandidateKey = (usize, i32, usize);
+
+/// 评分卡各组合共享的选股结果缓存
+///
+/// 同一份引擎数据下，选股结果只取决于选股策略、买入日期和forecast_idx，与信号和目标无关。
+/// 锁内只做查找和插入，未命中时在锁外选股；并行任务同时未命中同一键时各自选股，结果相同
+#[derive(Default)]
+pub struct CandidateCache {
+    entries: RwLock<HashMap<CandidateKey, Arc<Candidates>>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl CandidateCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// 清空缓存和命中统计，引擎数据变化后需要调用
+    pub fn clear(&self) {
+        self.entries.write().unwrap().clear();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+    
+    /// (命中次数, 未命中次数)
+    pub fn stats(&self) -> (usize, usize) {
+        (self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed))
+    }
+    
+    /// 包装选股策略，使其选股结果经由本缓存
+    pub fn selector<'a>(&'a self, selector: &'a dyn StockSelector, selector_idx: usize) -> CachedSelector<'a> {
+        CachedSelector { inner: selector, selector_idx, cache: self }
+    }
+    
+    fn get_or_select(&self, key: CandidateKey, select: impl FnOnce() -> Candidates) -> Arc<Candidates> {
+        if let Some(candidates) = self.entries.read().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return candidates.clone();
+        }
+        
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let candidates = Arc::new(select());
+        self.entries.write().unwrap()
+            .entry(key)
+            .or_insert(candidates)
+            .clone()
+    }
+}
+
+/// 经由CandidateCache选股的选股策略
+pub struct CachedSelector<'a> {
+    inner: &'a dyn StockSelector,
+    selector_idx: usize,
+    cache: &'a CandidateCache,
+}
+
+impl StockSelector for CachedSelector<'_> {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+    
+    /// 以第一只股票forecast_idx处的日期作为买入日期，按日期回测时各股票在该处的日期相同
+    fn run(&self, stock_data: &[(String, BarSlice)], forecast_idx: usize) -> Vec<(String, BarSlice)> {
+        let date = stock_data.first()
+            .and_then(|(_, bars)| bars.get(forecast_idx))
+            .map(|bar| bar.date);
+        let Some(date) = date else {
+            return self.inner.run(stock_data, forecast_idx);
+        };
+        
+        self.cache
+            .get_or_select((self.selector_idx, date, forecast_idx), || self.inner.run(stock_data, forecast_idx))
+            .as_ref()
+            .clone()
+    }
+    
+    /// 带得分的选股不经过缓存
+    fn run_with_scores(&self, stock_data: &[(String, BarSlice)], forecast_idx: usize) -> Vec<(String, BarSlice, f32)> {
+        self.inner.run_with_scores(stock_data, forecast_idx)
+    }
+}
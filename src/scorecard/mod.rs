@@ -0,0 +1,1134 @@
+pub mod cache;
+pub mod optimize;
+pub mod pareto;
+pub mod report;
+pub mod robustness;
+pub mod scorer;
+pub mod sizing;
+pub mod stats;
+
+use crate::backtest::{BacktestEngine, ParallelismConfig, ProgressCallback, ProgressEvent, DEFAULT_MAX_STALENESS_DAYS};
+use crate::strategies::StockSelector;
+use crate::signals::BuySignalGenerator;
+use crate::targets::Target;
+use crate::backtest::{BacktestResult, BootstrapInterval, DailyScore, MonteCarloSummary, WalkForwardConfig, WalkForwardResult};
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use log::{debug, info};
+use rayon::prelude::*;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+pub use cache::{CachedSelector, CandidateCache};
+pub use optimize::{GridSearch, GridSearchEntry, GridSearchReport, ParamSet};
+pub use pareto::{Direction, Metric, Objective};
+pub use report::ReportFormat;
+pub use scorer::{CombinationScorer, ExpectancyScorer, KellyScorer, SharpeScorer, WeightedScorer, WinRateScorer};
+pub use sizing::PositionSizing;
+pub use stats::ProportionTest;
+
+/// 未设置评分函数时使用的默认得分名称(逐日成功率均值)
+pub const DEFAULT_SCORER_NAME: &str = "success_rate";
+
+/// 自助法重抽样次数
+pub const BOOTSTRAP_RESAMPLES: usize = 1000;
+/// 自助法置信水平
+pub const BOOTSTRAP_CONFIDENCE: f32 = 0.9;
+/// 所有组合共用的自助法随机种子，保证结果可复现
+pub const BOOTSTRAP_SEED: u64 = 20240101;
+/// 蒙特卡洛重抽样次数，与自助法共用随机种子
+pub const MONTE_CARLO_ITERATIONS: usize = 1000;
+/// 默认的最少交易数，回测期内累计交易数低于该值的组合不参与评分
+pub const DEFAULT_MIN_TRADES: usize = 10;
+
+/// 最佳组合的排序依据
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RankingMetric {
+    /// 按得分点估计排序
+    #[default]
+    PointEstimate,
+    /// 按胜率置信区间下限排序，惩罚交易次数少的组合
+    LowerBound,
+    /// 按蒙特卡洛重抽样胜率的5%分位数排序
+    MonteCarloP5,
+}
+
+/// 评分卡中的一个策略组合及其得分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreEntry {
+    pub target_idx: usize,
+    pub selector_idx: usize,
+    pub signal_idx: usize,
+    pub score: f32,
+    /// 详细模式下的合并回测结果
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<BacktestResult>,
+    /// 评估该组合的耗时(毫秒)
+    #[serde(default)]
+    pub elapsed_ms: u64,
+    /// 详细模式下由逐笔收益自助法估计的置信区间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<BootstrapInterval>,
+    /// 详细模式下逐笔收益蒙特卡洛重抽样的分位数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monte_carlo: Option<MonteCarloSummary>,
+    /// 回测期内累计的交易数
+    #[serde(default)]
+    pub total_trades: usize,
+    /// 回测期内累计的盈利交易数，用于组合间胜率的显著性检验
+    #[serde(default)]
+    pub winning_trades: usize,
+    /// 交易数低于评分卡的min_trades，得分记为NaN且不参与最佳组合的选择
+    #[serde(default)]
+    pub insufficient_samples: bool,
+    /// 开启keep_daily_scores时每个回测日的成功率和交易数，按日期倒序
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_scores: Option<Vec<DailyScore>>,
+}
+
+/// 逐日得分的分布
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DailyScoreStats {
+    pub min: f32,
+    pub max: f32,
+    /// 总体标准差，越小越稳定
+    pub std: f32,
+}
+
+impl From<(usize, usize, usize, f32)> for ScoreEntry {
+    fn from((target_idx, selector_idx, signal_idx, score): (usize, usize, usize, f32)) -> Self {
+        Self {
+            target_idx,
+            selector_idx,
+            signal_idx,
+            score,
+            result: None,
+            elapsed_ms: 0,
+            interval: None,
+            monte_carlo: None,
+            total_trades: 0,
+            winning_trades: 0,
+            insufficient_samples: false,
+            daily_scores: None,
+        }
+    }
+}
+
+impl ScoreEntry {
+    /// 得分的置信区间半宽，取胜率区间的半宽；没有区间时为0
+    pub fn half_width(&self) -> f32 {
+        self.interval.map(|interval| interval.win_rate.half_width()).unwrap_or(0.0)
+    }
+    
+    /// 胜率置信区间下限，没有区间时退化为得分
+    pub fn lower_bound(&self) -> f32 {
+        self.interval.map(|interval| interval.win_rate.lower).unwrap_or(self.score)
+    }
+    
+    /// 逐日得分的最低、最高和标准差，未保留逐日得分或没有回测日时为None
+    pub fn daily_stats(&self) -> Option<DailyScoreStats> {
+        let scores = self.daily_scores.as_ref().filter(|scores| !scores.is_empty())?;
+        let count = scores.len() as f32;
+        let mean = scores.iter().map(|day| day.score).sum::<f32>() / count;
+        let variance = scores.iter().map(|day| (day.score - mean).powi(2)).sum::<f32>() / count;
+        
+        Some(DailyScoreStats {
+            min: scores.iter().map(|day| day.score).fold(f32::INFINITY, f32::min),
+            max: scores.iter().map(|day| day.score).fold(f32::NEG_INFINITY, f32::max),
+            std: variance.sqrt(),
+        })
+    }
+    
+    /// 按指定依据取排序值
+    pub fn ranking_value(&self, ranking: RankingMetric) -> f32 {
+        match ranking {
+            RankingMetric::PointEstimate => self.score,
+            RankingMetric::LowerBound => self.lower_bound(),
+            RankingMetric::MonteCarloP5 => self.monte_carlo.map(|summary| summary.win_rate_p5).unwrap_or(self.score),
+        }
+    }
+}
+
+/// 详细模式的评分卡结果
+#[derive(Debug, Clone)]
+pub struct ScorecardResults {
+    /// 得分矩阵: targets x selectors x signals
+    pub scores: Vec<Vec<Vec<f32>>>,
+    /// 每个组合的得分、逐日得分及详细回测结果
+    pub entries: Vec<ScoreEntry>,
+    /// 得分所用评分函数的名称
+    pub scorer: String,
+}
+
+impl ScorecardResults {
+    /// 由组合列表构建，同时填充得分矩阵
+    pub fn from_entries(entries: Vec<ScoreEntry>, targets: usize, selectors: usize, signals: usize) -> Self {
+        let mut scores = vec![vec![vec![0.0; signals]; selectors]; targets];
+        
+        for entry in &entries {
+            scores[entry.target_idx][entry.selector_idx][entry.signal_idx] = entry.score;
+        }
+        
+        Self { scores, entries, scorer: DEFAULT_SCORER_NAME.to_string() }
+    }
+    
+    /// 所有组合的总耗时(毫秒)，并行执行时大于实际经过的时间
+    pub fn total_elapsed_ms(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.elapsed_ms).sum()
+    }
+    
+    /// 按指定依据找出最佳组合，交易数不足的组合不参与
+    pub fn best(&self, ranking: RankingMetric) -> Option<&ScoreEntry> {
+        self.entries.iter()
+            .filter(|entry| !entry.insufficient_samples)
+            .max_by(|a, b| {
+                a.ranking_value(ranking)
+                    .partial_cmp(&b.ranking_value(ranking))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    }
+    
+    /// 耗时最长的n个组合
+    pub fn slowest(&self, n: usize) -> Vec<&ScoreEntry> {
+        let mut entries: Vec<&ScoreEntry> = self.entries.iter().collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.elapsed_ms));
+        entries.truncate(n);
+        entries
+    }
+}
+
+/// 推荐股票
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recommendation {
+    pub symbol: String,
+    #[serde(default)]
+    pub name: String,
+    pub buy_price: f32,
+    pub target_price: f32,
+    pub stop_loss_price: f32,
+    pub prev_close: Option<f32>,
+    #[serde(default)]
+    pub score: f32,
+    /// 按资金和单笔风险计算的建议买入股数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shares: Option<u32>,
+}
+
+impl Recommendation {
+    /// 按仓位规则填写建议股数
+    pub fn apply_sizing(&mut self, sizing: &PositionSizing) {
+        self.shares = Some(sizing.shares(self.buy_price, self.stop_loss_price));
+    }
+    
+    /// 建议买入金额，未计算仓位时为None
+    pub fn position_value(&self) -> Option<f64> {
+        self.shares.map(|shares| shares as f64 * self.buy_price as f64)
+    }
+}
+
+/// 评分卡中一个组合的滚动前推分析结果
+#[derive(Debug, Clone)]
+pub struct WalkForwardEntry {
+    pub target_idx: usize,
+    pub selector_idx: usize,
+    pub signal_idx: usize,
+    pub result: WalkForwardResult,
+}
+
+/// 滚动前推分析的评分卡结果
+#[derive(Debug, Clone)]
+pub struct WalkForwardResults {
+    /// 样本外胜率标准差矩阵: targets x selectors x signals，越小越稳定
+    pub stability: Vec<Vec<Vec<f32>>>,
+    pub entries: Vec<WalkForwardEntry>,
+}
+
+/// 策略评分卡
+pub struct Scorecard {
+    pub back_days: usize,
+    pub engine: BacktestEngine,
+    pub selectors: Vec<Box<dyn StockSelector>>,
+    pub signals: Vec<Box<dyn BuySignalGenerator>>,
+    pub targets: Vec<Box<dyn Target>>,
+    pub parallelism: ParallelismConfig,
+    /// 详细模式下的组合评分函数，None表示使用逐日成功率均值
+    pub scorer: Option<Box<dyn CombinationScorer>>,
+    /// 打印结果时是否附带蒙特卡洛胜率5%分位数
+    pub print_monte_carlo: bool,
+    /// 是否为每个组合保留逐日得分，打印结果时附带逐日得分的最低、最高和标准差
+    pub keep_daily_scores: bool,
+    /// 回测期内累计交易数低于该值的组合得分记为NaN，标记为样本不足
+    pub min_trades: usize,
+    combination_pool: Option<rayon::ThreadPool>,
+    /// 各组合共享的选股结果
+    candidates: CandidateCache,
+}
+
+impl Scorecard {
+    /// 创建新的评分卡
+    pub fn new(
+        back_days: usize,
+        selectors: Vec<Box<dyn StockSelector>>,
+        signals: Vec<Box<dyn BuySignalGenerator>>,
+        targets: Vec<Box<dyn Target>>,
+    ) -> anyhow::Result<Self> {
+        Self::with_parallelism(back_days, selectors, signals, targets, ParallelismConfig::default())
+    }
+    
+    /// 创建指定并行度的评分卡
+    pub fn with_parallelism(
+        back_days: usize,
+        selectors: Vec<Box<dyn StockSelector>>,
+        signals: Vec<Box<dyn BuySignalGenerator>>,
+        targets: Vec<Box<dyn Target>>,
+        parallelism: ParallelismConfig,
+    ) -> anyhow::Result<Self> {
+        info!("创建评分卡...");
+        let mut engine = BacktestEngine::new(true, Some(DEFAULT_MAX_STALENESS_DAYS))?;
+        engine.set_inner_threads(parallelism.inner_threads)?;
+        
+        // 加载股票数据
+        engine.load_data()?;
+        
+        Self::from_engine(back_days, engine, selectors, signals, targets, parallelism)
+    }
+    
+    /// 使用已加载数据的引擎创建评分卡，例如从自定义股票池加载
+    pub fn from_engine(
+        back_days: usize,
+        engine: BacktestEngine,
+        selectors: Vec<Box<dyn StockSelector>>,
+        signals: Vec<Box<dyn BuySignalGenerator>>,
+        targets: Vec<Box<dyn Target>>,
+        parallelism: ParallelismConfig,
+    ) -> anyhow::Result<Self> {
+        let combination_pool = ParallelismConfig::build_pool(parallelism.combination_threads)?;
+        
+        Ok(Self {
+            back_days,
+            engine,
+            selectors,
+            signals,
+            targets,
+            parallelism,
+            scorer: None,
+            print_monte_carlo: false,
+            keep_daily_scores: false,
+            min_trades: DEFAULT_MIN_TRADES,
+            combination_pool,
+            candidates: CandidateCache::new(),
+        })
+    }
+    
+    /// 设置详细模式下的组合评分函数
+    pub fn set_scorer(&mut self, scorer: Box<dyn CombinationScorer>) {
+        self.scorer = Some(scorer);
+    }
+    
+    /// 设置打印结果时是否附带蒙特卡洛胜率5%分位数，需要详细模式的结果
+    pub fn set_print_monte_carlo(&mut self, enabled: bool) {
+        self.print_monte_carlo = enabled;
+    }
+    
+    /// 设置是否为每个组合保留逐日得分，用于判断得分是否集中在少数几天
+    pub fn set_keep_daily_scores(&mut self, enabled: bool) {
+        self.keep_daily_scores = enabled;
+    }
+    
+    /// 设置最少交易数，设为0时所有组合都参与评分
+    pub fn set_min_trades(&mut self, min_trades: usize) {
+        self.min_trades = min_trades;
+    }
+    
+    /// 设置进度回调，组合开始和完成时以及引擎每完成一个回测日时调用
+    ///
+    /// run_detailed先运行一轮评分卡再运行一轮详细回测，两轮各自从0开始计数完成的组合
+    pub fn set_progress_callback(&mut self, callback: ProgressCallback) {
+        self.engine.set_progress_callback(callback);
+    }
+    
+    /// 组合开始评估
+    fn report_started(&self, (t, s, sig): (usize, usize, usize), total: usize) {
+        self.engine.report_progress(ProgressEvent::CombinationStarted {
+            selector: self.selectors[s].name(),
+            signal: self.signals[sig].name(),
+            target: self.targets[t].name(),
+            total,
+        });
+    }
+    
+    /// 组合评估完成，completed为本轮计数器
+    fn report_finished(&self, score: f32, completed: &AtomicUsize, total: usize) {
+        let completed = completed.fetch_add(1, Ordering::Relaxed) + 1;
+        self.engine.report_progress(ProgressEvent::CombinationFinished { score, completed, total });
+    }
+    
+    /// 当前评分函数名称
+    pub fn scorer_name(&self) -> String {
+        self.scorer.as_ref()
+            .map(|scorer| scorer.name())
+            .unwrap_or_else(|| DEFAULT_SCORER_NAME.to_string())
+    }
+    
+    /// 获取回测使用的股票数据，与引擎共享同一份K线
+    pub fn stock_data(&self) -> &[(String, Arc<Vec<DailyBar>>)] {
+        self.engine.stock_data()
+    }
+    
+    /// 运行评分卡，设置了评分函数时得分矩阵为评分函数对详细结果的打分
+    pub fn run(&self) -> Vec<Vec<Vec<f32>>> {
+        match self.scorer {
+            Some(_) => self.run_detailed().scores,
+            None => self.run_results().scores,
+        }
+    }
+    
+    /// 所有(目标, 策略, 信号)下标组合
+    fn combinations(&self) -> Vec<(usize, usize, usize)> {
+        (0..self.targets.len())
+            .flat_map(|t| (0..self.selectors.len())
+                .flat_map(move |s| (0..self.signals.len())
+                    .map(move |sig| (t, s, sig))))
+            .collect()
+    }
+    
+    /// 每个选股策略在每个回测日只选股一次: 清空缓存后按(选股策略, 持有天数)并行预先选股
+    ///
+    /// 持有天数决定回测日和K线截取位置，持有天数相同的目标共用选股结果
+    fn warm_candidates(&self) {
+        self.candidates.clear();
+        
+        let mut in_days: Vec<(usize, usize)> = Vec::new();
+        for (t, target) in self.targets.iter().enumerate() {
+            if !in_days.iter().any(|&(_, days)| days == target.in_days()) {
+                in_days.push((t, target.in_days()));
+            }
+        }
+        let tasks: Vec<(usize, usize)> = (0..self.selectors.len())
+            .flat_map(|s| in_days.iter().map(move |&(t, _)| (s, t)))
+            .collect();
+        
+        self.install(|| {
+            tasks.par_iter().for_each(|&(s, t)| {
+                let selector = self.candidates.selector(self.selectors[s].as_ref(), s);
+                self.engine.run_selection(&selector, self.targets[t].as_ref(), self.back_days);
+            });
+        });
+    }
+    
+    /// 运行评分卡，返回得分矩阵和每个组合的得分及耗时
+    pub fn run_results(&self) -> ScorecardResults {
+        info!("运行评分卡...");
+        self.warm_candidates();
+        
+        // 使用并行处理加速评分卡运行
+        let combinations = self.combinations();
+        let completed = AtomicUsize::new(0);
+            
+        let evaluate = || -> Vec<ScoreEntry> {
+            combinations.par_iter()
+                .map(|&(t, s, sig)| {
+                    let target = &self.targets[t];
+                    let selector = self.candidates.selector(self.selectors[s].as_ref(), s);
+                    let signal = &self.signals[sig];
+                    
+                    info!("评估组合: 策略={}, 信号={}, 目标={}",
+                        selector.name(), signal.name(), target.name());
+                    self.report_started((t, s, sig), combinations.len());
+                    
+                    // 在任务内部计时，并行执行时也只统计本组合的耗时
+                    let started = Instant::now();
+                    let daily = self.engine.run_backtest_daily(
+                        &selector,
+                        signal.as_ref(),
+                        target.as_ref(),
+                        self.back_days,
+                    );
+                    // 与run_backtest一致，逐日成功率之和除以实际回测的天数
+                    let score = daily.iter().map(|day| day.score).sum::<f32>() / daily.len().max(1) as f32;
+                    
+                    let mut entry = ScoreEntry::from((t, s, sig, score));
+                    entry.total_trades = daily.iter().map(|day| day.total_trades).sum();
+                    entry.winning_trades = daily.iter().map(|day| day.winning_trades).sum();
+                    entry.daily_scores = self.keep_daily_scores.then_some(daily);
+                    self.mark_insufficient(&mut entry);
+                    entry.elapsed_ms = started.elapsed().as_millis() as u64;
+                    self.report_finished(score, &completed, combinations.len());
+                    entry
+                })
+                .collect()
+        };
+        
+        // 在配置的线程池中并行评估各组合
+        let entries = self.install(evaluate);
+        let (hits, misses) = self.candidates.stats();
+        debug!("选股缓存: 命中 {} 次, 选股 {} 次", hits, misses);
+        
+        ScorecardResults::from_entries(entries, self.targets.len(), self.selectors.len(), self.signals.len())
+    }
+    
+    /// 运行评分卡，并为每个组合保留合并后的详细回测结果
+    ///
+    /// 设置了评分函数时，组合得分改为评分函数对详细结果的打分
+    pub fn run_detailed(&self) -> ScorecardResults {
+        let results = self.run_results();
+        
+        info!("运行详细回测以获取性能指标...");
+        let completed = AtomicUsize::new(0);
+        let total = results.entries.len();
+        let entries = self.install(|| {
+            results.entries.into_par_iter()
+                .map(|mut entry| {
+                    self.report_started((entry.target_idx, entry.selector_idx, entry.signal_idx), total);
+                    let started = Instant::now();
+                    let result = self.engine.run_detailed_backtest(
+                        &self.candidates.selector(self.selectors[entry.selector_idx].as_ref(), entry.selector_idx),
+                        self.signals[entry.signal_idx].as_ref(),
+                        self.targets[entry.target_idx].as_ref(),
+                        self.back_days,
+                    );
+                    entry.interval = result.bootstrap(BOOTSTRAP_RESAMPLES, BOOTSTRAP_CONFIDENCE, BOOTSTRAP_SEED);
+                    entry.monte_carlo = result.monte_carlo(MONTE_CARLO_ITERATIONS, BOOTSTRAP_SEED);
+                    if let Some(scorer) = &self.scorer {
+                        entry.score = scorer.score(&result);
+                    }
+                    entry.total_trades = result.total_trades;
+                    entry.winning_trades = result.winning_trades;
+                    entry.result = Some(result);
+                    self.mark_insufficient(&mut entry);
+                    entry.elapsed_ms += started.elapsed().as_millis() as u64;
+                    self.report_finished(entry.score, &completed, total);
+                    entry
+                })
+                .collect()
+        });
+        
+        let mut results = ScorecardResults::from_entries(
+            entries, self.targets.len(), self.selectors.len(), self.signals.len());
+        results.scorer = self.scorer_name();
+        results
+    }
+    
+    /// 交易数低于min_trades时把得分记为NaN并标记样本不足
+    fn mark_insufficient(&self, entry: &mut ScoreEntry) {
+        entry.insufficient_samples = entry.total_trades < self.min_trades;
+        if entry.insufficient_samples {
+            entry.score = f32::NAN;
+        }
+    }
+    
+    /// 对每个组合运行滚动前推分析，得到与得分矩阵对应的稳定性矩阵
+    pub fn run_walk_forward(&self, config: &WalkForwardConfig) -> WalkForwardResults {
+        info!("运行滚动前推分析: 样本内{}天, 样本外{}天, {}折", config.train_days, config.test_days, config.folds);
+        
+        let combinations = self.combinations();
+        self.candidates.clear();
+        let entries: Vec<WalkForwardEntry> = self.install(|| {
+            combinations.par_iter()
+                .map(|&(t, s, sig)| WalkForwardEntry {
+                    target_idx: t,
+                    selector_idx: s,
+                    signal_idx: sig,
+                    result: self.engine.run_walk_forward(
+                        &self.candidates.selector(self.selectors[s].as_ref(), s),
+                        self.signals[sig].as_ref(),
+                        self.targets[t].as_ref(),
+                        config,
+                    ),
+                })
+                .collect()
+        });
+        
+        let mut stability = vec![vec![vec![0.0; self.signals.len()]; self.selectors.len()]; self.targets.len()];
+        for entry in &entries {
+            stability[entry.target_idx][entry.selector_idx][entry.signal_idx] = entry.result.win_rate_std;
+        }
+        
+        WalkForwardResults { stability, entries }
+    }
+    
+    /// 打印每个组合的样本外胜率和稳定性，注明没有选股结果的折
+    pub fn print_walk_forward(&self, results: &WalkForwardResults) {
+        println!("滚动前推分析结果:");
+        println!("===========================================================");
+        
+        let mut last = None;
+        
+        for entry in &results.entries {
+            if last.map(|(t, _)| t) != Some(entry.target_idx) {
+                println!("\n目标: {}", self.targets[entry.target_idx].name());
+            }
+            if last != Some((entry.target_idx, entry.selector_idx)) {
+                println!("  策略: {}", self.selectors[entry.selector_idx].name());
+            }
+            last = Some((entry.target_idx, entry.selector_idx));
+            
+            let result = &entry.result;
+            println!("    信号: {}, 样本外胜率: {:.2}%, 胜率标准差: {:.2}%, 有效折数: {}/{}",
+                self.signals[entry.signal_idx].name(),
+                result.aggregate.win_rate * 100.0,
+                result.win_rate_std * 100.0,
+                result.valid_folds(),
+                result.folds.len());
+            for fold in result.empty_folds() {
+                println!("      第{}折未选出股票", fold.index + 1);
+            }
+        }
+        
+        println!("===========================================================");
+    }
+    
+    /// 在组合级线程池中执行
+    fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.combination_pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+    
+    /// 打印结果，详细模式下附带置信区间半宽，保留逐日得分时附带逐日得分的分布，交易数不足的组合标记为样本不足
+    pub fn print_results(&self, results: &ScorecardResults) {
+        println!("评分卡结果 (评分函数: {}):", results.scorer);
+        println!("===========================================================");
+        
+        let mut last = None;
+        
+        for entry in &results.entries {
+            if last.map(|(t, _)| t) != Some(entry.target_idx) {
+                println!("\n目标: {}", self.targets[entry.target_idx].name());
+            }
+            if last != Some((entry.target_idx, entry.selector_idx)) {
+                println!("  策略: {}", self.selectors[entry.selector_idx].name());
+            }
+            last = Some((entry.target_idx, entry.selector_idx));
+            
+            let signal = &self.signals[entry.signal_idx];
+            let mut percentile = match entry.monte_carlo {
+                Some(summary) if self.print_monte_carlo => format!(", 胜率P5: {:.2}%", summary.win_rate_p5 * 100.0),
+                _ => String::new(),
+            };
+            if let Some(stats) = entry.daily_stats() {
+                percentile.push_str(&format!(", 逐日得分: 最低 {:.2}% / 最高 {:.2}% / 标准差 {:.2}%",
+                    stats.min * 100.0, stats.max * 100.0, stats.std * 100.0));
+            }
+            if let Some(result) = entry.result.as_ref().filter(|result| result.has_benchmark()) {
+                percentile.push_str(&format!(", 超额收益: {:.2}%", result.avg_excess_return * 100.0));
+            }
+            if entry.insufficient_samples {
+                println!("    信号: {}, 得分: 样本不足 (交易数 {} < {}){}",
+                    signal.name(), entry.total_trades, self.min_trades, percentile);
+                continue;
+            }
+            match entry.interval {
+                Some(_) => println!("    信号: {}, 得分: {:.2}% ± {:.2}%{}",
+                    signal.name(), entry.score * 100.0, entry.half_width() * 100.0, percentile),
+                None => println!("    信号: {}, 得分: {:.2}%{}", signal.name(), entry.score * 100.0, percentile),
+            }
+        }
+        
+        println!("===========================================================");
+    }
+    
+    /// 打印总耗时和最慢的五个组合
+    pub fn print_timing(&self, results: &ScorecardResults) {
+        println!("\n耗时统计:");
+        println!("===========================================================");
+        println!("组合总耗时: {:.1}秒", results.total_elapsed_ms() as f64 / 1000.0);
+        println!("最慢的组合:");
+        
+        for entry in results.slowest(5) {
+            println!("  策略: {}, 信号: {}, 目标: {}, 耗时: {}毫秒",
+                self.selectors[entry.selector_idx].name(),
+                self.signals[entry.signal_idx].name(),
+                self.targets[entry.target_idx].name(),
+                entry.elapsed_ms);
+        }
+        
+        println!("===========================================================");
+    }
+    
+    /// 找出最佳组合，得分为NaN(交易数不足)的组合不参与；没有得分大于0的组合时返回(0, 0, 0, 0.0)
+    pub fn find_best_combination(&self, results: &[Vec<Vec<f32>>]) -> (usize, usize, usize, f32) {
+        self.find_top_combinations(results, 1)
+            .first()
+            .copied()
+            .unwrap_or((0, 0, 0, 0.0))
+    }
+    
+    /// 得分最高的k个组合(目标, 策略, 信号, 得分)，按得分从高到低
+    ///
+    /// 只包含得分大于0的组合，不足k个时全部返回；得分相同时下标(目标, 策略, 信号)较小的在前
+    pub fn find_top_combinations(&self, results: &[Vec<Vec<f32>>], k: usize) -> Vec<(usize, usize, usize, f32)> {
+        let mut ranked: Vec<(usize, usize, usize, f32)> = self.entries(results)
+            .into_iter()
+            .filter(|entry| entry.score > 0.0)
+            .map(|entry| (entry.target_idx, entry.selector_idx, entry.signal_idx, entry.score))
+            .collect();
+        // 按下标顺序展开后稳定排序，相同得分保持下标顺序
+        ranked.sort_by(|a, b| b.3.total_cmp(&a.3));
+        ranked.truncate(k);
+        ranked
+    }
+    
+    /// 展开结果矩阵为组合列表
+    pub fn entries(&self, results: &[Vec<Vec<f32>>]) -> Vec<ScoreEntry> {
+        let mut entries = Vec::new();
+        
+        for (t_idx, target_results) in results.iter().enumerate() {
+            for (s_idx, selector_results) in target_results.iter().enumerate() {
+                for (sig_idx, &score) in selector_results.iter().enumerate() {
+                    entries.push(ScoreEntry::from((t_idx, s_idx, sig_idx, score)));
+                }
+            }
+        }
+        
+        entries
+    }
+    
+    /// 使用最新数据为指定组合生成推荐股票，最多返回max只
+    pub fn recommend(&self, entry: &ScoreEntry, max: usize) -> Vec<Recommendation> {
+        let selector = &self.selectors[entry.selector_idx];
+        let signal = &self.signals[entry.signal_idx];
+        let target = &self.targets[entry.target_idx];
+        
+        info!("为策略 {} + {} 生成推荐股票...", selector.name(), signal.name());
+        
+        // 使用最新数据选股
+        let forecast_idx = 0;
+        let stock_data = self.engine.bar_slices();
+        let selected = selector.run_with_scores(&stock_data, forecast_idx);
+        
+        let scores: HashMap<String, f32> = selected.iter()
+            .map(|(symbol, _, score)| (symbol.clone(), *score))
+            .collect();
+        let candidates = selected.into_iter()
+            .map(|(symbol, data, _)| (symbol, data))
+            .collect();
+        
+        // 信号按T+1计算买入价，forecast_idx+1对应最新交易日
+        let signals = signal.generate_signals(candidates, forecast_idx + 1);
+        
+        let recommendations: Vec<Recommendation> = signals.into_iter()
+            .filter(|(_, _, buy_price)| *buy_price > 0.0)
+            .take(max)
+            .map(|(symbol, data, buy_price)| Recommendation {
+                name: self.engine.get_meta(&symbol).map(|meta| meta.name).unwrap_or_default(),
+                score: scores.get(&symbol).copied().unwrap_or(0.0),
+                target_price: buy_price * (1.0 + target.target_return()),
+                stop_loss_price: buy_price * (1.0 - target.stop_loss()),
+                prev_close: data.get(1).map(|bar| bar.close),
+                buy_price,
+                symbol,
+                shares: None,
+            })
+            .collect();
+        
+        info!("生成了 {} 只推荐股票", recommendations.len());
+        
+        recommendations
+    }
+    
+    /// 按指定依据打印最佳组合，详细模式下附带胜率、收益率、夏普比率和最大回撤等指标，
+    /// 并与排名其后的组合做胜率的显著性检验
+    pub fn print_best_combination(&self, results: &ScorecardResults, ranking: RankingMetric) {
+        let Some(best) = results.best(ranking) else {
+            return;
+        };
+        
+        println!("\n最佳组合:");
+        println!("===========================================================");
+        println!("策略: {}", self.selectors[best.selector_idx].name());
+        println!("信号: {}", self.signals[best.signal_idx].name());
+        println!("目标: {}", self.targets[best.target_idx].name());
+        println!("得分: {:.2}%", best.score * 100.0);
+        if let Some(result) = &best.result {
+            println!("交易数: {}, 胜率: {:.2}%, 平均收益率: {:.2}%, 期望收益: {:.2}%",
+                result.total_trades, result.win_rate * 100.0, result.avg_return * 100.0, result.expectancy * 100.0);
+            println!("夏普比率: {:.2}, 最大回撤: {:.2}%", result.sharpe_ratio, result.max_drawdown * 100.0);
+        }
+        if let Some(interval) = best.interval {
+            println!("胜率{:.0}%置信区间: [{:.2}%, {:.2}%]", interval.confidence * 100.0,
+                interval.win_rate.lower * 100.0, interval.win_rate.upper * 100.0);
+            println!("期望收益{:.0}%置信区间: [{:.2}%, {:.2}%]", interval.confidence * 100.0,
+                interval.expectancy.lower * 100.0, interval.expectancy.upper * 100.0);
+        }
+        if let Some(summary) = best.monte_carlo.filter(|_| self.print_monte_carlo) {
+            println!("蒙特卡洛胜率P5~P95: [{:.2}%, {:.2}%]", summary.win_rate_p5 * 100.0, summary.win_rate_p95 * 100.0);
+            println!("蒙特卡洛平均收益率P5~P95: [{:.2}%, {:.2}%]", summary.avg_return_p5 * 100.0, summary.avg_return_p95 * 100.0);
+            println!("蒙特卡洛最大回撤P95: {:.2}%", summary.max_drawdown_p95 * 100.0);
+        }
+        if let Some(result) = best.result.as_ref().filter(|result| result.has_benchmark()) {
+            println!("平均收益率: {:.2}%, 同期基准收益率: {:.2}%", result.avg_return * 100.0, result.benchmark_return * 100.0);
+            println!("平均超额收益: {:.2}%, 信息比率: {:.2}", result.avg_excess_return * 100.0, result.information_ratio);
+        }
+        self.print_runner_up_tests(results, ranking, best);
+        println!("===========================================================");
+    }
+    
+    /// 打印最佳组合与次优组合的胜率双比例z检验，p值不低于显著性水平时提示差异不显著
+    fn print_runner_up_tests(&self, results: &ScorecardResults, ranking: RankingMetric, best: &ScoreEntry) {
+        let tests = results.runner_up_tests(ranking, stats::RUNNER_UPS);
+        if tests.is_empty() {
+            return;
+        }
+        
+        println!("与次优组合的胜率比较 (双比例z检验, 最佳胜率 {:.2}% = {}/{}):",
+            tests[0].1.rate_a * 100.0, best.winning_trades, best.total_trades);
+        for (entry, test) in tests {
+            let verdict = if test.is_significant(stats::SIGNIFICANCE_LEVEL) { "差异显著" } else { "差异不显著" };
+            println!("  {} + {} / {}: 胜率 {:.2}% ({}/{}), p值 {:.3}, 最佳更优的置信度 {:.1}%, {}",
+                self.selectors[entry.selector_idx].name(),
+                self.signals[entry.signal_idx].name(),
+                self.targets[entry.target_idx].name(),
+                test.rate_b * 100.0, entry.winning_trades, entry.total_trades,
+                test.p_value, test.confidence() * 100.0, verdict);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::price::{ClosePriceSignal, OpenPriceSignal};
+    use crate::stock::series::BarSlice;
+    use crate::stock::data_provider::StockDataProvider;
+    use crate::targets::return_target::ReturnTarget;
+    use egostrategy_datahub::models::stock::StockData;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 选中全部股票并给出固定得分的选股器
+    struct ScoredSelector;
+
+    impl StockSelector for ScoredSelector {
+        fn name(&self) -> String {
+            "全部".to_string()
+        }
+
+        fn run(&self, stock_data: &[(String, BarSlice)], _forecast_idx: usize) -> Vec<(String, BarSlice)> {
+            stock_data.to_vec()
+        }
+
+        fn run_with_scores(&self, stock_data: &[(String, BarSlice)], _forecast_idx: usize) -> Vec<(String, BarSlice, f32)> {
+            stock_data.iter()
+                .map(|(symbol, data)| (symbol.clone(), data.clone(), 1.5))
+                .collect()
+        }
+    }
+
+    /// 统计选股次数的选股器
+    struct CountingSelector(Arc<AtomicUsize>);
+
+    impl StockSelector for CountingSelector {
+        fn name(&self) -> String {
+            "计数".to_string()
+        }
+
+        fn run(&self, stock_data: &[(String, BarSlice)], _forecast_idx: usize) -> Vec<(String, BarSlice)> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            stock_data.to_vec()
+        }
+    }
+
+    /// 每次生成信号都会休眠的慢速信号
+    struct SlowSignal;
+
+    impl BuySignalGenerator for SlowSignal {
+        fn name(&self) -> String {
+            "慢速".to_string()
+        }
+
+        fn generate_signals(&self, candidates: Vec<(String, BarSlice)>, forecast_idx: usize) -> Vec<(String, BarSlice, f32)> {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            ClosePriceSignal.generate_signals(candidates, forecast_idx)
+        }
+    }
+
+    /// 最新收盘价为latest_close、前一日收盘价为prev_close的K线，最新在前
+    fn stock(symbol: &str, name: &str, latest_close: f32, prev_close: f32) -> StockData {
+        let daily = (0..130)
+            .map(|i| {
+                let close = match i {
+                    0 => latest_close,
+                    1 => prev_close,
+                    _ => 9.0,
+                };
+                DailyBar {
+                    date: 20230630 - i,
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: 10000,
+                    amount: 100000,
+                }
+            })
+            .collect();
+        
+        StockData {
+            exchange: symbol[7..].to_string(),
+            symbol: symbol.to_string(),
+            name: name.to_string(),
+            daily,
+        }
+    }
+
+    /// 只选出指定股票的选股器
+    struct PickSelector(Vec<&'static str>);
+
+    impl StockSelector for PickSelector {
+        fn name(&self) -> String {
+            self.0.join(",")
+        }
+
+        fn run(&self, stock_data: &[(String, BarSlice)], _forecast_idx: usize) -> Vec<(String, BarSlice)> {
+            stock_data.iter()
+                .filter(|(symbol, _)| self.0.contains(&symbol.as_str()))
+                .cloned()
+                .collect()
+        }
+    }
+
+    /// 每天按固定比例涨跌的股票，最新在前
+    fn trending_stock(symbol: &str, daily_change: f32) -> StockData {
+        let daily = (0..130)
+            .map(|i| {
+                let close = 20.0 / (1.0 + daily_change).powi(i);
+                DailyBar {
+                    date: 20230630 - i,
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: 10000,
+                    amount: 100000,
+                }
+            })
+            .collect();
+        
+        StockData {
+            exchange: symbol[7..].to_string(),
+            symbol: symbol.to_string(),
+            name: String::new(),
+            daily,
+        }
+    }
+
+    fn scorecard(stocks: Vec<StockData>) -> Scorecard {
+        let provider = Arc::new(StockDataProvider::with_stocks(stocks).unwrap());
+        let mut engine = BacktestEngine::with_source(provider, true, None);
+        engine.load_data().unwrap();
+        
+        Scorecard {
+            back_days: 10,
+            engine,
+            selectors: vec![Box::new(ScoredSelector)],
+            signals: vec![Box::new(ClosePriceSignal)],
+            targets: vec![Box::new(ReturnTarget::new(0.05, 0.03, 5))],
+            parallelism: ParallelismConfig::default(),
+            scorer: None,
+            print_monte_carlo: false,
+            keep_daily_scores: false,
+            min_trades: DEFAULT_MIN_TRADES,
+            combination_pool: None,
+            candidates: CandidateCache::new(),
+        }
+    }
+
+    fn entry() -> ScoreEntry {
+        ScoreEntry::from((0, 0, 0, 0.6))
+    }
+
+    #[test]
+    fn recommend_derives_prices_from_target_parameters() {
+        let scorecard = scorecard(vec![stock("000001.SZ", "平安银行", 10.0, 9.8)]);
+        
+        let recommendations = scorecard.recommend(&entry(), 5);
+        
+        assert_eq!(recommendations.len(), 1);
+        let rec = &recommendations[0];
+        assert_eq!(rec.symbol, "000001.SZ");
+        assert_eq!(rec.name, "平安银行");
+        assert_eq!(rec.score, 1.5);
+        // 收盘价信号按最新收盘价买入
+        assert!((rec.buy_price - 10.0).abs() < 1e-5);
+        assert!((rec.target_price - 10.0 * 1.05).abs() < 1e-5);
+        assert!((rec.stop_loss_price - 10.0 * 0.97).abs() < 1e-5);
+        assert_eq!(rec.prev_close, Some(9.8));
+    }
+
+    #[test]
+    fn recommend_caps_at_max() {
+        let scorecard = scorecard(vec![
+            stock("000001.SZ", "平安银行", 10.0, 9.8),
+            stock("000002.SZ", "万科A", 8.0, 8.1),
+            stock("600000.SH", "浦发银行", 7.0, 7.2),
+        ]);
+        
+        assert_eq!(scorecard.recommend(&entry(), 2).len(), 2);
+        assert_eq!(scorecard.recommend(&entry(), 5).len(), 3);
+    }
+
+    #[test]
+    fn recommend_with_sizing_on_mock_data() {
+        let scorecard = scorecard(vec![
+            stock("000001.SZ", "平安银行", 10.0, 9.8),
+            stock("000002.SZ", "万科A", 8.0, 8.1),
+        ]);
+        let sizing = PositionSizing::new(100_000.0, 0.01);
+        
+        let mut recommendations = scorecard.recommend(&entry(), 5);
+        recommendations.iter_mut().for_each(|rec| rec.apply_sizing(&sizing));
+        
+        // 止损3%: 每股风险0.3元，最多亏1000元，3333股按整手取3300股
+        let pingan = recommendations.iter().find(|rec| rec.symbol == "000001.SZ").unwrap();
+        assert_eq!(pingan.shares, Some(3300));
+        assert!((pingan.position_value().unwrap() - 33000.0).abs() < 1e-3);
+        // 每股风险0.24元: 4166股 -> 4100股
+        let vanke = recommendations.iter().find(|rec| rec.symbol == "000002.SZ").unwrap();
+        assert_eq!((vanke.name.as_str(), vanke.shares), ("万科A", Some(4100)));
+    }
+
+    #[test]
+    fn slow_signal_dominates_slowest_list() {
+        let mut scorecard = scorecard(vec![stock("000001.SZ", "平安银行", 10.0, 9.8)]);
+        scorecard.selectors = vec![Box::new(ScoredSelector), Box::new(PickSelector(vec!["000001.SZ"]))];
+        scorecard.signals = vec![Box::new(ClosePriceSignal), Box::new(SlowSignal)];
+        
+        let results = scorecard.run_results();
+        
+        assert_eq!(results.entries.len(), 4);
+        let slowest = results.slowest(2);
+        assert!(slowest.iter().all(|entry| entry.signal_idx == 1));
+        // 每个组合回测10天，每天至少休眠5毫秒
+        assert!(slowest.iter().all(|entry| entry.elapsed_ms >= 50));
+        assert!(results.total_elapsed_ms() >= 100);
+    }
+
+    #[test]
+    fn selector_runs_once_per_day_across_signals() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut scorecard = scorecard(vec![stock("000001.SZ", "平安银行", 10.0, 9.8)]);
+        scorecard.selectors = vec![Box::new(CountingSelector(calls.clone()))];
+        scorecard.signals = vec![Box::new(ClosePriceSignal), Box::new(OpenPriceSignal)];
+        
+        scorecard.run_results();
+        
+        // 两个信号共用同一选股结果，10个回测日只选股10次
+        assert_eq!(calls.load(Ordering::Relaxed), 10);
+    }
+
+    /// 指定胜率和交易数的详细结果组合
+    fn detailed_entry(signal_idx: usize, trades: usize, wins: usize) -> ScoreEntry {
+        let result = BacktestResult {
+            trade_returns: (0..trades)
+                .map(|i| if i < wins { 0.05 } else { -0.03 })
+                .collect(),
+            ..BacktestResult::default()
+        };
+        
+        let mut entry = ScoreEntry::from((0, 0, signal_idx, wins as f32 / trades as f32));
+        entry.interval = result.bootstrap(BOOTSTRAP_RESAMPLES, BOOTSTRAP_CONFIDENCE, BOOTSTRAP_SEED);
+        entry.result = Some(result);
+        entry
+    }
+
+    #[test]
+    fn lower_bound_ranking_penalizes_small_sample() {
+        let results = ScorecardResults::from_entries(vec![
+            detailed_entry(0, 3, 3),
+            detailed_entry(1, 200, 140),
+        ], 1, 1, 2);
+        
+        assert_eq!(results.best(RankingMetric::PointEstimate).unwrap().signal_idx, 0);
+        assert_eq!(results.best(RankingMetric::LowerBound).unwrap().signal_idx, 1);
+    }
+
+    #[test]
+    fn swapping_scorer_changes_ranking_and_export_metadata() {
+        let mut scorecard = scorecard(vec![
+            trending_stock("000001.SZ", 0.01),
+            trending_stock("000002.SZ", 0.08),
+            trending_stock("000003.SZ", -0.01),
+        ]);
+        // 策略0每笔小幅盈利；策略1一半交易亏损，但盈利交易收益大
+        scorecard.selectors = vec![
+            Box::new(PickSelector(vec!["000001.SZ"])),
+            Box::new(PickSelector(vec!["000002.SZ", "000003.SZ"])),
+        ];
+        scorecard.targets = vec![Box::new(ReturnTarget::new(0.02, 0.5, 3))];
+        
+        scorecard.set_scorer(Box::new(WinRateScorer));
+        let by_win_rate = scorecard.run_detailed();
+        scorecard.set_scorer(Box::new(ExpectancyScorer));
+        let by_expectancy = scorecard.run_detailed();
+        
+        assert_eq!(by_win_rate.best(RankingMetric::PointEstimate).unwrap().selector_idx, 0);
+        assert_eq!(by_expectancy.best(RankingMetric::PointEstimate).unwrap().selector_idx, 1);
+        
+        let exported = crate::export::ExportData::from_results(&scorecard, &by_win_rate, 0, crate::export::BEST_COMBINATIONS);
+        assert_eq!(exported.scorer, "win_rate");
+        assert_eq!(exported.strategies[exported.best_combinations[0]].strategy_name, "000001.SZ");
+        
+        let exported = crate::export::ExportData::from_results(&scorecard, &by_expectancy, 0, crate::export::BEST_COMBINATIONS);
+        assert_eq!(exported.scorer, "expectancy");
+        assert_eq!(exported.strategies[exported.best_combinations[0]].strategy_name, "000002.SZ,000003.SZ");
+    }
+
+    #[test]
+    fn default_scorer_name_is_success_rate() {
+        let scorecard = scorecard(vec![trending_stock("000001.SZ", 0.01)]);
+        
+        assert_eq!(scorecard.run_detailed().scorer, DEFAULT_SCORER_NAME);
+    }
+
+    #[test]
+    fn top_combinations_rank_by_score_then_index() {
+        let scorecard = scorecard(vec![trending_stock("000001.SZ", 0.01)]);
+        // [目标][策略][信号]
+        let results = vec![
+            vec![vec![0.4, f32::NAN], vec![0.7, 0.0]],
+            vec![vec![0.4, 0.9], vec![-0.1, 0.4]],
+        ];
+        
+        let top = scorecard.find_top_combinations(&results, 4);
+        assert_eq!(top, vec![(1, 0, 1, 0.9), (0, 1, 0, 0.7), (0, 0, 0, 0.4), (1, 0, 0, 0.4)]);
+        
+        // 得分大于0的组合不足k个时全部返回
+        assert_eq!(scorecard.find_top_combinations(&results, 10).len(), 5);
+        assert_eq!(scorecard.find_best_combination(&results), (1, 0, 1, 0.9));
+    }
+
+    #[test]
+    fn best_combination_defaults_without_positive_scores() {
+        let scorecard = scorecard(vec![trending_stock("000001.SZ", 0.01)]);
+        let results = vec![vec![vec![0.0, f32::NAN, -0.2]]];
+        
+        assert!(scorecard.find_top_combinations(&results, 2).is_empty());
+        assert_eq!(scorecard.find_best_combination(&results), (0, 0, 0, 0.0));
+    }
+
+    #[test]
+    fn export_lists_top_k_combinations() {
+        let mut scorecard = scorecard(vec![
+            trending_stock("000001.SZ", 0.01),
+            trending_stock("000002.SZ", 0.08),
+            trending_stock("000003.SZ", -0.01),
+        ]);
+        scorecard.selectors = vec![
+            Box::new(PickSelector(vec!["000001.SZ"])),
+            Box::new(PickSelector(vec!["000002.SZ", "000003.SZ"])),
+        ];
+        scorecard.targets = vec![Box::new(ReturnTarget::new(0.02, 0.5, 3))];
+        let results = scorecard.run_detailed();
+        
+        let exported = crate::export::ExportData::from_results(&scorecard, &results, 0, 1);
+        assert_eq!(exported.best_combinations.len(), 1);
+        assert_eq!(exported.strategies[exported.best_combinations[0]].strategy_name, "000001.SZ");
+        
+        let exported = crate::export::ExportData::from_results(&scorecard, &results, 0, 5);
+        assert_eq!(exported.best_combinations.len(), exported.strategies.len());
+        assert_eq!(exported.best_combinations, exported.top_combinations(5));
+    }
+}
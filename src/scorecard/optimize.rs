@@ -0,0 +1,253 @@
+use crate::backtest::{BacktestResult, DailyScore};
+use crate::registry::Params;
+use crate::scorecard::Scorecard;
+use crate::signals::BuySignalGenerator;
+use crate::strategies::StockSelector;
+use crate::targets::Target;
+use anyhow::{bail, Result};
+use log::info;
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+/// 默认的参数组合数上限
+pub const DEFAULT_MAX_COMBINATIONS: usize = 500;
+
+/// 选股策略的一组参数，与组件配置中的参数表相同
+pub type ParamSet = Params;
+
+/// 由一组参数创建选股策略，参数无效时返回错误
+pub type SelectorFactory = Box<dyn Fn(&ParamSet) -> Result<Box<dyn StockSelector>> + Send + Sync>;
+
+/// 选股策略参数的网格搜索，信号和目标固定
+pub struct GridSearch {
+    pub selector_factory: SelectorFactory,
+    pub grid: Vec<ParamSet>,
+    /// 最近holdout_days个回测日作为留出期，不参与排序，只用于发现过拟合；为0时不留出
+    pub holdout_days: usize,
+    /// 参数组合数上限，网格超过该数量时拒绝运行
+    pub max_combinations: usize,
+}
+
+impl GridSearch {
+    pub fn new(selector_factory: SelectorFactory, grid: Vec<ParamSet>) -> Self {
+        Self {
+            selector_factory,
+            grid,
+            holdout_days: 0,
+            max_combinations: DEFAULT_MAX_COMBINATIONS,
+        }
+    }
+    
+    /// 设置留出期天数
+    pub fn with_holdout_days(mut self, holdout_days: usize) -> Self {
+        self.holdout_days = holdout_days;
+        self
+    }
+    
+    /// 设置参数组合数上限
+    pub fn with_max_combinations(mut self, max_combinations: usize) -> Self {
+        self.max_combinations = max_combinations;
+        self
+    }
+    
+    /// 以base为基础，各参数取值的笛卡尔积，axes中靠后的参数变化最快
+    pub fn cartesian(base: &ParamSet, axes: &[(String, Vec<toml::Value>)]) -> Vec<ParamSet> {
+        let mut grid = vec![base.clone()];
+        
+        for (name, values) in axes {
+            grid = grid.into_iter()
+                .flat_map(|params| {
+                    values.iter().map(move |value| {
+                        let mut params = params.clone();
+                        params.insert(name.clone(), value.clone());
+                        params
+                    })
+                })
+                .collect();
+        }
+        
+        grid
+    }
+}
+
+/// 一组参数的网格搜索结果
+#[derive(Debug, Clone)]
+pub struct GridSearchEntry {
+    pub params: ParamSet,
+    /// 样本内(留出期之前的回测日)得分，交易数不足时为NaN
+    pub in_sample: f32,
+    /// 留出期得分，未留出时为None
+    pub holdout: Option<f32>,
+    pub in_sample_trades: usize,
+    pub holdout_trades: usize,
+    /// 样本内交易数低于评分卡的min_trades
+    pub insufficient_samples: bool,
+    /// 样本内得分排在前四分之一，但留出期得分既低于样本内得分又低于所有参数组的中位数
+    pub overfit_suspect: bool,
+}
+
+/// 网格搜索报告，entries按样本内得分从高到低，交易数不足的排在最后
+#[derive(Debug, Clone)]
+pub struct GridSearchReport {
+    /// 得分所用评分函数的名称
+    pub scorer: String,
+    pub holdout_days: usize,
+    pub entries: Vec<GridSearchEntry>,
+}
+
+impl GridSearchReport {
+    /// 样本内得分最高的参数组
+    pub fn best(&self) -> Option<&GridSearchEntry> {
+        self.entries.first().filter(|entry| !entry.insufficient_samples)
+    }
+    
+    /// 疑似过拟合的参数组
+    pub fn overfit_suspects(&self) -> Vec<&GridSearchEntry> {
+        self.entries.iter().filter(|entry| entry.overfit_suspect).collect()
+    }
+    
+    /// 格式化为人类可读的报告
+    pub fn format_report(&self) -> String {
+        let mut report = String::new();
+        
+        report.push_str(&format!("参数组数: {}, 评分函数: {}, 留出期: {}天\n",
+            self.entries.len(), self.scorer, self.holdout_days));
+        
+        for entry in &self.entries {
+            let desc: Vec<String> = entry.params.iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            let score = if entry.insufficient_samples {
+                format!("样本不足 (交易数 {})", entry.in_sample_trades)
+            } else {
+                format!("{:.2}% (交易数 {})", entry.in_sample * 100.0, entry.in_sample_trades)
+            };
+            let holdout = match entry.holdout {
+                Some(holdout) => format!(", 留出期: {:.2}% (交易数 {})", holdout * 100.0, entry.holdout_trades),
+                None => String::new(),
+            };
+            let flag = if entry.overfit_suspect { ", 疑似过拟合" } else { "" };
+            report.push_str(&format!("  {}: 样本内 {}{}{}\n", desc.join(", "), score, holdout, flag));
+        }
+        
+        report
+    }
+}
+
+impl Scorecard {
+    /// 对选股策略参数做网格搜索，信号和目标取评分卡中的第signal_idx、target_idx个
+    ///
+    /// 各参数组在组合级线程池中并行回测。最近holdout_days个回测日为留出期，其余为样本内；
+    /// 设置了评分函数时得分为评分函数对合并后详细结果的打分，否则为逐日成功率均值。
+    /// 网格超过max_combinations或参数无效时返回错误
+    pub fn run_grid_search(&self, search: &GridSearch, signal_idx: usize, target_idx: usize) -> Result<GridSearchReport> {
+        if search.grid.len() > search.max_combinations {
+            bail!("参数组合数{}超过上限{}，请缩小网格或提高上限", search.grid.len(), search.max_combinations);
+        }
+        if search.holdout_days >= self.back_days {
+            bail!("留出期{}天应小于回测天数{}", search.holdout_days, self.back_days);
+        }
+        
+        // 先创建全部选股策略，参数无效时不运行回测
+        let selectors = search.grid.iter()
+            .map(|params| (search.selector_factory)(params))
+            .collect::<Result<Vec<_>>>()?;
+        info!("参数网格搜索: {} 组参数, 留出期{}天", selectors.len(), search.holdout_days);
+        
+        let signal = self.signals[signal_idx].as_ref();
+        let target = self.targets[target_idx].as_ref();
+        let scores: Vec<(f32, f32, usize, usize)> = self.install(|| {
+            selectors.par_iter()
+                .map(|selector| self.split_scores(selector.as_ref(), signal, target, search.holdout_days))
+                .collect()
+        });
+        
+        let mut entries: Vec<GridSearchEntry> = search.grid.iter()
+            .zip(scores)
+            .map(|(params, (in_sample, holdout, in_sample_trades, holdout_trades))| {
+                let insufficient_samples = in_sample_trades < self.min_trades;
+                GridSearchEntry {
+                    params: params.clone(),
+                    in_sample: if insufficient_samples { f32::NAN } else { in_sample },
+                    holdout: (search.holdout_days > 0).then_some(holdout),
+                    in_sample_trades,
+                    holdout_trades,
+                    insufficient_samples,
+                    overfit_suspect: false,
+                }
+            })
+            .collect();
+        
+        // 稳定排序，得分相同时保持网格顺序
+        entries.sort_by(|a, b| {
+            a.insufficient_samples.cmp(&b.insufficient_samples)
+                .then(b.in_sample.total_cmp(&a.in_sample))
+        });
+        flag_overfit(&mut entries);
+        
+        Ok(GridSearchReport { scorer: self.scorer_name(), holdout_days: search.holdout_days, entries })
+    }
+    
+    /// 回测一组参数，返回(样本内得分, 留出期得分, 样本内交易数, 留出期交易数)
+    ///
+    /// 回测日按日期倒序，前holdout_days个为留出期
+    fn split_scores(
+        &self,
+        selector: &dyn StockSelector,
+        signal: &dyn BuySignalGenerator,
+        target: &dyn Target,
+        holdout_days: usize,
+    ) -> (f32, f32, usize, usize) {
+        match &self.scorer {
+            Some(scorer) => {
+                let mut days = self.engine.run_backtest_detailed(selector, signal, target, self.back_days);
+                let in_sample = days.split_off(holdout_days.min(days.len()));
+                let merge = |days: Vec<(i32, BacktestResult)>| {
+                    BacktestResult::merge(days.into_iter().map(|(_, result)| result).collect())
+                };
+                let (in_sample, holdout) = (merge(in_sample), merge(days));
+                (scorer.score(&in_sample), scorer.score(&holdout), in_sample.total_trades, holdout.total_trades)
+            }
+            None => {
+                // 没有可用K线的回测日不在结果中，按日期划分留出期
+                let holdout_dates: HashSet<i32> = self.engine.trading_dates(target, self.back_days)
+                    .into_iter()
+                    .take(holdout_days)
+                    .collect();
+                let (holdout, in_sample): (Vec<DailyScore>, Vec<DailyScore>) = self.engine
+                    .run_backtest_daily(selector, signal, target, self.back_days)
+                    .into_iter()
+                    .partition(|day| holdout_dates.contains(&day.trade_date));
+                // 与run_backtest一致，成功率之和除以实际回测的天数
+                let mean = |days: &[DailyScore]| {
+                    days.iter().map(|day| day.score).sum::<f32>() / days.len().max(1) as f32
+                };
+                let trades = |days: &[DailyScore]| days.iter().map(|day| day.total_trades).sum();
+                (mean(&in_sample), mean(&holdout), trades(&in_sample), trades(&holdout))
+            }
+        }
+    }
+}
+
+/// 标记疑似过拟合: 样本内得分排在前四分之一，但留出期得分既低于样本内得分又低于中位数
+///
+/// entries已按样本内得分排序，只比较交易数足够的参数组
+fn flag_overfit(entries: &mut [GridSearchEntry]) {
+    let valid = entries.iter().filter(|entry| !entry.insufficient_samples).count();
+    let mut holdouts: Vec<f32> = entries[..valid].iter().filter_map(|entry| entry.holdout).collect();
+    if holdouts.is_empty() {
+        return;
+    }
+    
+    holdouts.sort_by(f32::total_cmp);
+    let median = if holdouts.len().is_multiple_of(2) {
+        (holdouts[holdouts.len() / 2 - 1] + holdouts[holdouts.len() / 2]) / 2.0
+    } else {
+        holdouts[holdouts.len() / 2]
+    };
+    
+    let top = valid.div_ceil(4);
+    for entry in &mut entries[..top] {
+        entry.overfit_suspect = entry.holdout.is_some_and(|holdout| holdout < entry.in_sample && holdout < median);
+    }
+}
@@ -0,0 +1,228 @@
+use crate::scorecard::{Scorecard, ScoreEntry, ScorecardResults};
+
+/// 组合评价指标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// 评分卡得分(逐日成功率均值)
+    Score,
+    WinRate,
+    AvgReturn,
+    MaxDrawdown,
+    SharpeRatio,
+    ProfitFactor,
+    StopLossRate,
+}
+
+impl Metric {
+    /// 获取指标名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::Score => "得分",
+            Metric::WinRate => "胜率",
+            Metric::AvgReturn => "平均收益率",
+            Metric::MaxDrawdown => "最大回撤",
+            Metric::SharpeRatio => "夏普比率",
+            Metric::ProfitFactor => "盈亏比",
+            Metric::StopLossRate => "止损率",
+        }
+    }
+    
+    /// 从组合中读取指标值，除得分外需要详细回测结果
+    pub fn value(&self, entry: &ScoreEntry) -> Option<f32> {
+        let value = match self {
+            Metric::Score => entry.score,
+            Metric::WinRate => entry.result.as_ref()?.win_rate,
+            Metric::AvgReturn => entry.result.as_ref()?.avg_return,
+            Metric::MaxDrawdown => entry.result.as_ref()?.max_drawdown,
+            Metric::SharpeRatio => entry.result.as_ref()?.sharpe_ratio,
+            Metric::ProfitFactor => entry.result.as_ref()?.profit_factor,
+            Metric::StopLossRate => entry.result.as_ref()?.stop_loss_rate,
+        };
+        
+        if value.is_nan() {
+            None
+        } else {
+            Some(value)
+        }
+    }
+}
+
+/// 优化方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Maximize,
+    Minimize,
+}
+
+/// 优化目标: 指标及其方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Objective {
+    pub metric: Metric,
+    pub direction: Direction,
+}
+
+impl Objective {
+    /// 越大越好的目标
+    pub fn maximize(metric: Metric) -> Self {
+        Self { metric, direction: Direction::Maximize }
+    }
+    
+    /// 越小越好的目标
+    pub fn minimize(metric: Metric) -> Self {
+        Self { metric, direction: Direction::Minimize }
+    }
+    
+    /// 读取指标值并统一为越大越好
+    fn oriented_value(&self, entry: &ScoreEntry) -> Option<f32> {
+        let value = self.metric.value(entry)?;
+        Some(match self.direction {
+            Direction::Maximize => value,
+            Direction::Minimize => -value,
+        })
+    }
+}
+
+/// a是否支配b: 所有目标都不差于b，且至少一个目标严格优于b
+fn dominates(a: &[f32], b: &[f32]) -> bool {
+    let mut strictly_better = false;
+    
+    for (&x, &y) in a.iter().zip(b.iter()) {
+        if x < y {
+            return false;
+        }
+        if x > y {
+            strictly_better = true;
+        }
+    }
+    
+    strictly_better
+}
+
+impl ScorecardResults {
+    /// 计算帕累托前沿，返回不被其他组合支配的组合
+    ///
+    /// 缺少任一目标指标的组合(例如非详细模式)不参与比较
+    pub fn pareto_front(&self, objectives: &[Objective]) -> Vec<&ScoreEntry> {
+        let candidates: Vec<(&ScoreEntry, Vec<f32>)> = self.entries.iter()
+            .filter_map(|entry| {
+                let values: Option<Vec<f32>> = objectives.iter()
+                    .map(|objective| objective.oriented_value(entry))
+                    .collect();
+                values.map(|values| (entry, values))
+            })
+            .collect();
+        
+        candidates.iter()
+            .filter(|(_, values)| {
+                !candidates.iter().any(|(_, other)| dominates(other, values))
+            })
+            .map(|(entry, _)| *entry)
+            .collect()
+    }
+}
+
+impl Scorecard {
+    /// 打印帕累托前沿，按指定指标从高到低排序
+    pub fn print_pareto(&self, results: &ScorecardResults, objectives: &[Objective], sort_by: Metric) {
+        let mut front = results.pareto_front(objectives);
+        front.sort_by(|a, b| {
+            let va = sort_by.value(a).unwrap_or(f32::MIN);
+            let vb = sort_by.value(b).unwrap_or(f32::MIN);
+            vb.partial_cmp(&va).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        
+        let objective_names: Vec<String> = objectives.iter()
+            .map(|objective| match objective.direction {
+                Direction::Maximize => format!("{}↑", objective.metric.name()),
+                Direction::Minimize => format!("{}↓", objective.metric.name()),
+            })
+            .collect();
+        
+        println!("\n帕累托前沿 ({}):", objective_names.join(", "));
+        println!("===========================================================");
+        
+        for entry in front {
+            println!("策略: {}, 信号: {}, 目标: {}",
+                self.selectors[entry.selector_idx].name(),
+                self.signals[entry.signal_idx].name(),
+                self.targets[entry.target_idx].name());
+            
+            let values: Vec<String> = objectives.iter()
+                .map(|objective| {
+                    let value = objective.metric.value(entry).unwrap_or(0.0);
+                    format!("{}: {:.4}", objective.metric.name(), value)
+                })
+                .collect();
+            println!("  {}", values.join(", "));
+        }
+        
+        println!("===========================================================");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::BacktestResult;
+
+    fn entry(signal_idx: usize, win_rate: f32, avg_return: f32, max_drawdown: f32) -> ScoreEntry {
+        let mut entry = ScoreEntry::from((0, 0, signal_idx, win_rate));
+        entry.result = Some(BacktestResult {
+            win_rate,
+            avg_return,
+            max_drawdown,
+            ..BacktestResult::default()
+        });
+        entry
+    }
+
+    fn objectives() -> Vec<Objective> {
+        vec![
+            Objective::maximize(Metric::WinRate),
+            Objective::maximize(Metric::AvgReturn),
+            Objective::minimize(Metric::MaxDrawdown),
+        ]
+    }
+
+    #[test]
+    fn front_keeps_only_non_dominated_entries() {
+        let results = ScorecardResults::from_entries(vec![
+            entry(0, 0.6, 0.02, 0.10),
+            entry(1, 0.5, 0.03, 0.08),
+            // 被组合0支配
+            entry(2, 0.5, 0.01, 0.12),
+            entry(3, 0.4, 0.02, 0.15),
+        ], 1, 1, 4);
+        
+        let front: Vec<usize> = results.pareto_front(&objectives())
+            .iter()
+            .map(|entry| entry.signal_idx)
+            .collect();
+        
+        assert_eq!(front, vec![0, 1]);
+    }
+
+    #[test]
+    fn entries_without_detailed_result_are_skipped() {
+        let results = ScorecardResults::from_entries(vec![
+            entry(0, 0.5, 0.01, 0.12),
+            ScoreEntry::from((0, 0, 1, 0.9)),
+        ], 1, 1, 2);
+        
+        let front = results.pareto_front(&objectives());
+        
+        assert_eq!(front.len(), 1);
+        assert_eq!(front[0].signal_idx, 0);
+    }
+
+    #[test]
+    fn minimize_prefers_smaller_values() {
+        let results = ScorecardResults::from_entries(
+            vec![entry(0, 0.5, 0.02, 0.10), entry(1, 0.5, 0.02, 0.05)], 1, 1, 2);
+        
+        let front = results.pareto_front(&[Objective::minimize(Metric::MaxDrawdown)]);
+        
+        assert_eq!(front.len(), 1);
+        assert_eq!(front[0].signal_idx, 1);
+    }
+}
@@ -0,0 +1,336 @@
+use crate::backtest::report::{escape, STYLE};
+use crate::scorecard::stats::{RUNNER_UPS, SIGNIFICANCE_LEVEL};
+use crate::scorecard::{RankingMetric, ScoreEntry, Scorecard, ScorecardResults};
+use anyhow::Result;
+use chrono::Local;
+use log::info;
+use std::fs;
+use std::path::Path;
+
+/// 在回测报告的样式之外补充的预格式化文本样式
+const PRE_STYLE: &str = "pre{background:#f6f8fa;border:1px solid #ddd;padding:8px 12px;font-size:13px}";
+
+/// 评分卡报告的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    /// 内嵌CSS的单文件HTML
+    Html,
+}
+
+impl ReportFormat {
+    /// 按扩展名判断: .html或.htm为HTML，其余为Markdown
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm") => ReportFormat::Html,
+            _ => ReportFormat::Markdown,
+        }
+    }
+}
+
+/// 报告的内容块，Markdown和HTML共用
+enum Block {
+    Heading(usize, String),
+    Paragraph(String),
+    /// 表头和各行
+    Table(Vec<String>, Vec<Vec<String>>),
+    Preformatted(String),
+}
+
+/// 生成评分卡报告并写入文件，按扩展名选择Markdown或HTML，自动创建上级目录
+pub fn write_report(
+    path: &Path,
+    scorecard: &Scorecard,
+    results: &ScorecardResults,
+    ranking: RankingMetric,
+    top_k: usize,
+    max_recommendations: usize,
+) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    
+    let format = ReportFormat::from_path(path);
+    fs::write(path, render(scorecard, results, ranking, top_k, max_recommendations, format))?;
+    
+    info!("评分卡报告已写入 {}", path.display());
+    Ok(())
+}
+
+/// 生成评分卡报告: 得分矩阵、最佳组合、各组合性能、推荐股票和各组合的详细回测结果
+///
+/// 最佳组合按ranking选出，推荐股票取find_top_combinations的前top_k个组合，每个组合最多max_recommendations只
+pub fn render(
+    scorecard: &Scorecard,
+    results: &ScorecardResults,
+    ranking: RankingMetric,
+    top_k: usize,
+    max_recommendations: usize,
+    format: ReportFormat,
+) -> String {
+    let title = "评分卡报告";
+    let mut blocks = vec![
+        Block::Paragraph(format!("更新日期: {} | 评分函数: {} | 回测天数: {} | 组合数: {}",
+            Local::now().format("%Y-%m-%d"), results.scorer, scorecard.back_days, results.entries.len())),
+    ];
+    
+    blocks.extend(matrix_blocks(scorecard, results));
+    blocks.extend(best_blocks(scorecard, results, ranking));
+    blocks.extend(performance_blocks(scorecard, results));
+    blocks.extend(recommendation_blocks(scorecard, results, top_k, max_recommendations));
+    blocks.extend(detail_blocks(scorecard, results));
+    
+    match format {
+        ReportFormat::Markdown => render_markdown(title, &blocks),
+        ReportFormat::Html => render_html(title, &blocks),
+    }
+}
+
+/// 每个目标一张表，行为选股策略，列为信号
+fn matrix_blocks(scorecard: &Scorecard, results: &ScorecardResults) -> Vec<Block> {
+    let mut blocks = vec![Block::Heading(2, "得分矩阵".to_string())];
+    
+    for (t, target) in scorecard.targets.iter().enumerate() {
+        let mut cells = vec![vec!["-".to_string(); scorecard.signals.len()]; scorecard.selectors.len()];
+        for entry in results.entries.iter().filter(|entry| entry.target_idx == t) {
+            cells[entry.selector_idx][entry.signal_idx] = score_text(entry);
+        }
+        
+        let mut headers = vec!["策略".to_string()];
+        headers.extend(scorecard.signals.iter().map(|signal| signal.name()));
+        let rows = scorecard.selectors.iter()
+            .zip(cells)
+            .map(|(selector, row)| std::iter::once(selector.name()).chain(row).collect())
+            .collect();
+        
+        blocks.push(Block::Heading(3, format!("目标: {}", target.name())));
+        blocks.push(Block::Table(headers, rows));
+    }
+    
+    blocks
+}
+
+/// 最佳组合的得分、胜率和与次优组合的显著性检验
+fn best_blocks(scorecard: &Scorecard, results: &ScorecardResults, ranking: RankingMetric) -> Vec<Block> {
+    let mut blocks = vec![Block::Heading(2, "最佳组合".to_string())];
+    let Some(best) = results.best(ranking) else {
+        blocks.push(Block::Paragraph("没有交易数足够的组合".to_string()));
+        return blocks;
+    };
+    
+    let mut rows = vec![
+        vec!["策略".to_string(), scorecard.selectors[best.selector_idx].name()],
+        vec!["信号".to_string(), scorecard.signals[best.signal_idx].name()],
+        vec!["目标".to_string(), scorecard.targets[best.target_idx].name()],
+        vec!["得分".to_string(), percent(best.score)],
+        vec!["交易数".to_string(), format!("{} (盈利 {})", best.total_trades, best.winning_trades)],
+    ];
+    if let Some(result) = &best.result {
+        rows.push(vec!["胜率".to_string(), percent(result.win_rate)]);
+        rows.push(vec!["平均收益率".to_string(), percent(result.avg_return)]);
+        rows.push(vec!["夏普比率".to_string(), format!("{:.2}", result.sharpe_ratio)]);
+        rows.push(vec!["最大回撤".to_string(), percent(result.max_drawdown)]);
+    }
+    if let Some(interval) = best.interval {
+        rows.push(vec![format!("胜率{:.0}%置信区间", interval.confidence * 100.0),
+            format!("[{}, {}]", percent(interval.win_rate.lower), percent(interval.win_rate.upper))]);
+    }
+    blocks.push(Block::Table(vec!["指标".to_string(), "值".to_string()], rows));
+    
+    let tests = results.runner_up_tests(ranking, RUNNER_UPS);
+    if !tests.is_empty() {
+        blocks.push(Block::Paragraph("与次优组合的胜率比较 (双比例z检验):".to_string()));
+        let rows = tests.into_iter()
+            .map(|(entry, test)| vec![
+                combination_name(scorecard, entry),
+                format!("{} ({}/{})", percent(test.rate_b), entry.winning_trades, entry.total_trades),
+                format!("{:.3}", test.p_value),
+                percent(test.confidence()),
+                if test.is_significant(SIGNIFICANCE_LEVEL) { "差异显著" } else { "差异不显著" }.to_string(),
+            ])
+            .collect();
+        blocks.push(Block::Table(
+            ["组合", "胜率", "p值", "最佳更优的置信度", "结论"].map(String::from).to_vec(),
+            rows,
+        ));
+    }
+    
+    blocks
+}
+
+/// 各组合的主要指标，按得分从高到低，交易数不足的排在最后
+fn performance_blocks(scorecard: &Scorecard, results: &ScorecardResults) -> Vec<Block> {
+    let headers = ["策略", "信号", "目标", "得分", "交易数", "胜率", "平均收益率", "最大回撤", "夏普比率"]
+        .map(String::from)
+        .to_vec();
+    let rows = ranked_entries(results).into_iter()
+        .map(|entry| {
+            let mut row = vec![
+                scorecard.selectors[entry.selector_idx].name(),
+                scorecard.signals[entry.signal_idx].name(),
+                scorecard.targets[entry.target_idx].name(),
+                score_text(entry),
+                entry.total_trades.to_string(),
+            ];
+            match &entry.result {
+                Some(result) => row.extend([
+                    percent(result.win_rate),
+                    percent(result.avg_return),
+                    percent(result.max_drawdown),
+                    format!("{:.2}", result.sharpe_ratio),
+                ]),
+                None => row.extend(std::iter::repeat_n("-".to_string(), 4)),
+            }
+            row
+        })
+        .collect();
+    
+    vec![Block::Heading(2, "各组合性能".to_string()), Block::Table(headers, rows)]
+}
+
+/// 得分最高的top_k个组合用最新数据生成的推荐股票
+fn recommendation_blocks(scorecard: &Scorecard, results: &ScorecardResults, top_k: usize, max: usize) -> Vec<Block> {
+    let mut blocks = vec![Block::Heading(2, "推荐股票".to_string())];
+    let top = scorecard.find_top_combinations(&results.scores, top_k);
+    if top.is_empty() || max == 0 {
+        blocks.push(Block::Paragraph("没有推荐股票".to_string()));
+        return blocks;
+    }
+    
+    for (t, s, sig, score) in top {
+        let Some(entry) = results.entries.iter()
+            .find(|entry| (entry.target_idx, entry.selector_idx, entry.signal_idx) == (t, s, sig)) else {
+            continue;
+        };
+        
+        blocks.push(Block::Heading(3, format!("{} (得分 {})", combination_name(scorecard, entry), percent(score))));
+        let recommendations = scorecard.recommend(entry, max);
+        if recommendations.is_empty() {
+            blocks.push(Block::Paragraph("没有推荐股票".to_string()));
+            continue;
+        }
+        
+        let rows = recommendations.iter()
+            .map(|rec| vec![
+                rec.symbol.clone(),
+                rec.name.clone(),
+                format!("{:.2}", rec.buy_price),
+                format!("{:.2}", rec.target_price),
+                format!("{:.2}", rec.stop_loss_price),
+                format!("{:.2}", rec.score),
+            ])
+            .collect();
+        blocks.push(Block::Table(
+            ["代码", "名称", "买入价", "目标价", "止损价", "选股评分"].map(String::from).to_vec(),
+            rows,
+        ));
+    }
+    
+    blocks
+}
+
+/// 各组合BacktestResult::format_report的文本，没有详细结果时省略
+fn detail_blocks(scorecard: &Scorecard, results: &ScorecardResults) -> Vec<Block> {
+    let mut blocks = vec![Block::Heading(2, "组合详情".to_string())];
+    
+    for entry in ranked_entries(results) {
+        if let Some(result) = &entry.result {
+            blocks.push(Block::Heading(3, combination_name(scorecard, entry)));
+            blocks.push(Block::Preformatted(result.format_report()));
+        }
+    }
+    if blocks.len() == 1 {
+        blocks.push(Block::Paragraph("没有详细回测结果".to_string()));
+    }
+    
+    blocks
+}
+
+/// 按得分从高到低排列的组合，交易数不足的排在最后
+fn ranked_entries(results: &ScorecardResults) -> Vec<&ScoreEntry> {
+    let mut entries: Vec<&ScoreEntry> = results.entries.iter().collect();
+    entries.sort_by(|a, b| {
+        a.insufficient_samples.cmp(&b.insufficient_samples)
+            .then(b.score.total_cmp(&a.score))
+    });
+    entries
+}
+
+fn combination_name(scorecard: &Scorecard, entry: &ScoreEntry) -> String {
+    format!("{} + {} / {}",
+        scorecard.selectors[entry.selector_idx].name(),
+        scorecard.signals[entry.signal_idx].name(),
+        scorecard.targets[entry.target_idx].name())
+}
+
+fn score_text(entry: &ScoreEntry) -> String {
+    if entry.insufficient_samples {
+        "样本不足".to_string()
+    } else {
+        percent(entry.score)
+    }
+}
+
+fn percent(value: f32) -> String {
+    format!("{:.2}%", value * 100.0)
+}
+
+fn render_markdown(title: &str, blocks: &[Block]) -> String {
+    let mut markdown = format!("# {}\n\n", title);
+    
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => markdown.push_str(&format!("{} {}\n\n", "#".repeat(*level), text)),
+            Block::Paragraph(text) => markdown.push_str(&format!("{}\n\n", text)),
+            Block::Table(headers, rows) => {
+                let row_text = |cells: &[String]| {
+                    let cells: Vec<String> = cells.iter().map(|cell| cell.replace('|', "\\|")).collect();
+                    format!("| {} |\n", cells.join(" | "))
+                };
+                markdown.push_str(&row_text(headers));
+                markdown.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+                for row in rows {
+                    markdown.push_str(&row_text(row));
+                }
+                markdown.push('\n');
+            }
+            Block::Preformatted(text) => markdown.push_str(&format!("```text\n{}```\n\n", text)),
+        }
+    }
+    
+    markdown
+}
+
+fn render_html(title: &str, blocks: &[Block]) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape(title)));
+    html.push_str(&format!("<style>{}{}</style>\n</head>\n<body>\n", STYLE, PRE_STYLE));
+    html.push_str(&format!("<h1>{}</h1>\n", escape(title)));
+    
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => html.push_str(&format!("<h{0}>{1}</h{0}>\n", level, escape(text))),
+            Block::Paragraph(text) => html.push_str(&format!("<p class=\"meta\">{}</p>\n", escape(text))),
+            Block::Table(headers, rows) => {
+                html.push_str("<table>\n<tr>");
+                for header in headers {
+                    html.push_str(&format!("<th>{}</th>", escape(header)));
+                }
+                html.push_str("</tr>\n");
+                for row in rows {
+                    html.push_str("<tr>");
+                    for cell in row {
+                        html.push_str(&format!("<td>{}</td>", escape(cell)));
+                    }
+                    html.push_str("</tr>\n");
+                }
+                html.push_str("</table>\n");
+            }
+            Block::Preformatted(text) => html.push_str(&format!("<pre>{}</pre>\n", escape(text))),
+        }
+    }
+    
+    html.push_str("</body>\n</html>\n");
+    html
+}
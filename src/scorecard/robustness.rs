@@ -0,0 +1,289 @@
+use crate::backtest::BacktestEngine;
+use crate::signals::BuySignalGenerator;
+use crate::strategies::StockSelector;
+use crate::targets::Target;
+use log::info;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+
+/// 选股策略数值参数表，整数参数由构造函数自行取整
+pub type ParamMap = BTreeMap<String, f32>;
+
+/// 参数扰动规格
+#[derive(Debug, Clone)]
+pub struct JitterSpec {
+    /// 需要扰动的参数名，为空时扰动全部参数
+    pub params: Vec<String>,
+    /// 最大扰动比例，例如0.1表示±10%
+    pub max_pct: f32,
+    /// 每侧的扰动档数，例如2表示 -10%、-5%、0、+5%、+10%
+    pub steps: usize,
+    /// 得分下限，低于该值的扰动视为失效
+    pub floor: f32,
+}
+
+impl Default for JitterSpec {
+    fn default() -> Self {
+        Self {
+            params: Vec::new(),
+            max_pct: 0.1,
+            steps: 1,
+            floor: 0.5,
+        }
+    }
+}
+
+/// 参数扰动稳健性报告
+#[derive(Debug, Clone)]
+pub struct RobustnessReport {
+    pub base_params: ParamMap,
+    pub base_score: f32,
+    /// 每组扰动参数及其得分(包含原始参数)
+    pub scores: Vec<(ParamMap, f32)>,
+    pub min_score: f32,
+    pub median_score: f32,
+    pub max_score: f32,
+    /// 得分不低于下限的扰动占比
+    pub above_floor_ratio: f32,
+    pub floor: f32,
+}
+
+impl RobustnessReport {
+    /// 得分低于下限的扰动参数
+    pub fn failing_params(&self) -> Vec<&ParamMap> {
+        self.scores.iter()
+            .filter(|(_, score)| *score < self.floor)
+            .map(|(params, _)| params)
+            .collect()
+    }
+    
+    /// 是否存在得分跌破下限的扰动
+    pub fn is_fragile(&self) -> bool {
+        self.above_floor_ratio < 1.0
+    }
+    
+    /// 格式化为人类可读的报告
+    pub fn format_report(&self) -> String {
+        let mut report = String::new();
+        
+        report.push_str(&format!("原始参数得分: {:.2}%\n", self.base_score * 100.0));
+        report.push_str(&format!("扰动次数: {}\n", self.scores.len()));
+        report.push_str(&format!("最低得分: {:.2}%\n", self.min_score * 100.0));
+        report.push_str(&format!("中位得分: {:.2}%\n", self.median_score * 100.0));
+        report.push_str(&format!("最高得分: {:.2}%\n", self.max_score * 100.0));
+        report.push_str(&format!("不低于{:.2}%的占比: {:.2}%\n",
+            self.floor * 100.0, self.above_floor_ratio * 100.0));
+        
+        for params in self.failing_params() {
+            let desc: Vec<String> = params.iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            report.push_str(&format!("  跌破下限: {}\n", desc.join(", ")));
+        }
+        
+        report
+    }
+}
+
+/// 生成参数扰动网格(各参数扰动档位的笛卡尔积)
+fn jitter_grid(base: &ParamMap, spec: &JitterSpec) -> Vec<ParamMap> {
+    let factors: Vec<f32> = if spec.steps == 0 {
+        vec![1.0]
+    } else {
+        let steps = spec.steps as i32;
+        (-steps..=steps)
+            .map(|k| 1.0 + spec.max_pct * k as f32 / steps as f32)
+            .collect()
+    };
+    
+    let mut grid = vec![base.clone()];
+    
+    for (name, &value) in base {
+        if !spec.params.is_empty() && !spec.params.contains(name) {
+            continue;
+        }
+        
+        grid = grid.into_iter()
+            .flat_map(|params| {
+                factors.iter().map(move |factor| {
+                    let mut params = params.clone();
+                    params.insert(name.clone(), value * factor);
+                    params
+                })
+            })
+            .collect();
+    }
+    
+    grid
+}
+
+/// 对选股策略参数做网格扰动，评估最佳组合是否处于参数的"刀锋"上
+pub fn jitter_test(
+    engine: &BacktestEngine,
+    selector_builder: &(dyn Fn(&ParamMap) -> Box<dyn StockSelector> + Sync),
+    base_params: &ParamMap,
+    signal_generator: &dyn BuySignalGenerator,
+    target: &dyn Target,
+    back_days: usize,
+    jitter_spec: &JitterSpec,
+) -> RobustnessReport {
+    let grid = jitter_grid(base_params, jitter_spec);
+    info!("参数扰动测试: {} 组参数", grid.len());
+    
+    let scores: Vec<(ParamMap, f32)> = grid.into_par_iter()
+        .map(|params| {
+            let selector = selector_builder(&params);
+            let score = engine.run_backtest(selector.as_ref(), signal_generator, target, back_days);
+            (params, score)
+        })
+        .collect();
+    
+    let base_score = scores.iter()
+        .find(|(params, _)| params == base_params)
+        .map(|(_, score)| *score)
+        .unwrap_or(0.0);
+    
+    let mut sorted: Vec<f32> = scores.iter().map(|(_, score)| *score).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    
+    let median_score = if sorted.is_empty() {
+        0.0
+    } else if sorted.len().is_multiple_of(2) {
+        (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2.0
+    } else {
+        sorted[sorted.len() / 2]
+    };
+    
+    let above_floor = sorted.iter().filter(|&&score| score >= jitter_spec.floor).count();
+    let above_floor_ratio = if sorted.is_empty() {
+        0.0
+    } else {
+        above_floor as f32 / sorted.len() as f32
+    };
+    
+    RobustnessReport {
+        base_params: base_params.clone(),
+        base_score,
+        min_score: sorted.first().copied().unwrap_or(0.0),
+        median_score,
+        max_score: sorted.last().copied().unwrap_or(0.0),
+        above_floor_ratio,
+        floor: jitter_spec.floor,
+        scores,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::price::ClosePriceSignal;
+    use crate::stock::series::BarSlice;
+    use crate::stock::data_provider::StockDataProvider;
+    use crate::targets::return_target::ReturnTarget;
+    use egostrategy_datahub::models::stock::{DailyData as DailyBar, StockData};
+    use std::sync::Arc;
+
+    /// 阈值超过上限时不再选出任何股票的选股器
+    struct ThresholdSelector {
+        threshold: f32,
+        limit: f32,
+    }
+
+    impl StockSelector for ThresholdSelector {
+        fn name(&self) -> String {
+            format!("阈值 {}", self.threshold)
+        }
+
+        fn run(&self, stock_data: &[(String, BarSlice)], _forecast_idx: usize) -> Vec<(String, BarSlice)> {
+            if self.threshold > self.limit {
+                Vec::new()
+            } else {
+                stock_data.to_vec()
+            }
+        }
+    }
+
+    /// 每天上涨2%的股票，持有期内必然达到目标收益
+    fn engine() -> BacktestEngine {
+        let daily = (0..130)
+            .map(|i| {
+                let close = 20.0 * 0.98f32.powi(i);
+                DailyBar {
+                    date: 20230630 - i,
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: 10000,
+                    amount: 100000,
+                }
+            })
+            .collect();
+        let stock = StockData {
+            exchange: "SZ".to_string(),
+            symbol: "000001.SZ".to_string(),
+            name: "平安银行".to_string(),
+            daily,
+        };
+        
+        let provider = Arc::new(StockDataProvider::with_stocks(vec![stock]).unwrap());
+        let mut engine = BacktestEngine::with_source(provider, true, None);
+        engine.load_data().unwrap();
+        engine
+    }
+
+    fn params(entries: &[(&str, f32)]) -> ParamMap {
+        entries.iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect()
+    }
+
+    #[test]
+    fn grid_is_cartesian_product_of_steps() {
+        let spec = JitterSpec { steps: 1, ..JitterSpec::default() };
+        
+        let grid = jitter_grid(&params(&[("a", 10.0), ("b", 20.0)]), &spec);
+        
+        assert_eq!(grid.len(), 9);
+        assert!(grid.contains(&params(&[("a", 11.0), ("b", 18.0)])));
+    }
+
+    #[test]
+    fn grid_only_perturbs_listed_params() {
+        let spec = JitterSpec { params: vec!["a".to_string()], steps: 2, ..JitterSpec::default() };
+        
+        let grid = jitter_grid(&params(&[("a", 10.0), ("b", 20.0)]), &spec);
+        
+        assert_eq!(grid.len(), 5);
+        assert!(grid.iter().all(|params| params["b"] == 20.0));
+    }
+
+    #[test]
+    fn report_flags_collapse_at_plus_ten_percent() {
+        let engine = engine();
+        let builder = |params: &ParamMap| -> Box<dyn StockSelector> {
+            Box::new(ThresholdSelector { threshold: params["threshold"], limit: 10.5 })
+        };
+        let spec = JitterSpec {
+            params: vec!["threshold".to_string()],
+            max_pct: 0.1,
+            steps: 1,
+            floor: 0.5,
+        };
+        let target = ReturnTarget::new(0.01, 0.05, 3);
+        
+        let report = jitter_test(&engine, &builder, &params(&[("threshold", 10.0)]),
+            &ClosePriceSignal, &target, 5, &spec);
+        
+        assert_eq!(report.scores.len(), 3);
+        assert_eq!(report.base_score, 1.0);
+        assert_eq!(report.max_score, 1.0);
+        assert_eq!(report.min_score, 0.0);
+        assert!((report.above_floor_ratio - 2.0 / 3.0).abs() < 1e-6);
+        assert!(report.is_fragile());
+        
+        let failing = report.failing_params();
+        assert_eq!(failing.len(), 1);
+        assert!((failing[0]["threshold"] - 11.0).abs() < 1e-5);
+    }
+}
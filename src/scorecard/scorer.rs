@@ -0,0 +1,188 @@
+use crate::backtest::BacktestResult;
+
+/// 组合评分函数，将详细回测结果映射为单一得分
+pub trait CombinationScorer: Send + Sync {
+    /// 获取评分函数名称
+    fn name(&self) -> String;
+    
+    /// 计算组合得分，越大越好
+    fn score(&self, result: &BacktestResult) -> f32;
+}
+
+/// 按胜率评分
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WinRateScorer;
+
+impl CombinationScorer for WinRateScorer {
+    fn name(&self) -> String {
+        "win_rate".to_string()
+    }
+    
+    fn score(&self, result: &BacktestResult) -> f32 {
+        result.win_rate
+    }
+}
+
+/// 按每笔交易的平均收益率评分
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExpectancyScorer;
+
+impl CombinationScorer for ExpectancyScorer {
+    fn name(&self) -> String {
+        "expectancy".to_string()
+    }
+    
+    fn score(&self, result: &BacktestResult) -> f32 {
+        result.avg_return
+    }
+}
+
+/// 按凯利仓位比例评分: W - (1 - W) / R，R为平均盈利与平均亏损之比
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KellyScorer;
+
+impl CombinationScorer for KellyScorer {
+    fn name(&self) -> String {
+        "kelly".to_string()
+    }
+    
+    fn score(&self, result: &BacktestResult) -> f32 {
+        let returns = &result.trade_returns;
+        if returns.is_empty() {
+            return 0.0;
+        }
+        
+        let wins: Vec<f32> = returns.iter().copied().filter(|&r| r > 0.0).collect();
+        let losses: Vec<f32> = returns.iter().copied().filter(|&r| r < 0.0).collect();
+        
+        let win_rate = wins.len() as f32 / returns.len() as f32;
+        if wins.is_empty() || losses.is_empty() {
+            return win_rate;
+        }
+        
+        let avg_win = wins.iter().sum::<f32>() / wins.len() as f32;
+        let avg_loss = -losses.iter().sum::<f32>() / losses.len() as f32;
+        
+        win_rate - (1.0 - win_rate) / (avg_win / avg_loss)
+    }
+}
+
+/// 按夏普比率评分，偏好收益稳定的组合
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SharpeScorer;
+
+impl CombinationScorer for SharpeScorer {
+    fn name(&self) -> String {
+        "sharpe".to_string()
+    }
+    
+    fn score(&self, result: &BacktestResult) -> f32 {
+        result.sharpe_ratio
+    }
+}
+
+/// 加权评分: win_rate * 胜率 + avg_return * 平均收益率 - drawdown_penalty * 最大回撤
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightedScorer {
+    pub win_rate: f32,
+    pub avg_return: f32,
+    pub drawdown_penalty: f32,
+}
+
+impl Default for WeightedScorer {
+    /// 平均收益率通常比胜率小一个数量级，默认权重放大收益率使两者可比
+    fn default() -> Self {
+        Self { win_rate: 1.0, avg_return: 10.0, drawdown_penalty: 0.5 }
+    }
+}
+
+impl WeightedScorer {
+    /// 解析"胜率权重,收益率权重,回撤惩罚"，例如"1,10,0.5"
+    pub fn parse(weights: &str) -> Option<Self> {
+        let weights: Vec<f32> = weights.split(',')
+            .map(|weight| weight.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        match weights[..] {
+            [win_rate, avg_return, drawdown_penalty] => Some(Self { win_rate, avg_return, drawdown_penalty }),
+            _ => None,
+        }
+    }
+}
+
+impl CombinationScorer for WeightedScorer {
+    fn name(&self) -> String {
+        format!("weighted:{},{},{}", self.win_rate, self.avg_return, self.drawdown_penalty)
+    }
+    
+    fn score(&self, result: &BacktestResult) -> f32 {
+        self.win_rate * result.win_rate + self.avg_return * result.avg_return - self.drawdown_penalty * result.max_drawdown
+    }
+}
+
+/// 按名称创建内置评分函数，weighted可用"weighted:1,10,0.5"指定权重
+pub fn builtin(name: &str) -> Option<Box<dyn CombinationScorer>> {
+    match name {
+        "win_rate" => Some(Box::new(WinRateScorer)),
+        "expectancy" => Some(Box::new(ExpectancyScorer)),
+        "kelly" => Some(Box::new(KellyScorer)),
+        "sharpe" => Some(Box::new(SharpeScorer)),
+        "weighted" => Some(Box::new(WeightedScorer::default())),
+        _ => {
+            let weights = name.strip_prefix("weighted:")?;
+            Some(Box::new(WeightedScorer::parse(weights)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(returns: &[f32]) -> BacktestResult {
+        let wins = returns.iter().filter(|&&r| r > 0.0).count();
+        BacktestResult {
+            win_rate: wins as f32 / returns.len() as f32,
+            avg_return: returns.iter().sum::<f32>() / returns.len() as f32,
+            trade_returns: returns.to_vec(),
+            ..BacktestResult::default()
+        }
+    }
+
+    #[test]
+    fn kelly_uses_win_loss_ratio() {
+        // W=0.5, R=0.04/0.02=2 => 0.5 - 0.5/2 = 0.25
+        let score = KellyScorer.score(&result(&[0.04, -0.02, 0.04, -0.02]));
+        
+        assert!((score - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn scorers_rank_differently() {
+        let frequent_small = result(&[0.01, 0.01, 0.01, -0.05]);
+        let rare_large = result(&[0.20, -0.02, -0.02, -0.02]);
+        
+        assert!(WinRateScorer.score(&frequent_small) > WinRateScorer.score(&rare_large));
+        assert!(ExpectancyScorer.score(&frequent_small) < ExpectancyScorer.score(&rare_large));
+    }
+
+    #[test]
+    fn builtin_resolves_names() {
+        for name in ["win_rate", "expectancy", "kelly", "sharpe"] {
+            assert_eq!(builtin(name).unwrap().name(), name);
+        }
+        assert_eq!(builtin("weighted").unwrap().name(), "weighted:1,10,0.5");
+        assert_eq!(builtin("weighted:2, 5, 1").unwrap().name(), "weighted:2,5,1");
+        assert!(builtin("weighted:1,10").is_none());
+        assert!(builtin("sortino").is_none());
+    }
+
+    #[test]
+    fn weighted_score_penalizes_drawdown() {
+        let mut result = result(&[0.10, -0.05]);
+        result.max_drawdown = 0.2;
+        
+        let scorer = WeightedScorer { win_rate: 1.0, avg_return: 10.0, drawdown_penalty: 0.5 };
+        // 0.5 + 10 × 0.025 - 0.5 × 0.2
+        assert!((scorer.score(&result) - 0.65).abs() < 1e-6);
+    }
+}
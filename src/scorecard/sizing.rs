@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+/// A股最小交易单位(一手)的股数
+pub const LOT_SIZE: u32 = 100;
+
+/// 取整时容忍的误差(手)，避免0.01f32等比例换算后差一点点而少算一手
+const LOT_EPSILON: f64 = 1e-6;
+
+/// 按单笔风险计算建议仓位
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PositionSizing {
+    /// 可用资金
+    pub capital: f64,
+    /// 单笔交易止损时最多亏损的资金比例，0.01表示1%
+    pub risk_per_trade: f32,
+}
+
+impl PositionSizing {
+    pub fn new(capital: f64, risk_per_trade: f32) -> Self {
+        Self { capital, risk_per_trade }
+    }
+    
+    /// 建议买入股数，按整手向下取整
+    ///
+    /// 止损时的亏损不超过 capital * risk_per_trade，买入金额不超过capital；
+    /// 止损价不低于买入价时只受资金限制
+    pub fn shares(&self, buy_price: f32, stop_loss_price: f32) -> u32 {
+        if buy_price <= 0.0 || self.capital <= 0.0 {
+            return 0;
+        }
+        
+        let by_capital = self.capital / buy_price as f64;
+        let risk_per_share = (buy_price - stop_loss_price) as f64;
+        let shares = if risk_per_share > 0.0 {
+            let by_risk = self.capital * self.risk_per_trade.max(0.0) as f64 / risk_per_share;
+            by_risk.min(by_capital)
+        } else {
+            by_capital
+        };
+        
+        round_to_lot(shares)
+    }
+}
+
+/// 按整手向下取整，容忍f32换算带来的微小误差
+pub fn round_to_lot(shares: f64) -> u32 {
+    if !shares.is_finite() || shares <= 0.0 {
+        return 0;
+    }
+    
+    let lots = (shares / LOT_SIZE as f64 + LOT_EPSILON).floor().min((u32::MAX / LOT_SIZE) as f64);
+    lots as u32 * LOT_SIZE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_down_to_whole_lots() {
+        assert_eq!(round_to_lot(250.0), 200);
+        assert_eq!(round_to_lot(199.99), 100);
+        assert_eq!(round_to_lot(99.0), 0);
+        // 换算误差不应少算一手
+        assert_eq!(round_to_lot(299.9999999), 300);
+        assert_eq!(round_to_lot(-100.0), 0);
+        assert_eq!(round_to_lot(f64::NAN), 0);
+    }
+
+    #[test]
+    fn risk_limits_shares_until_capital_binds() {
+        let sizing = PositionSizing::new(100_000.0, 0.01);
+        
+        // 每股风险0.3元，最多亏1000元: 3333股 -> 3300股
+        assert_eq!(sizing.shares(10.0, 9.7), 3300);
+        // 止损很近时受资金限制: 100000 / 10 = 10000股
+        assert_eq!(sizing.shares(10.0, 9.99), 10000);
+        // 止损价不低于买入价时只受资金限制
+        assert_eq!(sizing.shares(10.0, 10.5), 10000);
+        assert_eq!(sizing.shares(0.0, 9.7), 0);
+        assert_eq!(PositionSizing::new(500.0, 0.01).shares(10.0, 9.7), 0);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::scorecard::{RankingMetric, ScoreEntry, ScorecardResults};
+
+/// 判定差异显著的p值阈值
+pub const SIGNIFICANCE_LEVEL: f32 = 0.05;
+
+/// 与最佳组合比较的次优组合数
+pub const RUNNER_UPS: usize = 3;
+
+/// 双比例z检验的结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProportionTest {
+    /// 第一组的胜率
+    pub rate_a: f32,
+    /// 第二组的胜率
+    pub rate_b: f32,
+    /// 检验统计量，为正时第一组胜率更高
+    pub z: f32,
+    /// 单侧p值: 两组胜率相同时，观察到不小于z的差异的概率
+    pub p_value: f32,
+}
+
+impl ProportionTest {
+    /// 第一组胜率确实更高的置信度，即1 - p值
+    pub fn confidence(&self) -> f32 {
+        1.0 - self.p_value
+    }
+    
+    /// p值低于level时认为第一组显著更好
+    pub fn is_significant(&self, level: f32) -> bool {
+        self.p_value < level
+    }
+}
+
+/// 双比例z检验，检验第一组(wins_a / n_a)的胜率是否高于第二组(wins_b / n_b)
+///
+/// 标准误由两组合并的胜率估计。任一组没有交易时返回None；
+/// 两组全胜或全负时标准误为0，z记为0、p值为0.5
+pub fn two_proportion_z_test(wins_a: usize, n_a: usize, wins_b: usize, n_b: usize) -> Option<ProportionTest> {
+    if n_a == 0 || n_b == 0 {
+        return None;
+    }
+    
+    let (n_a_f, n_b_f) = (n_a as f64, n_b as f64);
+    let rate_a = wins_a as f64 / n_a_f;
+    let rate_b = wins_b as f64 / n_b_f;
+    let pooled = (wins_a + wins_b) as f64 / (n_a_f + n_b_f);
+    let se = (pooled * (1.0 - pooled) * (1.0 / n_a_f + 1.0 / n_b_f)).sqrt();
+    let z = if se > 0.0 { (rate_a - rate_b) / se } else { 0.0 };
+    
+    Some(ProportionTest {
+        rate_a: rate_a as f32,
+        rate_b: rate_b as f32,
+        z: z as f32,
+        p_value: (1.0 - normal_cdf(z)) as f32,
+    })
+}
+
+/// 标准正态分布的累积分布函数
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / std::f64::consts::SQRT_2)
+}
+
+/// 互补误差函数(Numerical Recipes的切比雪夫逼近，相对误差小于1.2e-7)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368 + t * (0.37409196 + t * (0.09678418 + t * (-0.18628806
+        + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let r = t * poly.exp();
+    
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+impl ScorecardResults {
+    /// 按指定依据找出最佳组合，并与排在其后的最多n个组合逐一做胜率的双比例z检验
+    ///
+    /// 胜率为累计的盈利交易数除以交易数。交易数不足的组合不参与；
+    /// 没有最佳组合或最佳组合没有交易时返回空列表
+    pub fn runner_up_tests(&self, ranking: RankingMetric, n: usize) -> Vec<(&ScoreEntry, ProportionTest)> {
+        let Some(best) = self.best(ranking) else {
+            return Vec::new();
+        };
+        
+        let mut runner_ups: Vec<&ScoreEntry> = self.entries.iter()
+            .filter(|entry| !entry.insufficient_samples && !std::ptr::eq(*entry, best))
+            .collect();
+        runner_ups.sort_by(|a, b| b.ranking_value(ranking).total_cmp(&a.ranking_value(ranking)));
+        
+        runner_ups.into_iter()
+            .take(n)
+            .filter_map(|entry| {
+                two_proportion_z_test(best.winning_trades, best.total_trades, entry.winning_trades, entry.total_trades)
+                    .map(|test| (entry, test))
+            })
+            .collect()
+    }
+}
@@ -2,7 +2,7 @@ pub mod price;
 pub mod pattern;
 pub mod volume;
 
-use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use crate::stock::series::BarSlice;
 
 /// 买入信号生成器特征
 pub trait BuySignalGenerator: Send + Sync {
@@ -12,7 +12,7 @@ pub trait BuySignalGenerator: Send + Sync {
     /// 生成买入信号
     fn generate_signals(
         &self,
-        candidates: Vec<(String, Vec<DailyBar>)>,
+        candidates: Vec<(String, BarSlice)>,
         forecast_idx: usize,
-    ) -> Vec<(String, Vec<DailyBar>, f32)>;
+    ) -> Vec<(String, BarSlice, f32)>;
 }
@@ -1,8 +1,10 @@
 use crate::signals::BuySignalGenerator;
-use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use crate::stock::series::BarSlice;
+use serde::Deserialize;
 
 /// 地包天买入信号
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct BottomReverseSignal {
     pub min_body_ratio: f32,
 }
@@ -22,9 +24,9 @@ impl BuySignalGenerator for BottomReverseSignal {
     
     fn generate_signals(
         &self,
-        candidates: Vec<(String, Vec<DailyBar>)>,
+        candidates: Vec<(String, BarSlice)>,
         forecast_idx: usize,
-    ) -> Vec<(String, Vec<DailyBar>, f32)> {
+    ) -> Vec<(String, BarSlice, f32)> {
         candidates.into_iter()
             .filter_map(|(symbol, data)| {
                 if data.len() <= forecast_idx + 1 {
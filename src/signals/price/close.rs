@@ -1,5 +1,5 @@
 use crate::signals::BuySignalGenerator;
-use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use crate::stock::series::BarSlice;
 
 /// 收盘价信号生成器
 #[derive(Debug, Clone)]
@@ -12,9 +12,9 @@ impl BuySignalGenerator for ClosePriceSignal {
     
     fn generate_signals(
         &self,
-        candidates: Vec<(String, Vec<DailyBar>)>,
+        candidates: Vec<(String, BarSlice)>,
         forecast_idx: usize,
-    ) -> Vec<(String, Vec<DailyBar>, f32)> {
+    ) -> Vec<(String, BarSlice, f32)> {
         candidates.into_iter()
             .map(|(symbol, data)| {
                 // 由于T+1交易制度，买入价格是forecast_idx-1天的收盘价
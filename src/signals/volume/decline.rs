@@ -1,8 +1,10 @@
 use crate::signals::BuySignalGenerator;
-use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use crate::stock::series::BarSlice;
+use serde::Deserialize;
 
 /// 成交量萎缩信号生成器
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct VolumeDeclineSignal {
     pub min_consecutive_days: usize,
     pub decline_ratio: f32,
@@ -26,9 +28,9 @@ impl BuySignalGenerator for VolumeDeclineSignal {
     
     fn generate_signals(
         &self,
-        candidates: Vec<(String, Vec<DailyBar>)>,
+        candidates: Vec<(String, BarSlice)>,
         forecast_idx: usize,
-    ) -> Vec<(String, Vec<DailyBar>, f32)> {
+    ) -> Vec<(String, BarSlice, f32)> {
         candidates.into_iter()
             .filter_map(|(symbol, data)| {
                 if data.len() <= forecast_idx + self.min_consecutive_days {
@@ -1,8 +1,10 @@
 use crate::signals::BuySignalGenerator;
-use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use crate::stock::series::BarSlice;
+use serde::Deserialize;
 
 /// 成交量突破信号生成器
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct VolumeSurgeSignal {
     pub volume_ratio: f32,
     pub price_filter: bool,
@@ -24,9 +26,9 @@ impl BuySignalGenerator for VolumeSurgeSignal {
     
     fn generate_signals(
         &self,
-        candidates: Vec<(String, Vec<DailyBar>)>,
+        candidates: Vec<(String, BarSlice)>,
         forecast_idx: usize,
-    ) -> Vec<(String, Vec<DailyBar>, f32)> {
+    ) -> Vec<(String, BarSlice, f32)> {
         candidates.into_iter()
             .filter_map(|(symbol, data)| {
                 if data.len() <= forecast_idx + 5 {  // 至少需要5天数据
@@ -0,0 +1,205 @@
+use crate::utils::csv;
+use anyhow::{anyhow, Context, Result};
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// 复权方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AdjustMode {
+    /// 不复权
+    #[default]
+    None,
+    /// 前复权: 以最新价格为基准调整历史价格
+    Forward,
+    /// 后复权: 以上市初始价格为基准调整之后的价格
+    Backward,
+}
+
+/// 各股票的累积复权因子，每只股票按日期升序
+#[derive(Debug, Clone, Default)]
+pub struct AdjustFactors {
+    factors: HashMap<String, Vec<(i32, f64)>>,
+}
+
+impl AdjustFactors {
+    /// 从CSV读取复权因子，列为symbol,date,factor，日期为YYYYMMDD
+    ///
+    /// factor为截至该日的累积复权因子，每个除权日一行
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("读取复权因子失败: {}", path.display()))?;
+        
+        let mut factors: HashMap<String, Vec<(i32, f64)>> = HashMap::new();
+        
+        for (line_idx, line) in content.lines().enumerate() {
+            let fields = csv::split_row(line);
+            if line.trim().is_empty() || fields[0].trim().eq_ignore_ascii_case("symbol") {
+                continue;
+            }
+            
+            let parse = || -> Result<(String, i32, f64)> {
+                let symbol = fields.first().ok_or_else(|| anyhow!("缺少symbol"))?.trim().to_string();
+                let date = fields.get(1).ok_or_else(|| anyhow!("缺少date"))?.trim().replace('-', "").parse()?;
+                let factor: f64 = fields.get(2).ok_or_else(|| anyhow!("缺少factor"))?.trim().parse()?;
+                if factor <= 0.0 {
+                    return Err(anyhow!("复权因子必须为正数"));
+                }
+                Ok((symbol, date, factor))
+            };
+            
+            let (symbol, date, factor) = parse()
+                .with_context(|| format!("{}:{}: 无效的复权因子", path.display(), line_idx + 1))?;
+            factors.entry(symbol).or_default().push((date, factor));
+        }
+        
+        for series in factors.values_mut() {
+            series.sort_by_key(|(date, _)| *date);
+        }
+        
+        Ok(Self { factors })
+    }
+    
+    /// 合并另一组复权因子，同一股票以新数据为准
+    pub fn extend(&mut self, other: AdjustFactors) {
+        self.factors.extend(other.factors);
+    }
+    
+    /// 股票数量
+    pub fn len(&self) -> usize {
+        self.factors.len()
+    }
+    
+    /// 是否没有任何复权因子
+    pub fn is_empty(&self) -> bool {
+        self.factors.is_empty()
+    }
+    
+    /// 是否有该股票的复权因子
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.factors.get(symbol).is_some_and(|series| !series.is_empty())
+    }
+    
+    /// 设置单只股票的复权因子
+    pub fn insert(&mut self, symbol: &str, mut series: Vec<(i32, f64)>) {
+        series.sort_by_key(|(date, _)| *date);
+        self.factors.insert(symbol.to_string(), series);
+    }
+    
+    /// 对K线复权，K线按日期倒序；没有该股票的因子时原样返回
+    pub fn apply(&self, symbol: &str, bars: &mut [DailyBar], mode: AdjustMode) {
+        let Some(series) = self.factors.get(symbol) else {
+            return;
+        };
+        if mode == AdjustMode::None || series.is_empty() {
+            return;
+        }
+        
+        let latest = series[series.len() - 1].1;
+        
+        for bar in bars.iter_mut() {
+            // 该日生效的因子: 日期不晚于K线的最后一个因子，早于全部因子时取1
+            let pos = series.partition_point(|(date, _)| *date <= bar.date);
+            let factor = if pos == 0 { 1.0 } else { series[pos - 1].1 };
+            
+            let ratio = match mode {
+                AdjustMode::Forward => factor / latest,
+                AdjustMode::Backward => factor,
+                AdjustMode::None => 1.0,
+            };
+            
+            bar.open = (bar.open as f64 * ratio) as f32;
+            bar.high = (bar.high as f64 * ratio) as f32;
+            bar.low = (bar.low as f64 * ratio) as f32;
+            bar.close = (bar.close as f64 * ratio) as f32;
+            bar.volume = (bar.volume as f64 / ratio).round() as i64;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 20230105除权的2:1拆股，除权前收盘20元，除权后收盘10元，按日期倒序
+    fn split_bars() -> Vec<DailyBar> {
+        [(20230107, 10.2), (20230106, 10.1), (20230105, 10.0), (20230104, 20.0), (20230103, 19.8)]
+            .iter()
+            .map(|&(date, close)| DailyBar {
+                date,
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: if close > 15.0 { 1000 } else { 2000 },
+                amount: 20000,
+            })
+            .collect()
+    }
+
+    fn split_factors() -> AdjustFactors {
+        let mut factors = AdjustFactors::default();
+        factors.insert("000001.SZ", vec![(20230105, 2.0), (20230101, 1.0)]);
+        factors
+    }
+
+    #[test]
+    fn forward_adjustment_keeps_prices_continuous_across_ex_date() {
+        let mut bars = split_bars();
+        
+        split_factors().apply("000001.SZ", &mut bars, AdjustMode::Forward);
+        
+        // 最新价格不变，除权前价格减半
+        assert_eq!(bars[0].close, 10.2);
+        assert_eq!(bars[2].close, 10.0);
+        assert!((bars[3].close - 10.0).abs() < 1e-5);
+        assert!((bars[4].close - 9.9).abs() < 1e-5);
+        assert_eq!(bars[3].volume, 2000);
+    }
+
+    #[test]
+    fn backward_adjustment_scales_prices_after_ex_date() {
+        let mut bars = split_bars();
+        
+        split_factors().apply("000001.SZ", &mut bars, AdjustMode::Backward);
+        
+        // 上市初始价格不变，除权后价格翻倍
+        assert_eq!(bars[4].close, 19.8);
+        assert_eq!(bars[3].close, 20.0);
+        assert!((bars[2].close - 20.0).abs() < 1e-5);
+        assert_eq!(bars[2].volume, 1000);
+    }
+
+    #[test]
+    fn unknown_symbol_and_none_mode_are_untouched() {
+        let mut bars = split_bars();
+        split_factors().apply("600000.SH", &mut bars, AdjustMode::Forward);
+        split_factors().apply("000001.SZ", &mut bars, AdjustMode::None);
+        
+        assert_eq!(bars[4].close, 19.8);
+        assert_eq!(bars[0].close, 10.2);
+    }
+
+    #[test]
+    fn load_reads_factor_csv_and_reports_bad_rows() {
+        let dir = std::env::temp_dir().join(format!("strategy_lab_adjust_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        
+        let path = dir.join("factors.csv");
+        fs::write(&path, "symbol,date,factor\n000001.SZ,2023-01-05,2.0\n000001.SZ,20230101,1.0\n").unwrap();
+        let factors = AdjustFactors::load(&path).unwrap();
+        assert_eq!(factors.len(), 1);
+        
+        let mut bars = split_bars();
+        factors.apply("000001.SZ", &mut bars, AdjustMode::Forward);
+        assert!((bars[3].close - 10.0).abs() < 1e-5);
+        
+        let bad = dir.join("bad.csv");
+        fs::write(&bad, "symbol,date,factor\n000001.SZ,20230105,-1\n").unwrap();
+        let err = AdjustFactors::load(&bad).unwrap_err();
+        assert!(format!("{:#}", err).contains("bad.csv:2"), "{:#}", err);
+        
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,35 @@
+use crate::stock::calendar::TradingCalendar;
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+
+/// 默认基准指数: 沪深300
+pub const DEFAULT_BENCHMARK: &str = "000300";
+
+/// 将指数K线对齐到个股日期
+///
+/// 两个序列都按日期倒序。个股某日没有对应的指数K线时(停牌、数据缺失)，
+/// 使用该日之前最近的指数K线；个股日期早于全部指数数据时为None
+pub fn align_to_dates(index: &[DailyBar], stock: &[DailyBar]) -> Vec<Option<DailyBar>> {
+    let calendar = TradingCalendar::from_bars(index);
+    
+    stock.iter()
+        .map(|bar| {
+            let date = if calendar.contains(bar.date) {
+                bar.date
+            } else {
+                calendar.prev(bar.date)?
+            };
+            
+            // 指数按日期倒序
+            index.binary_search_by(|index_bar| date.cmp(&index_bar.date))
+                .ok()
+                .map(|pos| index[pos].clone())
+        })
+        .collect()
+}
+
+/// 将指数收盘价对齐到个股日期，规则同align_to_dates
+pub fn align_closes(index: &[DailyBar], stock: &[DailyBar]) -> Vec<Option<f32>> {
+    align_to_dates(index, stock).into_iter()
+        .map(|bar| bar.map(|bar| bar.close))
+        .collect()
+}
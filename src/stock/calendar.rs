@@ -0,0 +1,152 @@
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+
+/// 交易日历，日期为YYYYMMDD整数，按升序保存
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TradingCalendar {
+    dates: Vec<i32>,
+}
+
+impl TradingCalendar {
+    /// 由任意日期集合构建，自动排序去重
+    pub fn from_dates(dates: impl IntoIterator<Item = i32>) -> Self {
+        let mut dates: Vec<i32> = dates.into_iter().collect();
+        dates.sort_unstable();
+        dates.dedup();
+        Self { dates }
+    }
+    
+    /// 由单个K线序列构建，例如基准指数
+    pub fn from_bars(bars: &[DailyBar]) -> Self {
+        Self::from_dates(bars.iter().map(|bar| bar.date))
+    }
+    
+    /// 由多只股票K线日期的并集构建
+    pub fn from_series<'a>(series: impl IntoIterator<Item = &'a [DailyBar]>) -> Self {
+        Self::from_dates(series.into_iter().flat_map(|bars| bars.iter().map(|bar| bar.date)))
+    }
+    
+    /// 全部交易日，升序
+    pub fn dates(&self) -> &[i32] {
+        &self.dates
+    }
+    
+    /// 交易日数量
+    pub fn len(&self) -> usize {
+        self.dates.len()
+    }
+    
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+    
+    /// 最新交易日
+    pub fn latest(&self) -> Option<i32> {
+        self.dates.last().copied()
+    }
+    
+    /// 是否为交易日
+    pub fn contains(&self, date: i32) -> bool {
+        self.dates.binary_search(&date).is_ok()
+    }
+    
+    /// 严格晚于date的第一个交易日
+    pub fn next(&self, date: i32) -> Option<i32> {
+        let pos = self.dates.partition_point(|&d| d <= date);
+        self.dates.get(pos).copied()
+    }
+    
+    /// 严格早于date的最后一个交易日
+    pub fn prev(&self, date: i32) -> Option<i32> {
+        let pos = self.dates.partition_point(|&d| d < date);
+        pos.checked_sub(1).map(|idx| self.dates[idx])
+    }
+    
+    /// 从date起偏移n个交易日，n为负表示向前；date不是交易日时先对齐到之前最近的交易日
+    pub fn offset(&self, date: i32, n: i64) -> Option<i32> {
+        let pos = self.dates.partition_point(|&d| d <= date);
+        let anchor = pos.checked_sub(1)? as i64;
+        let target = anchor + n;
+        
+        if target < 0 {
+            return None;
+        }
+        self.dates.get(target as usize).copied()
+    }
+    
+    /// a到b之间相隔的交易日数(不含a，含b)，与先后顺序无关
+    pub fn between(&self, a: i32, b: i32) -> usize {
+        let (from, to) = if a <= b { (a, b) } else { (b, a) };
+        let start = self.dates.partition_point(|&d| d <= from);
+        let end = self.dates.partition_point(|&d| d <= to);
+        end - start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(date: i32) -> DailyBar {
+        DailyBar {
+            date,
+            open: 10.0,
+            high: 10.0,
+            low: 10.0,
+            close: 10.0,
+            volume: 10000,
+            amount: 100000,
+        }
+    }
+
+    /// 2023-06-29(周四)至2023-07-04(周二)，跨过周末
+    fn calendar() -> TradingCalendar {
+        let a = vec![bar(20230703), bar(20230630), bar(20230629)];
+        let b = vec![bar(20230704), bar(20230703), bar(20230630)];
+        TradingCalendar::from_series([a.as_slice(), b.as_slice()])
+    }
+
+    #[test]
+    fn union_of_series_is_sorted_and_deduplicated() {
+        let calendar = calendar();
+        
+        assert_eq!(calendar.dates(), &[20230629, 20230630, 20230703, 20230704]);
+        assert_eq!(calendar.latest(), Some(20230704));
+        assert!(!calendar.contains(20230701));
+    }
+
+    #[test]
+    fn next_and_prev_skip_weekend() {
+        let calendar = calendar();
+        
+        assert_eq!(calendar.next(20230630), Some(20230703));
+        assert_eq!(calendar.next(20230701), Some(20230703));
+        assert_eq!(calendar.prev(20230703), Some(20230630));
+        assert_eq!(calendar.prev(20230702), Some(20230630));
+        assert_eq!(calendar.next(20230704), None);
+        assert_eq!(calendar.prev(20230629), None);
+    }
+
+    #[test]
+    fn offset_counts_trading_days_only() {
+        let calendar = calendar();
+        
+        assert_eq!(calendar.offset(20230630, 1), Some(20230703));
+        assert_eq!(calendar.offset(20230704, -2), Some(20230630));
+        assert_eq!(calendar.offset(20230704, -3), Some(20230629));
+        // 非交易日先对齐到之前最近的交易日
+        assert_eq!(calendar.offset(20230702, 1), Some(20230703));
+        assert_eq!(calendar.offset(20230704, 1), None);
+        assert_eq!(calendar.offset(20230629, -1), None);
+    }
+
+    #[test]
+    fn between_excludes_weekend() {
+        let calendar = calendar();
+        
+        assert_eq!(calendar.between(20230629, 20230704), 3);
+        assert_eq!(calendar.between(20230704, 20230629), 3);
+        assert_eq!(calendar.between(20230630, 20230703), 1);
+        assert_eq!(calendar.between(20230630, 20230630), 0);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::stock::calendar::TradingCalendar;
+use chrono::NaiveDate;
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+
+/// 单只股票的数据覆盖情况
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolCoverage {
+    pub symbol: String,
+    pub bar_count: usize,
+    /// 最早K线日期(YYYYMMDD)
+    pub first_date: Option<i32>,
+    /// 最新K线日期(YYYYMMDD)
+    pub last_date: Option<i32>,
+    /// 成交量为0的天数(停牌等)
+    pub zero_volume_days: usize,
+    /// 相邻K线之间最大的自然日间隔
+    pub max_gap_days: i64,
+}
+
+impl SymbolCoverage {
+    /// 由K线序列(按日期倒序)统计覆盖情况
+    pub fn from_bars(symbol: &str, bars: &[DailyBar]) -> Self {
+        let parse = |date: i32| NaiveDate::parse_from_str(&date.to_string(), "%Y%m%d").ok();
+        
+        let max_gap_days = bars.windows(2)
+            .filter_map(|pair| Some((parse(pair[0].date)? - parse(pair[1].date)?).num_days()))
+            .max()
+            .unwrap_or(0);
+        
+        Self {
+            symbol: symbol.to_string(),
+            bar_count: bars.len(),
+            first_date: bars.last().map(|bar| bar.date),
+            last_date: bars.first().map(|bar| bar.date),
+            zero_volume_days: bars.iter().filter(|bar| bar.volume == 0).count(),
+            max_gap_days,
+        }
+    }
+}
+
+/// 按数据质量从差到好排序: K线少的在前，其次是间隔大、停牌多的
+pub fn sort_worst_first(coverage: &mut [SymbolCoverage]) {
+    coverage.sort_by(|a, b| {
+        a.bar_count.cmp(&b.bar_count)
+            .then(b.max_gap_days.cmp(&a.max_gap_days))
+            .then(b.zero_volume_days.cmp(&a.zero_volume_days))
+            .then(a.symbol.cmp(&b.symbol))
+    });
+}
+
+/// K线数量的中位数
+pub fn median_bar_count(coverage: &[SymbolCoverage]) -> usize {
+    let mut counts: Vec<usize> = coverage.iter().map(|c| c.bar_count).collect();
+    counts.sort_unstable();
+    counts.get(counts.len() / 2).copied().unwrap_or(0)
+}
+
+/// 格式化覆盖情况最差的limit只股票
+pub fn format_coverage(coverage: &[SymbolCoverage], limit: usize) -> String {
+    let mut sorted = coverage.to_vec();
+    sort_worst_first(&mut sorted);
+    
+    let mut report = String::new();
+    report.push_str(&format!("数据覆盖: {} 只股票, K线数量中位数 {}\n",
+        coverage.len(), median_bar_count(coverage)));
+    report.push_str(&format!("{:<10} {:>8} {:>10} {:>10} {:>8} {:>8}\n",
+        "代码", "K线数", "最早", "最新", "零成交", "最大间隔"));
+    
+    for c in sorted.iter().take(limit) {
+        report.push_str(&format!("{:<10} {:>8} {:>10} {:>10} {:>8} {:>8}\n",
+            c.symbol,
+            c.bar_count,
+            c.first_date.map(|d| d.to_string()).unwrap_or_else(|| "-".to_string()),
+            c.last_date.map(|d| d.to_string()).unwrap_or_else(|| "-".to_string()),
+            c.zero_volume_days,
+            c.max_gap_days));
+    }
+    
+    report
+}
+
+/// 单只股票K线与交易日历的对齐情况
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolAlignment {
+    pub symbol: String,
+    /// 最新K线日期(YYYYMMDD)
+    pub last_date: Option<i32>,
+    /// 最新K线落后全局最新交易日的交易日数
+    pub lag_days: usize,
+    /// 最早到最新K线之间交易日历上缺少K线的交易日数
+    pub gap_days: usize,
+    /// 落后超过允许天数，不参与回测
+    pub stale: bool,
+}
+
+/// 加载后的数据对齐检查结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataQualityReport {
+    /// 全部股票中最新的交易日
+    pub latest_date: Option<i32>,
+    /// 允许落后的最大交易日数，None时不检查
+    pub max_staleness_days: Option<usize>,
+    pub symbols: Vec<SymbolAlignment>,
+}
+
+impl DataQualityReport {
+    /// 按交易日历检查每只股票(K线按日期倒序)
+    pub fn new<'a>(
+        series: impl IntoIterator<Item = (&'a str, &'a [DailyBar])>,
+        calendar: &TradingCalendar,
+        max_staleness_days: Option<usize>,
+    ) -> Self {
+        let latest_date = calendar.latest();
+        
+        let symbols = series.into_iter()
+            .map(|(symbol, bars)| {
+                let last_date = bars.first().map(|bar| bar.date);
+                let lag_days = match (last_date, latest_date) {
+                    (Some(last), Some(latest)) => calendar.between(last, latest),
+                    _ => 0,
+                };
+                // 区间内的交易日数(含首尾)减去K线数
+                let gap_days = match (bars.last(), bars.first()) {
+                    (Some(first), Some(last)) => (calendar.between(first.date, last.date) + 1).saturating_sub(bars.len()),
+                    _ => 0,
+                };
+                
+                SymbolAlignment {
+                    symbol: symbol.to_string(),
+                    last_date,
+                    lag_days,
+                    gap_days,
+                    stale: max_staleness_days.is_some_and(|max| lag_days > max),
+                }
+            })
+            .collect();
+        
+        Self { latest_date, max_staleness_days, symbols }
+    }
+    
+    /// 落后超过允许天数的股票
+    pub fn stale_symbols(&self) -> Vec<&SymbolAlignment> {
+        self.symbols.iter().filter(|symbol| symbol.stale).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(date: i32, volume: i64) -> DailyBar {
+        DailyBar {
+            date,
+            open: 10.0,
+            high: 10.0,
+            low: 10.0,
+            close: 10.0,
+            volume,
+            amount: 100000,
+        }
+    }
+
+    #[test]
+    fn counts_gaps_and_zero_volume_days() {
+        // 6月30日至7月3日跨周末间隔3天，6月20日至6月28日停牌间隔8天
+        let bars = vec![
+            bar(20230703, 10000),
+            bar(20230630, 0),
+            bar(20230629, 10000),
+            bar(20230628, 0),
+            bar(20230620, 10000),
+        ];
+        
+        let coverage = SymbolCoverage::from_bars("000001.SZ", &bars);
+        
+        assert_eq!(coverage.bar_count, 5);
+        assert_eq!(coverage.first_date, Some(20230620));
+        assert_eq!(coverage.last_date, Some(20230703));
+        assert_eq!(coverage.zero_volume_days, 2);
+        assert_eq!(coverage.max_gap_days, 8);
+    }
+
+    #[test]
+    fn gap_crosses_month_boundary_in_calendar_days() {
+        let coverage = SymbolCoverage::from_bars("000001.SZ", &[bar(20230301, 10000), bar(20230228, 10000)]);
+        
+        assert_eq!(coverage.max_gap_days, 1);
+    }
+
+    #[test]
+    fn empty_series_has_no_dates() {
+        let coverage = SymbolCoverage::from_bars("000001.SZ", &[]);
+        
+        assert_eq!(coverage.bar_count, 0);
+        assert_eq!((coverage.first_date, coverage.last_date), (None, None));
+        assert_eq!(coverage.max_gap_days, 0);
+    }
+
+    #[test]
+    fn worst_offenders_are_listed_first() {
+        let short = SymbolCoverage::from_bars("600000.SH", &[bar(20230630, 10000)]);
+        let gappy = SymbolCoverage::from_bars("000002.SZ", &[bar(20230630, 10000), bar(20230601, 10000)]);
+        let clean = SymbolCoverage::from_bars("000001.SZ", &[bar(20230630, 10000), bar(20230629, 10000)]);
+        let mut coverage = vec![clean.clone(), gappy.clone(), short.clone()];
+        
+        sort_worst_first(&mut coverage);
+        
+        assert_eq!(coverage, vec![short, gappy, clean]);
+        assert_eq!(median_bar_count(&coverage), 2);
+        let report = format_coverage(&coverage, 1);
+        assert!(report.contains("600000.SH"));
+        assert!(!report.contains("000001.SZ"));
+    }
+}
@@ -0,0 +1,330 @@
+use crate::stock::series::{self, BarOrder};
+use crate::utils::csv;
+use anyhow::{anyhow, bail, Context, Result};
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// 股票代码的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolSource {
+    /// 从指定列读取，一个文件可包含多只股票
+    Column(String),
+    /// 整个文件属于同一只股票
+    Fixed(String),
+    /// 使用文件名(不含扩展名)作为股票代码
+    FileStem,
+}
+
+/// CSV列映射，日期列支持YYYY-MM-DD和YYYYMMDD两种格式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvFormat {
+    pub symbol: SymbolSource,
+    pub date_column: String,
+    pub open_column: String,
+    pub high_column: String,
+    pub low_column: String,
+    pub close_column: String,
+    pub volume_column: String,
+    /// 成交额列，None表示文件中没有成交额
+    pub amount_column: Option<String>,
+}
+
+impl Default for CsvFormat {
+    fn default() -> Self {
+        Self {
+            symbol: SymbolSource::Column("symbol".to_string()),
+            date_column: "date".to_string(),
+            open_column: "open".to_string(),
+            high_column: "high".to_string(),
+            low_column: "low".to_string(),
+            close_column: "close".to_string(),
+            volume_column: "volume".to_string(),
+            amount_column: Some("amount".to_string()),
+        }
+    }
+}
+
+impl CsvFormat {
+    /// 每只股票一个文件，以文件名作为股票代码
+    pub fn per_file() -> Self {
+        Self {
+            symbol: SymbolSource::FileStem,
+            ..Self::default()
+        }
+    }
+}
+
+/// 表头中各列的位置
+struct ColumnIndex {
+    symbol: Option<usize>,
+    date: usize,
+    open: usize,
+    high: usize,
+    low: usize,
+    close: usize,
+    volume: usize,
+    amount: Option<usize>,
+}
+
+impl ColumnIndex {
+    fn new(header: &[String], format: &CsvFormat) -> Result<Self> {
+        let find = |name: &str| {
+            header.iter()
+                .position(|column| column.trim().eq_ignore_ascii_case(name))
+                .ok_or_else(|| anyhow!("缺少列: {}", name))
+        };
+        
+        Ok(Self {
+            symbol: match &format.symbol {
+                SymbolSource::Column(name) => Some(find(name)?),
+                _ => None,
+            },
+            date: find(&format.date_column)?,
+            open: find(&format.open_column)?,
+            high: find(&format.high_column)?,
+            low: find(&format.low_column)?,
+            close: find(&format.close_column)?,
+            volume: find(&format.volume_column)?,
+            amount: match &format.amount_column {
+                Some(name) => Some(find(name)?),
+                None => None,
+            },
+        })
+    }
+}
+
+/// 解析YYYY-MM-DD或YYYYMMDD格式的日期为YYYYMMDD整数
+fn parse_date(field: &str) -> Result<i32> {
+    let digits: String = field.trim().chars().filter(|c| *c != '-').collect();
+    
+    if digits.len() != 8 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        bail!("无法解析日期: {}", field);
+    }
+    
+    let date: i32 = digits.parse()?;
+    let (month, day) = (date / 100 % 100, date % 100);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        bail!("无效日期: {}", field);
+    }
+    
+    Ok(date)
+}
+
+fn parse_field<T: std::str::FromStr>(fields: &[String], idx: usize, name: &str) -> Result<T> {
+    let field = fields.get(idx).ok_or_else(|| anyhow!("缺少字段: {}", name))?;
+    field.trim().parse().map_err(|_| anyhow!("无法解析{}: {}", name, field))
+}
+
+/// 解析一行K线
+fn parse_bar(fields: &[String], columns: &ColumnIndex) -> Result<DailyBar> {
+    let date_field = fields.get(columns.date).ok_or_else(|| anyhow!("缺少字段: date"))?;
+    
+    let bar = DailyBar {
+        date: parse_date(date_field)?,
+        open: parse_field(fields, columns.open, "open")?,
+        high: parse_field(fields, columns.high, "high")?,
+        low: parse_field(fields, columns.low, "low")?,
+        close: parse_field(fields, columns.close, "close")?,
+        // 成交量可能带小数，取整处理
+        volume: parse_field::<f64>(fields, columns.volume, "volume")? as i64,
+        amount: match columns.amount {
+            Some(idx) => parse_field::<f64>(fields, idx, "amount")? as i64,
+            None => 0,
+        },
+    };
+    
+    if bar.open <= 0.0 || bar.high <= 0.0 || bar.low <= 0.0 || bar.close <= 0.0 {
+        bail!("价格必须为正数");
+    }
+    
+    Ok(bar)
+}
+
+/// 校验日期严格单调，并统一为按日期倒序(最新在前)
+fn normalize_order(symbol: &str, bars: Vec<DailyBar>) -> Result<Vec<DailyBar>> {
+    let ascending = bars.windows(2).all(|w| w[0].date < w[1].date);
+    let descending = bars.windows(2).all(|w| w[0].date > w[1].date);
+    
+    if !ascending && !descending {
+        bail!("股票 {} 的日期不是严格单调的", symbol);
+    }
+    
+    Ok(series::into_order(bars, BarOrder::NewestFirst))
+}
+
+/// 从CSV文件加载日线数据，返回的K线按日期倒序
+pub fn load_daily_bars_csv(path: &Path, format: &CsvFormat) -> Result<Vec<(String, Vec<DailyBar>)>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("读取CSV失败: {}", path.display()))?;
+    
+    let mut lines = content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let header = match lines.next() {
+        Some((_, line)) => csv::split_row(line),
+        None => return Ok(Vec::new()),
+    };
+    let columns = ColumnIndex::new(&header, format)
+        .with_context(|| format!("CSV表头无效: {}", path.display()))?;
+    
+    let file_symbol = match &format.symbol {
+        SymbolSource::Column(_) => None,
+        SymbolSource::Fixed(symbol) => Some(symbol.clone()),
+        SymbolSource::FileStem => Some(path.file_stem()
+            .ok_or_else(|| anyhow!("无法从文件名获取股票代码: {}", path.display()))?
+            .to_string_lossy()
+            .to_string()),
+    };
+    
+    let mut order = Vec::new();
+    let mut grouped: HashMap<String, Vec<DailyBar>> = HashMap::new();
+    
+    for (line_idx, line) in lines {
+        let fields = csv::split_row(line);
+        let line_no = line_idx + 1;
+        
+        let symbol = match (&file_symbol, columns.symbol) {
+            (Some(symbol), _) => symbol.clone(),
+            (None, Some(idx)) => fields.get(idx)
+                .map(|field| field.trim().to_string())
+                .filter(|field| !field.is_empty())
+                .ok_or_else(|| anyhow!("{}:{}: 缺少股票代码", path.display(), line_no))?,
+            (None, None) => unreachable!(),
+        };
+        
+        let bar = parse_bar(&fields, &columns)
+            .with_context(|| format!("{}:{}: 无效的K线", path.display(), line_no))?;
+        
+        grouped.entry(symbol.clone())
+            .or_insert_with(|| {
+                order.push(symbol);
+                Vec::new()
+            })
+            .push(bar);
+    }
+    
+    order.into_iter()
+        .map(|symbol| {
+            let bars = grouped.remove(&symbol).unwrap_or_default();
+            let bars = normalize_order(&symbol, bars)
+                .with_context(|| format!("CSV数据无效: {}", path.display()))?;
+            Ok((symbol, bars))
+        })
+        .collect()
+}
+
+/// 加载目录下所有CSV文件，按文件名排序
+pub fn load_daily_bars_dir(dir: &Path, format: &CsvFormat) -> Result<Vec<(String, Vec<DailyBar>)>> {
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .with_context(|| format!("读取数据目录失败: {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv")))
+        .collect();
+    paths.sort();
+    
+    let mut stock_data = Vec::new();
+    for path in paths {
+        stock_data.extend(load_daily_bars_csv(&path, format)?);
+    }
+    
+    Ok(stock_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// 在临时目录写入CSV文件
+    fn write_csv(name: &str, content: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("strategy_lab_csv_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn parses_dashed_dates_and_normalizes_to_newest_first() {
+        let path = write_csv("dashed.csv", "\
+symbol,date,open,high,low,close,volume,amount
+000001.SZ,2023-01-03,10.0,10.5,9.8,10.2,1000,10200
+000001.SZ,2023-01-04,10.2,10.8,10.1,10.6,1200,12720
+000001.SZ,2023-01-05,10.6,10.9,10.4,10.5,900,9450
+");
+        
+        let data = load_daily_bars_csv(&path, &CsvFormat::default()).unwrap();
+        
+        assert_eq!(data.len(), 1);
+        let (symbol, bars) = &data[0];
+        assert_eq!(symbol, "000001.SZ");
+        let dates: Vec<i32> = bars.iter().map(|bar| bar.date).collect();
+        assert_eq!(dates, vec![20230105, 20230104, 20230103]);
+        assert_eq!(bars[0].close, 10.5);
+        assert_eq!(bars[2].amount, 10200);
+    }
+
+    #[test]
+    fn parses_compact_dates_with_file_stem_symbol() {
+        let path = write_csv("600000.SH.csv", "\
+date,open,high,low,close,volume
+20230105,8.0,8.2,7.9,8.1,500.5
+20230104,7.9,8.0,7.8,8.0,400
+");
+        let format = CsvFormat { amount_column: None, ..CsvFormat::per_file() };
+        
+        let data = load_daily_bars_csv(&path, &format).unwrap();
+        
+        assert_eq!(data[0].0, "600000.SH");
+        let bars = &data[0].1;
+        assert_eq!((bars[0].date, bars[1].date), (20230105, 20230104));
+        assert_eq!(bars[0].volume, 500);
+        assert_eq!(bars[0].amount, 0);
+    }
+
+    #[test]
+    fn malformed_row_reports_line_number() {
+        let path = write_csv("malformed.csv", "\
+symbol,date,open,high,low,close,volume,amount
+000001.SZ,2023-01-03,10.0,10.5,9.8,10.2,1000,10200
+000001.SZ,2023-01-04,abc,10.8,10.1,10.6,1200,12720
+");
+        
+        let err = load_daily_bars_csv(&path, &CsvFormat::default()).unwrap_err();
+        
+        assert!(format!("{:#}", err).contains("malformed.csv:3"), "{:#}", err);
+    }
+
+    #[test]
+    fn non_positive_price_is_rejected() {
+        let path = write_csv("negative.csv", "\
+symbol,date,open,high,low,close,volume,amount
+000001.SZ,2023-01-03,10.0,10.5,-9.8,10.2,1000,10200
+");
+        
+        let err = load_daily_bars_csv(&path, &CsvFormat::default()).unwrap_err();
+        
+        assert!(format!("{:#}", err).contains("negative.csv:2"), "{:#}", err);
+    }
+
+    #[test]
+    fn non_monotonic_dates_are_rejected() {
+        let path = write_csv("unordered.csv", "\
+symbol,date,open,high,low,close,volume,amount
+000001.SZ,20230103,10.0,10.5,9.8,10.2,1000,10200
+000001.SZ,20230105,10.6,10.9,10.4,10.5,900,9450
+000001.SZ,20230104,10.2,10.8,10.1,10.6,1200,12720
+");
+        
+        assert!(load_daily_bars_csv(&path, &CsvFormat::default()).is_err());
+    }
+
+    #[test]
+    fn invalid_dates_are_rejected() {
+        assert_eq!(parse_date("2023-01-05").unwrap(), 20230105);
+        assert_eq!(parse_date("20230105").unwrap(), 20230105);
+        assert!(parse_date("2023-13-01").is_err());
+        assert!(parse_date("2023/01/05").is_err());
+        assert!(parse_date("230105").is_err());
+    }
+}
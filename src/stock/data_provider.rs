@@ -1,15 +1,326 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use egostrategy_datahub::data_provider::StockDataProvider as DataHubProvider;
 use egostrategy_datahub::models::stock::{StockData as Stock, DailyData as DailyBar};
+use crate::stock::adjust::{AdjustFactors, AdjustMode};
+use crate::stock::benchmark::DEFAULT_BENCHMARK;
+use crate::stock::coverage::SymbolCoverage;
+use crate::stock::csv_loader::{self, CsvFormat, SymbolSource};
+use crate::stock::disk_cache::DiskCache;
+use crate::stock::retry::{FetchError, LoadReport, RetryPolicy};
+use crate::stock::series::{self, BarOrder};
+use crate::stock::snapshot::UniverseSnapshot;
+use crate::utils::csv;
+use anyhow::Context;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use log::{info, debug};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use log::{info, debug, error, warn};
+use rayon::prelude::*;
+use serde::{Serialize, Deserialize};
+
+/// 价格规则的排除原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceRejection {
+    /// 低于最低价
+    BelowMin,
+    /// 高于最高价
+    AboveMax,
+}
+
+/// 股票池过滤规则
+#[derive(Debug, Clone, PartialEq)]
+pub struct StockFilterConfig {
+    /// 排除的代码前缀
+    pub exclude_prefixes: Vec<String>,
+    /// 只保留的代码前缀，None表示不限制
+    pub include_prefixes: Option<Vec<String>>,
+    /// 是否排除名称含ST的股票
+    pub exclude_st: bool,
+    /// 最新收盘价下限
+    pub min_price: Option<f32>,
+    /// 最新收盘价上限
+    pub max_price: Option<f32>,
+}
+
+impl Default for StockFilterConfig {
+    /// 默认排除科创板(688/689)和创业板(300/301/302)以及股价高于100元的股票
+    fn default() -> Self {
+        Self {
+            exclude_prefixes: ["688", "689", "300", "301", "302"]
+                .iter()
+                .map(|prefix| prefix.to_string())
+                .collect(),
+            include_prefixes: None,
+            exclude_st: false,
+            min_price: None,
+            max_price: Some(100.0),
+        }
+    }
+}
+
+impl StockFilterConfig {
+    /// 代码是否满足前缀规则
+    pub fn accepts_symbol(&self, symbol: &str) -> bool {
+        if self.exclude_prefixes.iter().any(|prefix| symbol.starts_with(prefix.as_str())) {
+            return false;
+        }
+        
+        match &self.include_prefixes {
+            Some(prefixes) => prefixes.iter().any(|prefix| symbol.starts_with(prefix.as_str())),
+            None => true,
+        }
+    }
+    
+    /// 名称是否满足ST规则
+    pub fn accepts_name(&self, name: &str) -> bool {
+        !(self.exclude_st && is_st_name(name))
+    }
+    
+    /// 最新收盘价是否在价格区间内
+    pub fn accepts_price(&self, close: f32) -> bool {
+        self.price_rejection(close).is_none()
+    }
+    
+    /// 最新收盘价不满足的价格规则
+    pub fn price_rejection(&self, close: f32) -> Option<PriceRejection> {
+        if self.min_price.is_some_and(|min| close < min) {
+            Some(PriceRejection::BelowMin)
+        } else if self.max_price.is_some_and(|max| close > max) {
+            Some(PriceRejection::AboveMax)
+        } else {
+            None
+        }
+    }
+    
+    /// K线是否满足价格规则，K线按日期倒序，第一根为最新
+    pub fn accepts_bars(&self, bars: &[DailyBar]) -> bool {
+        self.bars_rejection(bars).is_none()
+    }
+    
+    /// K线不满足的价格规则，没有K线时按价格为0处理
+    pub fn bars_rejection(&self, bars: &[DailyBar]) -> Option<PriceRejection> {
+        match bars.first() {
+            Some(latest) => self.price_rejection(latest.close),
+            None if self.min_price.is_some() || self.max_price.is_some() => Some(PriceRejection::BelowMin),
+            None => None,
+        }
+    }
+}
+
+/// 与提供者缓存共享的K线
+pub type SharedBars = Arc<Vec<DailyBar>>;
+
+/// 增量刷新结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefreshReport {
+    /// 追加了新K线的股票及新增数量
+    pub updated: Vec<(String, usize)>,
+    /// 没有新数据的股票
+    pub unchanged: Vec<String>,
+    /// 缓存最新日期不在新数据中(存在缺口或历史被修订)，整体重新获取的股票
+    pub refetched: Vec<String>,
+    /// 未缓存或上游不存在的股票，未做处理
+    pub skipped: Vec<String>,
+}
+
+/// 磁盘缓存命中统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+/// 名称是否为ST或*ST股票
+pub fn is_st_name(name: &str) -> bool {
+    let name = name.trim_start().to_uppercase();
+    name.starts_with("ST") || name.starts_with("*ST") || name.starts_with("S*ST")
+}
+
+/// 按代码和ST状态返回涨跌停幅度
+pub fn limit_band(symbol: &str, is_st: bool) -> f32 {
+    if symbol.starts_with("688") || symbol.starts_with("689") || symbol.starts_with("300")
+        || symbol.starts_with("301") || symbol.starts_with("302") {
+        // 科创板、创业板(含ST)为20%
+        0.2
+    } else if symbol.starts_with('4') || symbol.starts_with('8') || symbol.starts_with("92") {
+        // 北交所为30%
+        0.3
+    } else if is_st {
+        0.05
+    } else {
+        0.1
+    }
+}
+
+/// 按代码前缀推断交易所: 6开头为上交所，0/3开头为深交所，4/8/92开头为北交所
+pub fn exchange_from_symbol(symbol: &str) -> Option<&'static str> {
+    if symbol.starts_with("92") || symbol.starts_with('4') || symbol.starts_with('8') {
+        Some("BJ")
+    } else if symbol.starts_with('6') {
+        Some("SH")
+    } else if symbol.starts_with('0') || symbol.starts_with('3') {
+        Some("SZ")
+    } else {
+        None
+    }
+}
+
+/// 股票元数据
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StockMeta {
+    pub symbol: String,
+    pub name: String,
+    pub exchange: String,
+    /// 上市日期(YYYYMMDD)，datahub未提供时取最早一根K线的日期
+    pub list_date: Option<i32>,
+    pub is_st: bool,
+}
+
+impl StockMeta {
+    /// 由datahub股票数据构建，缺失的交易所按代码前缀推断
+    pub fn from_stock(stock: &Stock) -> Self {
+        let exchange = if stock.exchange.is_empty() {
+            exchange_from_symbol(&stock.symbol).unwrap_or_default().to_string()
+        } else {
+            stock.exchange.clone()
+        };
+        
+        Self {
+            symbol: stock.symbol.clone(),
+            name: stock.name.clone(),
+            exchange,
+            // K线按日期倒序，最后一根为最早
+            list_date: stock.daily.last().map(|bar| bar.date),
+            is_st: is_st_name(&stock.name),
+        }
+    }
+}
+
+/// 从股票列表文件读取的自定义股票池
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Universe {
+    /// 数据源中存在的股票代码，按文件中的顺序
+    pub symbols: Vec<String>,
+    /// 数据源中不存在的股票代码
+    pub unknown: Vec<String>,
+}
+
+/// 解析股票列表文件内容
+///
+/// 每行一个代码，可带逗号分隔的名称列；'#'之后为注释，空行和symbol表头被忽略
+pub fn parse_universe(content: &str) -> Vec<String> {
+    let mut symbols = Vec::new();
+    
+    for line in content.lines() {
+        let line = match line.find('#') {
+            Some(pos) => &line[..pos],
+            None => line,
+        };
+        
+        let Some(symbol) = csv::split_row(line).into_iter().next() else {
+            continue;
+        };
+        let symbol = symbol.trim();
+        
+        if symbol.is_empty() || symbol.eq_ignore_ascii_case("symbol") {
+            continue;
+        }
+        
+        if !symbols.iter().any(|existing| existing == symbol) {
+            symbols.push(symbol.to_string());
+        }
+    }
+    
+    symbols
+}
+
+/// 查询有歧义时最多给出的候选数量
+const MAX_SUGGESTIONS: usize = 10;
+
+/// 搜索时统一全角ASCII为半角并转为小写
+fn normalize_query(text: &str) -> String {
+    text.trim()
+        .chars()
+        .map(|c| match c {
+            '\u{3000}' => ' ',
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// 匹配程度，越小越好: 代码完全相同、名称完全相同、代码前缀、名称前缀、名称包含
+fn match_rank(meta: &StockMeta, query: &str) -> Option<u8> {
+    let symbol = normalize_query(&meta.symbol);
+    let name = normalize_query(&meta.name);
+    
+    if symbol == query {
+        Some(0)
+    } else if name == query {
+        Some(1)
+    } else if symbol.starts_with(query) {
+        Some(2)
+    } else if name.starts_with(query) {
+        Some(3)
+    } else if name.contains(query) {
+        Some(4)
+    } else {
+        None
+    }
+}
+
+/// 按代码前缀或名称子串搜索股票，结果按匹配程度和代码排序
+pub fn search_metas(metas: &[StockMeta], query: &str) -> Vec<StockMeta> {
+    let query = normalize_query(query);
+    if query.is_empty() {
+        return Vec::new();
+    }
+    
+    let mut matched: Vec<(u8, &StockMeta)> = metas.iter()
+        .filter_map(|meta| match_rank(meta, &query).map(|rank| (rank, meta)))
+        .collect();
+    matched.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.symbol.cmp(&b.1.symbol)));
+    
+    matched.into_iter().map(|(_, meta)| meta.clone()).collect()
+}
+
+/// 将查询解析为唯一的股票代码；代码或名称完全相同时直接采用，多个候选时报错并给出建议
+pub fn resolve_query(metas: &[StockMeta], query: &str) -> Result<String> {
+    let matches = search_metas(metas, query);
+    let normalized = normalize_query(query);
+    
+    match matches.as_slice() {
+        [] => bail!("未找到匹配 \"{}\" 的股票", query),
+        [only] => Ok(only.symbol.clone()),
+        [best, ..] if match_rank(best, &normalized).is_some_and(|rank| rank <= 1) => Ok(best.symbol.clone()),
+        _ => {
+            let suggestions: Vec<String> = matches.iter()
+                .take(MAX_SUGGESTIONS)
+                .map(|meta| format!("{} {}", meta.symbol, meta.name))
+                .collect();
+            bail!("\"{}\" 匹配到 {} 只股票，请指定更精确的代码或名称: {}",
+                query, matches.len(), suggestions.join(", "))
+        }
+    }
+}
 
 /// 优化的股票数据提供者
 pub struct StockDataProvider {
-    provider: DataHubProvider,
-    cache: Arc<Mutex<HashMap<String, Stock>>>,
-    name_cache: Arc<Mutex<HashMap<String, String>>>,
+    provider: RwLock<Option<Arc<DataHubProvider>>>,
+    /// 日线缓存，K线以Arc共享给引擎，避免重复复制
+    cache: Arc<RwLock<HashMap<String, Arc<Vec<DailyBar>>>>>,
+    meta_cache: Arc<RwLock<HashMap<String, StockMeta>>>,
+    disk_cache: Option<DiskCache>,
+    disk_hits: AtomicUsize,
+    disk_misses: AtomicUsize,
+    index_cache: RwLock<HashMap<String, Vec<DailyBar>>>,
+    index_dir: PathBuf,
+    benchmark_symbol: String,
+    adjust_factors: RwLock<AdjustFactors>,
+    retry_policy: RetryPolicy,
 }
 
 impl StockDataProvider {
@@ -19,127 +330,1083 @@ impl StockDataProvider {
         let provider = DataHubProvider::new_sync()?;
         info!("数据提供者初始化完成");
         
-        Ok(Self {
-            provider,
-            cache: Arc::new(Mutex::new(HashMap::new())),
-            name_cache: Arc::new(Mutex::new(HashMap::new())),
-        })
+        Ok(Self::from_upstream(Some(provider), None))
+    }
+    
+    /// 创建带磁盘缓存的数据提供者，上游数据源在首次缓存未命中时才初始化
+    pub fn with_disk_cache(dir: PathBuf) -> Self {
+        Self::from_upstream(None, Some(DiskCache::new(dir)))
+    }
+    
+    fn from_upstream(provider: Option<DataHubProvider>, disk_cache: Option<DiskCache>) -> Self {
+        Self {
+            provider: RwLock::new(provider.map(Arc::new)),
+            cache: Arc::new(RwLock::new(HashMap::new())),
+            meta_cache: Arc::new(RwLock::new(HashMap::new())),
+            disk_cache,
+            disk_hits: AtomicUsize::new(0),
+            disk_misses: AtomicUsize::new(0),
+            index_cache: RwLock::new(HashMap::new()),
+            index_dir: PathBuf::from("data/index"),
+            benchmark_symbol: DEFAULT_BENCHMARK.to_string(),
+            adjust_factors: RwLock::new(AdjustFactors::default()),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+    
+    /// 加载复权因子文件，返回文件中的股票数量
+    ///
+    /// datahub不提供复权因子，复权依赖用户提供的因子文件
+    pub fn load_adjust_factors(&self, path: &Path) -> Result<usize> {
+        let factors = AdjustFactors::load(path)?;
+        let count = factors.len();
+        self.adjust_factors.write().unwrap().extend(factors);
+        
+        info!("加载了 {} 只股票的复权因子", count);
+        Ok(count)
+    }
+    
+    /// 按复权方式调整K线，不需要复权时直接返回共享的K线
+    fn adjust(&self, symbol: &str, bars: Arc<Vec<DailyBar>>, mode: AdjustMode) -> Arc<Vec<DailyBar>> {
+        let factors = self.adjust_factors.read().unwrap();
+        if mode == AdjustMode::None || !factors.contains(symbol) {
+            return bars;
+        }
+        
+        let mut bars = Arc::unwrap_or_clone(bars);
+        factors.apply(symbol, &mut bars, mode);
+        Arc::new(bars)
+    }
+    
+    /// 设置批量加载时暂时性错误的重试策略
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = policy;
+    }
+    
+    /// 设置指数CSV所在目录，每个指数一个以代码命名的文件
+    pub fn set_index_dir(&mut self, dir: PathBuf) {
+        self.index_dir = dir;
+    }
+    
+    /// 设置基准指数代码
+    pub fn set_benchmark_symbol(&mut self, symbol: &str) {
+        self.benchmark_symbol = symbol.to_string();
+    }
+    
+    /// 获取指数日线数据，带缓存
+    ///
+    /// datahub不提供指数数据(指数代码可能与个股重复)，从指数目录下的CSV读取
+    pub fn get_index_bars(&self, index_symbol: &str) -> Option<Vec<DailyBar>> {
+        {
+            let cache = self.index_cache.read().unwrap();
+            if let Some(bars) = cache.get(index_symbol) {
+                return Some(bars.clone());
+            }
+        }
+        
+        let path = self.index_dir.join(format!("{}.csv", index_symbol));
+        let format = CsvFormat {
+            symbol: SymbolSource::Fixed(index_symbol.to_string()),
+            ..CsvFormat::default()
+        };
+        
+        let bars = match csv_loader::load_daily_bars_csv(&path, &format) {
+            Ok(mut data) if !data.is_empty() => data.remove(0).1,
+            Ok(_) => {
+                warn!("指数文件为空: {}", path.display());
+                return None;
+            }
+            Err(e) => {
+                warn!("加载指数 {} 失败: {:#}", index_symbol, e);
+                return None;
+            }
+        };
+        
+        let mut cache = self.index_cache.write().unwrap();
+        cache.insert(index_symbol.to_string(), bars.clone());
+        
+        Some(bars)
+    }
+    
+    /// 获取基准指数日线数据
+    pub fn get_benchmark(&self) -> Option<Vec<DailyBar>> {
+        self.get_index_bars(&self.benchmark_symbol)
+    }
+    
+    /// 设置磁盘缓存的过期天数
+    pub fn set_staleness_days(&mut self, days: i64) {
+        if let Some(disk_cache) = &mut self.disk_cache {
+            disk_cache.staleness_days = days;
+        }
+    }
+    
+    /// 磁盘缓存命中统计
+    pub fn cache_stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.disk_hits.load(Ordering::Relaxed),
+            misses: self.disk_misses.load(Ordering::Relaxed),
+        }
+    }
+    
+    /// 清空内存缓存和磁盘缓存
+    pub fn clear_cache(&self) -> Result<()> {
+        self.cache.write().unwrap().clear();
+        self.meta_cache.write().unwrap().clear();
+        
+        if let Some(disk_cache) = &self.disk_cache {
+            disk_cache.clear()?;
+        }
+        
+        Ok(())
+    }
+    
+    /// 获取上游数据源，未初始化时按需初始化
+    fn upstream(&self) -> Option<Arc<DataHubProvider>> {
+        self.try_upstream().ok()
+    }
+    
+    /// 获取上游数据源，初始化失败视为暂时性错误，下次调用时重新初始化
+    fn try_upstream(&self) -> Result<Arc<DataHubProvider>, FetchError> {
+        if let Some(provider) = self.provider.read().unwrap().as_ref() {
+            return Ok(provider.clone());
+        }
+        
+        info!("初始化数据提供者...");
+        match DataHubProvider::new_sync() {
+            Ok(provider) => {
+                // 并发初始化时保留先完成的实例
+                let mut slot = self.provider.write().unwrap();
+                let provider = slot.get_or_insert_with(|| Arc::new(provider)).clone();
+                info!("数据提供者初始化完成");
+                Ok(provider)
+            }
+            Err(e) => {
+                error!("初始化数据提供者失败: {}", e);
+                Err(FetchError::Transient(format!("初始化数据提供者失败: {}", e)))
+            }
+        }
+    }
+    
+    /// 使用内存中的股票数据创建数据提供者，不访问数据源
+    pub fn with_stocks(stocks: Vec<Stock>) -> Result<Self> {
+        let provider = DataHubProvider::new_with_data(stocks)?;
+        
+        Ok(Self::from_upstream(Some(provider), None))
     }
     
     /// 获取所有股票代码
     pub fn get_all_stocks(&self) -> Vec<String> {
-        let stocks = self.provider.get_all_stocks();
+        let Some(provider) = self.upstream() else {
+            return Vec::new();
+        };
+        let stocks = provider.get_all_stocks();
         info!("获取到 {} 只股票", stocks.len());
         stocks.iter().map(|stock| stock.symbol.clone()).collect()
     }
     
-    /// 过滤股票
+    /// 按默认规则过滤股票
     pub fn filter_stocks(&self, symbols: Vec<String>) -> Vec<String> {
+        self.filter_stocks_with(symbols, &StockFilterConfig::default())
+    }
+    
+    /// 按指定规则过滤股票代码和名称，价格规则在加载K线时应用
+    pub fn filter_stocks_with(&self, symbols: Vec<String>, config: &StockFilterConfig) -> Vec<String> {
         info!("开始过滤股票，原始数量: {}", symbols.len());
         
         let mut filtered = Vec::new();
         let mut excluded_by_code = 0;
+        let mut excluded_by_name = 0;
         
         for symbol in symbols {
-            // 排除科创板(688/689)、创业板(300/301/302)等
-            if symbol.starts_with("688") || 
-               symbol.starts_with("689") || 
-               symbol.starts_with("300") || 
-               symbol.starts_with("301") || 
-               symbol.starts_with("302") {
+            if !config.accepts_symbol(&symbol) {
                 excluded_by_code += 1;
                 continue;
             }
             
+            if config.exclude_st {
+                let name = self.get_stock_name(&symbol).unwrap_or_default();
+                if !config.accepts_name(&name) {
+                    excluded_by_name += 1;
+                    continue;
+                }
+            }
+            
             filtered.push(symbol);
         }
         
-        info!("过滤结果: 保留 {} 只股票, 排除 {} 只科创板/创业板股票", 
-            filtered.len(), excluded_by_code);
+        info!("过滤结果: 保留 {} 只股票, 按代码排除 {} 只, 按名称排除 {} 只", 
+            filtered.len(), excluded_by_code, excluded_by_name);
         
         filtered
     }
     
-    /// 获取股票日线数据，带缓存
-    pub fn get_daily_bars(&self, symbol: &str) -> Option<Vec<DailyBar>> {
+    /// 读取股票列表文件，并按数据源校验每个代码
+    pub fn load_universe_file(&self, path: &Path) -> Result<Universe> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("读取股票列表失败: {}", path.display()))?;
+        
+        let mut universe = Universe::default();
+        for symbol in parse_universe(&content) {
+            if self.get_stock_name(&symbol).is_some() {
+                universe.symbols.push(symbol);
+            } else {
+                universe.unknown.push(symbol);
+            }
+        }
+        
+        info!("股票列表 {}: {} 只有效, {} 只未知",
+            path.display(), universe.symbols.len(), universe.unknown.len());
+        
+        Ok(universe)
+    }
+    
+    /// 获取股票日线数据，带缓存；不复权时返回与缓存共享的K线
+    pub fn get_daily_bars(&self, symbol: &str, mode: AdjustMode) -> Option<Arc<Vec<DailyBar>>> {
+        self.try_daily_bars(symbol, mode).ok()
+    }
+    
+    /// 获取股票日线数据，区分股票不存在和暂时性错误
+    pub fn try_daily_bars(&self, symbol: &str, mode: AdjustMode) -> Result<Arc<Vec<DailyBar>>, FetchError> {
+        let bars = self.try_cached_bars(symbol)?;
+        Ok(self.adjust(symbol, bars, mode))
+    }
+    
+    /// 获取最近max_bars根日线数据，K线按日期倒序，保留最新的部分
+    pub fn get_daily_bars_range(&self, symbol: &str, max_bars: usize, mode: AdjustMode) -> Option<Arc<Vec<DailyBar>>> {
+        let bars = self.cached_bars(symbol)?;
+        let bars = if bars.len() > max_bars {
+            Arc::new(bars[..max_bars].to_vec())
+        } else {
+            bars
+        };
+        Some(self.adjust(symbol, bars, mode))
+    }
+    
+    /// 统计各股票的数据覆盖情况，数据源中不存在的股票被跳过
+    pub fn coverage_report(&self, symbols: &[String]) -> Vec<SymbolCoverage> {
+        symbols.par_iter()
+            .filter_map(|symbol| self.cached_bars(symbol).map(|bars| SymbolCoverage::from_bars(symbol, &bars)))
+            .collect()
+    }
+    
+    fn cached_bars(&self, symbol: &str) -> Option<Arc<Vec<DailyBar>>> {
+        self.try_cached_bars(symbol).ok()
+    }
+    
+    /// 从缓存获取日线，未命中时从数据源获取并写入缓存
+    fn try_cached_bars(&self, symbol: &str) -> Result<Arc<Vec<DailyBar>>, FetchError> {
         // 先检查缓存
         {
-            let cache = self.cache.lock().unwrap();
-            if let Some(stock) = cache.get(symbol) {
+            let cache = self.cache.read().unwrap();
+            if let Some(bars) = cache.get(symbol) {
                 debug!("缓存命中: {}", symbol);
-                return Some(stock.daily.clone());
+                return Ok(bars.clone());
             }
         }
         
-        // 缓存未命中，从数据源获取
+        // 缓存未命中，从数据源获取，顺便缓存元数据
         debug!("缓存未命中: {}, 从数据源获取", symbol);
-        let stock = self.fetch_stock_data(symbol)?;
+        let stock = self.try_fetch_stock_data(symbol)?;
+        self.meta_cache.write().unwrap()
+            .entry(symbol.to_string())
+            .or_insert_with(|| StockMeta::from_stock(&stock));
+        let bars = Arc::new(stock.daily);
         
         // 更新缓存
         {
-            let mut cache = self.cache.lock().unwrap();
-            cache.insert(symbol.to_string(), stock.clone());
+            let mut cache = self.cache.write().unwrap();
+            cache.insert(symbol.to_string(), bars.clone());
         }
         
-        Some(stock.daily)
+        Ok(bars)
     }
     
-    /// 从数据源获取股票数据
     fn fetch_stock_data(&self, symbol: &str) -> Option<Stock> {
-        match self.provider.get_stock_by_symbol(symbol) {
-            Some(stock) => Some(stock.clone()),
+        self.try_fetch_stock_data(symbol).ok()
+    }
+    
+    /// 从数据源获取股票数据，启用磁盘缓存时优先读取未过期的缓存
+    fn try_fetch_stock_data(&self, symbol: &str) -> Result<Stock, FetchError> {
+        if let Some(disk_cache) = &self.disk_cache {
+            if let Some(stock) = disk_cache.load(symbol) {
+                self.disk_hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(stock);
+            }
+            self.disk_misses.fetch_add(1, Ordering::Relaxed);
+        }
+        
+        let provider = self.try_upstream()?;
+        match provider.get_stock_by_symbol(symbol) {
+            Some(stock) => {
+                // 统一为倒序，与缓存和引擎约定一致
+                let mut stock = stock.clone();
+                stock.daily = series::into_order(stock.daily, BarOrder::NewestFirst);
+                if let Some(disk_cache) = &self.disk_cache {
+                    if let Err(e) = disk_cache.store(&stock) {
+                        warn!("写入磁盘缓存失败: {}, {}", symbol, e);
+                    }
+                }
+                Ok(stock)
+            }
             None => {
-                debug!("获取股票 {} 数据失败", symbol);
-                None
+                debug!("数据源中不存在股票 {}", symbol);
+                Err(FetchError::NotFound)
             }
         }
     }
     
     /// 获取股票名称
     pub fn get_stock_name(&self, symbol: &str) -> Option<String> {
+        self.get_meta(symbol).map(|meta| meta.name)
+    }
+    
+    /// 获取股票元数据，带缓存
+    pub fn get_meta(&self, symbol: &str) -> Option<StockMeta> {
         // 先检查缓存
         {
-            let cache = self.name_cache.lock().unwrap();
-            if let Some(name) = cache.get(symbol) {
-                return Some(name.clone());
+            let cache = self.meta_cache.read().unwrap();
+            if let Some(meta) = cache.get(symbol) {
+                return Some(meta.clone());
             }
         }
         
         // 缓存未命中，从数据源获取
         let stock = self.fetch_stock_data(symbol)?;
-        let name = stock.name.clone();
+        let meta = StockMeta::from_stock(&stock);
         
         // 更新缓存
         {
-            let mut cache = self.name_cache.lock().unwrap();
-            cache.insert(symbol.to_string(), name.clone());
+            let mut cache = self.meta_cache.write().unwrap();
+            cache.insert(symbol.to_string(), meta.clone());
         }
         
-        Some(name)
+        Some(meta)
     }
     
-    /// 批量加载股票数据
-    pub fn load_batch_data(&self, symbols: &[String], min_days: usize) -> Vec<(String, Vec<DailyBar>)> {
-        info!("Loading data for {} stocks", symbols.len());
+    /// 重新初始化上游数据源，并把缓存中的股票增量更新到最新交易日
+    pub fn refresh_latest(&self, symbols: &[String]) -> Result<RefreshReport> {
+        info!("重新初始化数据提供者以获取最新数据...");
+        let fresh = Arc::new(DataHubProvider::new_sync()?);
+        *self.provider.write().unwrap() = Some(fresh.clone());
+        
+        Ok(self.refresh_from(&fresh, symbols))
+    }
+    
+    /// 使用指定的上游数据把缓存中的股票增量更新到最新交易日
+    ///
+    /// 只在缓存的K线前面追加比缓存最新日期更新的K线；缓存的最新日期不在新数据中时整体替换
+    pub fn refresh_from(&self, upstream: &DataHubProvider, symbols: &[String]) -> RefreshReport {
+        let mut report = RefreshReport::default();
         
-        let mut result = Vec::new();
         for symbol in symbols {
-            if let Some(bars) = self.get_daily_bars(symbol) {
-                if bars.len() >= min_days {
-                    // 过滤掉股价过高的股票
-                    if let Some(last_bar) = bars.last() {
-                        if last_bar.close > 100.0 {
-                            debug!("过滤掉股价过高的股票: {}, 价格: {:.2}", symbol, last_bar.close);
-                            continue;
-                        }
-                    }
-                    
-                    result.push((symbol.clone(), bars));
+            let Some(fresh) = upstream.get_stock_by_symbol(symbol) else {
+                report.skipped.push(symbol.clone());
+                continue;
+            };
+            // 上游可能按日期正序返回，统一为倒序后再与缓存拼接
+            let daily = series::into_order(fresh.daily.clone(), BarOrder::NewestFirst);
+            
+            let mut cache = self.cache.write().unwrap();
+            let Some(cached) = cache.get_mut(symbol) else {
+                report.skipped.push(symbol.clone());
+                continue;
+            };
+            
+            // K线按日期倒序，第一根为最新；已共享出去的旧K线不受影响
+            let latest = cached.first().map(|bar| bar.date);
+            match latest.and_then(|date| daily.iter().position(|bar| bar.date == date)) {
+                Some(0) => {
+                    report.unchanged.push(symbol.clone());
+                    continue;
+                }
+                Some(new_bars) => {
+                    let mut merged = daily[..new_bars].to_vec();
+                    merged.extend_from_slice(cached);
+                    *cached = Arc::new(merged);
+                    report.updated.push((symbol.clone(), new_bars));
+                }
+                None => {
+                    *cached = Arc::new(daily);
+                    report.refetched.push(symbol.clone());
                 }
             }
+            
+            if let Some(disk_cache) = &self.disk_cache {
+                let stock = Stock {
+                    exchange: fresh.exchange.clone(),
+                    symbol: fresh.symbol.clone(),
+                    name: fresh.name.clone(),
+                    daily: cached.to_vec(),
+                };
+                if let Err(e) = disk_cache.store(&stock) {
+                    warn!("写入磁盘缓存失败: {}, {}", symbol, e);
+                }
+            }
+        }
+        
+        info!("增量刷新: 更新 {} 只, 无变化 {} 只, 重新获取 {} 只, 跳过 {} 只",
+            report.updated.len(), report.unchanged.len(), report.refetched.len(), report.skipped.len());
+        
+        report
+    }
+    
+    /// 获取上游数据源中所有股票的元数据
+    pub fn get_all_meta(&self) -> Vec<StockMeta> {
+        let Some(provider) = self.upstream() else {
+            return Vec::new();
+        };
+        
+        let metas: Vec<StockMeta> = provider.get_all_stocks().iter()
+            .map(StockMeta::from_stock)
+            .collect();
+        
+        let mut cache = self.meta_cache.write().unwrap();
+        for meta in &metas {
+            cache.insert(meta.symbol.clone(), meta.clone());
+        }
+        
+        metas
+    }
+    
+    /// 记录当前股票池快照，日期取上游数据的最新交易日
+    pub fn snapshot_universe(&self, path: &Path) -> Result<UniverseSnapshot> {
+        let provider = self.upstream().ok_or_else(|| anyhow!("数据源不可用，无法记录股票池快照"))?;
+        let date = provider.get_latest_trading_date()
+            .ok_or_else(|| anyhow!("数据源中没有K线，无法确定快照日期"))?;
+        
+        let snapshot = UniverseSnapshot::from_metas(date, &self.get_all_meta());
+        snapshot.save(path)?;
+        
+        info!("股票池快照 {} 只已保存到 {} (日期 {})", snapshot.entries.len(), path.display(), date);
+        Ok(snapshot)
+    }
+    
+    /// 按代码前缀或名称子串搜索股票，结果按匹配程度排序
+    pub fn search(&self, query: &str) -> Vec<StockMeta> {
+        search_metas(&self.get_all_meta(), query)
+    }
+    
+    /// 将代码或名称解析为唯一的股票代码，有歧义时报错并给出候选
+    pub fn resolve(&self, query: &str) -> Result<String> {
+        resolve_query(&self.get_all_meta(), query)
+    }
+    
+    /// 是否为ST股票，取自带缓存的元数据；股票不存在时返回None
+    pub fn is_st(&self, symbol: &str) -> Option<bool> {
+        self.get_meta(symbol).map(|meta| meta.is_st)
+    }
+    
+    /// 股票的涨跌停幅度，ST股票为5%；股票不存在时返回None
+    pub fn limit_band(&self, symbol: &str) -> Option<f32> {
+        self.is_st(symbol).map(|is_st| limit_band(symbol, is_st))
+    }
+    
+    /// 批量加载股票数据，按过滤规则排除价格区间外的股票，K线与缓存共享
+    ///
+    /// 暂时性错误按重试策略重试，返回的报告区分不存在和重试后仍失败的股票
+    pub fn load_batch_shared(
+        &self,
+        symbols: &[String],
+        min_days: usize,
+        config: &StockFilterConfig,
+        mode: AdjustMode,
+    ) -> (Vec<(String, SharedBars)>, LoadReport) {
+        self.load_batch(symbols, min_days, config, |symbol| self.fetch_with_retry(symbol, usize::MAX, mode))
+    }
+    
+    /// 批量加载股票数据，每只股票只保留最近max_bars根K线
+    pub fn load_batch_shared_range(
+        &self,
+        symbols: &[String],
+        min_days: usize,
+        max_bars: usize,
+        config: &StockFilterConfig,
+        mode: AdjustMode,
+    ) -> (Vec<(String, SharedBars)>, LoadReport) {
+        self.load_batch(symbols, min_days, config, |symbol| self.fetch_with_retry(symbol, max_bars, mode))
+    }
+    
+    /// 批量加载股票数据，返回K线副本
+    #[deprecated(note = "复制全部K线，使用 load_batch_shared")]
+    pub fn load_batch_data(
+        &self,
+        symbols: &[String],
+        min_days: usize,
+        config: &StockFilterConfig,
+        mode: AdjustMode,
+    ) -> Vec<(String, Vec<DailyBar>)> {
+        into_owned(self.load_batch_shared(symbols, min_days, config, mode).0)
+    }
+    
+    /// 批量加载最近max_bars根K线，返回K线副本
+    #[deprecated(note = "复制全部K线，使用 load_batch_shared_range")]
+    pub fn load_batch_data_range(
+        &self,
+        symbols: &[String],
+        min_days: usize,
+        max_bars: usize,
+        config: &StockFilterConfig,
+        mode: AdjustMode,
+    ) -> Vec<(String, Vec<DailyBar>)> {
+        into_owned(self.load_batch_shared_range(symbols, min_days, max_bars, config, mode).0)
+    }
+    
+    /// 按重试策略获取最近max_bars根复权后的K线
+    fn fetch_with_retry(&self, symbol: &str, max_bars: usize, mode: AdjustMode) -> Result<SharedBars, FetchError> {
+        self.retry_policy.run(symbol, || {
+            let bars = self.try_cached_bars(symbol)?;
+            let bars = if bars.len() > max_bars {
+                Arc::new(bars[..max_bars].to_vec())
+            } else {
+                bars
+            };
+            Ok(self.adjust(symbol, bars, mode))
+        })
+    }
+    
+    fn load_batch(
+        &self,
+        symbols: &[String],
+        min_days: usize,
+        config: &StockFilterConfig,
+        fetch: impl Fn(&str) -> Result<SharedBars, FetchError> + Sync,
+    ) -> (Vec<(String, SharedBars)>, LoadReport) {
+        info!("Loading data for {} stocks", symbols.len());
+        
+        // 并行获取，缓存使用读写锁，缓存命中时不会相互阻塞
+        let fetched: Vec<_> = symbols.par_iter()
+            .map(|symbol| (symbol.clone(), fetch(symbol)))
+            .collect();
+        
+        let mut report = LoadReport::default();
+        let mut result = Vec::new();
+        let mut below_min = 0;
+        let mut above_max = 0;
+        for (symbol, bars) in fetched {
+            report.record(&symbol, &bars);
+            let Ok(bars) = bars else {
+                continue;
+            };
+            if bars.len() < min_days {
+                continue;
+            }
+            match config.bars_rejection(&bars) {
+                Some(PriceRejection::BelowMin) => below_min += 1,
+                Some(PriceRejection::AboveMax) => above_max += 1,
+                None => result.push((symbol, bars)),
+            }
         }
         
+        // 按代码排序，保证输出顺序与并行调度无关
+        result.sort_by(|a, b| a.0.cmp(&b.0));
+        
+        info!("价格过滤: 低于最低价 {} 只, 高于最高价 {} 只", below_min, above_max);
+        log_load_report(&report);
         info!("Loaded data for {} stocks", result.len());
-        result
+        (result, report)
+    }
+}
+
+/// 把共享的K线转换为独立副本
+fn into_owned(data: Vec<(String, Arc<Vec<DailyBar>>)>) -> Vec<(String, Vec<DailyBar>)> {
+    data.into_iter()
+        .map(|(symbol, bars)| (symbol, Arc::unwrap_or_clone(bars)))
+        .collect()
+}
+
+/// 输出批量加载结果，重试后仍失败的股票单独列出
+pub fn log_load_report(report: &LoadReport) {
+    info!("获取结果: 成功 {} 只, 不存在 {} 只, 重试后失败 {} 只",
+        report.loaded, report.not_found.len(), report.failed_after_retry.len());
+    if !report.failed_after_retry.is_empty() {
+        warn!("重试后仍获取失败({:.1}%): {}",
+            report.failure_pct(), report.failed_after_retry.join(", "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 覆盖各板块代码前缀的股票池: (代码, 名称, 最新收盘价)
+    const UNIVERSE: [(&str, &str, f32); 9] = [
+        ("600000.SH", "浦发银行", 8.0),
+        ("000001.SZ", "平安银行", 12.0),
+        ("002594.SZ", "比亚迪", 250.0),
+        ("300750.SZ", "宁德时代", 180.0),
+        ("301236.SZ", "软通动力", 40.0),
+        ("688981.SH", "中芯国际", 50.0),
+        ("689009.SH", "九号公司", 45.0),
+        ("830799.BJ", "艾融软件", 20.0),
+        ("600083.SH", "*ST博信", 3.0),
+    ];
+
+    fn provider() -> StockDataProvider {
+        let stocks = UNIVERSE.iter()
+            .map(|&(symbol, name, close)| Stock {
+                exchange: symbol[7..].to_string(),
+                symbol: symbol.to_string(),
+                name: name.to_string(),
+                daily: vec![DailyBar {
+                    date: 20230630,
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: 10000,
+                    amount: 100000,
+                }],
+            })
+            .collect();
+        
+        StockDataProvider::with_stocks(stocks).unwrap()
+    }
+
+    fn symbols() -> Vec<String> {
+        UNIVERSE.iter().map(|(symbol, _, _)| symbol.to_string()).collect()
+    }
+
+    fn prefixes(prefixes: &[&str]) -> Vec<String> {
+        prefixes.iter().map(|prefix| prefix.to_string()).collect()
+    }
+
+    #[test]
+    fn default_excludes_star_and_chinext() {
+        let filtered = provider().filter_stocks(symbols());
+        
+        assert_eq!(filtered, vec!["600000.SH", "000001.SZ", "002594.SZ", "830799.BJ", "600083.SH"]);
+    }
+
+    #[test]
+    fn star_market_can_be_included_and_beijing_excluded() {
+        let config = StockFilterConfig {
+            exclude_prefixes: prefixes(&["300", "301", "302", "8", "4"]),
+            ..StockFilterConfig::default()
+        };
+        
+        let filtered = provider().filter_stocks_with(symbols(), &config);
+        
+        assert_eq!(filtered, vec![
+            "600000.SH", "000001.SZ", "002594.SZ", "688981.SH", "689009.SH", "600083.SH",
+        ]);
+    }
+
+    #[test]
+    fn include_prefixes_restrict_universe() {
+        let config = StockFilterConfig {
+            include_prefixes: Some(prefixes(&["60", "00"])),
+            ..StockFilterConfig::default()
+        };
+        
+        let filtered = provider().filter_stocks_with(symbols(), &config);
+        
+        assert_eq!(filtered, vec!["600000.SH", "000001.SZ", "002594.SZ", "600083.SH"]);
+    }
+
+    #[test]
+    fn st_names_are_excluded_when_configured() {
+        let config = StockFilterConfig { exclude_st: true, ..StockFilterConfig::default() };
+        
+        let filtered = provider().filter_stocks_with(symbols(), &config);
+        
+        assert!(!filtered.contains(&"600083.SH".to_string()));
+        assert!(filtered.contains(&"600000.SH".to_string()));
+    }
+
+    #[test]
+    fn price_band_applies_to_latest_close_when_loading() {
+        let provider = provider();
+        let config = StockFilterConfig {
+            min_price: Some(5.0),
+            max_price: Some(100.0),
+            ..StockFilterConfig::default()
+        };
+        
+        let symbols = provider.filter_stocks_with(symbols(), &config);
+        let loaded: Vec<String> = provider.load_batch_shared(&symbols, 1, &config, AdjustMode::None).0
+            .into_iter()
+            .map(|(symbol, _)| symbol)
+            .collect();
+        
+        assert_eq!(loaded, vec!["000001.SZ", "600000.SH", "830799.BJ"]);
+    }
+
+    /// n根K线的股票，按日期倒序，最新一根日期为20230630
+    fn long_provider(n: i32) -> StockDataProvider {
+        let daily = (0..n)
+            .map(|i| DailyBar {
+                date: 20230630 - i,
+                open: 10.0,
+                high: 10.0,
+                low: 10.0,
+                close: 10.0,
+                volume: 10000,
+                amount: 100000,
+            })
+            .collect();
+        let stock = Stock {
+            exchange: "SZ".to_string(),
+            symbol: "000001.SZ".to_string(),
+            name: "平安银行".to_string(),
+            daily,
+        };
+        
+        StockDataProvider::with_stocks(vec![stock]).unwrap()
+    }
+
+    #[test]
+    fn range_keeps_newest_bars() {
+        let provider = long_provider(300);
+        
+        let bars = provider.get_daily_bars_range("000001.SZ", 40, AdjustMode::None).unwrap();
+        
+        assert_eq!(bars.len(), 40);
+        assert_eq!(bars[0].date, 20230630);
+        assert_eq!(bars[39].date, 20230630 - 39);
+        // 缓存命中后结果不变
+        let cached = provider.get_daily_bars_range("000001.SZ", 40, AdjustMode::None).unwrap();
+        assert_eq!((cached.len(), cached[0].date), (40, 20230630));
+        assert_eq!(provider.get_daily_bars("000001.SZ", AdjustMode::None).unwrap().len(), 300);
+    }
+
+    #[test]
+    fn range_longer_than_history_returns_all() {
+        let provider = long_provider(30);
+        
+        assert_eq!(provider.get_daily_bars_range("000001.SZ", 100, AdjustMode::None).unwrap().len(), 30);
+    }
+
+    #[test]
+    fn batch_range_applies_min_days_to_trimmed_series() {
+        let provider = long_provider(300);
+        let symbols = vec!["000001.SZ".to_string()];
+        let config = StockFilterConfig::default();
+        
+        let (loaded, _) = provider.load_batch_shared_range(&symbols, 50, 60, &config, AdjustMode::None);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].1.len(), 60);
+        assert_eq!(loaded[0].1[0].date, 20230630);
+        
+        assert!(provider.load_batch_shared_range(&symbols, 61, 60, &config, AdjustMode::None).0.is_empty());
+    }
+
+    /// 最新K线日期为days_ago天前的股票
+    fn dated_stock(days_ago: i64) -> Stock {
+        let latest = chrono::Local::now().date_naive() - chrono::Duration::days(days_ago);
+        let date: i32 = latest.format("%Y%m%d").to_string().parse().unwrap();
+        
+        Stock {
+            exchange: "SZ".to_string(),
+            symbol: "000001.SZ".to_string(),
+            name: "平安银行".to_string(),
+            daily: vec![DailyBar {
+                date,
+                open: 10.0,
+                high: 10.0,
+                low: 10.0,
+                close: 10.0,
+                volume: 10000,
+                amount: 100000,
+            }],
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("strategy_lab_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// 以内存数据为上游、带磁盘缓存的数据提供者
+    fn cached_provider(stock: Stock, dir: &std::path::Path) -> StockDataProvider {
+        let upstream = DataHubProvider::new_with_data(vec![stock]).unwrap();
+        StockDataProvider::from_upstream(Some(upstream), Some(DiskCache::new(dir.to_path_buf())))
+    }
+
+    #[test]
+    fn second_load_is_served_from_disk_without_upstream() {
+        let dir = temp_dir("disk_cache_hit");
+        
+        let first = cached_provider(dated_stock(0), &dir);
+        assert_eq!(first.get_daily_bars("000001.SZ", AdjustMode::None).unwrap().len(), 1);
+        assert_eq!(first.cache_stats(), CacheStats { hits: 0, misses: 1 });
+        
+        // 新的提供者只配置磁盘缓存，上游数据源保持未初始化
+        let second = StockDataProvider::with_disk_cache(dir.clone());
+        let bars = second.get_daily_bars("000001.SZ", AdjustMode::None).unwrap();
+        assert_eq!(bars[0].close, 10.0);
+        assert_eq!(second.cache_stats(), CacheStats { hits: 1, misses: 0 });
+        assert_eq!(second.get_stock_name("000001.SZ").as_deref(), Some("平安银行"));
+        assert!(second.provider.read().unwrap().is_none());
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn stale_cache_falls_back_to_upstream_and_is_rewritten() {
+        let dir = temp_dir("disk_cache_stale");
+        cached_provider(dated_stock(30), &dir).get_daily_bars("000001.SZ", AdjustMode::None).unwrap();
+        
+        let provider = cached_provider(dated_stock(0), &dir);
+        provider.get_daily_bars("000001.SZ", AdjustMode::None).unwrap();
+        assert_eq!(provider.cache_stats(), CacheStats { hits: 0, misses: 1 });
+        
+        // 重写后的缓存已是最新数据
+        let reloaded = StockDataProvider::with_disk_cache(dir.clone());
+        reloaded.get_daily_bars("000001.SZ", AdjustMode::None).unwrap();
+        assert_eq!(reloaded.cache_stats(), CacheStats { hits: 1, misses: 0 });
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn clear_cache_removes_disk_files() {
+        let dir = temp_dir("disk_cache_clear");
+        let provider = cached_provider(dated_stock(0), &dir);
+        provider.get_daily_bars("000001.SZ", AdjustMode::None).unwrap();
+        
+        provider.clear_cache().unwrap();
+        provider.get_daily_bars("000001.SZ", AdjustMode::None).unwrap();
+        
+        assert_eq!(provider.cache_stats(), CacheStats { hits: 0, misses: 2 });
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    const UNIVERSE_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/universe.txt");
+
+    #[test]
+    fn parse_universe_skips_comments_blanks_and_header() {
+        let content = std::fs::read_to_string(UNIVERSE_FIXTURE).unwrap();
+        
+        assert_eq!(parse_universe(&content), vec!["000001.SZ", "600000.SH", "999999.SZ"]);
+    }
+
+    #[test]
+    fn universe_file_reports_unknown_symbols() {
+        let universe = provider().load_universe_file(Path::new(UNIVERSE_FIXTURE)).unwrap();
+        
+        assert_eq!(universe.symbols, vec!["000001.SZ", "600000.SH"]);
+        assert_eq!(universe.unknown, vec!["999999.SZ"]);
+    }
+
+    #[test]
+    fn missing_universe_file_is_an_error() {
+        assert!(provider().load_universe_file(Path::new("does/not/exist.txt")).is_err());
+    }
+
+    #[test]
+    fn price_rejection_reports_rule() {
+        let config = StockFilterConfig { min_price: Some(2.0), ..StockFilterConfig::default() };
+        
+        assert_eq!(config.price_rejection(1.5), Some(PriceRejection::BelowMin));
+        assert_eq!(config.price_rejection(150.0), Some(PriceRejection::AboveMax));
+        assert_eq!(config.price_rejection(10.0), None);
+        
+        let unbounded = StockFilterConfig { max_price: None, ..StockFilterConfig::default() };
+        assert!(unbounded.accepts_price(150.0));
+    }
+
+    /// n只代码不同、各130根K线的股票
+    fn wide_provider(n: usize) -> StockDataProvider {
+        let stocks = (0..n)
+            .map(|i| Stock {
+                exchange: "SZ".to_string(),
+                symbol: format!("{:06}.SZ", i + 1),
+                name: format!("股票{}", i + 1),
+                daily: long_provider(130).get_daily_bars("000001.SZ", AdjustMode::None).unwrap().to_vec(),
+            })
+            .collect();
+        
+        StockDataProvider::with_stocks(stocks).unwrap()
+    }
+
+    #[test]
+    fn parallel_batch_beats_serial_fetch_with_latency() {
+        let provider = wide_provider(16);
+        let symbols: Vec<String> = (1..=16).rev().map(|i| format!("{:06}.SZ", i)).collect();
+        let config = StockFilterConfig::default();
+        let latency = std::time::Duration::from_millis(20);
+        // 模拟每只股票20ms的网络延迟
+        let slow_fetch = |symbol: &str| {
+            std::thread::sleep(latency);
+            provider.get_daily_bars(symbol, AdjustMode::None).ok_or(FetchError::NotFound)
+        };
+        
+        let start = std::time::Instant::now();
+        let serial: Vec<_> = symbols.iter().filter_map(|symbol| slow_fetch(symbol).ok()).collect();
+        let serial_elapsed = start.elapsed();
+        
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(8).build().unwrap();
+        let start = std::time::Instant::now();
+        let (parallel, report) = pool.install(|| provider.load_batch(&symbols, 0, &config, slow_fetch));
+        let parallel_elapsed = start.elapsed();
+        
+        assert_eq!(serial.len(), 16);
+        assert_eq!(parallel.len(), 16);
+        assert_eq!(report.loaded, 16);
+        assert!(parallel_elapsed * 2 < serial_elapsed, "parallel {:?} vs serial {:?}", parallel_elapsed, serial_elapsed);
+        // 输出按代码排序，与输入顺序和调度无关
+        let loaded: Vec<&str> = parallel.iter().map(|(symbol, _)| symbol.as_str()).collect();
+        let mut sorted = loaded.clone();
+        sorted.sort();
+        assert_eq!(loaded, sorted);
+    }
+
+    #[test]
+    fn concurrent_reads_return_consistent_bars() {
+        let provider = wide_provider(4);
+        
+        std::thread::scope(|scope| {
+            for t in 0..8 {
+                let provider = &provider;
+                scope.spawn(move || {
+                    for i in 0..200 {
+                        let symbol = format!("{:06}.SZ", (t + i) % 4 + 1);
+                        let bars = provider.get_daily_bars(&symbol, AdjustMode::None).unwrap();
+                        assert_eq!(bars.len(), 130);
+                        assert_eq!(bars[0].date, 20230630);
+                    }
+                });
+            }
+        });
+        
+        let (loaded, _) = provider.load_batch_shared(&provider.get_all_stocks(), 120, &StockFilterConfig::default(), AdjustMode::None);
+        assert_eq!(loaded.len(), 4);
+    }
+
+    #[test]
+    fn exchange_is_derived_from_symbol_prefix() {
+        assert_eq!(exchange_from_symbol("600000"), Some("SH"));
+        assert_eq!(exchange_from_symbol("688981"), Some("SH"));
+        assert_eq!(exchange_from_symbol("000001"), Some("SZ"));
+        assert_eq!(exchange_from_symbol("300750"), Some("SZ"));
+        assert_eq!(exchange_from_symbol("830799"), Some("BJ"));
+        assert_eq!(exchange_from_symbol("430047"), Some("BJ"));
+        assert_eq!(exchange_from_symbol("920118"), Some("BJ"));
+        assert_eq!(exchange_from_symbol("900901"), None);
+    }
+
+    #[test]
+    fn meta_falls_back_to_prefix_and_detects_st() {
+        let mut stock = dated_stock(0);
+        stock.symbol = "600083.SH".to_string();
+        stock.name = "*ST博信".to_string();
+        stock.exchange = String::new();
+        let oldest = stock.daily.last().unwrap().date;
+        let provider = StockDataProvider::with_stocks(vec![stock]).unwrap();
+        
+        let meta = provider.get_meta("600083.SH").unwrap();
+        
+        assert_eq!(meta.exchange, "SH");
+        assert_eq!(meta.name, "*ST博信");
+        assert!(meta.is_st);
+        assert_eq!(meta.list_date, Some(oldest));
+        assert_eq!(provider.is_st("600083.SH"), Some(true));
+        assert_eq!(provider.get_all_meta(), vec![meta]);
+        assert!(provider.get_meta("000001.SZ").is_none());
+    }
+
+    #[test]
+    fn meta_keeps_exchange_reported_by_datahub() {
+        let provider = provider();
+        
+        let meta = provider.get_meta("000001.SZ").unwrap();
+        
+        assert_eq!(meta.exchange, "SZ");
+        assert_eq!(meta.name, "平安银行");
+        assert!(!meta.is_st);
+        assert_eq!(provider.get_all_meta().len(), UNIVERSE.len());
+    }
+
+    #[test]
+    fn search_ranks_exact_symbol_before_prefix_matches() {
+        let provider = provider();
+        
+        let symbols: Vec<String> = provider.search("600")
+            .into_iter()
+            .map(|meta| meta.symbol)
+            .collect();
+        assert_eq!(symbols, vec!["600000.SH", "600083.SH"]);
+        
+        assert_eq!(provider.search("000001.SZ")[0].name, "平安银行");
+        assert_eq!(provider.resolve("000001.sz").unwrap(), "000001.SZ");
+    }
+
+    #[test]
+    fn unique_name_substring_resolves() {
+        let provider = provider();
+        
+        assert_eq!(provider.resolve("平安").unwrap(), "000001.SZ");
+        assert_eq!(provider.resolve("博信").unwrap(), "600083.SH");
+        // 全角字符按半角匹配
+        assert_eq!(provider.resolve("８３０７９９").unwrap(), "830799.BJ");
+    }
+
+    #[test]
+    fn ambiguous_query_lists_suggestions() {
+        let provider = provider();
+        
+        let err = provider.resolve("银行").unwrap_err().to_string();
+        
+        assert!(err.contains("匹配到 2 只股票"), "{}", err);
+        assert!(err.contains("600000.SH 浦发银行"));
+        assert!(err.contains("000001.SZ 平安银行"));
+        assert!(provider.resolve("茅台").is_err());
+        assert!(provider.search("  ").is_empty());
+    }
+
+    /// 上游增量数据: 按dates顺序排列的K线，收盘价为日期的末两位
+    fn delta(dates: &[i32]) -> DataHubProvider {
+        let daily = dates.iter()
+            .map(|&date| DailyBar {
+                date,
+                open: 10.0,
+                high: 10.0,
+                low: 10.0,
+                close: (date % 100) as f32,
+                volume: 10000,
+                amount: 100000,
+            })
+            .collect();
+        let stock = Stock {
+            exchange: "SZ".to_string(),
+            symbol: "000001.SZ".to_string(),
+            name: "平安银行".to_string(),
+            daily,
+        };
+        
+        DataHubProvider::new_with_data(vec![stock]).unwrap()
+    }
+
+    #[test]
+    fn refresh_merges_upstream_delta_in_either_order() {
+        let symbols = vec!["000001.SZ".to_string()];
+        // 上游按日期正序和倒序返回相同的增量，合并结果一致
+        for dates in [[20230629, 20230630, 20230703, 20230704], [20230704, 20230703, 20230630, 20230629]] {
+            let provider = long_provider(30);
+            let before = provider.get_daily_bars("000001.SZ", AdjustMode::None).unwrap();
+            
+            let report = provider.refresh_from(&delta(&dates), &symbols);
+            
+            assert_eq!(report.updated, vec![("000001.SZ".to_string(), 2)]);
+            let bars = provider.get_daily_bars("000001.SZ", AdjustMode::None).unwrap();
+            assert_eq!(bars.len(), 32);
+            let newest: Vec<(i32, f32)> = bars[..4].iter().map(|bar| (bar.date, bar.close)).collect();
+            assert_eq!(newest, vec![(20230704, 4.0), (20230703, 3.0), (20230630, 10.0), (20230629, 10.0)]);
+            // 已共享出去的旧K线不受影响
+            assert_eq!((before.len(), before[0].date), (30, 20230630));
+        }
+    }
+
+    #[test]
+    fn refresh_without_new_bars_is_unchanged() {
+        let provider = long_provider(30);
+        provider.get_daily_bars("000001.SZ", AdjustMode::None).unwrap();
+        
+        let report = provider.refresh_from(&delta(&[20230629, 20230630]), &["000001.SZ".to_string()]);
+        
+        assert_eq!(report.unchanged, vec!["000001.SZ"]);
+        assert_eq!(provider.get_daily_bars("000001.SZ", AdjustMode::None).unwrap().len(), 30);
     }
 }
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use egostrategy_datahub::models::stock::{StockData as Stock, DailyData as DailyBar};
+use serde::{Serialize, Deserialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 磁盘缓存中的日线数据，datahub的类型不支持反序列化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedBar {
+    date: i32,
+    open: f32,
+    high: f32,
+    low: f32,
+    close: f32,
+    volume: i64,
+    amount: i64,
+}
+
+/// 磁盘缓存中的股票数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedStock {
+    exchange: String,
+    symbol: String,
+    name: String,
+    daily: Vec<CachedBar>,
+}
+
+impl From<&Stock> for CachedStock {
+    fn from(stock: &Stock) -> Self {
+        Self {
+            exchange: stock.exchange.clone(),
+            symbol: stock.symbol.clone(),
+            name: stock.name.clone(),
+            daily: stock.daily.iter()
+                .map(|bar| CachedBar {
+                    date: bar.date,
+                    open: bar.open,
+                    high: bar.high,
+                    low: bar.low,
+                    close: bar.close,
+                    volume: bar.volume,
+                    amount: bar.amount,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<CachedStock> for Stock {
+    fn from(cached: CachedStock) -> Self {
+        Self {
+            exchange: cached.exchange,
+            symbol: cached.symbol,
+            name: cached.name,
+            daily: cached.daily.into_iter()
+                .map(|bar| DailyBar {
+                    date: bar.date,
+                    open: bar.open,
+                    high: bar.high,
+                    low: bar.low,
+                    close: bar.close,
+                    volume: bar.volume,
+                    amount: bar.amount,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 按股票代码存放的磁盘缓存，每只股票一个JSON文件
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    pub dir: PathBuf,
+    /// 最新K线距今超过该天数时视为过期
+    pub staleness_days: i64,
+}
+
+impl DiskCache {
+    /// 创建磁盘缓存，默认允许3天的过期窗口以覆盖周末
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, staleness_days: 3 }
+    }
+    
+    fn path(&self, symbol: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", symbol))
+    }
+    
+    /// 读取未过期的缓存，文件不存在、损坏或过期时返回None
+    pub fn load(&self, symbol: &str) -> Option<Stock> {
+        let content = fs::read_to_string(self.path(symbol)).ok()?;
+        let cached: CachedStock = serde_json::from_str(&content).ok()?;
+        
+        // K线按日期倒序，第一根为最新
+        let latest = cached.daily.first()?.date;
+        if !self.is_fresh(latest) {
+            return None;
+        }
+        
+        Some(cached.into())
+    }
+    
+    /// 写入缓存，自动创建目录
+    pub fn store(&self, stock: &Stock) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_string(&CachedStock::from(stock))?;
+        fs::write(self.path(&stock.symbol), json)?;
+        Ok(())
+    }
+    
+    /// 删除所有缓存文件
+    pub fn clear(&self) -> Result<()> {
+        if !Path::new(&self.dir).exists() {
+            return Ok(());
+        }
+        
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                fs::remove_file(path)?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// 最新K线日期(YYYYMMDD)是否在过期窗口内
+    fn is_fresh(&self, latest: i32) -> bool {
+        let Some(date) = NaiveDate::parse_from_str(&latest.to_string(), "%Y%m%d").ok() else {
+            return false;
+        };
+        
+        (Local::now().date_naive() - date).num_days() <= self.staleness_days
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use super::indicator::Indicator;
+
+/// 全局缓存默认保留的股票数，覆盖全A股票池，避免按顺序扫描全市场时LRU反复淘汰
+pub const DEFAULT_CACHE_SYMBOLS: usize = 8192;
+
+/// 数据指纹: (K线数, 第一根日期, 最后一根日期)，同一股票数据变化时缓存失效
+type Fingerprint = (usize, i32, i32);
+
+fn fingerprint(bars: &[DailyBar]) -> Fingerprint {
+    (
+        bars.len(),
+        bars.first().map(|bar| bar.date).unwrap_or(0),
+        bars.last().map(|bar| bar.date).unwrap_or(0),
+    )
+}
+
+/// 单只股票的缓存: 指标名(含参数) -> 完整序列
+struct SymbolEntry {
+    fingerprint: Fingerprint,
+    series: HashMap<String, Arc<Vec<f32>>>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    tick: u64,
+    symbols: HashMap<String, SymbolEntry>,
+    hits: u64,
+    misses: u64,
+}
+
+/// 指标序列缓存 - 适用于倒序数据
+///
+/// 按(股票, 指标名含参数)保存在完整K线上计算的指标序列，选股时直接取series[forecast_idx]。
+/// 倒序指标在下标i处只依赖data[i..]，因此与在data[forecast_idx..]上重新计算的结果一致。
+/// 内存按股票数做LRU淘汰，可在rayon线程间共享
+pub struct IndicatorCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+impl IndicatorCache {
+    /// 创建最多保留capacity只股票的缓存
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+    
+    /// 进程内共享的缓存
+    pub fn shared() -> &'static IndicatorCache {
+        static SHARED: OnceLock<IndicatorCache> = OnceLock::new();
+        SHARED.get_or_init(|| IndicatorCache::new(DEFAULT_CACHE_SYMBOLS))
+    }
+    
+    /// 获取指标序列，未命中时用compute在完整K线上计算并缓存
+    ///
+    /// indicator应包含全部参数，如"atr:14:wilder"
+    pub fn get_or_compute<F>(&self, symbol: &str, bars: &[DailyBar], indicator: &str, compute: F) -> Arc<Vec<f32>>
+    where
+        F: FnOnce(&[DailyBar]) -> Vec<f32>,
+    {
+        let fp = fingerprint(bars);
+        if let Some(series) = self.lookup(symbol, fp, indicator) {
+            return series;
+        }
+        
+        // 计算时不持锁，并发重复计算的结果相同，后写入的覆盖即可
+        let series = Arc::new(compute(bars));
+        self.insert(symbol, fp, indicator, series.clone());
+        series
+    }
+    
+    /// 获取Indicator的完整序列，以name()作为缓存键
+    pub fn get_indicator<I>(&self, symbol: &str, bars: &[DailyBar], indicator: &I) -> Arc<Vec<f32>>
+    where
+        I: Indicator<Output = Vec<f32>> + ?Sized,
+    {
+        self.get_or_compute(symbol, bars, &indicator.name(), |bars| indicator.compute(bars))
+    }
+    
+    /// 获取指标在forecast_idx处的值，超出范围时为0
+    pub fn value_at<F>(&self, symbol: &str, bars: &[DailyBar], indicator: &str, forecast_idx: usize, compute: F) -> f32
+    where
+        F: FnOnce(&[DailyBar]) -> Vec<f32>,
+    {
+        self.get_or_compute(symbol, bars, indicator, compute)
+            .get(forecast_idx)
+            .copied()
+            .unwrap_or(0.0)
+    }
+    
+    /// 当前缓存的股票数
+    pub fn len(&self) -> usize {
+        self.lock().symbols.len()
+    }
+    
+    /// 缓存是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    /// 命中和未命中次数
+    pub fn stats(&self) -> (u64, u64) {
+        let state = self.lock();
+        (state.hits, state.misses)
+    }
+    
+    /// 清空缓存和统计
+    pub fn clear(&self) {
+        *self.lock() = CacheState::default();
+    }
+    
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        // 计算在锁外进行，锁内不会panic，中毒时直接沿用数据
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+    
+    fn lookup(&self, symbol: &str, fp: Fingerprint, indicator: &str) -> Option<Arc<Vec<f32>>> {
+        let mut state = self.lock();
+        state.tick += 1;
+        let tick = state.tick;
+        
+        let found = match state.symbols.get_mut(symbol) {
+            Some(entry) if entry.fingerprint == fp => {
+                entry.last_used = tick;
+                entry.series.get(indicator).cloned()
+            }
+            _ => None,
+        };
+        
+        if found.is_some() {
+            state.hits += 1;
+        } else {
+            state.misses += 1;
+        }
+        found
+    }
+    
+    fn insert(&self, symbol: &str, fp: Fingerprint, indicator: &str, series: Arc<Vec<f32>>) {
+        let mut state = self.lock();
+        state.tick += 1;
+        let tick = state.tick;
+        
+        if !state.symbols.contains_key(symbol) && state.symbols.len() >= self.capacity {
+            let oldest = state.symbols.iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.symbols.remove(&oldest);
+            }
+        }
+        
+        let entry = state.symbols.entry(symbol.to_string()).or_insert_with(|| SymbolEntry {
+            fingerprint: fp,
+            series: HashMap::new(),
+            last_used: tick,
+        });
+        
+        // 数据已变化，丢弃旧序列
+        if entry.fingerprint != fp {
+            entry.fingerprint = fp;
+            entry.series.clear();
+        }
+        entry.last_used = tick;
+        entry.series.insert(indicator.to_string(), series);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stock::indicators::{calculate_atr, AtrSmoothing};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn bars(n: i32) -> Vec<DailyBar> {
+        (0..n)
+            .map(|i| {
+                let close = 10.0 + (i as f32 * 0.7).sin();
+                DailyBar {
+                    date: 20230630 - i,
+                    open: close,
+                    high: close + 0.3,
+                    low: close - 0.2,
+                    close,
+                    volume: 10000,
+                    amount: 100000,
+                }
+            })
+            .collect()
+    }
+
+    fn atr(bars: &[DailyBar]) -> Vec<f32> {
+        let highs: Vec<f32> = bars.iter().map(|bar| bar.high).collect();
+        let lows: Vec<f32> = bars.iter().map(|bar| bar.low).collect();
+        let closes: Vec<f32> = bars.iter().map(|bar| bar.close).collect();
+        calculate_atr(&highs, &lows, &closes, 14, AtrSmoothing::Wilder)
+    }
+
+    #[test]
+    fn repeated_forecast_days_compute_once() {
+        let data = bars(300);
+        let cache = IndicatorCache::new(4);
+        let back_days = 100;
+        // 统计参与计算的K线数量，代表计算量
+        let work = AtomicUsize::new(0);
+        let counted_atr = |bars: &[DailyBar]| {
+            work.fetch_add(bars.len(), Ordering::Relaxed);
+            atr(bars)
+        };
+        
+        let mut uncached = Vec::new();
+        for forecast_idx in 0..back_days {
+            uncached.push(counted_atr(&data[forecast_idx..])[0]);
+        }
+        let uncached_work = work.swap(0, Ordering::Relaxed);
+        
+        let mut cached = Vec::new();
+        for forecast_idx in 0..back_days {
+            cached.push(cache.value_at("000001.SZ", &data, "atr:14:wilder", forecast_idx, counted_atr));
+        }
+        let cached_work = work.load(Ordering::Relaxed);
+        
+        assert_eq!(cached, uncached);
+        // 不缓存时每天重算一次(O(back_days × 窗口))，缓存后只计算一次完整序列
+        assert_eq!(cached_work, data.len());
+        assert!(uncached_work > cached_work * (back_days / 2));
+        assert_eq!(cache.stats(), (back_days as u64 - 1, 1));
+    }
+
+    #[test]
+    fn changed_bars_invalidate_series() {
+        let cache = IndicatorCache::new(4);
+        let calls = AtomicUsize::new(0);
+        let compute = |bars: &[DailyBar]| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            atr(bars)
+        };
+        
+        cache.get_or_compute("000001.SZ", &bars(200), "atr", compute);
+        cache.get_or_compute("000001.SZ", &bars(200), "atr", compute);
+        cache.get_or_compute("000001.SZ", &bars(201), "atr", compute);
+        
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn least_recently_used_symbol_is_evicted() {
+        let cache = IndicatorCache::new(2);
+        let data = bars(50);
+        
+        cache.get_or_compute("000001.SZ", &data, "atr", atr);
+        cache.get_or_compute("000002.SZ", &data, "atr", atr);
+        // 访问000001后，000002成为最久未使用的
+        cache.get_or_compute("000001.SZ", &data, "atr", atr);
+        cache.get_or_compute("000003.SZ", &data, "atr", atr);
+        assert_eq!(cache.len(), 2);
+        
+        cache.get_or_compute("000001.SZ", &data, "atr", atr);
+        assert_eq!(cache.stats(), (2, 3));
+        cache.get_or_compute("000002.SZ", &data, "atr", atr);
+        assert_eq!(cache.stats(), (2, 4));
+        
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use super::oscillator::calculate_rsi;
+use super::trend::calculate_macd;
+use super::volatility::{calculate_atr, calculate_bollinger_bands, AtrSmoothing};
+
+/// 指标统一接口 - 适用于倒序数据
+///
+/// 包装现有的指标函数，便于按配置组合指标和缓存；输出与对应函数一致，数据不足的位置为0
+pub trait Indicator: Send + Sync {
+    /// 计算结果类型
+    type Output;
+    
+    /// 指标名称，包含全部参数，可直接作为IndicatorCache的键
+    fn name(&self) -> String;
+    
+    /// 在完整K线上计算指标
+    fn compute(&self, bars: &[DailyBar]) -> Self::Output;
+    
+    /// 下标0处得到有效值至少需要的K线数
+    fn min_bars(&self) -> usize;
+}
+
+fn column(bars: &[DailyBar], field: fn(&DailyBar) -> f32) -> Vec<f32> {
+    bars.iter().map(field).collect()
+}
+
+/// 真实波动幅度(ATR)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Atr {
+    pub period: usize,
+    pub smoothing: AtrSmoothing,
+}
+
+impl Indicator for Atr {
+    type Output = Vec<f32>;
+    
+    fn name(&self) -> String {
+        format!("atr:{}:{:?}", self.period, self.smoothing)
+    }
+    
+    fn compute(&self, bars: &[DailyBar]) -> Vec<f32> {
+        calculate_atr(&column(bars, |bar| bar.high), &column(bars, |bar| bar.low), &column(bars, |bar| bar.close), self.period, self.smoothing)
+    }
+    
+    fn min_bars(&self) -> usize {
+        self.period
+    }
+}
+
+/// 相对强弱指标(RSI)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rsi {
+    pub period: usize,
+}
+
+impl Indicator for Rsi {
+    type Output = Vec<f32>;
+    
+    fn name(&self) -> String {
+        format!("rsi:{}", self.period)
+    }
+    
+    fn compute(&self, bars: &[DailyBar]) -> Vec<f32> {
+        calculate_rsi(&column(bars, |bar| bar.close), self.period)
+    }
+    
+    fn min_bars(&self) -> usize {
+        // period个涨跌幅需要period + 1天
+        self.period + 1
+    }
+}
+
+/// MACD，输出(DIF, DEA, 柱状图)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Macd {
+    pub fast: usize,
+    pub slow: usize,
+    pub signal: usize,
+}
+
+impl Default for Macd {
+    fn default() -> Self {
+        Self { fast: 12, slow: 26, signal: 9 }
+    }
+}
+
+impl Indicator for Macd {
+    type Output = (Vec<f32>, Vec<f32>, Vec<f32>);
+    
+    fn name(&self) -> String {
+        format!("macd:{}:{}:{}", self.fast, self.slow, self.signal)
+    }
+    
+    fn compute(&self, bars: &[DailyBar]) -> Self::Output {
+        calculate_macd(&column(bars, |bar| bar.close), self.fast, self.slow, self.signal)
+    }
+    
+    fn min_bars(&self) -> usize {
+        // DEA需要signal个有效的DIF，DIF需要slow天
+        self.slow + self.signal.saturating_sub(1)
+    }
+}
+
+/// 布林带，输出(中轨, 上轨, 下轨)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BollingerBands {
+    pub period: usize,
+    pub std_dev_multiplier: f32,
+}
+
+impl Indicator for BollingerBands {
+    type Output = (Vec<f32>, Vec<f32>, Vec<f32>);
+    
+    fn name(&self) -> String {
+        format!("boll:{}:{}", self.period, self.std_dev_multiplier)
+    }
+    
+    fn compute(&self, bars: &[DailyBar]) -> Self::Output {
+        calculate_bollinger_bands(&column(bars, |bar| bar.close), self.period, self.std_dev_multiplier)
+    }
+    
+    fn min_bars(&self) -> usize {
+        self.period
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stock::indicators::IndicatorCache;
+
+    fn bars(n: usize) -> Vec<DailyBar> {
+        (0..n)
+            .map(|i| {
+                let close = 10.0 + (i as f32 * 0.9).sin();
+                DailyBar {
+                    date: 20230630 - i as i32,
+                    open: close,
+                    high: close + 0.4,
+                    low: close - 0.3,
+                    close,
+                    volume: 1000,
+                    amount: 10000,
+                }
+            })
+            .collect()
+    }
+
+    /// 恰好min_bars根K线时下标0有效，少一根时为0
+    fn assert_min_bars<I: Indicator>(indicator: &I, first: impl Fn(&I::Output) -> f32) {
+        let enough = first(&indicator.compute(&bars(indicator.min_bars())));
+        let short = first(&indicator.compute(&bars(indicator.min_bars() - 1)));
+        
+        assert_ne!(enough, 0.0, "{}", indicator.name());
+        assert_eq!(short, 0.0, "{}", indicator.name());
+    }
+
+    #[test]
+    fn min_bars_marks_first_valid_value() {
+        assert_min_bars(&Atr { period: 14, smoothing: AtrSmoothing::Wilder }, |atr| atr[0]);
+        assert_min_bars(&Atr { period: 14, smoothing: AtrSmoothing::Simple }, |atr| atr[0]);
+        assert_min_bars(&Rsi { period: 14 }, |rsi| rsi[0]);
+        assert_min_bars(&Macd::default(), |(_, signal, _)| signal[0]);
+        assert_min_bars(&BollingerBands { period: 20, std_dev_multiplier: 2.0 }, |(middle, _, _)| middle[0]);
+    }
+
+    #[test]
+    fn names_include_parameters() {
+        assert_eq!(Atr { period: 14, smoothing: AtrSmoothing::Wilder }.name(), "atr:14:Wilder");
+        assert_eq!(Macd::default().name(), "macd:12:26:9");
+        assert_eq!(BollingerBands { period: 20, std_dev_multiplier: 2.0 }.name(), "boll:20:2");
+    }
+
+    #[test]
+    fn boxed_indicators_share_the_cache() {
+        let data = bars(60);
+        let cache = IndicatorCache::new(4);
+        let indicators: Vec<Box<dyn Indicator<Output = Vec<f32>>>> = vec![
+            Box::new(Atr { period: 14, smoothing: AtrSmoothing::Wilder }),
+            Box::new(Rsi { period: 14 }),
+        ];
+        
+        for indicator in &indicators {
+            let cached = cache.get_indicator("000001.SZ", &data, indicator.as_ref());
+            assert_eq!(*cached, indicator.compute(&data));
+        }
+        cache.get_indicator("000001.SZ", &data, indicators[0].as_ref());
+        
+        assert_eq!(cache.stats(), (1, 2));
+    }
+}
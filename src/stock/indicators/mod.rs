@@ -1,10 +1,132 @@
 pub mod trend;
 pub mod oscillator;
 pub mod volatility;
+pub mod volume;
 pub mod utils;
+pub mod cache;
+pub mod pivots;
+pub mod pattern;
+pub mod relative;
+pub mod indicator;
 
 // 重新导出常用函数，方便使用
-pub use trend::{calculate_ema, moving_average, calculate_macd};
-pub use oscillator::{calculate_rsi, calculate_stochastic, calculate_momentum};
-pub use volatility::{standard_deviation, calculate_atr, calculate_bollinger_bands, calculate_keltner_channel};
-pub use utils::{extract_price_data, calculate_price_change, calculate_cumulative_return, calculate_max_drawdown, calculate_sharpe_ratio};
+pub use trend::{calculate_ema, calculate_ema_series, moving_average, calculate_macd, calculate_vw_macd, calculate_dmi, calculate_ichimoku, IchimokuSeries, trend_quality};
+pub use oscillator::{calculate_rsi, calculate_stochastic, calculate_kdj, calculate_williams_r, calculate_cci, calculate_momentum, calculate_roc, normalized_momentum};
+pub use volatility::{standard_deviation, calculate_atr, AtrSmoothing, calculate_bollinger_bands, calculate_keltner_channel, donchian_channel};
+pub use volume::{calculate_obv, obv_slope};
+pub use utils::{extract_price_data, calculate_price_change, calculate_cumulative_return, calculate_max_drawdown, calculate_sharpe_ratio, calculate_vwap, anchored_vwap, closing_strength, avg_amplitude};
+pub use cache::IndicatorCache;
+pub use pivots::{pivot_highs, pivot_lows, last_swing_low, PivotKind};
+pub use pattern::{zigzag, SwingPoint, fractals};
+pub use relative::beta_to_benchmark;
+pub use indicator::{Indicator, Atr, Rsi, Macd, BollingerBands};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 倒序的持续上涨序列: closes[0]最新最高
+    fn rising() -> Vec<f32> {
+        (0..30).map(|i| 30.0 - 0.5 * i as f32).collect()
+    }
+
+    /// 倒序的震荡序列，首尾不对称
+    fn wavy() -> Vec<f32> {
+        (0..16).map(|i| 10.0 + (i as f32 * 0.9).sin() + 0.1 * i as f32).collect()
+    }
+
+    fn reversed(data: &[f32]) -> Vec<f32> {
+        data.iter().rev().copied().collect()
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-4, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn window_indicators_mirror_under_reversal() {
+        // 对称窗口的指标: 反转后第j个窗口即原序列第len-p-j个窗口
+        let data = wavy();
+        let rev = reversed(&data);
+        let (len, p) = (data.len(), 5);
+        
+        let ma = moving_average(&data, p);
+        let ma_rev = moving_average(&rev, p);
+        let (mid, upper, lower) = calculate_bollinger_bands(&data, p, 2.0);
+        let (mid_rev, upper_rev, lower_rev) = calculate_bollinger_bands(&rev, p, 2.0);
+        let (dc_upper, dc_lower, _) = donchian_channel(&data, &data, p, true);
+        let (dc_upper_rev, dc_lower_rev, _) = donchian_channel(&rev, &rev, p, true);
+        
+        for j in 0..=len-p {
+            let k = len - p - j;
+            assert_close(ma_rev[j], ma[k]);
+            assert_close(mid_rev[j], mid[k]);
+            assert_close(upper_rev[j], upper[k]);
+            assert_close(lower_rev[j], lower[k]);
+            assert_eq!(dc_upper_rev[j], dc_upper[k]);
+            assert_eq!(dc_lower_rev[j], dc_lower[k]);
+        }
+    }
+
+    #[test]
+    fn directional_indicators_flip_under_reversal() {
+        // 倒序的上涨序列反转后变为下跌，方向性指标应该反号
+        let up = rising();
+        let down = reversed(&up);
+        let highs = |closes: &[f32]| closes.iter().map(|c| c + 0.2).collect::<Vec<f32>>();
+        let lows = |closes: &[f32]| closes.iter().map(|c| c - 0.2).collect::<Vec<f32>>();
+        let volumes = vec![1000.0; up.len()];
+        
+        assert_eq!(calculate_rsi(&up, 14)[0], 100.0);
+        assert_eq!(calculate_rsi(&down, 14)[0], 0.0);
+        assert!(calculate_momentum(&up, 5)[0] > 0.0);
+        assert!(calculate_momentum(&down, 5)[0] < 0.0);
+        assert!(calculate_macd(&up, 5, 10, 3).0[0] > 0.0);
+        assert!(calculate_macd(&down, 5, 10, 3).0[0] < 0.0);
+        assert!(calculate_cumulative_return(&calculate_price_change(&up))[0] > 0.0);
+        assert!(calculate_cumulative_return(&calculate_price_change(&down))[0] < 0.0);
+        assert_eq!(calculate_max_drawdown(&up), 0.0);
+        assert!(calculate_max_drawdown(&down) > 0.0);
+        assert!(calculate_obv(&up, &volumes)[0] > 0.0);
+        assert!(calculate_obv(&down, &volumes)[0] < 0.0);
+        
+        let (k_up, _) = calculate_stochastic(&highs(&up), &lows(&up), &up, 9, 3);
+        let (k_down, _) = calculate_stochastic(&highs(&down), &lows(&down), &down, 9, 3);
+        assert!(k_up[0] > 80.0 && k_down[0] < 20.0);
+        
+        let (plus_up, minus_up, _) = calculate_dmi(&highs(&up), &lows(&up), &up, 14);
+        let (plus_down, minus_down, _) = calculate_dmi(&highs(&down), &lows(&down), &down, 14);
+        assert!(plus_up[0] > minus_up[0]);
+        assert!(plus_down[0] < minus_down[0]);
+        
+        let wr_up = calculate_williams_r(&highs(&up), &lows(&up), &up, 10);
+        let wr_down = calculate_williams_r(&highs(&down), &lows(&down), &down, 10);
+        assert!(wr_up[0] > -20.0 && wr_down[0] < -80.0);
+    }
+
+    #[test]
+    fn recursive_indicators_match_chronological_reference() {
+        let data = wavy();
+        let chronological = reversed(&data);
+        let period = 4;
+        
+        // 教科书式的正序EMA: 以最早period天的均值为种子
+        let k = 2.0 / (period as f32 + 1.0);
+        let mut ema = chronological[..period].iter().sum::<f32>() / period as f32;
+        for &x in &chronological[period..] {
+            ema = x * k + ema * (1.0 - k);
+        }
+        assert_close(calculate_ema_series(&data, period)[0], ema);
+        
+        // 正序Wilder RSI
+        let changes: Vec<f32> = chronological.windows(2).map(|w| w[1] - w[0]).collect();
+        let mut avg_gain = changes[..period].iter().map(|c| c.max(0.0)).sum::<f32>() / period as f32;
+        let mut avg_loss = changes[..period].iter().map(|c| (-c).max(0.0)).sum::<f32>() / period as f32;
+        for &c in &changes[period..] {
+            avg_gain = (avg_gain * (period as f32 - 1.0) + c.max(0.0)) / period as f32;
+            avg_loss = (avg_loss * (period as f32 - 1.0) + (-c).max(0.0)) / period as f32;
+        }
+        let rsi = 100.0 - 100.0 / (1.0 + avg_gain / avg_loss);
+        assert_close(calculate_rsi(&data, period)[0], rsi);
+    }
+}
@@ -1,57 +1,49 @@
 /// 计算相对强弱指标(RSI) - 适用于倒序数据
+///
+/// 以最早period个涨跌幅的均值为种子，Wilder平滑向新的方向(下标变小)递推；数据不足的位置为0
 pub fn calculate_rsi(closes: &[f32], period: usize) -> Vec<f32> {
     let len = closes.len();
     let mut rsi = vec![0.0; len];
     
-    if len <= period {
+    if period == 0 || len <= period {
         return rsi;
     }
     
     let mut gains = vec![0.0; len];
     let mut losses = vec![0.0; len];
     
-    // 注意：在倒序数据中，i-1是后一天，i是前一天
+    // 注意：在倒序数据中，i-1是后一天，i+1是前一天；最早一天没有涨跌幅
     for i in 0..(len-1) {
         let change = closes[i] - closes[i+1];
         gains[i] = if change > 0.0 { change } else { 0.0 };
         losses[i] = if change < 0.0 { -change } else { 0.0 };
     }
     
-    // 计算初始平均值
-    let mut avg_gain = 0.0;
-    let mut avg_loss = 0.0;
-    
-    for i in 0..period {
-        avg_gain += gains[i];
-        avg_loss += losses[i];
-    }
-    
-    avg_gain /= period as f32;
-    avg_loss /= period as f32;
-    
-    // 第一个RSI值
-    rsi[period-1] = if avg_loss == 0.0 { 
-        100.0 
-    } else { 
-        100.0 - (100.0 / (1.0 + avg_gain / avg_loss)) 
+    let to_rsi = |avg_gain: f32, avg_loss: f32| {
+        if avg_loss == 0.0 {
+            100.0
+        } else {
+            100.0 - (100.0 / (1.0 + avg_gain / avg_loss))
+        }
     };
     
+    // 第一个RSI值: 最早的period个涨跌幅
+    let seed = len - 1 - period;
+    let mut avg_gain = gains[seed..len-1].iter().sum::<f32>() / period as f32;
+    let mut avg_loss = losses[seed..len-1].iter().sum::<f32>() / period as f32;
+    rsi[seed] = to_rsi(avg_gain, avg_loss);
+    
     // 计算剩余的RSI值
-    for i in period..len {
-        avg_gain = (avg_gain * (period as f32 - 1.0) + gains[i-period]) / period as f32;
-        avg_loss = (avg_loss * (period as f32 - 1.0) + losses[i-period]) / period as f32;
-        
-        rsi[i] = if avg_loss == 0.0 { 
-            100.0 
-        } else { 
-            100.0 - (100.0 / (1.0 + avg_gain / avg_loss)) 
-        };
+    for i in (0..seed).rev() {
+        avg_gain = (avg_gain * (period as f32 - 1.0) + gains[i]) / period as f32;
+        avg_loss = (avg_loss * (period as f32 - 1.0) + losses[i]) / period as f32;
+        rsi[i] = to_rsi(avg_gain, avg_loss);
     }
     
     rsi
 }
 
-/// 计算随机指标(Stochastic Oscillator)
+/// 计算随机指标(Stochastic Oscillator) - 适用于倒序数据
 pub fn calculate_stochastic(highs: &[f32], lows: &[f32], closes: &[f32], k_period: usize, d_period: usize) -> (Vec<f32>, Vec<f32>) {
     let len = closes.len();
     let mut k_values = vec![0.0; len];
@@ -61,13 +53,13 @@ pub fn calculate_stochastic(highs: &[f32], lows: &[f32], closes: &[f32], k_perio
         return (k_values, d_values);
     }
     
-    // 计算%K
-    for i in k_period-1..len {
+    // 计算%K，窗口为当天及之前的k_period天(倒序中为i..i+k_period)
+    for i in 0..=len-k_period {
         let mut highest_high = f32::MIN;
         let mut lowest_low = f32::MAX;
         
         for j in 0..k_period {
-            let idx = i - j;
+            let idx = i + j;
             highest_high = highest_high.max(highs[idx]);
             lowest_low = lowest_low.min(lows[idx]);
         }
@@ -80,10 +72,10 @@ pub fn calculate_stochastic(highs: &[f32], lows: &[f32], closes: &[f32], k_perio
     }
     
     // 计算%D (简单移动平均)
-    for i in k_period+d_period-2..len {
+    for i in 0..(len + 2).saturating_sub(k_period + d_period) {
         let mut sum = 0.0;
         for j in 0..d_period {
-            sum += k_values[i-j];
+            sum += k_values[i+j];
         }
         d_values[i] = sum / d_period as f32;
     }
@@ -91,7 +83,103 @@ pub fn calculate_stochastic(highs: &[f32], lows: &[f32], closes: &[f32], k_perio
     (k_values, d_values)
 }
 
-/// 计算动量指标(Momentum)
+/// 计算KDJ指标(国内行情软件的平滑方式) - 适用于倒序数据
+///
+/// RSV取n日最高最低价，K = (k_smooth-1)/k_smooth·前K + 1/k_smooth·RSV，D同理平滑K，
+/// J = 3K - 2D，不截断到0..100。K、D初值为50，数据不足n天的位置为0
+pub fn calculate_kdj(highs: &[f32], lows: &[f32], closes: &[f32], n: usize, k_smooth: usize, d_smooth: usize) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
+    let len = highs.len().min(lows.len()).min(closes.len());
+    let mut k_values = vec![0.0; len];
+    let mut d_values = vec![0.0; len];
+    let mut j_values = vec![0.0; len];
+    
+    if n == 0 || k_smooth == 0 || d_smooth == 0 || len < n {
+        return (k_values, d_values, j_values);
+    }
+    
+    let mut prev_k = 50.0;
+    let mut prev_d = 50.0;
+    
+    // 从最早的完整窗口向最新递推，窗口为当天及之前的n天(倒序中为i..i+n)
+    for i in (0..=len-n).rev() {
+        let highest_high = highs[i..i+n].iter().fold(f32::MIN, |acc, &x| acc.max(x));
+        let lowest_low = lows[i..i+n].iter().fold(f32::MAX, |acc, &x| acc.min(x));
+        
+        let rsv = if highest_high != lowest_low {
+            100.0 * (closes[i] - lowest_low) / (highest_high - lowest_low)
+        } else {
+            50.0 // 如果最高价等于最低价，则取中间值
+        };
+        
+        let k = (prev_k * (k_smooth as f32 - 1.0) + rsv) / k_smooth as f32;
+        let d = (prev_d * (d_smooth as f32 - 1.0) + k) / d_smooth as f32;
+        
+        k_values[i] = k;
+        d_values[i] = d;
+        j_values[i] = 3.0 * k - 2.0 * d;
+        prev_k = k;
+        prev_d = d;
+    }
+    
+    (k_values, d_values, j_values)
+}
+
+/// 计算威廉指标(Williams %R) - 适用于倒序数据
+///
+/// 取值范围为[-100, 0]，最高价等于最低价时为-50；数据不足period天的位置为0
+pub fn calculate_williams_r(highs: &[f32], lows: &[f32], closes: &[f32], period: usize) -> Vec<f32> {
+    let len = highs.len().min(lows.len()).min(closes.len());
+    let mut wr = vec![0.0; len];
+    
+    if period == 0 || len < period {
+        return wr;
+    }
+    
+    // 窗口为当天及之前的period天(倒序中为i..i+period)
+    for i in 0..=len-period {
+        let highest_high = highs[i..i+period].iter().fold(f32::MIN, |acc, &x| acc.max(x));
+        let lowest_low = lows[i..i+period].iter().fold(f32::MAX, |acc, &x| acc.min(x));
+        
+        wr[i] = if highest_high != lowest_low {
+            -100.0 * (highest_high - closes[i]) / (highest_high - lowest_low)
+        } else {
+            -50.0
+        };
+    }
+    
+    wr
+}
+
+/// 计算顺势指标(CCI) - 适用于倒序数据
+///
+/// CCI = (TP - SMA(TP)) / (0.015 * 平均绝对偏差)，TP = (high + low + close) / 3；
+/// 平均绝对偏差为0时(价格完全走平)为0；数据不足period天的位置为0
+pub fn calculate_cci(highs: &[f32], lows: &[f32], closes: &[f32], period: usize) -> Vec<f32> {
+    let len = highs.len().min(lows.len()).min(closes.len());
+    let mut cci = vec![0.0; len];
+    
+    if period == 0 || len < period {
+        return cci;
+    }
+    
+    let typical: Vec<f32> = (0..len)
+        .map(|i| (highs[i] + lows[i] + closes[i]) / 3.0)
+        .collect();
+    
+    for i in 0..=len-period {
+        let window = &typical[i..i+period];
+        let mean = window.iter().sum::<f32>() / period as f32;
+        let mean_dev = window.iter().map(|&tp| (tp - mean).abs()).sum::<f32>() / period as f32;
+        
+        if mean_dev > f32::EPSILON {
+            cci[i] = (typical[i] - mean) / (0.015 * mean_dev);
+        }
+    }
+    
+    cci
+}
+
+/// 计算动量指标(Momentum) - 适用于倒序数据
 pub fn calculate_momentum(closes: &[f32], period: usize) -> Vec<f32> {
     let len = closes.len();
     let mut momentum = vec![0.0; len];
@@ -100,9 +188,221 @@ pub fn calculate_momentum(closes: &[f32], period: usize) -> Vec<f32> {
         return momentum;
     }
     
-    for i in period..len {
-        momentum[i] = closes[i] - closes[i-period];
+    for i in 0..len-period {
+        momentum[i] = closes[i] - closes[i+period];
     }
     
     momentum
 }
+
+/// 计算变动率(ROC): period天的涨跌幅(0.05表示5%) - 适用于倒序数据
+///
+/// 与calculate_momentum不同，结果不受价格高低影响，可在股票间比较；
+/// 数据不足或period天前收盘价不为正的位置为0
+pub fn calculate_roc(closes: &[f32], period: usize) -> Vec<f32> {
+    let len = closes.len();
+    let mut roc = vec![0.0; len];
+    
+    if period == 0 || len <= period {
+        return roc;
+    }
+    
+    for i in 0..len-period {
+        if closes[i+period] > 0.0 {
+            roc[i] = closes[i] / closes[i+period] - 1.0;
+        }
+    }
+    
+    roc
+}
+
+/// 计算标准化动量: period天ROC / 最近vol_window天日收益率的标准差 - 适用于倒序数据
+///
+/// 类似t统计量，衡量涨幅相对自身波动的显著程度，价格整体缩放时结果不变；
+/// 数据不足或波动为0的位置为0
+pub fn normalized_momentum(closes: &[f32], period: usize, vol_window: usize) -> Vec<f32> {
+    let len = closes.len();
+    let mut result = vec![0.0; len];
+    
+    if vol_window < 2 || len <= period.max(vol_window) {
+        return result;
+    }
+    
+    let roc = calculate_roc(closes, period);
+    let daily = calculate_roc(closes, 1);
+    
+    for i in 0..len-period.max(vol_window) {
+        let returns = &daily[i..i+vol_window];
+        let mean = returns.iter().sum::<f32>() / vol_window as f32;
+        let std_dev = (returns.iter().map(|&r| (r - mean).powi(2)).sum::<f32>() / vol_window as f32).sqrt();
+        if std_dev > f32::EPSILON {
+            result[i] = roc[i] / std_dev;
+        }
+    }
+    
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn momentum_compares_with_older_bar() {
+        // 倒序: closes[0]为最新
+        let closes = [14.0, 13.0, 12.0, 11.0, 10.0];
+        
+        let momentum = calculate_momentum(&closes, 2);
+        
+        assert_eq!(momentum, vec![2.0, 2.0, 2.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn stochastic_window_looks_back_in_time() {
+        // 倒序: 最新收于区间最高处，最早的两根没有完整窗口
+        let highs = [15.0, 14.0, 13.0, 12.0, 11.0];
+        let lows = [9.0, 9.0, 9.0, 9.0, 9.0];
+        let closes = [15.0, 12.0, 11.0, 10.0, 9.0];
+        
+        let (k, d) = calculate_stochastic(&highs, &lows, &closes, 3, 2);
+        
+        assert_eq!(k[0], 100.0);
+        assert_eq!(k[1], 60.0);
+        assert_eq!(k[2], 50.0);
+        assert_eq!((k[3], k[4]), (0.0, 0.0));
+        assert_eq!(d[0], 80.0);
+        assert_eq!(d[1], 55.0);
+        assert_eq!((d[2], d[3], d[4]), (0.0, 0.0, 0.0));
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-3, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn kdj_matches_reference_values() {
+        // 15根K线，倒序
+        let highs = [11.02, 10.52, 10.31, 10.62, 10.96, 11.13, 11.1, 10.61, 10.11, 10.06, 10.23, 10.56, 10.89, 10.69, 10.2];
+        let lows = [10.52, 10.02, 9.91, 10.07, 10.51, 10.68, 10.6, 10.11, 9.71, 9.51, 9.78, 10.11, 10.39, 10.19, 9.8];
+        let closes = [10.72, 10.27, 10.11, 10.32, 10.71, 10.93, 10.8, 10.36, 9.91, 9.76, 9.98, 10.36, 10.59, 10.44, 10.0];
+        
+        let (k, d, j) = calculate_kdj(&highs, &lows, &closes, 9, 3, 3);
+        
+        // 参考值由独立实现(K = 2/3·前K + 1/3·RSV，初值50)离线计算
+        for (i, ek, ed, ej) in [
+            (0, 58.5850, 58.0431, 59.6687),
+            (1, 52.3141, 57.7722, 41.3979),
+            (3, 64.0030, 63.2446, 65.5197),
+            (6, 60.3774, 53.4591, 74.2138),
+        ] {
+            assert_close(k[i], ek);
+            assert_close(d[i], ed);
+            assert_close(j[i], ej);
+        }
+        assert!(k[7..].iter().all(|&v| v == 0.0));
+    }
+
+    #[test]
+    fn kdj_j_line_is_not_clamped() {
+        // 倒序: 持续上涨，每天收于区间附近高点
+        let closes: Vec<f32> = (0..12).map(|i| 11.1 - 0.1 * i as f32).collect();
+        let highs: Vec<f32> = closes.iter().map(|c| c + 0.05).collect();
+        let lows: Vec<f32> = closes.iter().map(|c| c - 0.05).collect();
+        
+        let (k, d, j) = calculate_kdj(&highs, &lows, &closes, 9, 3, 3);
+        
+        assert_close(k[0], 85.6653);
+        assert_close(d[0], 73.9598);
+        assert!(j[0] > 100.0);
+        assert_close(j[0], 109.0764);
+    }
+
+    #[test]
+    fn williams_r_matches_hand_computed_values() {
+        // 倒序: 下标2、3所在窗口最高价等于最低价
+        let highs = [12.0, 11.0, 10.0, 10.0];
+        let lows = [9.0, 10.0, 10.0, 10.0];
+        let closes = [11.0, 10.0, 10.0, 10.0];
+        
+        let wr = calculate_williams_r(&highs, &lows, &closes, 2);
+        
+        assert_close(wr[0], -100.0 / 3.0);
+        assert_eq!(wr[1], -100.0);
+        assert_eq!(wr[2], -50.0);
+        // 最早一根数据不足，按0填充
+        assert_eq!(wr[3], 0.0);
+    }
+
+    #[test]
+    fn williams_r_stays_within_bounds() {
+        let highs = [10.5, 11.0, 10.8, 10.2, 10.4];
+        let lows = [9.8, 10.1, 10.0, 9.6, 9.9];
+        let closes = [10.5, 10.2, 10.1, 9.6, 10.0];
+        
+        let wr = calculate_williams_r(&highs, &lows, &closes, 3);
+        
+        assert!(wr[..3].iter().all(|&v| (-100.0..=0.0).contains(&v)));
+        assert_eq!(wr[3..], [0.0, 0.0]);
+        assert_eq!(calculate_williams_r(&highs, &lows, &closes, 6), vec![0.0; 5]);
+    }
+
+    #[test]
+    fn cci_matches_reference_values() {
+        let highs = [10.5, 11.0, 10.8, 10.2, 10.4, 10.0];
+        let lows = [9.8, 10.1, 10.0, 9.6, 9.9, 9.5];
+        let closes = [10.5, 10.2, 10.1, 9.6, 10.0, 9.8];
+        
+        let cci = calculate_cci(&highs, &lows, &closes, 4);
+        
+        assert_close(cci[0], 22.2222);
+        assert_close(cci[1], 88.0);
+        assert_close(cci[2], 98.6667);
+        assert_eq!(cci[3..], [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn cci_uses_typical_price_deviation() {
+        // 最高、最低、收盘相同时典型价格即收盘价
+        let prices = [13.0, 12.0, 11.0, 12.0, 10.0, 10.0];
+        
+        let cci = calculate_cci(&prices, &prices, &prices, 3);
+        
+        assert_close(cci[0], 100.0);
+        assert_close(cci[1], 50.0);
+        assert_close(cci[2], 0.0);
+        assert_close(cci[3], 100.0);
+    }
+
+    #[test]
+    fn cci_of_flat_series_is_zero() {
+        let flat = [10.0f32; 5];
+        
+        let cci = calculate_cci(&flat, &flat, &flat, 3);
+        
+        assert_eq!(cci, vec![0.0; 5]);
+    }
+
+    #[test]
+    fn roc_matches_geometric_growth() {
+        // 倒序，每天上涨2%
+        let closes: Vec<f32> = (0..10).map(|i| 10.0 * 1.02f32.powi(9 - i)).collect();
+        
+        let roc = calculate_roc(&closes, 3);
+        
+        assert_close(roc[0], 1.02f32.powi(3) - 1.0);
+        assert_eq!(roc[7], 0.0);
+    }
+
+    #[test]
+    fn normalized_momentum_is_scale_invariant() {
+        let closes: Vec<f32> = (0..40).map(|i| 20.0 - i as f32 * 0.2 + (i as f32 * 1.7).sin() * 0.3).collect();
+        let scaled: Vec<f32> = closes.iter().map(|c| c * 100.0).collect();
+        
+        let base = normalized_momentum(&closes, 10, 20);
+        let rescaled = normalized_momentum(&scaled, 10, 20);
+        
+        assert!(base[0] > 0.0);
+        assert!((base[0] - rescaled[0]).abs() < 1e-3 * base[0].abs());
+        assert_eq!(normalized_momentum(&[10.0; 40], 10, 20)[0], 0.0);
+    }
+}
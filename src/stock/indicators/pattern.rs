@@ -0,0 +1,175 @@
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use super::pivots::PivotKind;
+
+/// ZigZag波段拐点
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwingPoint {
+    /// K线下标(倒序)
+    pub idx: usize,
+    /// 波段高点取最高价，低点取最低价
+    pub price: f32,
+    pub kind: PivotKind,
+    /// 最新的拐点尚未被反向波动确认，后续K线可能把它推到更远的位置
+    pub provisional: bool,
+}
+
+/// 计算ZigZag波段结构 - 适用于倒序数据
+///
+/// 从最早一天向最新一天扫描，价格从当前极值反向波动至少reversal_pct(如0.05表示5%)时
+/// 确认该极值为拐点，高低点交替出现，小于阈值的波动被忽略。
+/// 返回拐点(从新到旧)，第一个为最新的、provisional为true的拐点
+pub fn zigzag(bars: &[DailyBar], reversal_pct: f32) -> Vec<SwingPoint> {
+    let len = bars.len();
+    let mut swings: Vec<SwingPoint> = Vec::new();
+    if len == 0 {
+        return swings;
+    }
+    
+    let confirm = |idx: usize, price: f32, kind: PivotKind| SwingPoint { idx, price, kind, provisional: false };
+    
+    // 方向确定前同时跟踪最高点和最低点
+    let oldest = len - 1;
+    let (mut high_idx, mut low_idx) = (oldest, oldest);
+    // 方向确定后跟踪当前波段的极值
+    let mut current: Option<SwingPoint> = None;
+    
+    for i in (0..oldest).rev() {
+        let bar = &bars[i];
+        match current {
+            None => {
+                if bar.high >= bars[low_idx].low * (1.0 + reversal_pct) {
+                    swings.push(confirm(low_idx, bars[low_idx].low, PivotKind::Low));
+                    current = Some(confirm(i, bar.high, PivotKind::High));
+                } else if bar.low <= bars[high_idx].high * (1.0 - reversal_pct) {
+                    swings.push(confirm(high_idx, bars[high_idx].high, PivotKind::High));
+                    current = Some(confirm(i, bar.low, PivotKind::Low));
+                } else {
+                    if bar.high > bars[high_idx].high {
+                        high_idx = i;
+                    }
+                    if bar.low < bars[low_idx].low {
+                        low_idx = i;
+                    }
+                }
+            }
+            Some(ref mut swing) => match swing.kind {
+                PivotKind::High => {
+                    if bar.high > swing.price {
+                        *swing = confirm(i, bar.high, PivotKind::High);
+                    } else if bar.low <= swing.price * (1.0 - reversal_pct) {
+                        swings.push(*swing);
+                        *swing = confirm(i, bar.low, PivotKind::Low);
+                    }
+                }
+                PivotKind::Low => {
+                    if bar.low < swing.price {
+                        *swing = confirm(i, bar.low, PivotKind::Low);
+                    } else if bar.high >= swing.price * (1.0 + reversal_pct) {
+                        swings.push(*swing);
+                        *swing = confirm(i, bar.high, PivotKind::High);
+                    }
+                }
+            },
+        }
+    }
+    
+    if let Some(mut last) = current {
+        last.provisional = true;
+        swings.push(last);
+    }
+    
+    swings.reverse();
+    swings
+}
+
+/// 比尔·威廉姆斯分形默认的单侧宽度(5根K线分形)
+pub const DEFAULT_FRACTAL_WIDTH: usize = 2;
+
+/// 计算5根K线分形 - 适用于倒序数据
+///
+/// 返回(分形高点, 分形低点)标记，见fractals_with_width
+pub fn fractals(highs: &[f32], lows: &[f32]) -> (Vec<bool>, Vec<bool>) {
+    fractals_with_width(highs, lows, DEFAULT_FRACTAL_WIDTH)
+}
+
+/// 计算单侧宽度为width的分形 - 适用于倒序数据
+///
+/// 第i天的最高价严格高于前后各width天的最高价时为分形高点，最低价严格低于时为分形低点，
+/// 并列不算。首尾各width天缺少一侧的K线，永远不是分形；最新width天的分形要等后续K线确认
+pub fn fractals_with_width(highs: &[f32], lows: &[f32], width: usize) -> (Vec<bool>, Vec<bool>) {
+    let len = highs.len().min(lows.len());
+    let mut up = vec![false; len];
+    let mut down = vec![false; len];
+    
+    if width == 0 || len < 2 * width + 1 {
+        return (up, down);
+    }
+    
+    for i in width..len-width {
+        let neighbors = (i-width..=i+width).filter(|&j| j != i);
+        up[i] = neighbors.clone().all(|j| highs[i] > highs[j]);
+        down[i] = neighbors.clone().all(|j| lows[i] < lows[j]);
+    }
+    
+    (up, down)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 按时间顺序的价格: 低点10，高点12，11.6/11.8的小幅波动，低点10.8，最新高点13
+    const PRICES: [f32; 12] = [10.5, 10.0, 11.0, 12.0, 11.6, 11.8, 11.0, 10.8, 11.5, 12.5, 13.0, 12.8];
+
+    fn bars() -> Vec<DailyBar> {
+        PRICES.iter()
+            .rev()
+            .enumerate()
+            .map(|(i, &v)| DailyBar {
+                date: 20230630 - i as i32,
+                open: v,
+                high: v,
+                low: v,
+                close: v,
+                volume: 1000,
+                amount: 10000,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn zigzag_ignores_sub_threshold_wiggle() {
+        let swings = zigzag(&bars(), 0.05);
+        let points: Vec<(usize, f32, PivotKind, bool)> = swings.iter()
+            .map(|s| (s.idx, s.price, s.kind, s.provisional))
+            .collect();
+        
+        assert_eq!(points, vec![
+            (1, 13.0, PivotKind::High, true),
+            (4, 10.8, PivotKind::Low, false),
+            (8, 12.0, PivotKind::High, false),
+            (10, 10.0, PivotKind::Low, false),
+        ]);
+    }
+
+    #[test]
+    fn zigzag_without_reversal_has_no_swings() {
+        assert!(zigzag(&[], 0.05).is_empty());
+        // 整体波动不足阈值，方向始终无法确定
+        assert!(zigzag(&bars()[..4], 0.2).is_empty());
+    }
+
+    #[test]
+    fn fractals_skip_boundaries_and_ties() {
+        // 倒序: 下标0和9分别是最高点和最低点，但缺少一侧K线；下标6、7为并列高点
+        let highs = [9.0, 5.0, 6.0, 8.0, 6.0, 5.0, 7.0, 7.0, 4.0, 3.0];
+        let lows = [3.0, 2.5, 3.0, 4.0, 2.0, 1.0, 2.5, 3.0, 3.5, 0.5];
+        
+        let (up, down) = fractals(&highs, &lows);
+        
+        let flagged = |flags: &[bool]| flags.iter().enumerate().filter(|(_, &f)| f).map(|(i, _)| i).collect::<Vec<_>>();
+        assert_eq!(flagged(&up), vec![3]);
+        assert_eq!(flagged(&down), vec![5]);
+        assert_eq!(fractals(&highs[..4], &lows[..4]), (vec![false; 4], vec![false; 4]));
+    }
+}
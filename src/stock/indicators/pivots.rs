@@ -0,0 +1,150 @@
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+
+/// last_swing_low使用的左右确认天数
+pub const DEFAULT_SWING_WIDTH: usize = 2;
+
+/// 拐点类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PivotKind {
+    /// 波段高点，比较最高价
+    High,
+    /// 波段低点，比较最低价
+    Low,
+}
+
+impl PivotKind {
+    fn value(self, bar: &DailyBar) -> f32 {
+        match self {
+            PivotKind::High => bar.high,
+            PivotKind::Low => bar.low,
+        }
+    }
+    
+    /// a是否比b更极端(高点更高、低点更低)
+    fn beats(self, a: f32, b: f32) -> bool {
+        match self {
+            PivotKind::High => a > b,
+            PivotKind::Low => a < b,
+        }
+    }
+}
+
+/// 查找拐点 - 适用于倒序数据
+///
+/// left为之前的天数(倒序中为i+1..=i+left)，right为之后用于确认的天数(i-right..i)。
+/// 第i天必须严格优于两侧所有K线，并列时都不算拐点；两侧数据不足的位置不参与判断，
+/// 因此最新的right天不会出现拐点
+pub fn find_pivots(bars: &[DailyBar], left: usize, right: usize, kind: PivotKind) -> Vec<usize> {
+    let len = bars.len();
+    if len <= left + right {
+        return Vec::new();
+    }
+    
+    (right..len-left)
+        .filter(|&i| {
+            let value = kind.value(&bars[i]);
+            (i-right..=i+left)
+                .filter(|&j| j != i)
+                .all(|j| kind.beats(value, kind.value(&bars[j])))
+        })
+        .collect()
+}
+
+/// 查找波段高点，返回下标(从新到旧) - 适用于倒序数据
+pub fn pivot_highs(bars: &[DailyBar], left: usize, right: usize) -> Vec<usize> {
+    find_pivots(bars, left, right, PivotKind::High)
+}
+
+/// 查找波段低点，返回下标(从新到旧) - 适用于倒序数据
+pub fn pivot_lows(bars: &[DailyBar], left: usize, right: usize) -> Vec<usize> {
+    find_pivots(bars, left, right, PivotKind::Low)
+}
+
+/// 压缩过密的拐点: 间隔小于min_separation天的拐点中只保留最极端的一个
+///
+/// 从最极端的拐点开始贪心保留，极端程度相同时保留较新的；返回下标(从新到旧)
+pub fn min_separation(bars: &[DailyBar], pivots: &[usize], kind: PivotKind, min_separation: usize) -> Vec<usize> {
+    let mut ordered: Vec<usize> = pivots.to_vec();
+    ordered.sort_by(|&a, &b| {
+        let (va, vb) = (kind.value(&bars[a]), kind.value(&bars[b]));
+        let by_value = match kind {
+            PivotKind::High => vb.partial_cmp(&va),
+            PivotKind::Low => va.partial_cmp(&vb),
+        };
+        by_value.unwrap_or(std::cmp::Ordering::Equal).then(a.cmp(&b))
+    });
+    
+    let mut kept: Vec<usize> = Vec::new();
+    for idx in ordered {
+        if kept.iter().all(|&k| k.abs_diff(idx) >= min_separation) {
+            kept.push(idx);
+        }
+    }
+    
+    kept.sort_unstable();
+    kept
+}
+
+/// 最近lookback天内最新的波段低点(左右各DEFAULT_SWING_WIDTH天确认) - 适用于倒序数据
+///
+/// 回测时应传入bars[forecast_idx..]，避免用到未来数据
+pub fn last_swing_low(bars: &[DailyBar], lookback: usize) -> Option<usize> {
+    pivot_lows(bars, DEFAULT_SWING_WIDTH, DEFAULT_SWING_WIDTH)
+        .into_iter()
+        .next()
+        .filter(|&i| i < lookback)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 锯齿形测试数据(倒序): 高点在2、6，低点在4、8，9和10为并列高点
+    const ZIGZAG: [f32; 13] = [1.0, 2.0, 6.0, 2.0, 1.0, 3.0, 7.0, 3.0, 1.0, 4.0, 4.0, 1.0, 2.0];
+
+    fn zigzag_bars() -> Vec<DailyBar> {
+        ZIGZAG.iter()
+            .enumerate()
+            .map(|(i, &v)| DailyBar {
+                date: 20230630 - i as i32,
+                open: v,
+                high: v + 0.5,
+                low: v - 0.5,
+                close: v,
+                volume: 1000,
+                amount: 10000,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_known_pivots_and_skips_ties() {
+        let bars = zigzag_bars();
+        
+        assert_eq!(pivot_highs(&bars, 2, 2), vec![2, 6]);
+        assert_eq!(pivot_lows(&bars, 2, 2), vec![4, 8]);
+        // 并列的9和10都不是严格最大值
+        assert!(pivot_highs(&bars, 1, 1).iter().all(|&i| i != 9 && i != 10));
+        assert!(pivot_highs(&bars[..4], 2, 2).is_empty());
+    }
+
+    #[test]
+    fn min_separation_keeps_most_extreme_pivot() {
+        let bars = zigzag_bars();
+        
+        assert_eq!(min_separation(&bars, &[2, 6], PivotKind::High, 5), vec![6]);
+        assert_eq!(min_separation(&bars, &[2, 6], PivotKind::High, 4), vec![2, 6]);
+        // 极端程度相同时保留较新的
+        assert_eq!(min_separation(&bars, &[4, 8], PivotKind::Low, 5), vec![4]);
+    }
+
+    #[test]
+    fn last_swing_low_respects_lookback() {
+        let bars = zigzag_bars();
+        
+        assert_eq!(last_swing_low(&bars, 10), Some(4));
+        assert_eq!(last_swing_low(&bars, 4), None);
+        // 截掉3天后原低点4右侧确认不足，返回原低点8
+        assert_eq!(last_swing_low(&bars[3..], 10), Some(5));
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use crate::utils::metrics::beta;
+
+/// 计算贝塔所需的最少重叠收益率个数(window更小时以window为准)
+pub const MIN_BETA_OBSERVATIONS: usize = 20;
+
+/// 收益率序列: (日期, 收益率)
+pub type DatedReturns = Vec<(i32, f32)>;
+
+/// 按日期对齐个股和基准，返回最近window个交易日的(个股收益率, 基准收益率) - 适用于倒序数据
+///
+/// 只保留两边都有收盘价的日期，收益率为相邻两个共同日期之间的涨跌幅，记在较新的日期上，顺序为从新到旧
+pub fn aligned_returns(bars: &[DailyBar], bench_bars: &[DailyBar], window: usize) -> (DatedReturns, DatedReturns) {
+    let bench_close: HashMap<i32, f32> = bench_bars.iter()
+        .map(|bar| (bar.date, bar.close))
+        .collect();
+    
+    // 共同日期(倒序)，多取一天用于计算最早一个收益率
+    let common: Vec<(i32, f32, f32)> = bars.iter()
+        .filter_map(|bar| bench_close.get(&bar.date).map(|&bench| (bar.date, bar.close, bench)))
+        .take(window + 1)
+        .collect();
+    
+    let mut stock_returns = Vec::new();
+    let mut bench_returns = Vec::new();
+    for pair in common.windows(2) {
+        let ((date, close, bench), (_, prev_close, prev_bench)) = (pair[0], pair[1]);
+        if prev_close > 0.0 && prev_bench > 0.0 {
+            stock_returns.push((date, close / prev_close - 1.0));
+            bench_returns.push((date, bench / prev_bench - 1.0));
+        }
+    }
+    
+    (stock_returns, bench_returns)
+}
+
+/// 计算最近window个交易日相对基准的贝塔 - 适用于倒序数据
+///
+/// 先按日期取交集再计算收益率，停牌、上市较晚等造成的缺失日期不会错位；
+/// 重叠收益率少于min(window, MIN_BETA_OBSERVATIONS)个或基准没有波动时返回None
+pub fn beta_to_benchmark(bars: &[DailyBar], bench_bars: &[DailyBar], window: usize) -> Option<f32> {
+    let (stock_returns, bench_returns) = aligned_returns(bars, bench_bars, window);
+    let required = window.clamp(2, MIN_BETA_OBSERVATIONS);
+    if stock_returns.len() < required {
+        return None;
+    }
+    
+    beta(&stock_returns, &bench_returns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stock::mock_data::create_mock_daily_bars;
+    
+    /// 基准收盘价每天交替涨跌，个股涨跌幅恒为基准的两倍 - 倒序
+    fn doubled_pair(count: usize) -> (Vec<DailyBar>, Vec<DailyBar>) {
+        let mut bench = create_mock_daily_bars(count);
+        let mut stock = create_mock_daily_bars(count);
+        let (mut bench_close, mut stock_close) = (100.0f32, 100.0f32);
+        for (i, (b, s)) in bench.iter_mut().zip(stock.iter_mut()).rev().enumerate() {
+            let r = if i % 2 == 0 { 0.01 } else { -0.005 } * (1.0 + (i % 3) as f32);
+            if i > 0 {
+                bench_close *= 1.0 + r;
+                stock_close *= 1.0 + 2.0 * r;
+            }
+            b.close = bench_close;
+            s.close = stock_close;
+        }
+        (stock, bench)
+    }
+    
+    #[test]
+    fn beta_to_benchmark_is_two_for_doubled_stock() {
+        let (stock, bench) = doubled_pair(30);
+        
+        assert!((beta_to_benchmark(&stock, &bench, 25).unwrap() - 2.0).abs() < 1e-3);
+    }
+    
+    #[test]
+    fn missing_stock_dates_do_not_shift_returns() {
+        let (stock, bench) = doubled_pair(40);
+        // 去掉个股较新的一天，该日的两个收益率合并为一个跨两天的收益率，日期依然对齐
+        let gapped: Vec<DailyBar> = stock.iter().enumerate()
+            .filter(|(i, _)| *i != 5)
+            .map(|(_, bar)| bar.clone())
+            .collect();
+        let (stock_returns, bench_returns) = aligned_returns(&gapped, &bench, 25);
+        
+        assert_eq!(stock_returns.len(), 25);
+        assert!(stock_returns.iter().zip(&bench_returns).all(|(s, b)| s.0 == b.0));
+        assert!(!stock_returns.iter().any(|&(date, _)| date == stock[5].date));
+        assert!((beta_to_benchmark(&gapped, &bench, 25).unwrap() - 2.0).abs() < 0.01);
+    }
+    
+    #[test]
+    fn too_little_overlap_is_none() {
+        let (stock, bench) = doubled_pair(30);
+        
+        assert_eq!(beta_to_benchmark(&stock[..10], &bench, 25), None);
+        assert_eq!(beta_to_benchmark(&stock, &bench[20..], 25), None);
+    }
+}
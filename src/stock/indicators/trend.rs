@@ -1,26 +1,35 @@
-
-/// 计算指数移动平均线(EMA) - 适用于倒序数据
-pub fn calculate_ema(data: &[f32], period: usize, idx: usize) -> f32 {
-    if idx >= data.len() || period > idx + 1 {
-        return 0.0;
+/// 计算指数移动平均线(EMA)序列 - 适用于倒序数据
+///
+/// 以最早period天的简单平均为种子，向新的方向(下标变小)递推:
+/// EMA[i] = data[i] * k + EMA[i+1] * (1 - k)，k = 2 / (period + 1)；数据不足的位置为0
+pub fn calculate_ema_series(data: &[f32], period: usize) -> Vec<f32> {
+    let len = data.len();
+    let mut ema = vec![0.0f32; len];
+    
+    if period == 0 || len < period {
+        return ema;
     }
     
-    // 计算EMA系数
     let k = 2.0 / (period as f32 + 1.0);
+    let seed = len - period;
+    ema[seed] = data[seed..].iter().sum::<f32>() / period as f32;
     
-    // 初始化EMA为简单移动平均
-    let mut ema = 0.0;
-    for i in 0..period {
-        ema += data[idx - i];
+    for i in (0..seed).rev() {
+        ema[i] = data[i] * k + ema[i+1] * (1.0 - k);
     }
-    ema /= period as f32;
     
-    // 计算EMA
-    for i in (0..=idx).rev().take(period) {
-        ema = data[i] * k + ema * (1.0 - k);
+    ema
+}
+
+/// 计算第idx天的指数移动平均线(EMA) - 适用于倒序数据
+///
+/// 只使用idx及之前的数据(data[idx..])，等价于calculate_ema_series(&data[idx..], period)[0]
+pub fn calculate_ema(data: &[f32], period: usize, idx: usize) -> f32 {
+    if idx >= data.len() {
+        return 0.0;
     }
     
-    ema
+    calculate_ema_series(&data[idx..], period)[0]
 }
 
 /// 计算移动平均线 - 适用于倒序数据
@@ -44,68 +53,459 @@ pub fn moving_average(data: &[f32], window: usize) -> Vec<f32> {
 }
 
 /// 计算MACD指标 - 适用于倒序数据
+///
+/// 返回(DIF, DEA, 柱状图)，DIF = EMA(fast) - EMA(slow)，DEA为DIF的signal_period日EMA，
+/// 柱状图 = DIF - DEA；数据不足的位置为0
 pub fn calculate_macd(closes: &[f32], fast_period: usize, slow_period: usize, signal_period: usize) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
     let len = closes.len();
     let mut macd = vec![0.0; len];
     let mut signal = vec![0.0; len];
     let mut histogram = vec![0.0; len];
     
-    if len <= slow_period {
+    if slow_period == 0 || len < slow_period {
         return (macd, signal, histogram);
     }
     
-    // 计算EMA
-    let mut fast_ema = vec![0.0; len];
-    let mut slow_ema = vec![0.0; len];
+    // 计算快线和慢线EMA
+    let fast_ema = calculate_ema_series(closes, fast_period);
+    let slow_ema = calculate_ema_series(closes, slow_period);
+    
+    // 计算MACD线(慢线有效的部分)
+    let valid = len - slow_period + 1;
+    for i in 0..valid {
+        macd[i] = fast_ema[i] - slow_ema[i];
+    }
+    
+    // 计算信号线和柱状图
+    let signal_ema = calculate_ema_series(&macd[..valid], signal_period);
+    if signal_period > 0 && valid >= signal_period {
+        for i in 0..=valid-signal_period {
+            signal[i] = signal_ema[i];
+            histogram[i] = macd[i] - signal[i];
+        }
+    }
     
-    // 计算EMA系数
-    let fast_k = 2.0 / (fast_period as f32 + 1.0);
-    let slow_k = 2.0 / (slow_period as f32 + 1.0);
-    let signal_k = 2.0 / (signal_period as f32 + 1.0);
+    (macd, signal, histogram)
+}
+
+/// 计算成交量加权EMA序列: EMA(价格 * 成交量) / EMA(成交量) - 适用于倒序数据
+///
+/// 每天的权重为其成交量相对成交量EMA的比例，成交量EMA为0的位置退化为普通EMA
+fn calculate_vw_ema_series(data: &[f32], volumes: &[f32], period: usize) -> Vec<f32> {
+    let len = data.len().min(volumes.len());
+    let weighted: Vec<f32> = (0..len).map(|i| data[i] * volumes[i]).collect();
+    let weighted_ema = calculate_ema_series(&weighted, period);
+    let volume_ema = calculate_ema_series(&volumes[..len], period);
+    let plain_ema = calculate_ema_series(&data[..len], period);
     
-    // 初始化EMA
-    let mut fast_sum = 0.0;
-    let mut slow_sum = 0.0;
+    (0..len)
+        .map(|i| if volume_ema[i] > 0.0 { weighted_ema[i] / volume_ema[i] } else { plain_ema[i] })
+        .collect()
+}
+
+/// 计算成交量加权MACD - 适用于倒序数据
+///
+/// 与calculate_macd相同，但快线和慢线为成交量加权EMA，弱化缩量阴跌/阴涨的影响；
+/// 信号线仍为DIF的普通EMA。返回(DIF, DEA, 柱状图)，数据不足的位置为0
+pub fn calculate_vw_macd(closes: &[f32], volumes: &[f32], fast_period: usize, slow_period: usize, signal_period: usize) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
+    let len = closes.len().min(volumes.len());
+    let mut macd = vec![0.0; len];
+    let mut signal = vec![0.0; len];
+    let mut histogram = vec![0.0; len];
     
-    for i in 0..fast_period {
-        fast_sum += closes[i];
+    if slow_period == 0 || len < slow_period {
+        return (macd, signal, histogram);
     }
-    fast_ema[fast_period-1] = fast_sum / fast_period as f32;
     
-    for i in 0..slow_period {
-        slow_sum += closes[i];
+    // 计算快线和慢线
+    let fast_ema = calculate_vw_ema_series(closes, volumes, fast_period);
+    let slow_ema = calculate_vw_ema_series(closes, volumes, slow_period);
+    
+    // 计算MACD线(慢线有效的部分)
+    let valid = len - slow_period + 1;
+    for i in 0..valid {
+        macd[i] = fast_ema[i] - slow_ema[i];
     }
-    slow_ema[slow_period-1] = slow_sum / slow_period as f32;
     
-    // 计算快线和慢线EMA
-    for i in fast_period..len {
-        fast_ema[i] = closes[i-fast_period] * fast_k + fast_ema[i-1] * (1.0 - fast_k);
+    // 计算信号线和柱状图
+    let signal_ema = calculate_ema_series(&macd[..valid], signal_period);
+    if signal_period > 0 && valid >= signal_period {
+        for i in 0..=valid-signal_period {
+            signal[i] = signal_ema[i];
+            histogram[i] = macd[i] - signal[i];
+        }
     }
     
-    for i in slow_period..len {
-        slow_ema[i] = closes[i-slow_period] * slow_k + slow_ema[i-1] * (1.0 - slow_k);
+    (macd, signal, histogram)
+}
+
+/// 计算趋向指标(DMI/ADX) - 适用于倒序数据
+///
+/// 使用Wilder平滑: +DM/-DM和真实波幅平滑得到+DI/-DI，DX再平滑得到ADX。
+/// 返回(+DI, -DI, ADX)，数据不足的位置为0
+pub fn calculate_dmi(highs: &[f32], lows: &[f32], closes: &[f32], period: usize) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
+    let len = highs.len().min(lows.len()).min(closes.len());
+    let mut plus_di = vec![0.0f32; len];
+    let mut minus_di = vec![0.0f32; len];
+    let mut adx = vec![0.0f32; len];
+    
+    if period == 0 || len < period + 1 {
+        return (plus_di, minus_di, adx);
     }
     
-    // 计算MACD线
-    for i in slow_period..len {
-        macd[i] = fast_ema[i] - slow_ema[i];
+    let p = period as f32;
+    let (mut tr_sum, mut plus_sum, mut minus_sum) = (0.0f32, 0.0f32, 0.0f32);
+    let mut dx_sum = 0.0f32;
+    
+    // t为从最早一根K线起的天数，倒序中对应下标len-1-t
+    for t in 1..len {
+        let i = len - 1 - t;
+        let prev = i + 1;
+        
+        let up = highs[i] - highs[prev];
+        let down = lows[prev] - lows[i];
+        let plus_dm = if up > down && up > 0.0 { up } else { 0.0 };
+        let minus_dm = if down > up && down > 0.0 { down } else { 0.0 };
+        let tr = (highs[i] - lows[i])
+            .max((highs[i] - closes[prev]).abs())
+            .max((lows[i] - closes[prev]).abs());
+        
+        // 前period天求和作为初值，之后按Wilder方法平滑
+        if t <= period {
+            tr_sum += tr;
+            plus_sum += plus_dm;
+            minus_sum += minus_dm;
+            if t < period {
+                continue;
+            }
+        } else {
+            tr_sum = tr_sum - tr_sum / p + tr;
+            plus_sum = plus_sum - plus_sum / p + plus_dm;
+            minus_sum = minus_sum - minus_sum / p + minus_dm;
+        }
+        
+        if tr_sum > 0.0 {
+            plus_di[i] = 100.0 * plus_sum / tr_sum;
+            minus_di[i] = 100.0 * minus_sum / tr_sum;
+        }
+        let di_sum = plus_di[i] + minus_di[i];
+        let dx = if di_sum > 0.0 { 100.0 * (plus_di[i] - minus_di[i]).abs() / di_sum } else { 0.0 };
+        
+        // ADX首值为period个DX的均值，之后按Wilder方法平滑
+        if t < 2 * period - 1 {
+            dx_sum += dx;
+        } else if t == 2 * period - 1 {
+            adx[i] = (dx_sum + dx) / p;
+        } else {
+            adx[i] = (adx[prev] * (p - 1.0) + dx) / p;
+        }
     }
     
-    // 计算信号线
-    let mut signal_sum = 0.0;
-    for i in slow_period..(slow_period+signal_period) {
-        signal_sum += macd[i];
+    (plus_di, minus_di, adx)
+}
+
+/// 一目均衡表(Ichimoku)各分量，与输入等长、倒序排列
+#[derive(Debug, Clone, Default)]
+pub struct IchimokuSeries {
+    /// 转换线: 近tenkan天(含当天)最高价与最低价的中点
+    pub tenkan: Vec<f32>,
+    /// 基准线: 近kijun天(含当天)最高价与最低价的中点
+    pub kijun: Vec<f32>,
+    /// 先行带A: (转换线 + 基准线) / 2，向未来平移kijun天
+    pub senkou_a: Vec<f32>,
+    /// 先行带B: 近senkou_b天最高价与最低价的中点，向未来平移kijun天
+    pub senkou_b: Vec<f32>,
+    /// 迟行线: 收盘价向过去平移kijun天
+    pub chikou: Vec<f32>,
+}
+
+/// 窗口i..i+period内最高价与最低价的中点，数据不足的位置为0
+fn midpoint_series(highs: &[f32], lows: &[f32], period: usize, len: usize) -> Vec<f32> {
+    let mut mid = vec![0.0; len];
+    if period == 0 || len < period {
+        return mid;
     }
-    signal[slow_period+signal_period-1] = signal_sum / signal_period as f32;
     
-    for i in (slow_period+signal_period)..len {
-        signal[i] = macd[i-signal_period] * signal_k + signal[i-1] * (1.0 - signal_k);
+    for i in 0..=len-period {
+        let highest = highs[i..i+period].iter().fold(f32::MIN, |acc, &x| acc.max(x));
+        let lowest = lows[i..i+period].iter().fold(f32::MAX, |acc, &x| acc.min(x));
+        mid[i] = (highest + lowest) / 2.0;
     }
     
-    // 计算柱状图
-    for i in (slow_period+signal_period)..len {
-        histogram[i] = macd[i] - signal[i];
+    mid
+}
+
+/// 计算一目均衡表(Ichimoku) - 适用于倒序数据
+///
+/// 倒序数据中"向未来平移"意味着下标变小，平移量统一为kijun:
+/// - senkou_a[i] = (tenkan[i+kijun] + kijun[i+kijun]) / 2，即kijun天前算出、画在第i天的云层
+/// - senkou_b[i] = 第i+kijun天算出的senkou_b中点
+/// - chikou[i] = closes[i-kijun]，即kijun天后的收盘价画在第i天
+///
+/// 超出今天的未来云层不输出；chikou引用了第i天之后的数据，只能用于展示，不能用于选股信号。
+/// 数据不足或平移越界的位置为0
+pub fn calculate_ichimoku(highs: &[f32], lows: &[f32], closes: &[f32], tenkan: usize, kijun: usize, senkou_b: usize) -> IchimokuSeries {
+    let len = highs.len().min(lows.len()).min(closes.len());
+    let tenkan_line = midpoint_series(highs, lows, tenkan, len);
+    let kijun_line = midpoint_series(highs, lows, kijun, len);
+    let span_b = midpoint_series(highs, lows, senkou_b, len);
+    
+    let mut senkou_a = vec![0.0; len];
+    let mut senkou_b_line = vec![0.0; len];
+    let mut chikou = vec![0.0; len];
+    
+    for i in 0..len {
+        // 先行带: 取kijun天前(倒序中下标更大)的值
+        let src = i + kijun;
+        if src < len {
+            if tenkan_line[src] != 0.0 && kijun_line[src] != 0.0 {
+                senkou_a[i] = (tenkan_line[src] + kijun_line[src]) / 2.0;
+            }
+            senkou_b_line[i] = span_b[src];
+        }
+        
+        // 迟行线: 取kijun天后(倒序中下标更小)的收盘价
+        if i >= kijun {
+            chikou[i] = closes[i - kijun];
+        }
     }
     
-    (macd, signal, histogram)
+    IchimokuSeries {
+        tenkan: tenkan_line,
+        kijun: kijun_line,
+        senkou_a,
+        senkou_b: senkou_b_line,
+        chikou,
+    }
+}
+
+/// 计算趋势质量: 最近window天收盘价线性回归的R²(0..1) - 适用于倒序数据
+///
+/// 越接近1说明价格越贴近一条直线稳步运行，单日跳空加震荡的走势得分较低，
+/// 可作为动量、均线多头排列得分的乘数。价格完全走平或数据不足window天的位置为0
+pub fn trend_quality(closes: &[f32], window: usize) -> Vec<f32> {
+    let len = closes.len();
+    let mut quality = vec![0.0f32; len];
+    
+    if window < 2 || len < window {
+        return quality;
+    }
+    
+    // x为距窗口最早一天的天数，窗口内均值和方差固定
+    let n = window as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let var_x: f64 = (0..window).map(|x| (x as f64 - mean_x).powi(2)).sum();
+    
+    for i in 0..=len-window {
+        let values = &closes[i..i+window];
+        let mean_y = values.iter().map(|&y| y as f64).sum::<f64>() / n;
+        
+        // values[0]为最新，对应x = window - 1
+        let mut cov = 0.0;
+        let mut var_y = 0.0;
+        for (j, &y) in values.iter().enumerate() {
+            let dy = y as f64 - mean_y;
+            cov += ((window - 1 - j) as f64 - mean_x) * dy;
+            var_y += dy * dy;
+        }
+        
+        if var_y > 0.0 {
+            quality[i] = ((cov * cov) / (var_x * var_y)).clamp(0.0, 1.0) as f32;
+        }
+    }
+    
+    quality
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 30根K线，倒序
+    const HIGHS: [f32; 30] = [
+        13.42, 13.05, 12.49, 12.22, 11.81, 11.69, 12.11, 12.34, 12.49, 12.69,
+        12.32, 11.76, 11.49, 11.09, 10.97, 11.4, 11.63, 11.77, 11.97, 11.59,
+        11.03, 10.76, 10.37, 10.26, 10.69, 10.92, 11.06, 11.25, 10.87, 10.3,
+    ];
+    const LOWS: [f32; 30] = [
+        12.57, 12.4, 11.84, 11.47, 11.06, 11.14, 11.26, 11.69, 11.84, 11.94,
+        11.57, 11.21, 10.64, 10.44, 10.32, 10.65, 10.88, 11.22, 11.12, 10.94,
+        10.38, 10.01, 9.62, 9.71, 9.84, 10.27, 10.41, 10.5, 10.12, 9.75,
+    ];
+    const CLOSES: [f32; 30] = [
+        12.92, 12.65, 12.19, 11.72, 11.41, 11.39, 11.61, 11.94, 12.19, 12.19,
+        11.92, 11.46, 10.99, 10.69, 10.67, 10.9, 11.23, 11.47, 11.47, 11.19,
+        10.73, 10.26, 9.97, 9.96, 10.19, 10.52, 10.76, 10.75, 10.47, 10.0,
+    ];
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-3, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn dmi_matches_reference_values() {
+        let (plus_di, minus_di, adx) = calculate_dmi(&HIGHS, &LOWS, &CLOSES, 5);
+        
+        // 参考值由独立的Wilder平滑实现(双精度)离线计算
+        let expected = [
+            (0, 36.8692, 6.0349, 43.6864),
+            (1, 35.0229, 7.7079, 36.6410),
+            (5, 16.6737, 20.3134, 30.4166),
+            (10, 35.0576, 8.7589, 36.0086),
+            (19, 36.3189, 7.0259, 34.4655),
+            (20, 27.9972, 9.0561, 26.1865),
+            (24, 25.0, 17.3684, 0.0),
+        ];
+        for (i, plus, minus, a) in expected {
+            assert_close(plus_di[i], plus);
+            assert_close(minus_di[i], minus);
+            assert_close(adx[i], a);
+        }
+        // 最早的period根K线没有DI
+        assert!(plus_di[25..].iter().all(|&v| v == 0.0));
+    }
+
+    #[test]
+    fn flat_series_settles_without_nan() {
+        let flat = [10.0f32; 40];
+        
+        let (plus_di, minus_di, adx) = calculate_dmi(&flat, &flat, &flat, 14);
+        
+        assert!(plus_di.iter().chain(&minus_di).chain(&adx).all(|v| v.is_finite()));
+        assert_eq!(adx[0], 0.0);
+    }
+
+    #[test]
+    fn short_series_is_all_zero() {
+        let (plus_di, _, adx) = calculate_dmi(&HIGHS[..5], &LOWS[..5], &CLOSES[..5], 5);
+        
+        assert_eq!(plus_di, vec![0.0; 5]);
+        assert_eq!(adx, vec![0.0; 5]);
+    }
+
+    #[test]
+    fn ichimoku_displacements_point_the_right_way() {
+        // 倒序的持续上涨: 第i天最高价20-i，越早越低，平移方向错误时数值明显不同
+        let highs: Vec<f32> = (0..12).map(|i| 20.0 - i as f32).collect();
+        let lows: Vec<f32> = highs.iter().map(|h| h - 1.0).collect();
+        let closes: Vec<f32> = highs.iter().map(|h| h - 0.5).collect();
+        
+        let series = calculate_ichimoku(&highs, &lows, &closes, 2, 3, 4);
+        
+        assert_eq!(series.tenkan[0], 19.0);
+        assert_eq!(series.kijun[0], 18.5);
+        // 先行带取3天前算出的值: (tenkan[3] + kijun[3]) / 2 = (16 + 15.5) / 2
+        assert_eq!(series.senkou_a[0], 15.75);
+        assert_eq!(series.senkou_a[6], 9.75);
+        assert_eq!(series.senkou_a[7], 0.0);
+        // span_b[3] = 15，span_b[9]数据不足
+        assert_eq!(series.senkou_b[0], 15.0);
+        assert_eq!(series.senkou_b[5], 10.0);
+        assert_eq!(series.senkou_b[6], 0.0);
+        // 迟行线取3天后的收盘价，画在更早的位置
+        assert_eq!(series.chikou[3], closes[0]);
+        assert_eq!(series.chikou[11], closes[8]);
+        assert_eq!(series.chikou[..3], [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn trend_quality_prefers_steady_trend_over_single_gap() {
+        // 倒序: 20 -> 10，即时间上从10稳步涨到20
+        let steady: Vec<f32> = (0..20).map(|i| 20.0 - i as f32 * 10.0 / 19.0).collect();
+        // 总涨幅相同，但全部来自一次跳空，其余为±0.3震荡
+        let gap: Vec<f32> = (0..20)
+            .map(|i| {
+                let noise = if i % 2 == 0 { 0.3 } else { -0.3 };
+                if i < 3 { 20.0 + noise } else { 10.0 + noise }
+            })
+            .collect();
+        
+        let steady_quality = trend_quality(&steady, 20);
+        let gap_quality = trend_quality(&gap, 20);
+        
+        assert!((steady_quality[0] - 1.0).abs() < 1e-5);
+        assert!(gap_quality[0] < 0.5);
+        assert_eq!(trend_quality(&[12.0; 20], 20)[0], 0.0);
+        assert_eq!(steady_quality[1], 0.0);
+    }
+
+    #[test]
+    fn vw_macd_matches_plain_macd_on_equal_volume() {
+        let volumes = [1000.0f32; 30];
+        let (dif, dea, hist) = calculate_macd(&CLOSES, 5, 10, 3);
+        let (vw_dif, vw_dea, vw_hist) = calculate_vw_macd(&CLOSES, &volumes, 5, 10, 3);
+        
+        for i in 0..CLOSES.len() {
+            assert!((dif[i] - vw_dif[i]).abs() < 1e-4);
+            assert!((dea[i] - vw_dea[i]).abs() < 1e-4);
+            assert!((hist[i] - vw_hist[i]).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn vw_macd_discounts_low_volume_rebound() {
+        // 按时间顺序: 横盘，放量下跌，缩量反弹，最后转为倒序
+        let mut closes = vec![10.0f32; 20];
+        let mut volumes = vec![1000.0f32; 20];
+        for i in 1..=5 {
+            closes.push(10.0 - 0.2 * i as f32);
+            volumes.push(5000.0);
+        }
+        for i in 1..=5 {
+            closes.push(9.0 + 0.18 * i as f32);
+            volumes.push(200.0);
+        }
+        closes.reverse();
+        volumes.reverse();
+        
+        let (dif, _, _) = calculate_macd(&closes, 5, 10, 3);
+        let (vw_dif, _, _) = calculate_vw_macd(&closes, &volumes, 5, 10, 3);
+        
+        // 普通DIF已翻正，缩量反弹的权重低，加权DIF仍为负
+        assert!(dif[0] > 0.0);
+        assert!(vw_dif[0] < 0.0);
+        assert!(vw_dif[0] < dif[0] - 0.05);
+    }
+
+    fn assert_series_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn ema_of_constant_is_constant() {
+        let data = [5.0; 6];
+        
+        assert_series_close(&calculate_ema_series(&data, 3), &[5.0, 5.0, 5.0, 5.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn ema_of_linear_lags_by_one_step() {
+        // 按日期顺序为1..=6，period 3时k = 0.5，种子SMA(1, 2, 3) = 2，之后EMA恒比当天低1
+        let data = [6.0, 5.0, 4.0, 3.0, 2.0, 1.0];
+        
+        assert_series_close(&calculate_ema_series(&data, 3), &[5.0, 4.0, 3.0, 2.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn ema_of_step_halves_remaining_gap() {
+        // 按日期顺序为[0, 0, 0, 10, 10]，k = 0.5: 0 -> 5 -> 7.5
+        let data = [10.0, 10.0, 0.0, 0.0, 0.0];
+        
+        assert_series_close(&calculate_ema_series(&data, 3), &[7.5, 5.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn ema_at_idx_only_uses_earlier_bars() {
+        let data = [10.0, 10.0, 0.0, 0.0, 0.0];
+        let series = calculate_ema_series(&data, 3);
+        
+        for (idx, ema) in series.iter().enumerate() {
+            assert_eq!(calculate_ema(&data, 3, idx), *ema);
+        }
+        assert_eq!(calculate_ema(&data, 3, data.len()), 0.0);
+        assert_series_close(&calculate_ema_series(&data, 0), &[0.0; 5]);
+    }
 }
@@ -1,7 +1,10 @@
 use egostrategy_datahub::models::stock::DailyData as DailyBar;
 
+/// 价格数据列: (开盘价, 最高价, 最低价, 收盘价, 成交量, 成交额)
+pub type PriceColumns = (Vec<f32>, Vec<f32>, Vec<f32>, Vec<f32>, Vec<f32>, Vec<f32>);
+
 /// 从DailyBar提取价格数据
-pub fn extract_price_data(bars: &[DailyBar]) -> (Vec<f32>, Vec<f32>, Vec<f32>, Vec<f32>, Vec<f32>, Vec<f32>) {
+pub fn extract_price_data(bars: &[DailyBar]) -> PriceColumns {
     let opens: Vec<f32> = bars.iter().map(|bar| bar.open).collect();
     let highs: Vec<f32> = bars.iter().map(|bar| bar.high).collect();
     let lows: Vec<f32> = bars.iter().map(|bar| bar.low).collect();
@@ -12,12 +15,14 @@ pub fn extract_price_data(bars: &[DailyBar]) -> (Vec<f32>, Vec<f32>, Vec<f32>, V
     (opens, highs, lows, closes, volumes, amounts)
 }
 
-/// 计算涨跌幅
+/// 计算涨跌幅 - 适用于倒序数据
+///
+/// changes[i]为第i天相对前一天(i+1)的涨跌幅，最早一天为0
 pub fn calculate_price_change(closes: &[f32]) -> Vec<f32> {
     let len = closes.len();
     let mut changes = vec![0.0; len];
     
-    for i in 0..(len-1) {
+    for i in 0..len.saturating_sub(1) {
         if closes[i+1] != 0.0 {
             changes[i] = (closes[i] - closes[i+1]) / closes[i+1];
         }
@@ -26,38 +31,42 @@ pub fn calculate_price_change(closes: &[f32]) -> Vec<f32> {
     changes
 }
 
-/// 计算累计收益率
+/// 计算累计收益率 - 适用于倒序数据
+///
+/// 以最早一天为基准，cumulative[i]为从最早一天到第i天的累计收益率，最早一天为0
 pub fn calculate_cumulative_return(changes: &[f32]) -> Vec<f32> {
     let len = changes.len();
-    let mut cumulative = vec![1.0; len];
-    
-    for i in 1..len {
-        cumulative[i] = cumulative[i-1] * (1.0 + changes[i-1]);
+    let mut cumulative = vec![0.0; len];
+    if len == 0 {
+        return cumulative;
     }
     
-    // 转换为百分比收益率
-    for i in 0..len {
-        cumulative[i] = cumulative[i] - 1.0;
+    let mut growth = 1.0;
+    for i in (0..len-1).rev() {
+        growth *= 1.0 + changes[i];
+        cumulative[i] = growth - 1.0;
     }
     
     cumulative
 }
 
-/// 计算最大回撤
+/// 计算最大回撤 - 适用于倒序数据
+///
+/// 从最早一天向最新一天扫描收盘价
 pub fn calculate_max_drawdown(closes: &[f32]) -> f32 {
     let len = closes.len();
     if len <= 1 {
         return 0.0;
     }
     
-    let mut max_price = closes[0];
+    let mut max_price = closes[len - 1];
     let mut max_drawdown = 0.0;
     
-    for i in 1..len {
-        if closes[i] > max_price {
-            max_price = closes[i];
-        } else {
-            let drawdown = (max_price - closes[i]) / max_price;
+    for &close in closes[..len-1].iter().rev() {
+        if close > max_price {
+            max_price = close;
+        } else if max_price > 0.0 {
+            let drawdown = (max_price - close) / max_price;
             if drawdown > max_drawdown {
                 max_drawdown = drawdown;
             }
@@ -88,3 +97,170 @@ pub fn calculate_sharpe_ratio(returns: &[f32], risk_free_rate: f32) -> f32 {
     
     excess_return / std_dev
 }
+
+/// 计算滚动成交量加权均价(VWAP) - 适用于倒序数据
+///
+/// 用成交额/成交量近似，窗口内成交量为0时沿用前一天的值；数据不足window天的位置为0
+pub fn calculate_vwap(amounts: &[f32], volumes: &[f32], window: usize) -> Vec<f32> {
+    let len = amounts.len().min(volumes.len());
+    let mut vwap = vec![0.0f32; len];
+    
+    if window == 0 || len < window {
+        return vwap;
+    }
+    
+    // 从最早的完整窗口向最新计算，便于沿用前一天的值
+    for i in (0..=len-window).rev() {
+        let amount: f32 = amounts[i..i+window].iter().sum();
+        let volume: f32 = volumes[i..i+window].iter().sum();
+        vwap[i] = if volume > 0.0 {
+            amount / volume
+        } else if i + window < len {
+            vwap[i+1]
+        } else {
+            0.0
+        };
+    }
+    
+    vwap
+}
+
+/// 计算从anchor_idx当天起累积的锚定VWAP - 适用于倒序数据
+///
+/// 锚点之前(下标大于anchor_idx)的位置为0，累积成交量仍为0的位置也为0
+pub fn anchored_vwap(bars: &[DailyBar], anchor_idx: usize) -> Vec<f32> {
+    let len = bars.len();
+    let mut vwap = vec![0.0f32; len];
+    
+    if anchor_idx >= len {
+        return vwap;
+    }
+    
+    let mut amount = 0.0f64;
+    let mut volume = 0.0f64;
+    for i in (0..=anchor_idx).rev() {
+        amount += bars[i].amount as f64;
+        volume += bars[i].volume as f64;
+        // 成交量为0的K线不改变累积值，相当于沿用前一天的VWAP
+        if volume > 0.0 {
+            vwap[i] = (amount / volume) as f32;
+        }
+    }
+    
+    vwap
+}
+
+/// 计算收盘强度: (收盘价 - 最低价) / (最高价 - 最低价) - 适用于倒序数据
+///
+/// 1表示收在最高点，0表示收在最低点；最高价等于最低价(一字板、十字星无振幅)时为0.5
+pub fn closing_strength(bars: &[DailyBar]) -> Vec<f32> {
+    bars.iter()
+        .map(|bar| {
+            let range = bar.high - bar.low;
+            if range > 0.0 {
+                ((bar.close - bar.low) / range).clamp(0.0, 1.0)
+            } else {
+                0.5
+            }
+        })
+        .collect()
+}
+
+/// 计算最近window天的平均振幅: (最高价 - 最低价) / 前收盘价 - 适用于倒序数据
+///
+/// 最早一天没有前收盘价，用当天开盘价代替；参考价不为正的当天振幅记为0。
+/// 数据不足window天的位置为0
+pub fn avg_amplitude(bars: &[DailyBar], window: usize) -> Vec<f32> {
+    let len = bars.len();
+    let mut result = vec![0.0f32; len];
+    
+    if window == 0 || len < window {
+        return result;
+    }
+    
+    let amplitudes: Vec<f32> = (0..len)
+        .map(|i| {
+            let prev_close = if i + 1 < len { bars[i+1].close } else { bars[i].open };
+            if prev_close > 0.0 {
+                (bars[i].high - bars[i].low) / prev_close
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    
+    for i in 0..=len-window {
+        result[i] = amplitudes[i..i+window].iter().sum::<f32>() / window as f32;
+    }
+    
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(amount: i64, volume: i64) -> DailyBar {
+        DailyBar {
+            date: 20230630,
+            open: 10.0,
+            high: 10.0,
+            low: 10.0,
+            close: 10.0,
+            volume,
+            amount,
+        }
+    }
+
+    #[test]
+    fn rolling_vwap_carries_forward_over_zero_volume() {
+        // 倒序: 下标1、2两天无成交
+        let amounts = [220.0, 0.0, 0.0, 100.0];
+        let volumes = [20.0, 0.0, 0.0, 10.0];
+        
+        assert_eq!(calculate_vwap(&amounts, &volumes, 2), vec![11.0, 10.0, 10.0, 0.0]);
+        assert_eq!(calculate_vwap(&amounts, &volumes, 1), vec![11.0, 10.0, 10.0, 10.0]);
+    }
+
+    #[test]
+    fn rolling_vwap_without_any_volume_is_zero() {
+        assert_eq!(calculate_vwap(&[0.0, 0.0], &[0.0, 0.0], 1), vec![0.0, 0.0]);
+        assert_eq!(calculate_vwap(&[100.0], &[10.0], 2), vec![0.0]);
+    }
+
+    #[test]
+    fn anchored_vwap_accumulates_from_anchor() {
+        // 倒序，锚点为下标2，下标1无成交
+        let bars = [bar(300, 20), bar(0, 0), bar(100, 10), bar(500, 10)];
+        
+        let vwap = anchored_vwap(&bars, 2);
+        
+        assert_eq!(vwap[3], 0.0);
+        assert_eq!(vwap[2], 10.0);
+        assert_eq!(vwap[1], 10.0);
+        assert!((vwap[0] - 400.0 / 30.0).abs() < 1e-5);
+        assert_eq!(anchored_vwap(&bars, 4), vec![0.0; 4]);
+    }
+
+    fn ohlc(open: f32, high: f32, low: f32, close: f32) -> DailyBar {
+        DailyBar { open, high, low, close, ..bar(0, 0) }
+    }
+
+    #[test]
+    fn closing_strength_guards_zero_range() {
+        let bars = [ohlc(10.0, 12.0, 10.0, 11.5), ohlc(10.0, 10.0, 10.0, 10.0), ohlc(8.0, 11.0, 9.0, 10.0)];
+        
+        assert_eq!(closing_strength(&bars), vec![0.75, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn avg_amplitude_uses_open_for_first_bar() {
+        let bars = [ohlc(10.0, 12.0, 10.0, 11.5), ohlc(10.0, 10.0, 10.0, 10.0), ohlc(8.0, 11.0, 9.0, 10.0)];
+        
+        // 最早一天以开盘价8为参考价
+        assert_eq!(avg_amplitude(&bars, 1), vec![0.2, 0.0, 0.25]);
+        assert_eq!(avg_amplitude(&bars, 2), vec![0.1, 0.125, 0.0]);
+        assert_eq!(avg_amplitude(&[ohlc(0.0, 1.0, 0.5, 0.8)], 1), vec![0.0]);
+        assert_eq!(avg_amplitude(&bars, 4), vec![0.0; 3]);
+    }
+}
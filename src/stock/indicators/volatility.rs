@@ -1,3 +1,5 @@
+use super::trend::calculate_ema_series;
+
 /// 计算标准差
 pub fn standard_deviation(data: &[f32]) -> f32 {
     if data.len() <= 1 {
@@ -12,9 +14,21 @@ pub fn standard_deviation(data: &[f32]) -> f32 {
     variance.sqrt()
 }
 
+/// ATR的平滑方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AtrSmoothing {
+    /// 近window天真实波幅的简单平均
+    Simple,
+    /// Wilder递推平滑: 以最早window天的均值为种子，ATR = (前一天ATR * (window - 1) + TR) / window
+    #[default]
+    Wilder,
+}
+
 /// 计算真实波动幅度(ATR) - 适用于倒序数据
-pub fn calculate_atr(high: &[f32], low: &[f32], close: &[f32], window: usize) -> Vec<f32> {
-    let len = high.len();
+///
+/// 最早一天没有前收盘价，TR取high - low；数据不足window天的位置为0
+pub fn calculate_atr(high: &[f32], low: &[f32], close: &[f32], window: usize, smoothing: AtrSmoothing) -> Vec<f32> {
+    let len = high.len().min(low.len()).min(close.len());
     let mut tr = vec![0.0f32; len];
     
     for i in 0..len {
@@ -28,86 +42,187 @@ pub fn calculate_atr(high: &[f32], low: &[f32], close: &[f32], window: usize) ->
         }
     }
     
-    // 使用简单移动平均计算ATR
     let mut atr = vec![0.0f32; len];
     
-    if len < window {
+    if window == 0 || len < window {
         return atr;
     }
     
-    // 计算第一个ATR值
-    let mut sum = 0.0;
-    for i in 0..window {
-        sum += tr[i];
-    }
-    atr[window-1] = sum / window as f32;
-    
-    // 计算剩余的ATR值
-    for i in window..len {
-        atr[i] = (atr[i-1] * (window as f32 - 1.0) + tr[i]) / window as f32;
+    match smoothing {
+        AtrSmoothing::Simple => {
+            for i in 0..=len-window {
+                atr[i] = tr[i..i+window].iter().sum::<f32>() / window as f32;
+            }
+        }
+        AtrSmoothing::Wilder => {
+            // 种子为最早window天的平均TR，然后向新的方向(下标变小)递推
+            let seed = len - window;
+            atr[seed] = tr[seed..].iter().sum::<f32>() / window as f32;
+            
+            for i in (0..seed).rev() {
+                atr[i] = (atr[i+1] * (window as f32 - 1.0) + tr[i]) / window as f32;
+            }
+        }
     }
     
     atr
 }
 
 /// 计算布林带 - 适用于倒序数据
+///
+/// 窗口为当天及之前的period天(i..i+period)，返回(中轨, 上轨, 下轨)；数据不足的位置为0
 pub fn calculate_bollinger_bands(closes: &[f32], period: usize, std_dev_multiplier: f32) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
     let len = closes.len();
     let mut middle_band = vec![0.0; len];
     let mut upper_band = vec![0.0; len];
     let mut lower_band = vec![0.0; len];
     
-    if len <= period {
+    if period == 0 || len < period {
         return (middle_band, upper_band, lower_band);
     }
     
-    for i in 0..(len-period+1) {
+    for i in 0..=len-period {
         let slice = &closes[i..(i+period)];
         let sma = slice.iter().sum::<f32>() / period as f32;
-        middle_band[i+period-1] = sma;
+        middle_band[i] = sma;
         
         let std_dev = (slice.iter().map(|&x| (x - sma).powi(2)).sum::<f32>() / period as f32).sqrt();
-        upper_band[i+period-1] = sma + std_dev_multiplier * std_dev;
-        lower_band[i+period-1] = sma - std_dev_multiplier * std_dev;
+        upper_band[i] = sma + std_dev_multiplier * std_dev;
+        lower_band[i] = sma - std_dev_multiplier * std_dev;
     }
     
     (middle_band, upper_band, lower_band)
 }
 
-/// 计算肯特纳通道(Keltner Channel)
+/// 计算肯特纳通道(Keltner Channel) - 适用于倒序数据
+///
+/// 中轨为ema_period日EMA，上下轨为中轨 ± multiplier * ATR(Wilder)；数据不足的位置为0
 pub fn calculate_keltner_channel(closes: &[f32], highs: &[f32], lows: &[f32], ema_period: usize, atr_period: usize, multiplier: f32) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
-    let len = closes.len();
+    let len = closes.len().min(highs.len()).min(lows.len());
     let mut middle_band = vec![0.0; len];
     let mut upper_band = vec![0.0; len];
     let mut lower_band = vec![0.0; len];
     
-    if len <= ema_period.max(atr_period) {
+    if ema_period == 0 || atr_period == 0 || len < ema_period.max(atr_period) {
         return (middle_band, upper_band, lower_band);
     }
     
-    // 计算EMA
-    let k = 2.0 / (ema_period as f32 + 1.0);
+    let ema = calculate_ema_series(&closes[..len], ema_period);
+    let atr = calculate_atr(highs, lows, closes, atr_period, AtrSmoothing::Wilder);
     
-    // 初始化EMA为简单移动平均
-    let mut ema_sum = 0.0;
-    for i in 0..ema_period {
-        ema_sum += closes[i];
+    // 计算通道
+    for i in 0..=len-ema_period.max(atr_period) {
+        middle_band[i] = ema[i];
+        upper_band[i] = ema[i] + multiplier * atr[i];
+        lower_band[i] = ema[i] - multiplier * atr[i];
     }
-    middle_band[ema_period-1] = ema_sum / ema_period as f32;
     
-    // 计算剩余的EMA值
-    for i in ema_period..len {
-        middle_band[i] = closes[i] * k + middle_band[i-1] * (1.0 - k);
-    }
+    (middle_band, upper_band, lower_band)
+}
+
+/// 计算唐奇安通道(Donchian Channel) - 适用于倒序数据
+///
+/// include_current为false时窗口为当天之前的period天(i+1..i+1+period)，用于突破判断；
+/// 为true时窗口包含当天(i..i+period)。数据不足的位置为0
+pub fn donchian_channel(highs: &[f32], lows: &[f32], period: usize, include_current: bool) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
+    let len = highs.len().min(lows.len());
+    let mut upper_band = vec![0.0; len];
+    let mut lower_band = vec![0.0; len];
+    let mut middle_band = vec![0.0; len];
     
-    // 计算ATR
-    let atr = calculate_atr(highs, lows, closes, atr_period);
+    let offset = if include_current { 0 } else { 1 };
+    if period == 0 || len < period + offset {
+        return (upper_band, lower_band, middle_band);
+    }
     
-    // 计算通道
-    for i in atr_period.max(ema_period)..len {
-        upper_band[i] = middle_band[i] + multiplier * atr[i];
-        lower_band[i] = middle_band[i] - multiplier * atr[i];
+    for i in 0..=len-period-offset {
+        let start = i + offset;
+        let upper = highs[start..start+period].iter().fold(f32::MIN, |acc, &x| acc.max(x));
+        let lower = lows[start..start+period].iter().fold(f32::MAX, |acc, &x| acc.min(x));
+        
+        upper_band[i] = upper;
+        lower_band[i] = lower;
+        middle_band[i] = (upper + lower) / 2.0;
     }
     
-    (middle_band, upper_band, lower_band)
+    (upper_band, lower_band, middle_band)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 倒序: 今天最高13，是前几天都没有的新高
+    const HIGHS: [f32; 4] = [13.0, 11.0, 12.0, 10.0];
+    const LOWS: [f32; 4] = [9.0, 10.0, 8.0, 9.0];
+
+    #[test]
+    fn donchian_including_current_bar() {
+        let (upper, lower, middle) = donchian_channel(&HIGHS, &LOWS, 2, true);
+        
+        assert_eq!(upper, vec![13.0, 12.0, 12.0, 0.0]);
+        assert_eq!(lower, vec![9.0, 8.0, 8.0, 0.0]);
+        assert_eq!(middle, vec![11.0, 10.0, 10.0, 0.0]);
+    }
+
+    #[test]
+    fn donchian_excluding_current_bar_uses_prior_window() {
+        let (upper, lower, middle) = donchian_channel(&HIGHS, &LOWS, 2, false);
+        
+        assert_eq!(upper, vec![12.0, 12.0, 0.0, 0.0]);
+        assert_eq!(lower, vec![8.0, 8.0, 0.0, 0.0]);
+        assert_eq!(middle, vec![10.0, 10.0, 0.0, 0.0]);
+        // 突破判断: 今天的最高价高于之前2天的上轨
+        assert!(HIGHS[0] > upper[0]);
+    }
+
+    #[test]
+    fn donchian_short_series_is_zero() {
+        let (upper, _, _) = donchian_channel(&HIGHS, &LOWS, 4, false);
+        
+        assert_eq!(upper, vec![0.0; 4]);
+    }
+
+    /// 5天K线(倒序)，按日期顺序的TR为[1.0, 1.0, 0.6, 1.4, 0.6]
+    fn fixture() -> (Vec<f32>, Vec<f32>, Vec<f32>) {
+        let high = vec![11.8, 12.0, 11.0, 11.0, 10.5];
+        let low = vec![11.2, 11.0, 10.4, 10.0, 9.5];
+        let close = vec![11.4, 11.5, 10.6, 10.8, 10.0];
+        (high, low, close)
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn simple_atr_averages_last_window_true_ranges() {
+        let (high, low, close) = fixture();
+        let atr = calculate_atr(&high, &low, &close, 3, AtrSmoothing::Simple);
+        
+        assert_close(&atr, &[2.6 / 3.0, 1.0, 2.6 / 3.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn wilder_atr_recurses_from_oldest_window() {
+        let (high, low, close) = fixture();
+        let atr = calculate_atr(&high, &low, &close, 3, AtrSmoothing::Wilder);
+        
+        // 种子 = (1.0 + 1.0 + 0.6) / 3，之后ATR = (前一天ATR * 2 + TR) / 3
+        let seed = 2.6 / 3.0;
+        let next = (seed * 2.0 + 1.4) / 3.0;
+        let newest = (next * 2.0 + 0.6) / 3.0;
+        assert_close(&atr, &[newest, next, seed, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn atr_is_zero_without_enough_bars() {
+        let (high, low, close) = fixture();
+        
+        assert_close(&calculate_atr(&high, &low, &close, 6, AtrSmoothing::Wilder), &[0.0; 5]);
+        assert_close(&calculate_atr(&high, &low, &close, 0, AtrSmoothing::Simple), &[0.0; 5]);
+    }
 }
@@ -0,0 +1,90 @@
+/// 计算能量潮指标(OBV) - 适用于倒序数据
+///
+/// 以最早一根K线为0向最新累积: 收盘价高于前一天加上当日成交量，低于则减去，持平不变
+pub fn calculate_obv(closes: &[f32], volumes: &[f32]) -> Vec<f32> {
+    let len = closes.len().min(volumes.len());
+    let mut obv = vec![0.0f32; len];
+    
+    if len < 2 {
+        return obv;
+    }
+    
+    // 在倒序数据中，i是当天，i+1是前一天
+    for i in (0..len-1).rev() {
+        let change = closes[i] - closes[i+1];
+        obv[i] = if change > 0.0 {
+            obv[i+1] + volumes[i]
+        } else if change < 0.0 {
+            obv[i+1] - volumes[i]
+        } else {
+            obv[i+1]
+        };
+    }
+    
+    obv
+}
+
+/// 计算OBV在最近window天内的斜率(最小二乘，单位为每天) - 适用于倒序数据
+///
+/// 斜率为正表示OBV上升；数据不足window天的位置为0
+pub fn obv_slope(obv: &[f32], window: usize) -> Vec<f32> {
+    let len = obv.len();
+    let mut slope = vec![0.0f32; len];
+    
+    if window < 2 || len < window {
+        return slope;
+    }
+    
+    // x为距窗口最早一天的天数，窗口内均值和方差固定
+    let n = window as f32;
+    let mean_x = (n - 1.0) / 2.0;
+    let var_x: f32 = (0..window).map(|x| (x as f32 - mean_x).powi(2)).sum();
+    
+    for i in 0..=len-window {
+        let values = &obv[i..i+window];
+        let mean_y = values.iter().sum::<f32>() / n;
+        
+        // values[0]为最新，对应x = window - 1
+        let cov: f32 = values.iter()
+            .enumerate()
+            .map(|(j, y)| ((window - 1 - j) as f32 - mean_x) * (y - mean_y))
+            .sum();
+        slope[i] = cov / var_x;
+    }
+    
+    slope
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn obv_matches_hand_computed_example() {
+        // 倒序，从最早到最新: 10 → 11(涨) → 11(平) → 10.5(跌) → 11.5(涨) → 12(涨)
+        let closes = [12.0, 11.5, 10.5, 11.0, 11.0, 10.0];
+        let volumes = [600.0, 500.0, 400.0, 150.0, 200.0, 100.0];
+        
+        let obv = calculate_obv(&closes, &volumes);
+        
+        assert_eq!(obv, vec![900.0, 300.0, -200.0, 200.0, 200.0, 0.0]);
+    }
+
+    #[test]
+    fn obv_of_short_series_is_zero() {
+        assert_eq!(calculate_obv(&[10.0], &[100.0]), vec![0.0]);
+        assert!(calculate_obv(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn slope_is_positive_for_rising_obv() {
+        // 倒序: 最新为5，每天上升1
+        let obv = [5.0, 4.0, 3.0, 2.0, 1.0];
+        
+        let slope = obv_slope(&obv, 3);
+        
+        assert_eq!(slope, vec![1.0, 1.0, 1.0, 0.0, 0.0]);
+        assert_eq!(obv_slope(&[1.0, 3.0, 5.0], 3)[0], -2.0);
+        assert_eq!(obv_slope(&obv, 6), vec![0.0; 5]);
+    }
+}
@@ -0,0 +1,137 @@
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use crate::stock::data_provider::{self, StockMeta};
+
+/// 判断价格是否达到涨跌停价时允许的误差(半分钱)
+const PRICE_TOLERANCE: f32 = 0.005;
+
+/// 涨跌停幅度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LimitBand {
+    /// 幅度比例，例如0.1表示10%
+    pub pct: f32,
+}
+
+impl LimitBand {
+    pub const MAIN: LimitBand = LimitBand { pct: 0.1 };
+    pub const ST: LimitBand = LimitBand { pct: 0.05 };
+    pub const GROWTH: LimitBand = LimitBand { pct: 0.2 };
+    pub const BSE: LimitBand = LimitBand { pct: 0.3 };
+    
+    /// 按代码和ST状态确定涨跌停幅度
+    pub fn for_symbol(symbol: &str, is_st: bool) -> Self {
+        Self { pct: data_provider::limit_band(symbol, is_st) }
+    }
+    
+    /// 幅度的万分比，避免浮点误差影响取整
+    fn basis_points(&self) -> i64 {
+        (self.pct as f64 * 10000.0).round() as i64
+    }
+}
+
+/// 股票元数据对应的涨跌停幅度
+pub fn band_for(meta: &StockMeta) -> LimitBand {
+    LimitBand::for_symbol(&meta.symbol, meta.is_st)
+}
+
+/// 按交易所规则计算: 前收盘价 × (1 ± 幅度)，四舍五入到分
+fn limit_price(prev_close: f32, bp: i64) -> f32 {
+    let prev_cents = (prev_close as f64 * 100.0).round() as i64;
+    let cents = (prev_cents * bp + 5000) / 10000;
+    cents as f32 / 100.0
+}
+
+/// 涨停价
+pub fn limit_up_price(prev_close: f32, band: LimitBand) -> f32 {
+    limit_price(prev_close, 10000 + band.basis_points())
+}
+
+/// 跌停价
+pub fn limit_down_price(prev_close: f32, band: LimitBand) -> f32 {
+    limit_price(prev_close, 10000 - band.basis_points())
+}
+
+/// 收盘价是否涨停
+pub fn is_limit_up(bar: &DailyBar, prev_close: f32, band: LimitBand) -> bool {
+    bar.close >= limit_up_price(prev_close, band) - PRICE_TOLERANCE
+}
+
+/// 收盘价是否跌停
+pub fn is_limit_down(bar: &DailyBar, prev_close: f32, band: LimitBand) -> bool {
+    bar.close <= limit_down_price(prev_close, band) + PRICE_TOLERANCE
+}
+
+/// 价格是否达到涨停价，涨停价上的买单通常无法成交
+pub fn at_limit_up(price: f32, prev_close: f32, band: LimitBand) -> bool {
+    price >= limit_up_price(prev_close, band) - PRICE_TOLERANCE
+}
+
+/// 是否封死跌停: 最低价等于收盘价且为跌停价，当天无法卖出
+pub fn is_locked_limit_down(bar: &DailyBar, prev_close: f32, band: LimitBand) -> bool {
+    is_limit_down(bar, prev_close, band) && bar.low >= bar.close - PRICE_TOLERANCE
+}
+
+/// 价格是否达到跌停价，跌停价上的卖出开仓(融券)通常无法成交
+pub fn at_limit_down(price: f32, prev_close: f32, band: LimitBand) -> bool {
+    price <= limit_down_price(prev_close, band) + PRICE_TOLERANCE
+}
+
+/// 是否封死涨停: 最高价等于收盘价且为涨停价，当天无法买入
+pub fn is_locked_limit_up(bar: &DailyBar, prev_close: f32, band: LimitBand) -> bool {
+    is_limit_up(bar, prev_close, band) && bar.high <= bar.close + PRICE_TOLERANCE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(close: f32) -> DailyBar {
+        DailyBar {
+            date: 20230630,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 10000,
+            amount: 100000,
+        }
+    }
+
+    #[test]
+    fn rounds_half_up_to_cents() {
+        // 9.87 × 1.1 = 10.857, 9.87 × 0.9 = 8.883
+        assert_eq!(limit_up_price(9.87, LimitBand::MAIN), 10.86);
+        assert_eq!(limit_down_price(9.87, LimitBand::MAIN), 8.88);
+        // 10.05 × 1.1 = 11.055，恰好半分时进位
+        assert_eq!(limit_up_price(10.05, LimitBand::MAIN), 11.06);
+        // 3.33 × 1.05 = 3.4965, 3.33 × 0.95 = 3.1635
+        assert_eq!(limit_up_price(3.33, LimitBand::ST), 3.5);
+        assert_eq!(limit_down_price(3.33, LimitBand::ST), 3.16);
+        assert_eq!(limit_up_price(12.34, LimitBand::GROWTH), 14.81);
+        assert_eq!(limit_down_price(12.34, LimitBand::BSE), 8.64);
+    }
+
+    #[test]
+    fn detection_tolerates_float_error() {
+        assert!(is_limit_up(&close(10.859999), 9.87, LimitBand::MAIN));
+        assert!(!is_limit_up(&close(10.85), 9.87, LimitBand::MAIN));
+        assert!(is_limit_down(&close(8.880001), 9.87, LimitBand::MAIN));
+        assert!(!is_limit_down(&close(8.89), 9.87, LimitBand::MAIN));
+    }
+
+    #[test]
+    fn band_follows_board_and_st_status() {
+        let meta = |symbol: &str, is_st: bool| StockMeta {
+            symbol: symbol.to_string(),
+            name: String::new(),
+            exchange: String::new(),
+            list_date: None,
+            is_st,
+        };
+        
+        assert_eq!(band_for(&meta("600000.SH", false)), LimitBand::MAIN);
+        assert_eq!(band_for(&meta("600083.SH", true)), LimitBand::ST);
+        assert_eq!(band_for(&meta("300750.SZ", false)), LimitBand::GROWTH);
+        assert_eq!(band_for(&meta("688981.SH", false)), LimitBand::GROWTH);
+        assert_eq!(band_for(&meta("830799.BJ", false)), LimitBand::BSE);
+    }
+}
@@ -1,16 +1,18 @@
+use chrono::{Datelike, Duration, NaiveDate};
 use egostrategy_datahub::models::stock::DailyData as DailyBar;
 
-/// 创建模拟的日线数据
+/// 创建模拟的日线数据，从20230101起逐日递增，按日期倒序返回，可直接传给BacktestEngine::with_data
 pub fn create_mock_daily_bars(count: usize) -> Vec<DailyBar> {
+    let start = NaiveDate::from_ymd_opt(2023, 1, 1).expect("valid date");
     let mut bars = Vec::with_capacity(count);
     
     for i in 0..count {
         // 使用日期的数字表示，例如20230101
-        let day = i % 30 + 1;
-        let date_num = 20230100 + day;
+        let date = start + Duration::days(i as i64);
+        let date_num = date.year() * 10000 + date.month() as i32 * 100 + date.day() as i32;
         
         let bar = DailyBar {
-            date: date_num as i32,
+            date: date_num,
             open: 10.0 + (i as f32 * 0.1),
             high: 10.5 + (i as f32 * 0.1),
             low: 9.5 + (i as f32 * 0.1),
@@ -21,5 +23,7 @@ pub fn create_mock_daily_bars(count: usize) -> Vec<DailyBar> {
         bars.push(bar);
     }
     
+    // 与引擎中的K线一致，按日期倒序
+    bars.reverse();
     bars
 }
@@ -1,3 +1,15 @@
 pub mod data_provider;
+pub mod source;
+pub mod disk_cache;
+pub mod csv_loader;
+pub mod benchmark;
+pub mod calendar;
+pub mod coverage;
+pub mod limits;
+pub mod retry;
+pub mod series;
+pub mod snapshot;
+pub mod adjust;
+pub mod validate;
 pub mod mock_data;
 pub mod indicators;
@@ -0,0 +1,158 @@
+use log::warn;
+use serde::{Serialize, Deserialize};
+use std::thread;
+use std::time::Duration;
+use thiserror::Error;
+
+/// 获取股票数据失败的原因
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FetchError {
+    /// 数据源中不存在该股票，重试无意义
+    #[error("数据源中不存在该股票")]
+    NotFound,
+    /// 暂时性错误(上游初始化失败、读取失败等)，可以重试
+    #[error("暂时性错误: {0}")]
+    Transient(String),
+}
+
+/// 暂时性错误的重试策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// 最多尝试次数(含第一次)
+    pub attempts: u32,
+    /// 第一次重试前等待的毫秒数，之后每次翻倍
+    pub backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            backoff_ms: 200,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 不重试
+    pub fn none() -> Self {
+        Self { attempts: 1, backoff_ms: 0 }
+    }
+    
+    /// 执行操作，暂时性错误时按指数退避重试，不存在的股票直接返回
+    pub fn run<T>(&self, what: &str, mut op: impl FnMut() -> Result<T, FetchError>) -> Result<T, FetchError> {
+        let mut backoff = self.backoff_ms;
+        let mut attempt = 1;
+        loop {
+            match op() {
+                Err(FetchError::Transient(reason)) if attempt < self.attempts => {
+                    warn!("获取 {} 失败(第 {} 次): {}，{} 毫秒后重试", what, attempt, reason, backoff);
+                    thread::sleep(Duration::from_millis(backoff));
+                    backoff = backoff.saturating_mul(2);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// 批量加载结果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoadReport {
+    /// 成功获取K线的股票数量(价格、历史长度过滤之前)
+    pub loaded: usize,
+    /// 数据源中不存在的股票
+    pub not_found: Vec<String>,
+    /// 重试后仍然失败的股票
+    pub failed_after_retry: Vec<String>,
+}
+
+impl LoadReport {
+    /// 请求加载的股票总数
+    pub fn total(&self) -> usize {
+        self.loaded + self.not_found.len() + self.failed_after_retry.len()
+    }
+    
+    /// 重试后仍失败的股票占比(百分比)
+    pub fn failure_pct(&self) -> f32 {
+        match self.total() {
+            0 => 0.0,
+            total => self.failed_after_retry.len() as f32 / total as f32 * 100.0,
+        }
+    }
+    
+    /// 记录一只股票的获取结果
+    pub fn record<T>(&mut self, symbol: &str, result: &Result<T, FetchError>) {
+        match result {
+            Ok(_) => self.loaded += 1,
+            Err(FetchError::NotFound) => self.not_found.push(symbol.to_string()),
+            Err(FetchError::Transient(_)) => self.failed_after_retry.push(symbol.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transient_errors_are_retried_until_success() {
+        let policy = RetryPolicy { attempts: 3, backoff_ms: 0 };
+        let mut calls = 0;
+        
+        let result = policy.run("000001.SZ", || {
+            calls += 1;
+            if calls < 3 {
+                Err(FetchError::Transient("timeout".to_string()))
+            } else {
+                Ok(calls)
+            }
+        });
+        
+        assert_eq!(result, Ok(3));
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn gives_up_after_configured_attempts() {
+        let policy = RetryPolicy { attempts: 2, backoff_ms: 0 };
+        let mut calls = 0;
+        
+        let result: Result<(), _> = policy.run("000001.SZ", || {
+            calls += 1;
+            Err(FetchError::Transient("timeout".to_string()))
+        });
+        
+        assert!(matches!(result, Err(FetchError::Transient(_))));
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn not_found_is_not_retried() {
+        let mut calls = 0;
+        
+        let result: Result<(), _> = RetryPolicy::default().run("000001.SZ", || {
+            calls += 1;
+            Err(FetchError::NotFound)
+        });
+        
+        assert_eq!(result, Err(FetchError::NotFound));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn report_counts_each_outcome() {
+        let mut report = LoadReport::default();
+        report.record("000001.SZ", &Ok(()));
+        report.record("000002.SZ", &Ok(()));
+        report.record("000003.SZ", &Err::<(), _>(FetchError::NotFound));
+        report.record("000004.SZ", &Err::<(), _>(FetchError::Transient("timeout".to_string())));
+        
+        assert_eq!(report.loaded, 2);
+        assert_eq!(report.not_found, vec!["000003.SZ"]);
+        assert_eq!(report.failed_after_retry, vec!["000004.SZ"]);
+        assert_eq!(report.total(), 4);
+        assert_eq!(report.failure_pct(), 25.0);
+    }
+}
@@ -0,0 +1,165 @@
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// K线排列顺序。引擎、数据源和大部分信号使用倒序(最新在前)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BarOrder {
+    /// 按日期倒序，第一根为最新
+    #[default]
+    NewestFirst,
+    /// 按日期正序，第一根为最早
+    Chronological,
+}
+
+impl BarOrder {
+    /// 由首尾K线的日期推断顺序，少于两根或首尾日期相同时返回None
+    pub fn detect(bars: &[DailyBar]) -> Option<Self> {
+        let (first, last) = (bars.first()?, bars.last()?);
+        if first.date > last.date {
+            Some(BarOrder::NewestFirst)
+        } else if first.date < last.date {
+            Some(BarOrder::Chronological)
+        } else {
+            None
+        }
+    }
+    
+    /// 相反的顺序
+    pub fn reversed(self) -> Self {
+        match self {
+            BarOrder::NewestFirst => BarOrder::Chronological,
+            BarOrder::Chronological => BarOrder::NewestFirst,
+        }
+    }
+}
+
+/// 确认K线为期望的顺序，debug构建下不符时直接panic
+pub fn assert_order(bars: &[DailyBar], expected: BarOrder) {
+    if let Some(actual) = BarOrder::detect(bars) {
+        debug_assert_eq!(actual, expected,
+            "K线顺序不符: 期望 {:?}, 实际 {:?} ({} .. {})",
+            expected, actual, bars[0].date, bars[bars.len() - 1].date);
+    }
+}
+
+/// 将K线转换为指定顺序，顺序无法判断时原样返回
+pub fn into_order(mut bars: Vec<DailyBar>, order: BarOrder) -> Vec<DailyBar> {
+    if BarOrder::detect(&bars) == Some(order.reversed()) {
+        bars.reverse();
+    }
+    bars
+}
+
+/// 共享K线的只读视图: 引用计数的整段K线加起始下标
+///
+/// 克隆和截取后缀都不复制K线，选股、信号和目标之间传递时只增加引用计数。
+/// 通过Deref按`&[DailyBar]`使用，需要独立副本时调用`to_vec()`
+#[derive(Clone)]
+pub struct BarSlice {
+    bars: Arc<Vec<DailyBar>>,
+    start: usize,
+}
+
+impl BarSlice {
+    pub fn new(bars: Arc<Vec<DailyBar>>) -> Self {
+        Self { bars, start: 0 }
+    }
+    
+    /// 跳过前n根K线的视图，超出长度时为空
+    pub fn skip(&self, n: usize) -> Self {
+        Self {
+            bars: self.bars.clone(),
+            start: (self.start + n).min(self.bars.len()),
+        }
+    }
+}
+
+impl Deref for BarSlice {
+    type Target = [DailyBar];
+    
+    fn deref(&self) -> &[DailyBar] {
+        &self.bars[self.start..]
+    }
+}
+
+impl From<Arc<Vec<DailyBar>>> for BarSlice {
+    fn from(bars: Arc<Vec<DailyBar>>) -> Self {
+        Self::new(bars)
+    }
+}
+
+impl From<Vec<DailyBar>> for BarSlice {
+    fn from(bars: Vec<DailyBar>) -> Self {
+        Self::new(Arc::new(bars))
+    }
+}
+
+impl fmt::Debug for BarSlice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BarSlice")
+            .field("len", &self.len())
+            .field("start", &self.start)
+            .finish()
+    }
+}
+
+/// 将独立持有的K线转换为共享视图，兼容仍按`Vec<DailyBar>`组织数据的调用方
+pub fn share_bars(stock_data: Vec<(String, Vec<DailyBar>)>) -> Vec<(String, BarSlice)> {
+    stock_data.into_iter()
+        .map(|(symbol, bars)| (symbol, BarSlice::from(bars)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bars(dates: &[i32]) -> Vec<DailyBar> {
+        dates.iter()
+            .map(|&date| DailyBar {
+                date,
+                open: 10.0,
+                high: 10.0,
+                low: 10.0,
+                close: 10.0,
+                volume: 10000,
+                amount: 100000,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detect_reads_first_and_last_dates() {
+        assert_eq!(BarOrder::detect(&bars(&[20230630, 20230629])), Some(BarOrder::NewestFirst));
+        assert_eq!(BarOrder::detect(&bars(&[20230629, 20230630])), Some(BarOrder::Chronological));
+        assert_eq!(BarOrder::detect(&bars(&[20230630])), None);
+        assert_eq!(BarOrder::detect(&[]), None);
+    }
+
+    #[test]
+    fn into_order_reverses_only_when_needed() {
+        let newest_first = into_order(bars(&[20230628, 20230629, 20230630]), BarOrder::NewestFirst);
+        assert_eq!(newest_first.iter().map(|bar| bar.date).collect::<Vec<_>>(), vec![20230630, 20230629, 20230628]);
+        
+        let unchanged = into_order(newest_first, BarOrder::NewestFirst);
+        assert_eq!(unchanged[0].date, 20230630);
+        
+        let chronological = into_order(unchanged, BarOrder::Chronological);
+        assert_eq!(chronological[0].date, 20230628);
+    }
+
+    #[test]
+    fn matching_order_passes_assertion() {
+        assert_order(&bars(&[20230630, 20230629]), BarOrder::NewestFirst);
+        assert_order(&bars(&[20230630]), BarOrder::Chronological);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "K线顺序不符")]
+    fn chronological_bars_fail_fast_where_newest_first_expected() {
+        assert_order(&bars(&[20230629, 20230630]), BarOrder::NewestFirst);
+    }
+}
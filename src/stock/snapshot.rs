@@ -0,0 +1,160 @@
+use crate::stock::data_provider::StockMeta;
+use anyhow::{Context, Result};
+use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// 快照中的单只股票
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    pub symbol: String,
+    pub name: String,
+    /// 上市日期(YYYYMMDD)，未知时为None
+    pub list_date: Option<i32>,
+}
+
+/// 某一日的股票池快照，用于回测历史区间时还原当时的股票池
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UniverseSnapshot {
+    /// 快照日期(YYYYMMDD)
+    pub date: i32,
+    pub entries: Vec<SnapshotEntry>,
+}
+
+impl UniverseSnapshot {
+    /// 由股票元数据创建快照，按代码排序
+    pub fn from_metas(date: i32, metas: &[StockMeta]) -> Self {
+        let mut entries: Vec<SnapshotEntry> = metas.iter()
+            .map(|meta| SnapshotEntry {
+                symbol: meta.symbol.clone(),
+                name: meta.name.clone(),
+                list_date: meta.list_date,
+            })
+            .collect();
+        entries.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        
+        Self { date, entries }
+    }
+    
+    /// 读取JSON快照文件
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("读取股票池快照失败: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("解析股票池快照失败: {}", path.display()))
+    }
+    
+    /// 写入JSON快照文件，自动创建上级目录
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+    
+    /// 快照中的股票代码
+    pub fn symbols(&self) -> Vec<String> {
+        self.entries.iter().map(|entry| entry.symbol.clone()).collect()
+    }
+}
+
+/// 幸存者偏差统计: 历史股票池中有多少股票在当前数据源中已不存在
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SurvivorshipReport {
+    /// 快照日期(YYYYMMDD)
+    pub snapshot_date: i32,
+    /// 快照中(经过滤规则筛选后)的股票数量
+    pub snapshot_symbols: usize,
+    /// 快照中存在但数据源中缺失的股票(退市或数据被截断)
+    pub missing_from_provider: Vec<String>,
+}
+
+impl SurvivorshipReport {
+    /// 将快照中的股票划分为数据源中可用的和缺失的
+    pub fn partition(snapshot_date: i32, symbols: Vec<String>, available: &HashSet<String>) -> (Vec<String>, Self) {
+        let snapshot_symbols = symbols.len();
+        let (present, missing): (Vec<String>, Vec<String>) = symbols.into_iter()
+            .partition(|symbol| available.contains(symbol));
+        
+        (present, Self {
+            snapshot_date,
+            snapshot_symbols,
+            missing_from_provider: missing,
+        })
+    }
+    
+    /// 历史股票池中不可用股票的比例
+    pub fn bias(&self) -> f32 {
+        if self.snapshot_symbols == 0 {
+            0.0
+        } else {
+            self.missing_from_provider.len() as f32 / self.snapshot_symbols as f32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SNAPSHOT_2022: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/snapshot_20220630.json");
+    const SNAPSHOT_2023: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/snapshot_20230630.json");
+
+    /// 当前数据源中存在的股票: 两只已退市的股票不在其中
+    fn available() -> HashSet<String> {
+        ["000001.SZ", "600000.SH", "601398.SH"].iter().map(|symbol| symbol.to_string()).collect()
+    }
+
+    #[test]
+    fn older_snapshot_reports_delisted_symbols() {
+        let snapshot = UniverseSnapshot::load(Path::new(SNAPSHOT_2022)).unwrap();
+        
+        let (present, report) = SurvivorshipReport::partition(snapshot.date, snapshot.symbols(), &available());
+        
+        assert_eq!(present, vec!["000001.SZ", "600000.SH"]);
+        assert_eq!(report.snapshot_date, 20220630);
+        assert_eq!(report.snapshot_symbols, 4);
+        assert_eq!(report.missing_from_provider, vec!["000023.SZ", "600087.SH"]);
+        assert_eq!(report.bias(), 0.5);
+    }
+
+    #[test]
+    fn recent_snapshot_has_no_bias() {
+        let snapshot = UniverseSnapshot::load(Path::new(SNAPSHOT_2023)).unwrap();
+        
+        let (present, report) = SurvivorshipReport::partition(snapshot.date, snapshot.symbols(), &available());
+        
+        assert_eq!(present, vec!["000001.SZ", "600000.SH", "601398.SH"]);
+        assert!(report.missing_from_provider.is_empty());
+        assert_eq!(report.bias(), 0.0);
+    }
+
+    #[test]
+    fn save_and_load_round_trip_sorted_by_symbol() {
+        let meta = |symbol: &str, name: &str| StockMeta {
+            symbol: symbol.to_string(),
+            name: name.to_string(),
+            exchange: String::new(),
+            list_date: Some(20200101),
+            is_st: false,
+        };
+        let snapshot = UniverseSnapshot::from_metas(20230630, &[meta("600000.SH", "浦发银行"), meta("000001.SZ", "平安银行")]);
+        let path = std::env::temp_dir()
+            .join(format!("strategy_lab_snapshot_{}", std::process::id()))
+            .join("universe.json");
+        
+        snapshot.save(&path).unwrap();
+        let loaded = UniverseSnapshot::load(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        
+        assert_eq!(loaded, snapshot);
+        assert_eq!(loaded.symbols(), vec!["000001.SZ", "600000.SH"]);
+    }
+
+    #[test]
+    fn empty_snapshot_has_zero_bias() {
+        assert_eq!(SurvivorshipReport::default().bias(), 0.0);
+    }
+}
@@ -0,0 +1,528 @@
+use crate::stock::adjust::AdjustMode;
+use crate::stock::coverage::SymbolCoverage;
+use crate::stock::retry::FetchError;
+use crate::stock::data_provider::{
+    limit_band, parse_universe, RefreshReport, StockDataProvider, StockFilterConfig,
+    StockMeta, Universe,
+};
+use anyhow::{bail, Context, Result};
+use egostrategy_datahub::models::stock::{StockData as Stock, DailyData as DailyBar};
+use log::{debug, info};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+/// 回测数据源，引擎通过该接口获取股票列表、K线和元数据
+pub trait DataSource: Send + Sync {
+    /// 所有股票代码
+    fn all_symbols(&self) -> Vec<String>;
+    
+    /// 股票日线数据，按日期倒序，可与数据源共享
+    fn daily_bars(&self, symbol: &str) -> Option<Arc<Vec<DailyBar>>>;
+    
+    /// 股票元数据
+    fn meta(&self, symbol: &str) -> Option<StockMeta>;
+    
+    /// 复权后的日线数据，不支持复权的数据源返回原始K线
+    fn daily_bars_adjusted(&self, symbol: &str, _mode: AdjustMode) -> Option<Arc<Vec<DailyBar>>> {
+        self.daily_bars(symbol)
+    }
+    
+    /// 复权后的日线数据，区分股票不存在和可重试的暂时性错误
+    fn try_daily_bars(&self, symbol: &str, mode: AdjustMode) -> Result<Arc<Vec<DailyBar>>, FetchError> {
+        self.daily_bars_adjusted(symbol, mode).ok_or(FetchError::NotFound)
+    }
+    
+    /// 最近max_bars根复权后的日线数据
+    fn daily_bars_range(&self, symbol: &str, max_bars: usize, mode: AdjustMode) -> Option<Arc<Vec<DailyBar>>> {
+        let bars = self.daily_bars_adjusted(symbol, mode)?;
+        if bars.len() > max_bars {
+            Some(Arc::new(bars[..max_bars].to_vec()))
+        } else {
+            Some(bars)
+        }
+    }
+    
+    /// 基准指数日线数据
+    fn benchmark(&self) -> Option<Vec<DailyBar>> {
+        None
+    }
+    
+    /// 按代码获取指数日线数据(按日期倒序)
+    fn index_bars(&self, _symbol: &str) -> Option<Vec<DailyBar>> {
+        None
+    }
+    
+    /// 加载复权因子文件
+    fn load_adjust_factors(&self, _path: &Path) -> Result<usize> {
+        bail!("该数据源不支持复权因子")
+    }
+    
+    /// 增量刷新最新交易日数据，不支持刷新的数据源不做处理
+    fn refresh_latest(&self, symbols: &[String]) -> Result<RefreshReport> {
+        Ok(RefreshReport {
+            unchanged: symbols.to_vec(),
+            ..RefreshReport::default()
+        })
+    }
+    
+    /// 按代码、名称规则过滤股票，价格规则在加载K线时应用
+    fn filter_symbols(&self, symbols: Vec<String>, config: &StockFilterConfig) -> Vec<String> {
+        symbols.into_iter()
+            .filter(|symbol| config.accepts_symbol(symbol))
+            .filter(|symbol| {
+                !config.exclude_st || self.meta(symbol).is_none_or(|meta| config.accepts_name(&meta.name))
+            })
+            .collect()
+    }
+    
+    /// 读取股票列表文件，并校验每个代码
+    fn load_universe_file(&self, path: &Path) -> Result<Universe> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("读取股票列表失败: {}", path.display()))?;
+        
+        let mut universe = Universe::default();
+        for symbol in parse_universe(&content) {
+            if self.meta(&symbol).is_some() {
+                universe.symbols.push(symbol);
+            } else {
+                universe.unknown.push(symbol);
+            }
+        }
+        
+        info!("股票列表 {}: {} 只有效, {} 只未知",
+            path.display(), universe.symbols.len(), universe.unknown.len());
+        
+        Ok(universe)
+    }
+    
+    /// 是否为ST股票
+    fn is_st(&self, symbol: &str) -> Option<bool> {
+        self.meta(symbol).map(|meta| meta.is_st)
+    }
+    
+    /// 各股票的数据覆盖情况
+    fn coverage_report(&self, symbols: &[String]) -> Vec<SymbolCoverage> {
+        symbols.iter()
+            .filter_map(|symbol| self.daily_bars(symbol).map(|bars| SymbolCoverage::from_bars(symbol, &bars)))
+            .collect()
+    }
+    
+    /// 涨跌停幅度
+    fn limit_band(&self, symbol: &str) -> Option<f32> {
+        self.is_st(symbol).map(|is_st| limit_band(symbol, is_st))
+    }
+}
+
+impl DataSource for StockDataProvider {
+    fn all_symbols(&self) -> Vec<String> {
+        self.get_all_stocks()
+    }
+    
+    fn daily_bars(&self, symbol: &str) -> Option<Arc<Vec<DailyBar>>> {
+        self.get_daily_bars(symbol, AdjustMode::None)
+    }
+    
+    fn meta(&self, symbol: &str) -> Option<StockMeta> {
+        self.get_meta(symbol)
+    }
+    
+    fn daily_bars_adjusted(&self, symbol: &str, mode: AdjustMode) -> Option<Arc<Vec<DailyBar>>> {
+        self.get_daily_bars(symbol, mode)
+    }
+    
+    fn try_daily_bars(&self, symbol: &str, mode: AdjustMode) -> Result<Arc<Vec<DailyBar>>, FetchError> {
+        StockDataProvider::try_daily_bars(self, symbol, mode)
+    }
+    
+    fn daily_bars_range(&self, symbol: &str, max_bars: usize, mode: AdjustMode) -> Option<Arc<Vec<DailyBar>>> {
+        self.get_daily_bars_range(symbol, max_bars, mode)
+    }
+    
+    fn benchmark(&self) -> Option<Vec<DailyBar>> {
+        self.get_benchmark()
+    }
+    
+    fn index_bars(&self, symbol: &str) -> Option<Vec<DailyBar>> {
+        self.get_index_bars(symbol)
+    }
+    
+    fn load_adjust_factors(&self, path: &Path) -> Result<usize> {
+        StockDataProvider::load_adjust_factors(self, path)
+    }
+    
+    fn refresh_latest(&self, symbols: &[String]) -> Result<RefreshReport> {
+        StockDataProvider::refresh_latest(self, symbols)
+    }
+    
+    fn filter_symbols(&self, symbols: Vec<String>, config: &StockFilterConfig) -> Vec<String> {
+        self.filter_stocks_with(symbols, config)
+    }
+    
+    fn load_universe_file(&self, path: &Path) -> Result<Universe> {
+        StockDataProvider::load_universe_file(self, path)
+    }
+    
+    fn coverage_report(&self, symbols: &[String]) -> Vec<SymbolCoverage> {
+        StockDataProvider::coverage_report(self, symbols)
+    }
+}
+
+/// 内存数据源，用于测试和注入外部数据
+#[derive(Debug, Clone, Default)]
+pub struct MockDataSource {
+    symbols: Vec<String>,
+    stocks: HashMap<String, (StockMeta, Arc<Vec<DailyBar>>)>,
+    benchmark: Option<Vec<DailyBar>>,
+    indices: HashMap<String, Vec<DailyBar>>,
+}
+
+impl MockDataSource {
+    /// 由股票K线创建，名称与代码相同
+    pub fn new(stock_data: Vec<(String, Vec<DailyBar>)>) -> Self {
+        let mut source = Self::default();
+        for (symbol, bars) in stock_data {
+            source.insert(&symbol, &symbol, bars);
+        }
+        source
+    }
+    
+    /// 添加或替换一只股票，交易所按代码前缀推断
+    pub fn insert(&mut self, symbol: &str, name: &str, bars: Vec<DailyBar>) {
+        if !self.stocks.contains_key(symbol) {
+            self.symbols.push(symbol.to_string());
+        }
+        let stock = Stock {
+            exchange: String::new(),
+            symbol: symbol.to_string(),
+            name: name.to_string(),
+            daily: bars,
+        };
+        let meta = StockMeta::from_stock(&stock);
+        self.stocks.insert(symbol.to_string(), (meta, Arc::new(stock.daily)));
+    }
+    
+    /// 设置基准指数
+    pub fn set_benchmark(&mut self, bars: Vec<DailyBar>) {
+        self.benchmark = Some(bars);
+    }
+    
+    /// 添加或替换一个指数
+    pub fn insert_index(&mut self, symbol: &str, bars: Vec<DailyBar>) {
+        self.indices.insert(symbol.to_string(), bars);
+    }
+}
+
+impl DataSource for MockDataSource {
+    fn all_symbols(&self) -> Vec<String> {
+        self.symbols.clone()
+    }
+    
+    fn daily_bars(&self, symbol: &str) -> Option<Arc<Vec<DailyBar>>> {
+        self.stocks.get(symbol).map(|(_, bars)| bars.clone())
+    }
+    
+    fn meta(&self, symbol: &str) -> Option<StockMeta> {
+        self.stocks.get(symbol).map(|(meta, _)| meta.clone())
+    }
+    
+    fn benchmark(&self) -> Option<Vec<DailyBar>> {
+        self.benchmark.clone()
+    }
+    
+    /// 没有单独添加的指数时使用同代码的K线，便于从CSV目录加载指数
+    fn index_bars(&self, symbol: &str) -> Option<Vec<DailyBar>> {
+        self.indices.get(symbol)
+            .cloned()
+            .or_else(|| self.stocks.get(symbol).map(|(_, bars)| bars.to_vec()))
+    }
+    
+    fn is_st(&self, symbol: &str) -> Option<bool> {
+        self.stocks.get(symbol).map(|(meta, _)| meta.is_st)
+    }
+}
+
+/// 单只股票K线的来源
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Provenance {
+    /// 提供主体K线的数据源序号
+    pub primary: usize,
+    /// 补充了更新K线的数据源序号及补充的K线数量
+    pub merged: Vec<(usize, usize)>,
+}
+
+/// 组合数据源: 按顺序查询多个数据源，前面的数据源缺少股票或最新K线时由后面的补充
+pub struct CompositeDataSource {
+    pub sources: Vec<Arc<dyn DataSource>>,
+    provenance: RwLock<HashMap<String, Provenance>>,
+}
+
+impl CompositeDataSource {
+    /// 由按优先级排列的数据源创建
+    pub fn new(sources: Vec<Arc<dyn DataSource>>) -> Self {
+        Self {
+            sources,
+            provenance: RwLock::new(HashMap::new()),
+        }
+    }
+    
+    /// 最近一次获取该股票K线时的来源
+    pub fn provenance(&self, symbol: &str) -> Option<Provenance> {
+        self.provenance.read().unwrap().get(symbol).cloned()
+    }
+    
+    /// 按顺序取第一个有结果的数据源
+    fn first<T>(&self, op: impl Fn(&dyn DataSource) -> Option<T>) -> Option<T> {
+        self.sources.iter().find_map(|source| op(source.as_ref()))
+    }
+}
+
+impl DataSource for CompositeDataSource {
+    fn all_symbols(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.sources.iter()
+            .flat_map(|source| source.all_symbols())
+            .filter(|symbol| seen.insert(symbol.clone()))
+            .collect()
+    }
+    
+    fn daily_bars(&self, symbol: &str) -> Option<Arc<Vec<DailyBar>>> {
+        self.daily_bars_adjusted(symbol, AdjustMode::None)
+    }
+    
+    fn meta(&self, symbol: &str) -> Option<StockMeta> {
+        self.first(|source| source.meta(symbol))
+    }
+    
+    fn daily_bars_adjusted(&self, symbol: &str, mode: AdjustMode) -> Option<Arc<Vec<DailyBar>>> {
+        self.try_daily_bars(symbol, mode).ok()
+    }
+    
+    /// 取第一个有该股票的数据源为主体，后面的数据源只补充比当前最新日期更新的K线，
+    /// 同一日期以前面的数据源为准
+    ///
+    /// 所有数据源都失败时，只要有一个是暂时性错误就返回暂时性错误，以便调用方重试而不是记为不存在
+    fn try_daily_bars(&self, symbol: &str, mode: AdjustMode) -> Result<Arc<Vec<DailyBar>>, FetchError> {
+        let mut sources = self.sources.iter().enumerate();
+        let mut transient = None;
+        let (primary, mut bars) = sources.by_ref()
+            .find_map(|(idx, source)| match source.try_daily_bars(symbol, mode) {
+                Ok(bars) => Some((idx, bars)),
+                Err(FetchError::NotFound) => None,
+                Err(e) => {
+                    debug!("数据源 {} 获取 {} 失败: {}", idx, symbol, e);
+                    transient.get_or_insert(e);
+                    None
+                }
+            })
+            .ok_or_else(|| transient.take().unwrap_or(FetchError::NotFound))?;
+        
+        let mut provenance = Provenance { primary, merged: Vec::new() };
+        for (idx, source) in sources {
+            let Ok(other) = source.try_daily_bars(symbol, mode) else {
+                continue;
+            };
+            
+            // K线按日期倒序，第一根为最新
+            let latest = bars.first().map_or(i32::MIN, |bar| bar.date);
+            let newer = other.iter().take_while(|bar| bar.date > latest).count();
+            if newer == 0 {
+                continue;
+            }
+            
+            let mut merged = other[..newer].to_vec();
+            merged.extend_from_slice(&bars);
+            bars = Arc::new(merged);
+            provenance.merged.push((idx, newer));
+        }
+        
+        if !provenance.merged.is_empty() {
+            debug!("股票 {} 由后备数据源补充了 {:?}", symbol, provenance.merged);
+        }
+        self.provenance.write().unwrap().insert(symbol.to_string(), provenance);
+        
+        Ok(bars)
+    }
+    
+    fn benchmark(&self) -> Option<Vec<DailyBar>> {
+        self.first(|source| source.benchmark())
+    }
+    
+    fn index_bars(&self, symbol: &str) -> Option<Vec<DailyBar>> {
+        self.first(|source| source.index_bars(symbol))
+    }
+    
+    /// 复权因子加载到所有支持复权的数据源
+    fn load_adjust_factors(&self, path: &Path) -> Result<usize> {
+        let mut loaded = None;
+        for source in &self.sources {
+            if let Ok(count) = source.load_adjust_factors(path) {
+                loaded = Some(count);
+            }
+        }
+        loaded.with_context(|| format!("没有数据源支持复权因子: {}", path.display()))
+    }
+    
+    /// 刷新所有数据源，返回主数据源的刷新结果
+    fn refresh_latest(&self, symbols: &[String]) -> Result<RefreshReport> {
+        let mut reports = Vec::new();
+        for source in &self.sources {
+            reports.push(source.refresh_latest(symbols)?);
+        }
+        Ok(reports.into_iter().next().unwrap_or_default())
+    }
+    
+    fn is_st(&self, symbol: &str) -> Option<bool> {
+        self.first(|source| source.is_st(symbol))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bars(n: i32, close: f32) -> Vec<DailyBar> {
+        (0..n)
+            .map(|i| DailyBar {
+                date: 20230630 - i,
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 10000,
+                amount: 100000,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mock_keeps_insertion_order_and_derives_meta() {
+        let mut source = MockDataSource::default();
+        source.insert("600083.SH", "*ST博信", bars(3, 3.0));
+        source.insert("000001.SZ", "平安银行", bars(3, 12.0));
+        source.insert("600083.SH", "*ST博信", bars(5, 3.0));
+        
+        assert_eq!(source.all_symbols(), vec!["600083.SH", "000001.SZ"]);
+        assert_eq!(source.daily_bars("600083.SH").unwrap().len(), 5);
+        let meta = source.meta("600083.SH").unwrap();
+        assert_eq!(meta.exchange, "SH");
+        assert!(meta.is_st);
+        assert_eq!(source.limit_band("600083.SH"), Some(0.05));
+        assert!(source.meta("000002.SZ").is_none());
+    }
+
+    #[test]
+    fn default_methods_trim_and_filter() {
+        let mut source = MockDataSource::default();
+        source.insert("600083.SH", "*ST博信", bars(10, 3.0));
+        source.insert("688981.SH", "中芯国际", bars(10, 50.0));
+        source.insert("000001.SZ", "平安银行", bars(10, 12.0));
+        
+        let range = source.daily_bars_range("000001.SZ", 4, AdjustMode::Forward).unwrap();
+        assert_eq!(range.len(), 4);
+        assert_eq!(range[0].date, 20230630);
+        
+        let config = StockFilterConfig { exclude_st: true, ..StockFilterConfig::default() };
+        let filtered = source.filter_symbols(source.all_symbols(), &config);
+        assert_eq!(filtered, vec!["000001.SZ"]);
+        assert!(source.load_adjust_factors(Path::new("factors.csv")).is_err());
+        let report = source.refresh_latest(&filtered).unwrap();
+        assert_eq!(report.unchanged, filtered);
+    }
+
+    /// 日期从latest起倒序排列的n根K线
+    fn dated_bars(latest: i32, n: i32, close: f32) -> Vec<DailyBar> {
+        bars(n, close).into_iter()
+            .enumerate()
+            .map(|(i, bar)| DailyBar { date: latest - i as i32, ..bar })
+            .collect()
+    }
+
+    #[test]
+    fn composite_merges_newer_bars_from_secondary() {
+        // 主数据源缺少最近两天，后备数据源有最近四天
+        let mut primary = MockDataSource::default();
+        primary.insert("000001.SZ", "平安银行", dated_bars(20230628, 5, 10.0));
+        let mut secondary = MockDataSource::default();
+        secondary.insert("000001.SZ", "平安银行", dated_bars(20230630, 4, 11.0));
+        secondary.insert("600000.SH", "浦发银行", dated_bars(20230630, 3, 8.0));
+        let composite = CompositeDataSource::new(vec![Arc::new(primary), Arc::new(secondary)]);
+        
+        let merged = composite.daily_bars("000001.SZ").unwrap();
+        
+        let dates: Vec<i32> = merged.iter().map(|bar| bar.date).collect();
+        assert_eq!(dates, vec![20230630, 20230629, 20230628, 20230627, 20230626, 20230625, 20230624]);
+        // 重叠日期保留主数据源的价格
+        assert_eq!(merged[0].close, 11.0);
+        assert_eq!(merged[2].close, 10.0);
+        assert_eq!(composite.provenance("000001.SZ"), Some(Provenance { primary: 0, merged: vec![(1, 2)] }));
+        
+        // 主数据源没有的股票由后备数据源提供
+        assert_eq!(composite.daily_bars("600000.SH").unwrap().len(), 3);
+        assert_eq!(composite.provenance("600000.SH"), Some(Provenance { primary: 1, merged: Vec::new() }));
+        assert_eq!(composite.all_symbols(), vec!["000001.SZ", "600000.SH"]);
+        assert!(composite.daily_bars("000002.SZ").is_none());
+    }
+
+    #[test]
+    fn composite_keeps_primary_when_it_is_up_to_date() {
+        let mut primary = MockDataSource::default();
+        primary.insert("000001.SZ", "平安银行", dated_bars(20230630, 3, 10.0));
+        let mut secondary = MockDataSource::default();
+        secondary.insert("000001.SZ", "平安银行", dated_bars(20230630, 5, 11.0));
+        let composite = CompositeDataSource::new(vec![Arc::new(primary), Arc::new(secondary)]);
+        
+        let bars = composite.daily_bars("000001.SZ").unwrap();
+        
+        assert_eq!(bars.len(), 3);
+        assert!(bars.iter().all(|bar| bar.close == 10.0));
+        assert_eq!(composite.provenance("000001.SZ").unwrap().merged, Vec::new());
+    }
+
+    /// 每次获取K线都暂时性失败的数据源
+    struct UnavailableSource;
+
+    impl DataSource for UnavailableSource {
+        fn all_symbols(&self) -> Vec<String> {
+            Vec::new()
+        }
+        
+        fn daily_bars(&self, _symbol: &str) -> Option<Arc<Vec<DailyBar>>> {
+            None
+        }
+        
+        fn meta(&self, _symbol: &str) -> Option<StockMeta> {
+            None
+        }
+        
+        fn try_daily_bars(&self, _symbol: &str, _mode: AdjustMode) -> Result<Arc<Vec<DailyBar>>, FetchError> {
+            Err(FetchError::Transient("连接超时".to_string()))
+        }
+    }
+
+    #[test]
+    fn composite_keeps_transient_failure_when_no_source_has_bars() {
+        let mut other = MockDataSource::default();
+        other.insert("600000.SH", "浦发银行", dated_bars(20230630, 3, 8.0));
+        let not_found: Arc<dyn DataSource> = Arc::new(other);
+        
+        // 无论暂时失败的数据源排在前面还是后面，都不应记为不存在
+        for sources in [vec![not_found.clone(), Arc::new(UnavailableSource)], vec![Arc::new(UnavailableSource), not_found.clone()]] {
+            let composite = CompositeDataSource::new(sources);
+            
+            let err = composite.try_daily_bars("000001.SZ", AdjustMode::None).unwrap_err();
+            assert!(matches!(err, FetchError::Transient(_)), "{:?}", err);
+            assert!(composite.daily_bars("000001.SZ").is_none());
+            assert!(composite.provenance("000001.SZ").is_none());
+        }
+        
+        let composite = CompositeDataSource::new(vec![not_found.clone()]);
+        assert!(matches!(composite.try_daily_bars("000001.SZ", AdjustMode::None), Err(FetchError::NotFound)));
+        
+        // 后面的数据源有数据时，前面的暂时失败不影响结果
+        let composite = CompositeDataSource::new(vec![Arc::new(UnavailableSource), not_found]);
+        assert_eq!(composite.try_daily_bars("600000.SH", AdjustMode::None).unwrap().len(), 3);
+        assert_eq!(composite.provenance("600000.SH"), Some(Provenance { primary: 1, merged: Vec::new() }));
+    }
+}
@@ -0,0 +1,332 @@
+use chrono::NaiveDate;
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use std::collections::BTreeMap;
+
+/// 相邻两根K线之间超过该自然日数视为缺失交易日(春节长假约10天)
+pub const MAX_GAP_DAYS: i64 = 15;
+
+/// 数据质量规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ValidationRule {
+    /// 开高低收存在0或负数
+    NonPositivePrice,
+    /// 最高价低于最低价
+    HighBelowLow,
+    /// 与后一根(更早的)K线日期相同
+    DuplicateDate,
+    /// 日期不满足倒序
+    NonMonotonicDate,
+    /// 与后一根K线相隔过久，可能缺失交易日，只报告不处理
+    MissingDays,
+}
+
+impl ValidationRule {
+    /// 获取规则名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValidationRule::NonPositivePrice => "非正价格",
+            ValidationRule::HighBelowLow => "最高价低于最低价",
+            ValidationRule::DuplicateDate => "重复日期",
+            ValidationRule::NonMonotonicDate => "日期乱序",
+            ValidationRule::MissingDays => "缺失交易日",
+        }
+    }
+    
+    /// 是否为单根K线的错误，需要按处理策略删除或修复
+    pub fn is_bar_error(&self) -> bool {
+        !matches!(self, ValidationRule::MissingDays)
+    }
+}
+
+/// 一条数据质量问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub rule: ValidationRule,
+    /// 问题K线在序列中的下标
+    pub index: usize,
+    pub date: i32,
+}
+
+/// 单只股票的数据质量报告
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub symbol: String,
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// 是否没有任何问题
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+    
+    /// 是否存在需要处理的K线错误
+    pub fn has_bar_errors(&self) -> bool {
+        self.issues.iter().any(|issue| issue.rule.is_bar_error())
+    }
+    
+    /// 指定规则的问题数量
+    pub fn count(&self, rule: ValidationRule) -> usize {
+        self.issues.iter().filter(|issue| issue.rule == rule).count()
+    }
+    
+    /// 指定规则的问题
+    pub fn issues_for(&self, rule: ValidationRule) -> Vec<&ValidationIssue> {
+        self.issues.iter().filter(|issue| issue.rule == rule).collect()
+    }
+    
+    /// 存在K线错误的下标，升序去重
+    pub fn bad_indices(&self) -> Vec<usize> {
+        let mut indices: Vec<usize> = self.issues.iter()
+            .filter(|issue| issue.rule.is_bar_error())
+            .map(|issue| issue.index)
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+}
+
+/// 数据质量问题的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationPolicy {
+    /// 存在任何K线错误时丢弃整只股票
+    DropSymbol,
+    /// 只删除有问题的K线
+    #[default]
+    DropBars,
+    /// 价格错误的K线用前一交易日收盘价填充，日期错误的K线删除
+    RepairForwardFill,
+}
+
+/// 检查K线序列(按日期倒序)的数据质量
+pub fn validate_bars(symbol: &str, bars: &[DailyBar]) -> ValidationReport {
+    let mut issues = Vec::new();
+    
+    for (index, bar) in bars.iter().enumerate() {
+        if bar.open <= 0.0 || bar.high <= 0.0 || bar.low <= 0.0 || bar.close <= 0.0 {
+            issues.push(ValidationIssue { rule: ValidationRule::NonPositivePrice, index, date: bar.date });
+        } else if bar.high < bar.low {
+            issues.push(ValidationIssue { rule: ValidationRule::HighBelowLow, index, date: bar.date });
+        }
+    }
+    
+    // 倒序: 每根K线的日期都应晚于下一根
+    for (offset, pair) in bars.windows(2).enumerate() {
+        let (newer, older) = (&pair[0], &pair[1]);
+        let index = offset + 1;
+        
+        if older.date == newer.date {
+            issues.push(ValidationIssue { rule: ValidationRule::DuplicateDate, index, date: older.date });
+        } else if older.date > newer.date {
+            issues.push(ValidationIssue { rule: ValidationRule::NonMonotonicDate, index, date: older.date });
+        } else if gap_days(older.date, newer.date).is_some_and(|gap| gap > MAX_GAP_DAYS) {
+            issues.push(ValidationIssue { rule: ValidationRule::MissingDays, index, date: older.date });
+        }
+    }
+    
+    issues.sort_by_key(|issue| (issue.index, issue.rule));
+    
+    ValidationReport { symbol: symbol.to_string(), issues }
+}
+
+fn gap_days(from: i32, to: i32) -> Option<i64> {
+    let parse = |date: i32| NaiveDate::parse_from_str(&date.to_string(), "%Y%m%d").ok();
+    Some((parse(to)? - parse(from)?).num_days())
+}
+
+/// 按策略处理有问题的K线，DropSymbol且存在K线错误时返回None
+pub fn apply_policy(bars: Vec<DailyBar>, report: &ValidationReport, policy: ValidationPolicy) -> Option<Vec<DailyBar>> {
+    if !report.has_bar_errors() {
+        return Some(bars);
+    }
+    
+    match policy {
+        ValidationPolicy::DropSymbol => None,
+        ValidationPolicy::DropBars => {
+            let bad = report.bad_indices();
+            Some(bars.into_iter()
+                .enumerate()
+                .filter(|(index, _)| bad.binary_search(index).is_err())
+                .map(|(_, bar)| bar)
+                .collect())
+        }
+        ValidationPolicy::RepairForwardFill => {
+            let date_errors: Vec<usize> = report.issues.iter()
+                .filter(|issue| matches!(issue.rule, ValidationRule::DuplicateDate | ValidationRule::NonMonotonicDate))
+                .map(|issue| issue.index)
+                .collect();
+            let price_errors: Vec<usize> = report.issues.iter()
+                .filter(|issue| matches!(issue.rule, ValidationRule::NonPositivePrice | ValidationRule::HighBelowLow))
+                .map(|issue| issue.index)
+                .collect();
+            
+            let mut repaired: Vec<DailyBar> = bars.into_iter()
+                .enumerate()
+                .filter(|(index, _)| !date_errors.contains(index))
+                .map(|(index, bar)| (price_errors.contains(&index), bar))
+                .rev()
+                .scan(None::<f32>, |prev_close, (bad, mut bar)| {
+                    // 从最早的K线向最新方向填充，缺少前值的坏K线被删除
+                    if bad {
+                        match *prev_close {
+                            Some(close) => {
+                                bar.open = close;
+                                bar.high = close;
+                                bar.low = close;
+                                bar.close = close;
+                                bar.volume = 0;
+                                bar.amount = 0;
+                            }
+                            None => return Some(None),
+                        }
+                    }
+                    *prev_close = Some(bar.close);
+                    Some(Some(bar))
+                })
+                .flatten()
+                .collect();
+            repaired.reverse();
+            
+            Some(repaired)
+        }
+    }
+}
+
+/// 汇总多只股票的报告，按规则统计问题数量
+pub fn summarize<'a>(reports: impl IntoIterator<Item = &'a ValidationReport>) -> BTreeMap<ValidationRule, usize> {
+    let mut summary = BTreeMap::new();
+    
+    for report in reports {
+        for issue in &report.issues {
+            *summary.entry(issue.rule).or_insert(0) += 1;
+        }
+    }
+    
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(date: i32, low: f32, high: f32, close: f32) -> DailyBar {
+        DailyBar {
+            date,
+            open: close,
+            high,
+            low,
+            close,
+            volume: 1000,
+            amount: 10000,
+        }
+    }
+
+    /// 按日期倒序的K线，下标1价格为0，下标3最高价低于最低价
+    fn corrupt_bars() -> Vec<DailyBar> {
+        vec![
+            bar(20230110, 9.5, 10.5, 10.0),
+            bar(20230109, 0.0, 10.5, 10.0),
+            bar(20230106, 9.5, 10.5, 10.2),
+            bar(20230105, 10.5, 9.5, 10.1),
+            bar(20230104, 9.5, 10.5, 9.9),
+        ]
+    }
+
+    fn rules(report: &ValidationReport) -> Vec<(ValidationRule, usize)> {
+        report.issues.iter().map(|issue| (issue.rule, issue.index)).collect()
+    }
+
+    #[test]
+    fn clean_series_has_no_issues() {
+        let bars = vec![bar(20230105, 9.5, 10.5, 10.0), bar(20230104, 9.5, 10.5, 10.0)];
+        
+        assert!(validate_bars("000001.SZ", &bars).is_clean());
+    }
+
+    #[test]
+    fn price_rules_trigger_on_bad_bars() {
+        let report = validate_bars("000001.SZ", &corrupt_bars());
+        
+        assert_eq!(rules(&report), vec![
+            (ValidationRule::NonPositivePrice, 1),
+            (ValidationRule::HighBelowLow, 3),
+        ]);
+        assert_eq!(report.count(ValidationRule::HighBelowLow), 1);
+        assert_eq!(report.issues_for(ValidationRule::NonPositivePrice)[0].date, 20230109);
+    }
+
+    #[test]
+    fn date_rules_trigger_on_duplicates_order_and_gaps() {
+        let bars = vec![
+            bar(20230301, 9.5, 10.5, 10.0),
+            // 距上一根超过15个自然日
+            bar(20230201, 9.5, 10.5, 10.0),
+            bar(20230201, 9.5, 10.5, 10.0),
+            bar(20230203, 9.5, 10.5, 10.0),
+        ];
+        
+        let report = validate_bars("000001.SZ", &bars);
+        
+        assert_eq!(rules(&report), vec![
+            (ValidationRule::MissingDays, 1),
+            (ValidationRule::DuplicateDate, 2),
+            (ValidationRule::NonMonotonicDate, 3),
+        ]);
+        assert_eq!(report.bad_indices(), vec![2, 3]);
+    }
+
+    #[test]
+    fn missing_days_alone_are_not_bar_errors() {
+        let bars = vec![bar(20230301, 9.5, 10.5, 10.0), bar(20230201, 9.5, 10.5, 10.0)];
+        let report = validate_bars("000001.SZ", &bars);
+        
+        assert!(!report.has_bar_errors());
+        assert_eq!(apply_policy(bars, &report, ValidationPolicy::DropSymbol).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn drop_symbol_policy_discards_series() {
+        let bars = corrupt_bars();
+        let report = validate_bars("000001.SZ", &bars);
+        
+        assert!(apply_policy(bars, &report, ValidationPolicy::DropSymbol).is_none());
+    }
+
+    #[test]
+    fn drop_bars_policy_removes_only_bad_bars() {
+        let bars = corrupt_bars();
+        let report = validate_bars("000001.SZ", &bars);
+        
+        let kept = apply_policy(bars, &report, ValidationPolicy::DropBars).unwrap();
+        
+        let dates: Vec<i32> = kept.iter().map(|bar| bar.date).collect();
+        assert_eq!(dates, vec![20230110, 20230106, 20230104]);
+    }
+
+    #[test]
+    fn forward_fill_policy_repairs_with_previous_close() {
+        let bars = corrupt_bars();
+        let report = validate_bars("000001.SZ", &bars);
+        
+        let repaired = apply_policy(bars, &report, ValidationPolicy::RepairForwardFill).unwrap();
+        
+        assert_eq!(repaired.len(), 5);
+        // 下标3用更早一天(下标4)的收盘价填充，下标1用下标2的收盘价填充
+        assert_eq!((repaired[3].low, repaired[3].high, repaired[3].close), (9.9, 9.9, 9.9));
+        assert_eq!(repaired[1].close, 10.2);
+        assert_eq!(repaired[1].volume, 0);
+        assert!(validate_bars("000001.SZ", &repaired).is_clean());
+    }
+
+    #[test]
+    fn summary_counts_issues_per_rule() {
+        let first = validate_bars("000001.SZ", &corrupt_bars());
+        let second = validate_bars("600000.SH", &corrupt_bars());
+        
+        let summary = summarize([&first, &second]);
+        
+        assert_eq!(summary[&ValidationRule::NonPositivePrice], 2);
+        assert_eq!(summary[&ValidationRule::HighBelowLow], 2);
+    }
+}
@@ -1,5 +1,5 @@
 use egostrategy_datahub::models::stock::DailyData as DailyBar;
-use crate::stock::indicators::{calculate_atr, standard_deviation, extract_price_data};
+use crate::stock::indicators::{calculate_atr, AtrSmoothing, standard_deviation, extract_price_data};
 use super::StockSelector;
 
 /// ATR策略的特征提取结果
@@ -26,7 +26,7 @@ pub fn extract_atr_features(history: &[DailyBar]) -> AtrFeatures {
     let last = &history[0];
     
     // 计算ATR
-    let atr_values = calculate_atr(&highs, &lows, &closes, 14);
+    let atr_values = calculate_atr(&highs, &lows, &closes, 14, AtrSmoothing::default());
     let atr = atr_values[0]; // 最新的ATR值（倒序数据中的第一个）
     
     // 计算振幅
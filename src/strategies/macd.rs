@@ -1,4 +1,4 @@
-use crate::stock::indicators::calculate_ema;
+use crate::stock::indicators::calculate_ema_series;
 use egostrategy_datahub::models::stock::DailyData as DailyBar;
 use crate::strategies::StockSelector;
 
@@ -13,31 +13,26 @@ impl MacdSelector {
         Self { top_n }
     }
     
-    /// 计算MACD值
+    /// 计算第idx天的MACD值 - 数据为倒序，只使用idx及之前的数据
     fn calculate_macd(&self, data: &[DailyBar], idx: usize) -> (f32, f32, f32) {
-        if idx < 26 || data.len() <= idx {
+        // DEA需要9个有效的DIF，DIF需要26天数据
+        if data.len() < idx + 26 + 8 {
             return (0.0, 0.0, 0.0);
         }
         
-        let prices: Vec<f32> = data.iter().map(|bar| bar.close).collect();
+        let prices: Vec<f32> = data[idx..].iter().map(|bar| bar.close).collect();
         
-        // 计算12日EMA
-        let ema12 = calculate_ema(&prices, 12, idx);
+        // 计算12日和26日EMA
+        let ema12 = calculate_ema_series(&prices, 12);
+        let ema26 = calculate_ema_series(&prices, 26);
         
-        // 计算26日EMA
-        let ema26 = calculate_ema(&prices, 26, idx);
-        
-        // 计算MACD值(DIF)
-        let dif = ema12 - ema26;
+        // 计算DIF序列(只保留26日EMA有效的部分)
+        let valid = prices.len() - 26 + 1;
+        let dif_values: Vec<f32> = (0..valid).map(|i| ema12[i] - ema26[i]).collect();
+        let dif = dif_values[0];
         
         // 计算DEA(9日DIF的EMA)
-        let mut dif_values = Vec::new();
-        for i in (idx - 8)..=idx {
-            let (diff, _, _) = self.calculate_macd(data, i);
-            dif_values.push(diff);
-        }
-        
-        let dea = dif_values.iter().sum::<f32>() / 9.0;
+        let dea = calculate_ema_series(&dif_values, 9)[0];
         
         // 计算MACD柱状图值
         let macd = 2.0 * (dif - dea);
@@ -56,12 +51,13 @@ impl StockSelector for MacdSelector {
     }
     
     fn calculate_score(&self, _symbol: &str, data: &[DailyBar], forecast_idx: usize) -> f32 {
-        if data.len() <= forecast_idx || forecast_idx < 26 {
+        if data.len() <= forecast_idx + 1 {
             return 0.0;
         }
         
+        // 倒序数据中前一天为forecast_idx + 1
         let (_, _, macd_current) = self.calculate_macd(data, forecast_idx);
-        let (_, _, macd_prev) = self.calculate_macd(data, forecast_idx - 1);
+        let (_, _, macd_prev) = self.calculate_macd(data, forecast_idx + 1);
         
         // MACD由负转正，或者MACD值增长较快时，得分较高
         if macd_prev < 0.0 && macd_current > 0.0 {
@@ -2,7 +2,7 @@ pub mod trend;
 pub mod reversal;
 pub mod volume;
 
-use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use crate::stock::series::BarSlice;
 
 /// 选股策略特征
 pub trait StockSelector: Send + Sync {
@@ -10,5 +10,13 @@ pub trait StockSelector: Send + Sync {
     fn name(&self) -> String;
     
     /// 运行选股策略
-    fn run(&self, stock_data: &[(String, Vec<DailyBar>)], forecast_idx: usize) -> Vec<(String, Vec<DailyBar>)>;
+    fn run(&self, stock_data: &[(String, BarSlice)], forecast_idx: usize) -> Vec<(String, BarSlice)>;
+    
+    /// 运行选股策略并返回每只入选股票的得分，默认得分为0
+    fn run_with_scores(&self, stock_data: &[(String, BarSlice)], forecast_idx: usize) -> Vec<(String, BarSlice, f32)> {
+        self.run(stock_data, forecast_idx)
+            .into_iter()
+            .map(|(symbol, data)| (symbol, data, 0.0))
+            .collect()
+    }
 }
@@ -1,8 +1,11 @@
 use crate::strategies::StockSelector;
+use crate::stock::series::BarSlice;
 use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use serde::Deserialize;
 
 /// 突破回踩选股策略
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct BreakthroughPullbackSelector {
     pub top_n: usize,
     pub lookback_days: usize,
@@ -11,12 +14,24 @@ pub struct BreakthroughPullbackSelector {
     pub volume_decline_ratio: f32,
 }
 
+impl Default for BreakthroughPullbackSelector {
+    fn default() -> Self {
+        Self {
+            top_n: 10,
+            lookback_days: 10,
+            min_breakthrough_percent: 5.0,
+            max_pullback_percent: 5.0,
+            volume_decline_ratio: 0.7,
+        }
+    }
+}
+
 impl StockSelector for BreakthroughPullbackSelector {
     fn name(&self) -> String {
         "突破回踩策略".to_string()
     }
     
-    fn run(&self, stock_data: &[(String, Vec<DailyBar>)], forecast_idx: usize) -> Vec<(String, Vec<DailyBar>)> {
+    fn run(&self, stock_data: &[(String, BarSlice)], forecast_idx: usize) -> Vec<(String, BarSlice)> {
         let mut candidates = Vec::new();
         
         for (symbol, data) in stock_data {
@@ -1,8 +1,12 @@
 use crate::strategies::StockSelector;
+use crate::stock::indicators::{Atr, AtrSmoothing, IndicatorCache};
+use crate::stock::series::BarSlice;
 use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use serde::Deserialize;
 
 /// ATR选股策略的权重配置
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct AtrSelectorWeights {
     pub atr_weight: f32,
     pub volume_weight: f32,
@@ -20,19 +24,37 @@ impl Default for AtrSelectorWeights {
 }
 
 /// 基于ATR的选股策略
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct AtrSelector {
     pub top_n: usize,
     pub lookback_days: usize,
     pub score_weights: AtrSelectorWeights,
 }
 
+impl Default for AtrSelector {
+    fn default() -> Self {
+        Self {
+            top_n: 10,
+            lookback_days: 100,
+            score_weights: AtrSelectorWeights::default(),
+        }
+    }
+}
+
 impl StockSelector for AtrSelector {
     fn name(&self) -> String {
         "ATR选股策略".to_string()
     }
     
-    fn run(&self, stock_data: &[(String, Vec<DailyBar>)], forecast_idx: usize) -> Vec<(String, Vec<DailyBar>)> {
+    fn run(&self, stock_data: &[(String, BarSlice)], forecast_idx: usize) -> Vec<(String, BarSlice)> {
+        self.run_with_scores(stock_data, forecast_idx)
+            .into_iter()
+            .map(|(symbol, data, _)| (symbol, data))
+            .collect()
+    }
+    
+    fn run_with_scores(&self, stock_data: &[(String, BarSlice)], forecast_idx: usize) -> Vec<(String, BarSlice, f32)> {
         // 计算每只股票的得分
         let mut scores = Vec::new();
         
@@ -42,7 +64,7 @@ impl StockSelector for AtrSelector {
             }
             
             // 计算ATR
-            let atr = self.calculate_atr(data, forecast_idx);
+            let atr = self.calculate_atr(symbol, data, forecast_idx);
             
             // 计算成交量得分
             let volume_score = self.calculate_volume_score(data, forecast_idx);
@@ -63,46 +85,31 @@ impl StockSelector for AtrSelector {
         scores.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
         
         // 取前N名
-        scores.into_iter()
-            .take(self.top_n)
-            .map(|(symbol, data, _)| (symbol, data))
-            .collect()
+        scores.truncate(self.top_n);
+        scores
     }
 }
 
 impl AtrSelector {
-    /// 计算ATR (Average True Range)
-    fn calculate_atr(&self, data: &[DailyBar], forecast_idx: usize) -> f32 {
+    /// 计算ATR (Average True Range)，使用与其他策略一致的indicators::calculate_atr
+    ///
+    /// 完整ATR序列按股票缓存在IndicatorCache::shared()中，后续预测日直接取值
+    fn calculate_atr(&self, symbol: &str, data: &[DailyBar], forecast_idx: usize) -> f32 {
         if data.len() <= forecast_idx + 1 {
             return 0.0;
         }
         
-        let mut tr_sum = 0.0;
         let period = self.lookback_days.min(data.len() - forecast_idx - 1);
-        
-        for i in 0..period {
-            let idx = forecast_idx + i;
-            let prev_idx = idx + 1;
-            
-            if prev_idx >= data.len() {
-                continue;
-            }
-            
-            // 计算真实波动幅度 (True Range)
-            let high_low = data[idx].high - data[idx].low;
-            let high_prev_close = (data[idx].high - data[prev_idx].close).abs();
-            let low_prev_close = (data[idx].low - data[prev_idx].close).abs();
-            
-            let tr = high_low.max(high_prev_close).max(low_prev_close);
-            tr_sum += tr;
+        if period == 0 {
+            return 0.0;
         }
         
-        // 计算ATR
-        let atr = if period > 0 {
-            tr_sum / period as f32
-        } else {
-            0.0
-        };
+        let indicator = Atr { period, smoothing: AtrSmoothing::default() };
+        let atr = IndicatorCache::shared()
+            .get_indicator(symbol, data, &indicator)
+            .get(forecast_idx)
+            .copied()
+            .unwrap_or(0.0);
         
         // 归一化ATR (相对于价格)
         let price = data[forecast_idx].close;
@@ -158,3 +165,37 @@ impl AtrSelector {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stock::indicators::{calculate_atr, extract_price_data};
+    use crate::stock::mock_data::create_mock_daily_bars;
+    
+    /// 真实波幅逐日变化的K线，按日期倒序
+    fn varying_bars() -> Vec<DailyBar> {
+        let mut bars = create_mock_daily_bars(40);
+        for (i, bar) in bars.iter_mut().enumerate() {
+            bar.high += (i % 3) as f32 * 0.3;
+            bar.low -= (i % 4) as f32 * 0.2;
+        }
+        bars
+    }
+    
+    /// 原先自行平均TR的AtrSelector与直接调用calculate_atr(ATR特征的算法)得到相同的ATR
+    #[test]
+    fn selector_atr_agrees_with_shared_atr() {
+        let bars = varying_bars();
+        let selector = AtrSelector { lookback_days: 14, ..AtrSelector::default() };
+        let (_, highs, lows, closes, _, _) = extract_price_data(&bars);
+        let shared = calculate_atr(&highs, &lows, &closes, 14, AtrSmoothing::default());
+        
+        for forecast_idx in [0, 3] {
+            let normalized = selector.calculate_atr("ATR_AGREE_TEST", &bars, forecast_idx);
+            
+            assert!(shared[forecast_idx] > 0.0);
+            assert!((normalized * bars[forecast_idx].close - shared[forecast_idx]).abs() < 1e-4,
+                "forecast_idx {}: {} != {}", forecast_idx, normalized * bars[forecast_idx].close, shared[forecast_idx]);
+        }
+    }
+}
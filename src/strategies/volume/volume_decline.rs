@@ -1,8 +1,11 @@
 use crate::strategies::StockSelector;
+use crate::stock::series::BarSlice;
 use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use serde::Deserialize;
 
 /// 成交量萎缩选股策略
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct VolumeDecliningSelector {
     pub top_n: usize,
     pub lookback_days: usize,
@@ -32,7 +35,15 @@ impl StockSelector for VolumeDecliningSelector {
         "成交量萎缩策略".to_string()
     }
     
-    fn run(&self, stock_data: &[(String, Vec<DailyBar>)], forecast_idx: usize) -> Vec<(String, Vec<DailyBar>)> {
+    fn run(&self, stock_data: &[(String, BarSlice)], forecast_idx: usize) -> Vec<(String, BarSlice)> {
+        // 移除排序用的比例值
+        self.run_with_scores(stock_data, forecast_idx)
+            .into_iter()
+            .map(|(symbol, data, _)| (symbol, data))
+            .collect()
+    }
+    
+    fn run_with_scores(&self, stock_data: &[(String, BarSlice)], forecast_idx: usize) -> Vec<(String, BarSlice, f32)> {
         let mut candidates = Vec::new();
         
         for (symbol, data) in stock_data {
@@ -59,10 +70,7 @@ impl StockSelector for VolumeDecliningSelector {
             candidates.truncate(self.top_n);
         }
         
-        // 移除排序用的比例值
-        candidates.into_iter()
-            .map(|(symbol, data, _)| (symbol, data))
-            .collect()
+        candidates
     }
 }
 
@@ -1,5 +1,5 @@
-use crate::targets::Target;
-use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use crate::targets::{Target, TradeOutcome};
+use crate::stock::series::BarSlice;
 
 /// 组合目标 - 同时满足多个目标
 pub struct CombinedTarget {
@@ -60,7 +60,7 @@ impl Target for CombinedTarget {
             .unwrap_or(1)
     }
     
-    fn run(&self, signals: Vec<(String, Vec<DailyBar>, f32)>, forecast_idx: usize) -> f32 {
+    fn run(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize) -> f32 {
         // 对每个目标运行评估，然后计算加权平均得分
         let mut weighted_score = 0.0;
         
@@ -77,7 +77,7 @@ impl Target for CombinedTarget {
         weighted_score
     }
     
-    fn evaluate_signals(&self, signals: Vec<(String, Vec<DailyBar>, f32)>, forecast_idx: usize) 
+    fn evaluate_signals(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize) 
         -> (usize, usize, usize, usize, Vec<f32>, Vec<f32>) {
         // 使用第一个目标的评估结果作为基础
         if self.targets.is_empty() {
@@ -92,4 +92,11 @@ impl Target for CombinedTarget {
         // 使用第一个目标的评估结果
         self.targets[0].evaluate_signals(cloned_signals, forecast_idx)
     }
+    
+    fn evaluate_trades(&self, signals: &[(String, BarSlice, f32)], forecast_idx: usize) -> Vec<TradeOutcome> {
+        // 与evaluate_signals一致，使用第一个目标的逐笔结果
+        self.targets.first()
+            .map(|target| target.evaluate_trades(signals, forecast_idx))
+            .unwrap_or_default()
+    }
 }
@@ -1,8 +1,12 @@
-use crate::targets::Target;
+use crate::backtest::result::ExitReason;
+use crate::targets::{is_suspended, next_tradable, summarize_outcomes, Target, TradeOutcome};
+use crate::stock::series::BarSlice;
 use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use serde::Deserialize;
 
 /// 止损目标 - 在指定天数内不触发止损
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct GuardTarget {
     pub stop_loss: f32,
     pub in_days: usize,
@@ -25,7 +29,7 @@ impl Target for GuardTarget {
         self.in_days
     }
     
-    fn run(&self, signals: Vec<(String, Vec<DailyBar>, f32)>, forecast_idx: usize) -> f32 {
+    fn run(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize) -> f32 {
         let (total_trades, winning_trades, _, _, _, _) = self.evaluate_signals(signals, forecast_idx);
         
         if total_trades > 0 {
@@ -35,94 +39,205 @@ impl Target for GuardTarget {
         }
     }
     
-    fn evaluate_signals(&self, signals: Vec<(String, Vec<DailyBar>, f32)>, forecast_idx: usize) 
+    fn evaluate_signals(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize) 
         -> (usize, usize, usize, usize, Vec<f32>, Vec<f32>) {
-        let mut total_trades = signals.len();
-        let mut winning_trades = 0;
-        let mut losing_trades = 0;
-        let mut stop_loss_trades = 0;
-        let mut returns = Vec::new();
-        let mut hold_days = Vec::new();
+        summarize_outcomes(&self.evaluate_trades(&signals, forecast_idx))
+    }
+    
+    fn evaluate_trades(&self, signals: &[(String, BarSlice, f32)], forecast_idx: usize) -> Vec<TradeOutcome> {
+        signals.iter()
+            .enumerate()
+            .filter_map(|(signal_idx, (_, data, buy_price))| self.evaluate_trade(signal_idx, data, *buy_price, forecast_idx))
+            .collect()
+    }
+}
+
+impl GuardTarget {
+    /// 评估单笔交易，买入价无效或数据不足时返回None
+    fn evaluate_trade(&self, signal_idx: usize, data: &[DailyBar], buy_price: f32, forecast_idx: usize) -> Option<TradeOutcome> {
+        if buy_price <= 0.0 {
+            return None;
+        }
         
-        for (_, data, buy_price) in signals {
-            if buy_price <= 0.0 {
-                total_trades -= 1;
-                continue;
-            }
-            
-            // 确保有足够的历史数据进行回测
-            if forecast_idx < self.in_days || data.len() <= forecast_idx {
-                total_trades -= 1;
+        // 确保有足够的历史数据进行回测
+        if forecast_idx < self.in_days || data.len() <= forecast_idx {
+            return None;
+        }
+        
+        // 买入在forecast_idx-1成交，当天停牌无法买入，不计入交易
+        if forecast_idx == 0 || is_suspended(&data[forecast_idx - 1]) {
+            return None;
+        }
+        
+        // 计算最大收益和止损
+        let mut max_return = -1.0;
+        let mut exit_idx = None;
+        let mut is_win = true; // 默认成功，除非触发止损
+        let mut is_stop_loss = false;
+        
+        // 从买入次日forecast_idx-1开始，按日期顺序到forecast_idx-in_days
+        for idx in (forecast_idx - self.in_days..forecast_idx).rev() {
+            let bar = &data[idx];
+            // 停牌日无法卖出
+            if is_suspended(bar) {
                 continue;
             }
             
-            // 计算最大收益和止损
-            let mut max_return = -1.0;
-            let mut exit_day = 0;
-            let mut is_win = true; // 默认成功，除非触发止损
-            let mut is_stop_loss = false;
+            // 先检查收盘价是否触发止损
+            let current_return = (bar.close - buy_price) / buy_price;
             
-            // 检查从forecast_idx-self.in_days到forecast_idx-1的数据
-            for i in (forecast_idx - self.in_days)..forecast_idx {
-                // 先检查收盘价是否触发止损
-                let current_return = (data[i].close - buy_price) / buy_price;
-                
-                // 如果亏损超过止损的2倍，认为是止损失败
-                if current_return < -2.0 * self.stop_loss {
-                    is_win = false;
-                    // 不再使用is_stop_loss_fail
-                    max_return = current_return;
-                    exit_day = i - (forecast_idx - self.in_days) + 1;
-                    break;
-                }
-                // 如果亏损超过止损线，认为是正常止损
-                else if current_return < -self.stop_loss {
-                    is_win = false;
-                    is_stop_loss = true;
-                    max_return = current_return;
-                    exit_day = i - (forecast_idx - self.in_days) + 1;
-                    break;
-                }
-                
-                // 更新最大收益
-                if current_return > max_return {
-                    max_return = current_return;
-                }
+            // 如果亏损超过止损的2倍，认为是止损失败
+            if current_return < -2.0 * self.stop_loss {
+                is_win = false;
+                max_return = current_return;
+                exit_idx = Some(idx);
+                break;
+            }
+            // 如果亏损超过止损线，认为是正常止损
+            else if current_return < -self.stop_loss {
+                is_win = false;
+                is_stop_loss = true;
+                max_return = current_return;
+                exit_idx = Some(idx);
+                break;
             }
             
-            // 如果没有提前退出，使用最后一天的收盘价计算收益
-            if exit_day == 0 {
-                let last_idx = forecast_idx - 1;
+            // 更新最大收益
+            if current_return > max_return {
+                max_return = current_return;
+            }
+        }
+        
+        // 如果没有提前退出，使用最后一天的收盘价计算收益，最后一天停牌则顺延到复牌当天
+        let mut suspended_days = 0;
+        let exit_idx = match exit_idx {
+            Some(idx) => idx,
+            None => {
+                let last_idx = next_tradable(data, forecast_idx - self.in_days);
+                suspended_days = forecast_idx - self.in_days - last_idx;
                 let last_return = (data[last_idx].close - buy_price) / buy_price;
                 max_return = last_return;
-                exit_day = self.in_days;
                 
                 // 检查最后一天是否触发止损
                 if last_return < -self.stop_loss {
                     is_win = false;
-                    if last_return < -2.0 * self.stop_loss {
-                        // 不再使用is_stop_loss_fail
-                    } else {
-                        is_stop_loss = true;
-                    }
-                }
-            }
-            
-            // 统计结果
-            if is_win {
-                winning_trades += 1;
-            } else {
-                losing_trades += 1;
-                if is_stop_loss {
-                    stop_loss_trades += 1;
+                    is_stop_loss = last_return >= -2.0 * self.stop_loss;
                 }
-                // 不再统计止损失败交易数
+                last_idx
             }
-            
-            returns.push(max_return);
-            hold_days.push(exit_day as f32);
+        };
+        
+        // 止损目标没有止盈，未触发止损的交易都是到期平仓
+        let exit_reason = if is_win {
+            ExitReason::TimeExpired
+        } else if max_return < -2.0 * self.stop_loss {
+            ExitReason::StopLossFailed
+        } else {
+            ExitReason::StopLoss
+        };
+        
+        Some(TradeOutcome {
+            signal_idx,
+            exit_idx,
+            return_pct: max_return,
+            hold_days: forecast_idx - exit_idx,
+            is_win,
+            is_stop_loss,
+            exit_reason,
+            suspended_days,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stock::mock_data::create_mock_daily_bars;
+    
+    /// 由按日期顺序的收盘价生成K线(倒序)，开盘价、最高价和最低价都等于收盘价
+    fn bars(closes: &[f32]) -> Vec<DailyBar> {
+        let mut bars = create_mock_daily_bars(closes.len());
+        for (bar, &close) in bars.iter_mut().rev().zip(closes) {
+            bar.open = close;
+            bar.high = close;
+            bar.low = close;
+            bar.close = close;
         }
+        bars
+    }
+    
+    /// 将data[idx]改为停牌(成交量和成交额为0)
+    fn suspend(data: &mut [DailyBar], idx: usize) {
+        data[idx].volume = 0;
+        data[idx].amount = 0;
+    }
+    
+    /// 止损3%、持有3天；6根K线时forecast_idx为4，持有期按日期顺序为data[3]、data[2]、data[1]
+    fn target() -> GuardTarget {
+        GuardTarget { stop_loss: 0.03, in_days: 3 }
+    }
+    
+    #[test]
+    fn stops_on_first_crossing_day() {
+        // 第二个持有日跌4%止损，第三个持有日跌10%不应被采用
+        let data = bars(&[10.0, 10.0, 10.0, 9.6, 9.0, 9.0]);
+        let outcome = target().evaluate_trade(0, &data, 10.0, 4).unwrap();
+        
+        assert_eq!(outcome.exit_reason, ExitReason::StopLoss);
+        assert_eq!(outcome.exit_idx, 2);
+        assert_eq!(outcome.hold_days, 2);
+        assert!((outcome.return_pct + 0.04).abs() < 1e-6);
+        assert!(!outcome.is_win);
+    }
+    
+    #[test]
+    fn expires_on_last_holding_day() {
+        let data = bars(&[10.0, 10.0, 10.1, 9.9, 10.2, 9.0]);
+        let outcome = target().evaluate_trade(0, &data, 10.0, 4).unwrap();
+        
+        assert_eq!(outcome.exit_reason, ExitReason::TimeExpired);
+        assert_eq!(outcome.exit_idx, 1);
+        assert_eq!(outcome.hold_days, 3);
+        assert!((outcome.return_pct - 0.02).abs() < 1e-6);
+        assert!(outcome.is_win);
+    }
+    
+    #[test]
+    fn suspended_bar_cannot_trigger_stop() {
+        let mut data = bars(&[10.0, 10.0, 10.0, 9.0, 10.1, 10.0]);
+        suspend(&mut data, 2);
+        let outcome = target().evaluate_trade(0, &data, 10.0, 4).unwrap();
+        
+        assert_eq!(outcome.exit_reason, ExitReason::TimeExpired);
+        assert_eq!(outcome.exit_idx, 1);
+        assert_eq!(outcome.suspended_days, 0);
+        assert!((outcome.return_pct - 0.01).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn suspended_last_day_defers_exit_to_resumption() {
+        // 最后一个持有日停牌，顺延到复牌当天data[0]按收盘价卖出
+        let mut data = bars(&[10.0, 10.0, 10.0, 10.1, 10.3, 9.5]);
+        suspend(&mut data, 1);
+        let outcome = target().evaluate_trade(0, &data, 10.0, 4).unwrap();
+        
+        assert_eq!(outcome.exit_idx, 0);
+        assert_eq!(outcome.suspended_days, 1);
+        assert_eq!(outcome.hold_days, 4);
+        assert_eq!(outcome.exit_reason, ExitReason::StopLoss);
+        assert!((outcome.return_pct + 0.05).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn suspended_entry_day_is_not_a_trade() {
+        // 买入在data[3]成交，当天停牌无法买入
+        let mut data = bars(&[10.0; 6]);
+        suspend(&mut data, 3);
+        assert!(target().evaluate_trade(0, &data, 10.0, 4).is_none());
         
-        (total_trades, winning_trades, losing_trades, stop_loss_trades, returns, hold_days)
+        // 信号日data[4]停牌不影响次日买入
+        let mut data = bars(&[10.0; 6]);
+        suspend(&mut data, 4);
+        assert!(target().evaluate_trade(0, &data, 10.0, 4).is_some());
     }
 }
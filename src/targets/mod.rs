@@ -1,8 +1,101 @@
 pub mod return_target;
 pub mod guard_target;
 pub mod combined_target;
+pub mod trailing_stop_target;
+pub mod short_target;
+pub mod scaled_exit_target;
 
+use crate::backtest::result::ExitReason;
+use crate::stock::series::BarSlice;
 use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use serde::{Deserialize, Serialize};
+
+/// 单笔交易的评估结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradeOutcome {
+    /// 在信号列表中的下标
+    pub signal_idx: usize,
+    /// 卖出当天在该股票K线中的下标(倒序)
+    pub exit_idx: usize,
+    pub return_pct: f32,
+    pub hold_days: usize,
+    pub is_win: bool,
+    pub is_stop_loss: bool,
+    pub exit_reason: ExitReason,
+    /// 卖出日停牌而顺延的天数
+    pub suspended_days: usize,
+}
+
+/// 分批卖出中的一笔
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PartialExit {
+    /// 卖出当天在该股票K线中的下标(倒序)
+    pub exit_idx: usize,
+    pub exit_price: f32,
+    /// 卖出的仓位比例
+    pub fraction: f32,
+    pub exit_reason: ExitReason,
+}
+
+/// 判断是否达到目标收益使用的价格
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetFillMode {
+    /// 收盘价达到目标价才算成功，按收盘价卖出
+    #[default]
+    CloseOnly,
+    /// 盘中最高价达到目标价即算成功，按目标价卖出(相当于挂限价卖单)
+    IntradayHigh,
+}
+
+/// 交易方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// 做多: 买入开仓，卖出平仓
+    #[default]
+    Long,
+    /// 做空: 卖出开仓，买入平仓，价格下跌时盈利
+    Short,
+}
+
+impl Direction {
+    /// 导出时使用的固定名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Long => "long",
+            Direction::Short => "short",
+        }
+    }
+}
+
+/// 是否停牌: 成交量或成交额为0的K线当天无法买卖
+pub fn is_suspended(bar: &DailyBar) -> bool {
+    bar.volume == 0 || bar.amount == 0
+}
+
+/// 从exit_idx开始向后(日期更新的方向)找到第一根可交易的K线，都停牌时返回exit_idx
+pub fn next_tradable(data: &[DailyBar], exit_idx: usize) -> usize {
+    (0..=exit_idx).rev()
+        .find(|&idx| !is_suspended(&data[idx]))
+        .unwrap_or(exit_idx)
+}
+
+/// 汇总逐笔结果为evaluate_signals的返回值:
+/// (交易数, 盈利数, 亏损数, 止损数, 收益率, 持有天数)
+pub fn summarize_outcomes(outcomes: &[TradeOutcome]) -> (usize, usize, usize, usize, Vec<f32>, Vec<f32>) {
+    let winning_trades = outcomes.iter().filter(|outcome| outcome.is_win).count();
+    let stop_loss_trades = outcomes.iter().filter(|outcome| !outcome.is_win && outcome.is_stop_loss).count();
+    
+    (
+        outcomes.len(),
+        winning_trades,
+        outcomes.len() - winning_trades,
+        stop_loss_trades,
+        outcomes.iter().map(|outcome| outcome.return_pct).collect(),
+        outcomes.iter().map(|outcome| outcome.hold_days as f32).collect(),
+    )
+}
 
 /// 目标特征
 pub trait Target: Send + Sync {
@@ -18,10 +111,25 @@ pub trait Target: Send + Sync {
     /// 获取目标天数
     fn in_days(&self) -> usize;
     
+    /// 交易方向，默认做多
+    fn direction(&self) -> Direction {
+        Direction::Long
+    }
+    
     /// 运行目标评估，返回成功率
-    fn run(&self, signals: Vec<(String, Vec<DailyBar>, f32)>, forecast_idx: usize) -> f32;
+    fn run(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize) -> f32;
     
     /// 详细评估信号，返回交易详情
-    fn evaluate_signals(&self, signals: Vec<(String, Vec<DailyBar>, f32)>, forecast_idx: usize) 
+    fn evaluate_signals(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize) 
         -> (usize, usize, usize, usize, Vec<f32>, Vec<f32>);
+    
+    /// 分批卖出的目标返回单笔交易每一批的卖出日和卖出价，按日期顺序；默认一次全部卖出，返回None
+    fn exit_legs(&self, _data: &[DailyBar], _buy_price: f32, _forecast_idx: usize) -> Option<Vec<PartialExit>> {
+        None
+    }
+    
+    /// 逐笔评估信号，顺序与evaluate_signals返回的收益率一致；默认不提供逐笔结果
+    fn evaluate_trades(&self, _signals: &[(String, BarSlice, f32)], _forecast_idx: usize) -> Vec<TradeOutcome> {
+        Vec::new()
+    }
 }
@@ -1,17 +1,34 @@
-use crate::targets::Target;
+use crate::backtest::result::ExitReason;
+use crate::targets::{is_suspended, next_tradable, summarize_outcomes, Target, TargetFillMode, TradeOutcome};
+use crate::stock::series::BarSlice;
 use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use serde::Deserialize;
 
 /// 收益率目标
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ReturnTarget {
     pub target_return: f32,
     pub stop_loss: f32,
     pub in_days: usize,
+    /// 判断达到目标的价格，默认只看收盘价
+    #[serde(default)]
+    pub fill_mode: TargetFillMode,
+    /// 最高价涨幅达到该比例后，之后的交易日止损价上移到买入价，None时不启用
+    #[serde(default)]
+    pub breakeven_trigger: Option<f32>,
 }
 
 impl Target for ReturnTarget {
     fn name(&self) -> String {
-        format!("收益率目标 {}% / {}天", self.target_return * 100.0, self.in_days)
+        let name = match self.fill_mode {
+            TargetFillMode::CloseOnly => format!("收益率目标 {}% / {}天", self.target_return * 100.0, self.in_days),
+            TargetFillMode::IntradayHigh => format!("收益率目标 {}% / {}天 (盘中)", self.target_return * 100.0, self.in_days),
+        };
+        match self.breakeven_trigger {
+            Some(trigger) => format!("{} 保本{}%", name, trigger * 100.0),
+            None => name,
+        }
     }
     
     fn target_return(&self) -> f32 {
@@ -26,7 +43,7 @@ impl Target for ReturnTarget {
         self.in_days
     }
     
-    fn run(&self, signals: Vec<(String, Vec<DailyBar>, f32)>, forecast_idx: usize) -> f32 {
+    fn run(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize) -> f32 {
         let (total_trades, winning_trades, _, _, _, _) = self.evaluate_signals(signals, forecast_idx);
         
         if total_trades > 0 {
@@ -36,102 +53,272 @@ impl Target for ReturnTarget {
         }
     }
     
-    fn evaluate_signals(&self, signals: Vec<(String, Vec<DailyBar>, f32)>, forecast_idx: usize) 
+    fn evaluate_signals(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize) 
         -> (usize, usize, usize, usize, Vec<f32>, Vec<f32>) {
-        let mut total_trades = signals.len();
-        let mut winning_trades = 0;
-        let mut losing_trades = 0;
-        let mut stop_loss_trades = 0;
-        let mut returns = Vec::new();
-        let mut hold_days = Vec::new();
+        summarize_outcomes(&self.evaluate_trades(&signals, forecast_idx))
+    }
+    
+    fn evaluate_trades(&self, signals: &[(String, BarSlice, f32)], forecast_idx: usize) -> Vec<TradeOutcome> {
+        signals.iter()
+            .enumerate()
+            .filter_map(|(signal_idx, (_, data, buy_price))| self.evaluate_trade(signal_idx, data, *buy_price, forecast_idx))
+            .collect()
+    }
+}
+
+impl ReturnTarget {
+    pub fn new(target_return: f32, stop_loss: f32, in_days: usize) -> Self {
+        Self {
+            target_return,
+            stop_loss,
+            in_days,
+            fill_mode: TargetFillMode::CloseOnly,
+            breakeven_trigger: None,
+        }
+    }
+    
+    /// 设置判断达到目标的价格
+    pub fn with_fill_mode(mut self, fill_mode: TargetFillMode) -> Self {
+        self.fill_mode = fill_mode;
+        self
+    }
+    
+    /// 最高价涨幅达到trigger后把止损价移到买入价
+    pub fn with_breakeven_trigger(mut self, trigger: f32) -> Self {
+        self.breakeven_trigger = Some(trigger);
+        self
+    }
+    
+    /// 评估单笔交易，买入价无效或数据不足时返回None
+    fn evaluate_trade(&self, signal_idx: usize, data: &[DailyBar], buy_price: f32, forecast_idx: usize) -> Option<TradeOutcome> {
+        if buy_price <= 0.0 {
+            return None;
+        }
+        
+        // 确保有足够的历史数据进行回测
+        if forecast_idx < self.in_days || data.len() <= forecast_idx {
+            return None;
+        }
+        
+        // 买入在forecast_idx-1成交，当天停牌无法买入，不计入交易
+        if forecast_idx == 0 || is_suspended(&data[forecast_idx - 1]) {
+            return None;
+        }
+        
+        // 计算最大收益和止损
+        let mut max_return = -1.0;
+        let mut exit_idx = None;
+        let mut is_win = false;
+        let mut is_stop_loss = false;
+        let mut break_even = false;
+        let mut break_even_exit = false;
         
-        for (_, data, buy_price) in signals {
-            if buy_price <= 0.0 {
-                total_trades -= 1;
+        // 从买入次日forecast_idx-1开始，按日期顺序到forecast_idx-in_days
+        for idx in (forecast_idx - self.in_days..forecast_idx).rev() {
+            let bar = &data[idx];
+            // 停牌日无法卖出
+            if is_suspended(bar) {
                 continue;
             }
             
-            // 确保有足够的历史数据进行回测
-            if forecast_idx < self.in_days || data.len() <= forecast_idx {
-                total_trades -= 1;
-                continue;
+            // 之前的交易日已达到保本触发线时，最低价回落到买入价即按买入价卖出(开盘即低于买入价时按开盘价)
+            if break_even && bar.low <= buy_price {
+                max_return = (bar.open.min(buy_price) - buy_price) / buy_price;
+                break_even_exit = true;
+                exit_idx = Some(idx);
+                break;
             }
             
-            // 计算最大收益和止损
-            let mut max_return = -1.0;
-            let mut exit_day = 0;
-            let mut is_win = false;
-            let mut is_stop_loss = false;
+            // 先检查收盘价是否触发止损
+            let current_return = (bar.close - buy_price) / buy_price;
             
-            // 检查从forecast_idx-self.in_days到forecast_idx-1的数据
-            for i in (forecast_idx - self.in_days)..forecast_idx {
-                // 先检查收盘价是否触发止损
-                let current_return = (data[i].close - buy_price) / buy_price;
-                
-                // 如果亏损超过止损的2倍，认为是止损失败
-                if current_return < -2.0 * self.stop_loss {
-                    // 不再使用is_stop_loss_fail
-                    max_return = current_return;
-                    exit_day = i - (forecast_idx - self.in_days) + 1;
-                    break;
-                }
-                // 如果亏损超过止损线，认为是正常止损
-                else if current_return < -self.stop_loss {
-                    is_stop_loss = true;
-                    max_return = current_return;
-                    exit_day = i - (forecast_idx - self.in_days) + 1;
-                    break;
-                }
-                // 如果达到目标收益，认为是成功
-                else if current_return >= self.target_return {
-                    is_win = true;
-                    max_return = current_return;
-                    exit_day = i - (forecast_idx - self.in_days) + 1;
-                    break;
-                }
-                
-                // 更新最大收益
-                if current_return > max_return {
-                    max_return = current_return;
-                }
+            // 盘中模式下最高价达到目标价即按目标价卖出；同一天收盘跌破止损时保守地先算止损，
+            // 需要其他处理时由引擎的ConflictPolicy改写
+            let target_hit = self.fill_mode == TargetFillMode::IntradayHigh
+                && bar.high >= buy_price * (1.0 + self.target_return);
+            if target_hit && current_return >= -self.stop_loss {
+                is_win = true;
+                max_return = self.target_return;
+                exit_idx = Some(idx);
+                break;
+            }
+            
+            // 如果亏损超过止损的2倍，认为是止损失败
+            if current_return < -2.0 * self.stop_loss {
+                max_return = current_return;
+                exit_idx = Some(idx);
+                break;
+            }
+            // 如果亏损超过止损线，认为是正常止损
+            else if current_return < -self.stop_loss {
+                is_stop_loss = true;
+                max_return = current_return;
+                exit_idx = Some(idx);
+                break;
+            }
+            // 如果达到目标收益，认为是成功
+            else if current_return >= self.target_return {
+                is_win = true;
+                max_return = current_return;
+                exit_idx = Some(idx);
+                break;
+            }
+            
+            // 更新最大收益
+            if current_return > max_return {
+                max_return = current_return;
             }
             
-            // 如果没有提前退出，使用最后一天的收盘价计算收益
-            if exit_day == 0 {
-                let last_idx = forecast_idx - 1;
+            // 当天的最高价只影响之后交易日的止损价
+            if let Some(trigger) = self.breakeven_trigger {
+                break_even |= bar.high >= buy_price * (1.0 + trigger);
+            }
+        }
+        
+        // 如果没有提前退出，使用最后一天的收盘价计算收益，最后一天停牌则顺延到复牌当天
+        let mut suspended_days = 0;
+        let exit_idx = match exit_idx {
+            Some(idx) => idx,
+            None => {
+                let last_idx = next_tradable(data, forecast_idx - self.in_days);
+                suspended_days = forecast_idx - self.in_days - last_idx;
                 let last_return = (data[last_idx].close - buy_price) / buy_price;
                 max_return = last_return;
-                exit_day = self.in_days;
                 
                 // 检查最后一天是否达到目标收益
                 if last_return >= self.target_return {
                     is_win = true;
                 }
                 // 检查最后一天是否触发止损
-                else if last_return < -self.stop_loss {
-                    if last_return < -2.0 * self.stop_loss {
-                        // 不再使用is_stop_loss_fail
-                    } else {
-                        is_stop_loss = true;
-                    }
-                }
-            }
-            
-            // 统计结果
-            if is_win {
-                winning_trades += 1;
-            } else {
-                losing_trades += 1;
-                if is_stop_loss {
-                    stop_loss_trades += 1;
+                else if last_return < -self.stop_loss && last_return >= -2.0 * self.stop_loss {
+                    is_stop_loss = true;
                 }
-                // 不再统计止损失败交易数
+                last_idx
             }
-            
-            returns.push(max_return);
-            hold_days.push(exit_day as f32);
+        };
+        
+        let exit_reason = if is_win {
+            ExitReason::TargetReached
+        } else if break_even_exit {
+            ExitReason::BreakEvenStop
+        } else if max_return < -2.0 * self.stop_loss {
+            ExitReason::StopLossFailed
+        } else if is_stop_loss {
+            ExitReason::StopLoss
+        } else {
+            ExitReason::TimeExpired
+        };
+        
+        Some(TradeOutcome {
+            signal_idx,
+            exit_idx,
+            return_pct: max_return,
+            hold_days: forecast_idx - exit_idx,
+            is_win,
+            is_stop_loss,
+            exit_reason,
+            suspended_days,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stock::mock_data::create_mock_daily_bars;
+    
+    /// 由按日期顺序的收盘价生成K线(倒序)，开盘价、最高价和最低价都等于收盘价
+    fn bars(closes: &[f32]) -> Vec<DailyBar> {
+        let mut bars = create_mock_daily_bars(closes.len());
+        for (bar, &close) in bars.iter_mut().rev().zip(closes) {
+            bar.open = close;
+            bar.high = close;
+            bar.low = close;
+            bar.close = close;
         }
+        bars
+    }
+    
+    /// 目标5%、止损3%、持有3天；6根K线时forecast_idx为4，持有期按日期顺序为data[3]、data[2]、data[1]
+    fn target() -> ReturnTarget {
+        ReturnTarget::new(0.05, 0.03, 3)
+    }
+    
+    #[test]
+    fn exits_on_first_day_reaching_target() {
+        // 第一个持有日涨6%即卖出，之后更高的收盘价不应被采用
+        let data = bars(&[10.0, 10.0, 10.6, 9.9, 11.0, 10.0]);
+        let outcome = target().evaluate_trade(0, &data, 10.0, 4).unwrap();
+        
+        assert_eq!(outcome.exit_reason, ExitReason::TargetReached);
+        assert_eq!(outcome.exit_idx, 3);
+        assert_eq!(outcome.hold_days, 1);
+        assert!((outcome.return_pct - 0.06).abs() < 1e-6);
+        assert!(outcome.is_win);
+    }
+    
+    #[test]
+    fn stops_on_first_crossing_day() {
+        // 第二个持有日跌4%止损，第三个持有日涨到目标不应被采用
+        let data = bars(&[10.0, 10.0, 10.1, 9.6, 11.0, 10.0]);
+        let outcome = target().evaluate_trade(0, &data, 10.0, 4).unwrap();
+        
+        assert_eq!(outcome.exit_reason, ExitReason::StopLoss);
+        assert_eq!(outcome.exit_idx, 2);
+        assert_eq!(outcome.hold_days, 2);
+        assert!((outcome.return_pct + 0.04).abs() < 1e-6);
+        assert!(!outcome.is_win);
+    }
+    
+    #[test]
+    fn expires_at_close_of_last_holding_day() {
+        // 持有到期在data[forecast_idx - in_days]即data[2]按收盘价卖出，不使用之后的data[1]、data[0]
+        let data = bars(&[10.0, 10.0, 10.1, 10.2, 10.3, 12.0, 12.0]);
+        let outcome = target().evaluate_trade(0, &data, 10.0, 5).unwrap();
+        
+        assert_eq!(outcome.exit_reason, ExitReason::TimeExpired);
+        assert_eq!(outcome.exit_idx, 2);
+        assert_eq!(outcome.hold_days, 3);
+        assert!((outcome.return_pct - 0.03).abs() < 1e-6);
+        assert!(!outcome.is_win);
+    }
+    
+    #[test]
+    fn breakeven_stop_sells_at_entry_after_trigger_day() {
+        // 第一个持有日最高价涨3%触发保本，第二个持有日最低价回落到买入价以下，按买入价卖出
+        let mut data = bars(&[10.0, 10.0, 10.1, 9.9, 11.0, 10.0]);
+        data[3].high = 10.3;
+        data[2].open = 10.05;
+        let outcome = target().with_breakeven_trigger(0.02).evaluate_trade(0, &data, 10.0, 4).unwrap();
+        
+        assert_eq!(outcome.exit_reason, ExitReason::BreakEvenStop);
+        assert_eq!(outcome.exit_idx, 2);
+        assert_eq!(outcome.hold_days, 2);
+        assert!(outcome.return_pct.abs() < 1e-6);
+    }
+    
+    #[test]
+    fn breakeven_stop_uses_open_on_gap_down() {
+        let mut data = bars(&[10.0, 10.0, 10.1, 9.9, 11.0, 10.0]);
+        data[3].high = 10.3;
+        data[2].open = 9.8;
+        let outcome = target().with_breakeven_trigger(0.02).evaluate_trade(0, &data, 10.0, 4).unwrap();
+        
+        assert_eq!(outcome.exit_reason, ExitReason::BreakEvenStop);
+        assert_eq!(outcome.exit_idx, 2);
+        assert!((outcome.return_pct + 0.02).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn breakeven_trigger_day_does_not_stop_itself() {
+        // 触发当天的最低价不检查保本止损，没有回落时持有到期
+        let mut data = bars(&[10.0, 10.0, 10.1, 10.2, 10.4, 10.0]);
+        data[3].high = 10.3;
+        data[3].low = 9.9;
+        let outcome = target().with_breakeven_trigger(0.02).evaluate_trade(0, &data, 10.0, 4).unwrap();
         
-        (total_trades, winning_trades, losing_trades, stop_loss_trades, returns, hold_days)
+        assert_eq!(outcome.exit_reason, ExitReason::TimeExpired);
+        assert_eq!(outcome.exit_idx, 1);
+        assert!((outcome.return_pct - 0.04).abs() < 1e-6);
     }
 }
@@ -0,0 +1,185 @@
+use crate::backtest::result::ExitReason;
+use crate::targets::{is_suspended, next_tradable, summarize_outcomes, PartialExit, Target, TradeOutcome};
+use crate::stock::series::BarSlice;
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use serde::Deserialize;
+
+/// 剩余仓位小于该比例时视为已全部卖出
+const MIN_REMAINING: f32 = 1e-6;
+
+/// 分批止盈目标 - 盈利达到各档收益率时分别卖出对应比例的仓位，剩余仓位止损或到期卖出
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScaledExitTarget {
+    /// 各档(收益率, 卖出的仓位比例)，比例之和超过1时后面的档位只卖出剩余仓位
+    pub legs: Vec<(f32, f32)>,
+    /// 剩余仓位的止损比例
+    pub stop_loss: f32,
+    pub in_days: usize,
+}
+
+impl Target for ScaledExitTarget {
+    fn name(&self) -> String {
+        let legs: Vec<String> = self.sorted_legs().iter()
+            .map(|(target_return, fraction)| format!("{}%×{}%", target_return * 100.0, fraction * 100.0))
+            .collect();
+        format!("分批止盈目标 {} / {}天", legs.join("+"), self.in_days)
+    }
+    
+    /// 全部档位都卖出时的加权收益率
+    fn target_return(&self) -> f32 {
+        self.legs.iter().map(|(target_return, fraction)| target_return * fraction).sum()
+    }
+    
+    fn stop_loss(&self) -> f32 {
+        self.stop_loss
+    }
+    
+    fn in_days(&self) -> usize {
+        self.in_days
+    }
+    
+    fn run(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize) -> f32 {
+        let (total_trades, winning_trades, _, _, _, _) = self.evaluate_signals(signals, forecast_idx);
+        
+        if total_trades > 0 {
+            winning_trades as f32 / total_trades as f32
+        } else {
+            0.0
+        }
+    }
+    
+    fn evaluate_signals(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize)
+        -> (usize, usize, usize, usize, Vec<f32>, Vec<f32>) {
+        summarize_outcomes(&self.evaluate_trades(&signals, forecast_idx))
+    }
+    
+    fn evaluate_trades(&self, signals: &[(String, BarSlice, f32)], forecast_idx: usize) -> Vec<TradeOutcome> {
+        signals.iter()
+            .enumerate()
+            .filter_map(|(signal_idx, (_, data, buy_price))| self.evaluate_trade(signal_idx, data, *buy_price, forecast_idx))
+            .collect()
+    }
+    
+    fn exit_legs(&self, data: &[DailyBar], buy_price: f32, forecast_idx: usize) -> Option<Vec<PartialExit>> {
+        self.partial_exits(data, buy_price, forecast_idx).map(|(exits, _)| exits)
+    }
+}
+
+impl ScaledExitTarget {
+    /// 按收益率从低到高排列的档位
+    fn sorted_legs(&self) -> Vec<(f32, f32)> {
+        let mut legs = self.legs.clone();
+        legs.sort_by(|a, b| a.0.total_cmp(&b.0));
+        legs
+    }
+    
+    /// 按日期顺序逐日卖出，返回各批卖出和到期日停牌顺延的天数，买入价无效或数据不足时返回None
+    ///
+    /// 每天先检查最低价是否跌破止损价(开盘即低于止损价时按开盘价卖出剩余仓位)，
+    /// 再按最高价卖出达到的档位(开盘即高于目标价时按开盘价)；同一天止损和止盈都触及时保守地全部止损
+    fn partial_exits(&self, data: &[DailyBar], buy_price: f32, forecast_idx: usize) -> Option<(Vec<PartialExit>, usize)> {
+        if buy_price <= 0.0 {
+            return None;
+        }
+        
+        // 确保有足够的历史数据进行回测
+        if forecast_idx < self.in_days || data.len() <= forecast_idx {
+            return None;
+        }
+        
+        // 买入在forecast_idx-1成交，当天停牌无法买入，不计入交易
+        if forecast_idx == 0 || is_suspended(&data[forecast_idx - 1]) {
+            return None;
+        }
+        
+        let stop_price = buy_price * (1.0 - self.stop_loss);
+        let mut legs = self.sorted_legs().into_iter().peekable();
+        let mut remaining = 1.0;
+        let mut exits = Vec::new();
+        
+        // 从买入次日forecast_idx-1开始，按日期顺序到forecast_idx-in_days
+        for idx in (forecast_idx - self.in_days..forecast_idx).rev() {
+            let bar = &data[idx];
+            // 停牌日无法卖出
+            if is_suspended(bar) {
+                continue;
+            }
+            
+            if bar.low <= stop_price {
+                exits.push(PartialExit {
+                    exit_idx: idx,
+                    exit_price: bar.open.min(stop_price),
+                    fraction: remaining,
+                    exit_reason: ExitReason::StopLoss,
+                });
+                return Some((exits, 0));
+            }
+            
+            while let Some(&(target_return, fraction)) = legs.peek() {
+                let target_price = buy_price * (1.0 + target_return);
+                if bar.high < target_price {
+                    break;
+                }
+                legs.next();
+                
+                let fraction = fraction.min(remaining);
+                if fraction <= 0.0 {
+                    continue;
+                }
+                remaining -= fraction;
+                exits.push(PartialExit {
+                    exit_idx: idx,
+                    exit_price: bar.open.max(target_price),
+                    fraction,
+                    exit_reason: ExitReason::TargetReached,
+                });
+            }
+            
+            if remaining < MIN_REMAINING {
+                return Some((exits, 0));
+            }
+        }
+        
+        // 剩余仓位按最后一天收盘价卖出，最后一天停牌则顺延到复牌当天
+        let last_idx = next_tradable(data, forecast_idx - self.in_days);
+        exits.push(PartialExit {
+            exit_idx: last_idx,
+            exit_price: data[last_idx].close,
+            fraction: remaining,
+            exit_reason: ExitReason::TimeExpired,
+        });
+        
+        Some((exits, forecast_idx - self.in_days - last_idx))
+    }
+    
+    /// 评估单笔交易，收益率为各批收益率按卖出比例加权，加权收益为正计为成功
+    ///
+    /// 卖出日和退出原因取最后一批；全部档位都达到时为TargetReached
+    fn evaluate_trade(&self, signal_idx: usize, data: &[DailyBar], buy_price: f32, forecast_idx: usize) -> Option<TradeOutcome> {
+        let (exits, suspended_days) = self.partial_exits(data, buy_price, forecast_idx)?;
+        let last = exits.last()?;
+        
+        let return_pct: f32 = exits.iter()
+            .map(|exit| exit.fraction * (exit.exit_price - buy_price) / buy_price)
+            .sum();
+        let last_return = (last.exit_price - buy_price) / buy_price;
+        let exit_reason = if last.exit_reason == ExitReason::StopLoss && last_return < -2.0 * self.stop_loss {
+            ExitReason::StopLossFailed
+        } else {
+            last.exit_reason
+        };
+        let is_win = return_pct > 0.0;
+        
+        Some(TradeOutcome {
+            signal_idx,
+            exit_idx: last.exit_idx,
+            return_pct,
+            hold_days: forecast_idx - last.exit_idx,
+            is_win,
+            is_stop_loss: !is_win && exit_reason == ExitReason::StopLoss,
+            exit_reason,
+            suspended_days,
+        })
+    }
+}
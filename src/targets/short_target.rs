@@ -0,0 +1,87 @@
+use crate::targets::{Direction, PartialExit, Target, TradeOutcome};
+use crate::stock::series::BarSlice;
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+
+/// 做空目标 - 把任意目标用于做空方向
+///
+/// 每个信号的K线以开仓价为轴镜像(价格p变为2 * 开仓价 - p，最高价与最低价互换)后交给内部目标评估。
+/// 镜像K线上做多的收益率(p' - e) / e正好等于做空的收益率(e - p) / e，
+/// 因此止损价在开仓价之上、目标价在开仓价之下，停牌判断不受影响
+pub struct ShortTarget {
+    pub inner: Box<dyn Target>,
+}
+
+impl ShortTarget {
+    pub fn new(inner: Box<dyn Target>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Target for ShortTarget {
+    fn name(&self) -> String {
+        format!("做空 {}", self.inner.name())
+    }
+    
+    fn target_return(&self) -> f32 {
+        self.inner.target_return()
+    }
+    
+    fn stop_loss(&self) -> f32 {
+        self.inner.stop_loss()
+    }
+    
+    fn in_days(&self) -> usize {
+        self.inner.in_days()
+    }
+    
+    fn direction(&self) -> Direction {
+        Direction::Short
+    }
+    
+    fn run(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize) -> f32 {
+        self.inner.run(mirror_signals(&signals, forecast_idx), forecast_idx)
+    }
+    
+    fn evaluate_signals(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize)
+        -> (usize, usize, usize, usize, Vec<f32>, Vec<f32>) {
+        self.inner.evaluate_signals(mirror_signals(&signals, forecast_idx), forecast_idx)
+    }
+    
+    fn evaluate_trades(&self, signals: &[(String, BarSlice, f32)], forecast_idx: usize) -> Vec<TradeOutcome> {
+        self.inner.evaluate_trades(&mirror_signals(signals, forecast_idx), forecast_idx)
+    }
+    
+    /// 内部目标在镜像K线上的卖出价换算回实际的平仓价
+    fn exit_legs(&self, data: &[DailyBar], buy_price: f32, forecast_idx: usize) -> Option<Vec<PartialExit>> {
+        let legs = self.inner.exit_legs(&mirror_bars(data, buy_price, forecast_idx), buy_price, forecast_idx)?;
+        Some(legs.into_iter()
+            .map(|leg| PartialExit { exit_price: 2.0 * buy_price - leg.exit_price, ..leg })
+            .collect())
+    }
+}
+
+/// 以pivot为轴镜像一根K线，成交量和成交额不变
+pub fn mirror_bar(bar: &DailyBar, pivot: f32) -> DailyBar {
+    DailyBar {
+        date: bar.date,
+        open: 2.0 * pivot - bar.open,
+        high: 2.0 * pivot - bar.low,
+        low: 2.0 * pivot - bar.high,
+        close: 2.0 * pivot - bar.close,
+        volume: bar.volume,
+        amount: bar.amount,
+    }
+}
+
+/// 以pivot为轴镜像开仓日forecast_idx及之后的K线和开仓前一根
+pub fn mirror_bars(data: &[DailyBar], pivot: f32, forecast_idx: usize) -> Vec<DailyBar> {
+    let end = data.len().min(forecast_idx + 2);
+    data[..end].iter().map(|bar| mirror_bar(bar, pivot)).collect()
+}
+
+/// 以各自的开仓价镜像信号的K线，见mirror_bars
+pub fn mirror_signals(signals: &[(String, BarSlice, f32)], forecast_idx: usize) -> Vec<(String, BarSlice, f32)> {
+    signals.iter()
+        .map(|(symbol, data, price)| (symbol.clone(), BarSlice::from(mirror_bars(data, *price, forecast_idx)), *price))
+        .collect()
+}
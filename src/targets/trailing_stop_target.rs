@@ -0,0 +1,135 @@
+use crate::backtest::result::ExitReason;
+use crate::targets::{is_suspended, next_tradable, summarize_outcomes, Target, TradeOutcome};
+use crate::stock::series::BarSlice;
+use egostrategy_datahub::models::stock::DailyData as DailyBar;
+use serde::Deserialize;
+
+/// 移动止损目标 - 盈利达到activation_return后，止损价跟随最高价上移
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrailingStopTarget {
+    /// 启动移动止损前的固定止损比例
+    pub stop_loss: f32,
+    /// 最高价相对买入价的涨幅达到该比例后启动移动止损
+    pub activation_return: f32,
+    /// 移动止损价相对持有期最高价的回撤比例
+    pub trail_pct: f32,
+    pub in_days: usize,
+}
+
+impl Target for TrailingStopTarget {
+    fn name(&self) -> String {
+        format!("移动止损目标 {}% 回撤{}% / {}天", self.activation_return * 100.0, self.trail_pct * 100.0, self.in_days)
+    }
+    
+    fn target_return(&self) -> f32 {
+        self.activation_return
+    }
+    
+    fn stop_loss(&self) -> f32 {
+        self.stop_loss
+    }
+    
+    fn in_days(&self) -> usize {
+        self.in_days
+    }
+    
+    fn run(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize) -> f32 {
+        let (total_trades, winning_trades, _, _, _, _) = self.evaluate_signals(signals, forecast_idx);
+        
+        if total_trades > 0 {
+            winning_trades as f32 / total_trades as f32
+        } else {
+            0.0
+        }
+    }
+    
+    fn evaluate_signals(&self, signals: Vec<(String, BarSlice, f32)>, forecast_idx: usize)
+        -> (usize, usize, usize, usize, Vec<f32>, Vec<f32>) {
+        summarize_outcomes(&self.evaluate_trades(&signals, forecast_idx))
+    }
+    
+    fn evaluate_trades(&self, signals: &[(String, BarSlice, f32)], forecast_idx: usize) -> Vec<TradeOutcome> {
+        signals.iter()
+            .enumerate()
+            .filter_map(|(signal_idx, (_, data, buy_price))| self.evaluate_trade(signal_idx, data, *buy_price, forecast_idx))
+            .collect()
+    }
+}
+
+impl TrailingStopTarget {
+    /// 评估单笔交易，买入价无效或数据不足时返回None
+    ///
+    /// 按日期顺序逐日检查: 当天最低价跌破止损价时按止损价卖出(开盘即低于止损价时按开盘价)，
+    /// 再用当天最高价更新峰值，因此当天的新高只影响之后的止损价。以卖出价盈利计为成功
+    fn evaluate_trade(&self, signal_idx: usize, data: &[DailyBar], buy_price: f32, forecast_idx: usize) -> Option<TradeOutcome> {
+        if buy_price <= 0.0 {
+            return None;
+        }
+        
+        // 确保有足够的历史数据进行回测
+        if forecast_idx < self.in_days || data.len() <= forecast_idx {
+            return None;
+        }
+        
+        // 买入在forecast_idx-1成交，当天停牌无法买入，不计入交易
+        if forecast_idx == 0 || is_suspended(&data[forecast_idx - 1]) {
+            return None;
+        }
+        
+        let fixed_stop = buy_price * (1.0 - self.stop_loss);
+        let activation_price = buy_price * (1.0 + self.activation_return);
+        let mut peak = buy_price;
+        let mut exit = None;
+        
+        // 从买入次日forecast_idx-1开始，按日期顺序到forecast_idx-in_days
+        for idx in (forecast_idx - self.in_days..forecast_idx).rev() {
+            let bar = &data[idx];
+            // 停牌日无法卖出
+            if is_suspended(bar) {
+                continue;
+            }
+            
+            let trailing = peak >= activation_price;
+            let stop_price = if trailing {
+                (peak * (1.0 - self.trail_pct)).max(fixed_stop)
+            } else {
+                fixed_stop
+            };
+            
+            if bar.low <= stop_price {
+                let exit_price = bar.open.min(stop_price);
+                let reason = if trailing { ExitReason::TrailingStop } else { ExitReason::StopLoss };
+                exit = Some((idx, exit_price, reason, 0));
+                break;
+            }
+            
+            peak = peak.max(bar.high);
+        }
+        
+        // 没有触发止损时按最后一天收盘价卖出，最后一天停牌则顺延到复牌当天
+        let (exit_idx, exit_price, exit_reason, suspended_days) = exit.unwrap_or_else(|| {
+            let last_idx = next_tradable(data, forecast_idx - self.in_days);
+            (last_idx, data[last_idx].close, ExitReason::TimeExpired, forecast_idx - self.in_days - last_idx)
+        });
+        
+        let return_pct = (exit_price - buy_price) / buy_price;
+        let exit_reason = if exit_reason == ExitReason::StopLoss && return_pct < -2.0 * self.stop_loss {
+            ExitReason::StopLossFailed
+        } else {
+            exit_reason
+        };
+        let is_win = return_pct > 0.0;
+        
+        Some(TradeOutcome {
+            signal_idx,
+            exit_idx,
+            return_pct,
+            hold_days: forecast_idx - exit_idx,
+            is_win,
+            is_stop_loss: !is_win && exit_reason == ExitReason::StopLoss,
+            exit_reason,
+            suspended_days,
+        })
+    }
+}
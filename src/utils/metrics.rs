@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 /// 计算夏普比率
 /// 
 /// * `returns` - 收益率序列
@@ -23,6 +25,13 @@ pub fn sharpe_ratio(returns: &[f32], risk_free_rate: f32) -> f32 {
     excess_return / std_dev
 }
 
+/// 计算信息比率: 平均超额收益 / 超额收益标准差
+/// 
+/// * `excess_returns` - 相对基准的超额收益率序列
+pub fn information_ratio(excess_returns: &[f32]) -> f32 {
+    sharpe_ratio(excess_returns, 0.0)
+}
+
 /// 计算索提诺比率
 /// 
 /// * `returns` - 收益率序列
@@ -146,3 +155,129 @@ pub fn expected_return(returns: &[f32]) -> f32 {
     
     win_rate * avg_win + (1.0 - win_rate) * avg_loss
 }
+
+/// 按日期取交集后两个序列的协方差和各自方差(均为离差平方和)，重叠不足2个时返回None
+///
+/// 只有一边存在的日期被忽略，两个序列的顺序和长度可以不同
+fn co_moments(a: &[(i32, f32)], b: &[(i32, f32)]) -> Option<(f64, f64, f64)> {
+    let b_by_date: HashMap<i32, f32> = b.iter().copied().collect();
+    let pairs: Vec<(f64, f64)> = a.iter()
+        .filter_map(|&(date, x)| b_by_date.get(&date).map(|&y| (x as f64, y as f64)))
+        .collect();
+    
+    let n = pairs.len();
+    if n < 2 {
+        return None;
+    }
+    
+    let mean_a = pairs.iter().map(|&(x, _)| x).sum::<f64>() / n as f64;
+    let mean_b = pairs.iter().map(|&(_, y)| y).sum::<f64>() / n as f64;
+    
+    let mut cov = 0.0;
+    let mut var_a = 0.0;
+    let mut var_b = 0.0;
+    for &(x, y) in &pairs {
+        let da = x - mean_a;
+        let db = y - mean_b;
+        cov += da * db;
+        var_a += da * da;
+        var_b += db * db;
+    }
+    
+    Some((cov, var_a, var_b))
+}
+
+/// 计算贝塔系数: Cov(个股, 基准) / Var(基准)
+/// 
+/// * `stock_returns` - 个股的(日期, 收益率)序列
+/// * `bench_returns` - 基准的(日期, 收益率)序列，按日期与个股取交集
+/// 
+/// 重叠日期少于2个或基准收益率没有波动时返回None
+pub fn beta(stock_returns: &[(i32, f32)], bench_returns: &[(i32, f32)]) -> Option<f32> {
+    match co_moments(stock_returns, bench_returns)? {
+        (cov, _, var_bench) if var_bench > 0.0 => Some((cov / var_bench) as f32),
+        _ => None,
+    }
+}
+
+/// 计算皮尔逊相关系数，取值范围[-1, 1]
+/// 
+/// * `a` - 第一个(日期, 值)序列
+/// * `b` - 第二个(日期, 值)序列，按日期与a取交集
+/// 
+/// 重叠日期少于2个或任一序列没有波动时返回None
+pub fn correlation(a: &[(i32, f32)], b: &[(i32, f32)]) -> Option<f32> {
+    match co_moments(a, b)? {
+        (cov, var_a, var_b) if var_a > 0.0 && var_b > 0.0 => {
+            Some((cov / (var_a * var_b).sqrt()).clamp(-1.0, 1.0) as f32)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// 从20230101起逐日编号的(日期, 值)序列
+    fn dated(values: &[f32]) -> Vec<(i32, f32)> {
+        values.iter().enumerate().map(|(i, &v)| (20230101 + i as i32, v)).collect()
+    }
+    
+    const BENCH: [f32; 6] = [0.01, -0.02, 0.015, 0.003, -0.007, 0.02];
+    
+    #[test]
+    fn profit_factor_is_gross_profit_over_gross_loss() {
+        // (0.05 + 0.03 + 0.01) / (0.02 + 0.04)
+        assert!((profit_factor(&[0.05, 0.03, -0.02, -0.04, 0.01]) - 1.5).abs() < 1e-5);
+        assert_eq!(profit_factor(&[0.05, 0.0, 0.01]), f32::INFINITY);
+        assert_eq!(profit_factor(&[-0.01, -0.02]), 0.0);
+    }
+    
+    #[test]
+    fn beta_of_doubled_returns_is_two() {
+        let bench = dated(&BENCH);
+        let stock = dated(&BENCH.map(|r| r * 2.0));
+        
+        assert!((beta(&stock, &bench).unwrap() - 2.0).abs() < 1e-5);
+    }
+    
+    #[test]
+    fn correlation_of_scaled_returns_is_plus_or_minus_one() {
+        let bench = dated(&BENCH);
+        let up = dated(&BENCH.map(|r| r * 3.0 + 0.001));
+        let down = dated(&BENCH.map(|r| -r));
+        
+        assert!((correlation(&up, &bench).unwrap() - 1.0).abs() < 1e-5);
+        assert!((correlation(&down, &bench).unwrap() + 1.0).abs() < 1e-5);
+    }
+    
+    #[test]
+    fn co_moments_intersect_on_dates() {
+        let bench = dated(&BENCH);
+        // 个股缺少第2、4天，多出一个基准没有的日期，且顺序为倒序
+        let mut stock: Vec<(i32, f32)> = bench.iter()
+            .enumerate()
+            .filter(|(i, _)| *i != 1 && *i != 3)
+            .map(|(_, &(date, r))| (date, r * 2.0))
+            .collect();
+        stock.push((20240101, 0.5));
+        stock.reverse();
+        
+        assert!((beta(&stock, &bench).unwrap() - 2.0).abs() < 1e-5);
+        assert!((correlation(&stock, &bench).unwrap() - 1.0).abs() < 1e-5);
+    }
+    
+    #[test]
+    fn too_few_observations_is_none() {
+        let bench = dated(&BENCH);
+        let one_day = vec![(20230103, 0.03)];
+        let other_days = vec![(20220101, 0.01), (20220102, 0.02)];
+        let flat = dated(&[0.01; 6]);
+        
+        assert_eq!(beta(&one_day, &bench), None);
+        assert_eq!(correlation(&other_days, &bench), None);
+        assert_eq!(beta(&bench, &flat), None);
+        assert_eq!(correlation(&flat, &bench), None);
+    }
+}
@@ -1 +1,3 @@
 pub mod metrics;
+pub mod csv;
+pub mod rng;
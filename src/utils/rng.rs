@@ -0,0 +1,26 @@
+/// SplitMix64伪随机数生成器，用于需要固定种子、结果可复现的抽样
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// 使用指定种子创建
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+    
+    /// 生成下一个64位随机数
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    
+    /// 生成[0, n)范围内的随机下标，n必须大于0
+    pub fn next_index(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}